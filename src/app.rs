@@ -1,10 +1,15 @@
 use ratatui::widgets::ScrollbarState;
+use std::time::{Duration, Instant};
 use crate::{
+    export,
     handlers,
     menu::{Menu, MenuItem},
     types::ScrollPosition,
 };
 
+/// 底部栏临时提示的显示时长
+const STATUS_MESSAGE_DURATION: Duration = Duration::from_secs(5);
+
 // 定义焦点区域枚举
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum FocusArea {
@@ -20,6 +25,11 @@ pub struct App {
     pub scrollbar_state: ScrollbarState,
     pub needs_refresh: bool,  // 新增：是否需要刷新UI
     pub selection_mode: bool,  // 新增：是否在文本选择模式
+    pub history_selected: usize,  // 历史记录列表中选中的行
+    pub history_baseline: Option<String>,  // 作为对比基准的记录 (generated_at)
+    pub status_message: Option<(String, Instant)>,  // 底部栏临时提示
+    pub disk_latency_selected: usize,  // 磁盘延迟分布图显示的测试项
+    pub route_trace_selected: usize,  // 路由追踪界面显示的目标
     content_cache: Option<(MenuItem, String)>,
 }
 
@@ -33,6 +43,11 @@ impl App {
             scrollbar_state: ScrollbarState::default(),
             needs_refresh: false,  // 初始化为不需要刷新
             selection_mode: false,  // 初始化为不在选择模式
+            history_selected: 0,
+            history_baseline: None,
+            status_message: None,
+            disk_latency_selected: 0,
+            route_trace_selected: 0,
             content_cache: None,
         }
     }
@@ -97,26 +112,235 @@ impl App {
     // 新增：处理菜单选择（回车键）
     pub fn handle_menu_selection(&mut self) {
         if self.focus_area == FocusArea::Menu && self.show_menu {
-            // 根据当前选中的菜单项执行相应操作
-            match self.menu.selected_item() {
-                crate::menu::MenuItem::CpuTest => {
-                    // 启动CPU测试
-                    crate::handlers::cpu_test::start_cpu_test();
-                }
-                crate::menu::MenuItem::DiskTest => {
-                    // 启动磁盘测试（如果磁盘测试有类似的start函数）
-                    // crate::handlers::disk_test::start_disk_test();
-                }
-                _ => {
-                    // 其他菜单项的处理
-                }
-            }
-            
+            // 测试不会因为选中菜单项而启动，在内容区域按 Enter 才开始
             // 选择后自动切换到内容区域
             self.set_focus(FocusArea::Content);
         }
     }
     
+    // 历史记录界面：移动选中行
+    pub fn history_select_previous(&mut self) {
+        self.history_selected = self.history_selected.saturating_sub(1);
+    }
+    
+    pub fn history_select_next(&mut self) {
+        let count = handlers::history::get_history().runs.len();
+        if self.history_selected + 1 < count {
+            self.history_selected += 1;
+        }
+    }
+    
+    // 历史记录界面：把选中行设为对比基准，再次按下取消
+    pub fn toggle_history_baseline(&mut self) {
+        let data = handlers::history::get_history();
+        let selected = data
+            .display_order()
+            .get(self.history_selected)
+            .map(|&index| data.runs[index].generated_at.clone());
+        
+        if selected.is_some() && selected == self.history_baseline {
+            self.history_baseline = None;
+        } else {
+            self.history_baseline = selected;
+        }
+        self.clear_cache();
+    }
+    
+    /// 导出当前测试结果为 Markdown 和 HTML 报告
+    pub fn export_report(&mut self) {
+        let message = match export::export_current_report() {
+            Ok((markdown, html)) => format!("报告已导出: {} / {}", markdown.display(), html.display()),
+            Err(e) => format!("导出报告失败: {}", e),
+        };
+        self.set_status(message);
+    }
+    
+    /// 切换磁盘测试目标
+    pub fn select_next_disk_target(&mut self) {
+        let message = match handlers::disk_test::select_next_target() {
+            Ok(target) => format!("测试目标已切换为 {}，按 Enter 开始测试", target.dir.display()),
+            Err(e) => e,
+        };
+        self.set_status(message);
+        self.clear_cache();
+    }
+    
+    /// 切换磁盘测试配置（内置引擎 / fio 任务）
+    pub fn select_next_disk_profile(&mut self) {
+        let message = match handlers::disk_test::select_next_profile() {
+            Ok(profile) => format!("测试配置已切换为 {}，按 Enter 开始测试", profile.name()),
+            Err(e) => e,
+        };
+        self.set_status(message);
+        self.clear_cache();
+    }
+    
+    /// 开关 O_DIRECT
+    pub fn toggle_disk_direct_io(&mut self) {
+        let result = handlers::disk_test::change_io_mode(|mode| mode.direct = !mode.direct);
+        self.report_io_mode_change(result);
+    }
+    
+    /// 切换写入同步方式
+    pub fn cycle_disk_sync_mode(&mut self) {
+        let result = handlers::disk_test::change_io_mode(|mode| mode.sync = mode.sync.next());
+        self.report_io_mode_change(result);
+    }
+    
+    /// 切换每个任务的队列深度
+    pub fn cycle_disk_queue_depth(&mut self) {
+        let result = handlers::disk_test::change_io_mode(|mode| mode.queue_depth = mode.next_queue_depth());
+        self.report_io_mode_change(result);
+    }
+    
+    /// 切换并发任务数
+    pub fn cycle_disk_jobs(&mut self) {
+        let result = handlers::disk_test::change_io_mode(|mode| mode.jobs = mode.next_jobs());
+        self.report_io_mode_change(result);
+    }
+    
+    fn report_io_mode_change(&mut self, result: Result<handlers::disk_test::DiskIoMode, String>) {
+        let message = match result {
+            Ok(mode) => format!("I/O 模式已切换为 {}，按 Enter 开始测试", mode.label()),
+            Err(e) => e,
+        };
+        self.set_status(message);
+        self.clear_cache();
+    }
+    
+    /// 切换测试时长：磁盘测试为每个测试项的时长，网速测试为下载/上传各自的测量时长，路由追踪为探测轮数
+    pub fn cycle_test_duration(&mut self) {
+        let message = match self.menu.selected_item() {
+            MenuItem::DiskTest => handlers::disk_test::change_test_params(|p| p.duration_secs = p.next_duration())
+                .map(|params| params.label()),
+            MenuItem::NetworkSpeedTest => handlers::network_test::change_speed_test_config(|c| c.duration = c.next_duration())
+                .map(|config| config.label()),
+            MenuItem::RouteTrace => handlers::route_trace::cycle_rounds()
+                .map(|rounds| format!("每个目标探测 {} 轮", rounds)),
+            _ => return,
+        };
+        self.report_params_change(message);
+    }
+    
+    /// 切换磁盘读取测试的文件大小
+    pub fn cycle_disk_file_size(&mut self) {
+        let message = handlers::disk_test::change_test_params(|p| p.file_size_mb = p.next_file_size())
+            .map(|params| params.label());
+        self.report_params_change(message);
+    }
+    
+    /// 切换并发度：CPU 测试为线程数，网速测试为连接数
+    pub fn cycle_test_concurrency(&mut self) {
+        let message = match self.menu.selected_item() {
+            MenuItem::CpuTest => handlers::cpu_test::cycle_thread_count()
+                .map(|threads| format!("多核测试线程数 {}", threads)),
+            MenuItem::NetworkSpeedTest => handlers::network_test::change_speed_test_config(|c| c.connections = c.next_connections())
+                .map(|config| config.label()),
+            _ => return,
+        };
+        self.report_params_change(message);
+    }
+    
+    /// 切换网速测试的服务器：全部或其中一台
+    pub fn select_next_network_server(&mut self) {
+        let message = handlers::network_test::select_next_server()
+            .map(|server| format!("测速服务器: {}", server.as_deref().unwrap_or("全部")));
+        self.report_params_change(message);
+    }
+    
+    /// 启动/停止本机的 iperf3 服务端，供另一台机器的 iperf3 测试本机
+    pub fn toggle_iperf_server(&mut self) {
+        let message = match handlers::iperf::toggle_server(handlers::iperf::DEFAULT_PORT) {
+            Ok(Some(port)) => format!("iperf3 服务端已在端口 {} 启动，退出程序时停止", port),
+            Ok(None) => "iperf3 服务端已停止".to_string(),
+            Err(e) => e,
+        };
+        self.set_status(message);
+        self.clear_cache();
+    }
+    
+    /// 在系统信息页测试各解析器的 DNS 解析
+    pub fn start_dns_test(&mut self) {
+        let message = match handlers::dns_test::start_dns_test() {
+            Ok(()) => "DNS 测试已开始".to_string(),
+            Err(e) => e,
+        };
+        self.set_status(message);
+        self.clear_cache();
+    }
+    
+    fn report_params_change(&mut self, result: Result<String, String>) {
+        let message = match result {
+            Ok(label) => format!("{}，按 Enter 开始测试", label),
+            Err(e) => e,
+        };
+        self.set_status(message);
+        self.clear_cache();
+    }
+    
+    /// 取消当前页面正在运行的测试，测试线程退出前会清理测试文件
+    pub fn cancel_current_test(&mut self) {
+        let Some(control) = handlers::test_control(self.menu.selected_item()) else { return };
+        let message = if control.cancel() { "正在取消测试..." } else { "没有正在运行的测试" };
+        self.set_status(message.to_string());
+        self.clear_cache();
+    }
+    
+    /// 暂停/继续当前页面的测试，暂停在当前测试项结束后生效
+    pub fn toggle_current_test_pause(&mut self) {
+        let Some(control) = handlers::test_control(self.menu.selected_item()) else { return };
+        let message = match control.toggle_pause() {
+            Some(true) => "当前测试项结束后暂停，按空格继续",
+            Some(false) => "测试已继续",
+            None => "没有正在运行的测试",
+        };
+        self.set_status(message.to_string());
+        self.clear_cache();
+    }
+    
+    /// 按当前设置开始（或重新运行）当前页面的测试
+    pub fn start_current_test(&mut self) {
+        let result = match self.menu.selected_item() {
+            MenuItem::SystemInfo => handlers::dns_test::start_dns_test(),
+            MenuItem::DiskTest => handlers::disk_test::start_disk_test(),
+            MenuItem::CpuTest => handlers::cpu_test::start_cpu_test(),
+            MenuItem::NetworkSpeedTest => handlers::network_test::start_network_test(),
+            MenuItem::MemoryTest => handlers::memory_test::start_memory_test(),
+            MenuItem::RouteTrace => handlers::route_trace::start_route_trace(),
+            MenuItem::UnlockCheck => handlers::unlock_check::start_unlock_check(),
+            _ => return,
+        };
+        let message = match result {
+            Ok(()) => "测试已开始，按 X 取消".to_string(),
+            Err(e) => e,
+        };
+        self.set_status(message);
+        self.clear_cache();
+    }
+    
+    /// 延迟分布图切换到下一项测试，界面绘制时按结果数取模
+    pub fn select_next_disk_latency(&mut self) {
+        self.disk_latency_selected = self.disk_latency_selected.wrapping_add(1);
+    }
+    
+    /// 路由追踪界面切换到下一个目标，界面绘制时按目标数取模
+    pub fn select_next_route_trace(&mut self) {
+        self.route_trace_selected = self.route_trace_selected.wrapping_add(1);
+    }
+    
+    /// 在底部栏显示一条临时提示
+    pub fn set_status(&mut self, message: String) {
+        self.status_message = Some((message, Instant::now()));
+    }
+    
+    /// 未过期的临时提示
+    pub fn current_status(&self) -> Option<&str> {
+        self.status_message
+            .as_ref()
+            .filter(|(_, shown_at)| shown_at.elapsed() < STATUS_MESSAGE_DURATION)
+            .map(|(message, _)| message.as_str())
+    }
+    
     pub fn scroll_up(&mut self, lines: u16) {
         self.scroll_position.scroll_up(lines);
         self.update_scrollbar();
@@ -0,0 +1,238 @@
+// src/cancel.rs
+// 测试的取消与暂停：界面按键或 Ctrl+C 设置标志，测试循环定期检查
+use std::fmt;
+use std::ops::Deref;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// 暂停时检查状态的间隔
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 测试被取消时返回的错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("测试已取消")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// 取消令牌，克隆后共享同一组标志
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    state: Arc<TokenState>,
+}
+
+#[derive(Debug, Default)]
+struct TokenState {
+    cancelled: AtomicBool,
+    paused: AtomicBool,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 请求取消，同时解除暂停，让等待中的测试尽快退出
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        self.state.paused.store(false, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.state.paused.store(paused && !self.is_cancelled(), Ordering::SeqCst);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state.paused.load(Ordering::SeqCst)
+    }
+
+    /// 已取消时返回 Err，用于测量循环内部的快速检查
+    pub fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            Err(Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 测试项之间调用：暂停时在这里等待，已取消时返回 Err
+    ///
+    /// 暂停只在测试项之间生效，正在计时的测量不会被打断，结果不受影响
+    pub fn checkpoint(&self) -> Result<(), Cancelled> {
+        while self.is_paused() {
+            thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+        self.check()
+    }
+
+    /// 异步版本的 checkpoint，供网络测试使用
+    pub async fn checkpoint_async(&self) -> Result<(), Cancelled> {
+        while self.is_paused() {
+            tokio::time::sleep(PAUSE_POLL_INTERVAL).await;
+        }
+        self.check()
+    }
+}
+
+/// 某一类测试的控制入口：每次开始测试时换上新的令牌，界面通过它取消或暂停正在运行的测试
+pub struct TestControl {
+    current: Mutex<Option<CancelToken>>,
+}
+
+impl TestControl {
+    pub const fn new() -> Self {
+        Self { current: Mutex::new(None) }
+    }
+
+    /// 开始一次新的测试，返回的句柄在测试结束（包括 panic）时自动注销令牌
+    pub fn begin(&'static self) -> ActiveRun {
+        let token = CancelToken::new();
+        if let Some(previous) = self.current.lock().unwrap().replace(token.clone()) {
+            previous.cancel();
+        }
+        ActiveRun { control: self, token }
+    }
+
+    /// 没有测试在运行时开始一次新的测试，已有测试在运行时返回 None；检查与登记在同一次加锁内完成
+    pub fn try_begin(&'static self) -> Option<ActiveRun> {
+        let mut current = self.current.lock().unwrap();
+        if current.is_some() {
+            return None;
+        }
+        let token = CancelToken::new();
+        *current = Some(token.clone());
+        Some(ActiveRun { control: self, token })
+    }
+
+    /// 取消正在运行的测试，没有测试在运行时返回 false
+    pub fn cancel(&self) -> bool {
+        match self.current.lock().unwrap().as_ref() {
+            Some(token) if !token.is_cancelled() => {
+                token.cancel();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 切换暂停状态，返回切换后是否处于暂停；没有测试在运行时返回 None
+    pub fn toggle_pause(&self) -> Option<bool> {
+        let current = self.current.lock().unwrap();
+        let token = current.as_ref().filter(|token| !token.is_cancelled())?;
+        token.set_paused(!token.is_paused());
+        Some(token.is_paused())
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.current.lock().unwrap().is_some()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.current.lock().unwrap().as_ref().is_some_and(|token| token.is_paused())
+    }
+
+    fn finish(&self, token: &CancelToken) {
+        let mut current = self.current.lock().unwrap();
+        if current.as_ref().is_some_and(|c| Arc::ptr_eq(&c.state, &token.state)) {
+            *current = None;
+        }
+    }
+}
+
+/// 正在运行的测试持有的令牌，离开作用域时从 TestControl 注销
+pub struct ActiveRun {
+    control: &'static TestControl,
+    token: CancelToken,
+}
+
+impl Deref for ActiveRun {
+    type Target = CancelToken;
+
+    fn deref(&self) -> &CancelToken {
+        &self.token
+    }
+}
+
+impl Drop for ActiveRun {
+    fn drop(&mut self) {
+        self.control.finish(&self.token);
+    }
+}
+
+/// 在测试线程中运行测试主体，panic 时返回错误说明，调用方据此把测试标记为失败而不是一直显示运行中
+pub fn catch_panic<T>(f: impl FnOnce() -> T) -> Result<T, String> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let reason = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "未知错误".to_string());
+        format!("测试异常退出: {}", reason)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_lifecycle() {
+        static CONTROL: TestControl = TestControl::new();
+        assert!(!CONTROL.cancel());
+        assert_eq!(CONTROL.toggle_pause(), None);
+
+        let run = CONTROL.begin();
+        assert!(CONTROL.is_running());
+        assert_eq!(CONTROL.toggle_pause(), Some(true));
+        assert!(run.is_paused());
+
+        // 取消会解除暂停，checkpoint 立即返回
+        assert!(CONTROL.cancel());
+        assert!(!CONTROL.cancel());
+        assert!(!run.is_paused());
+        assert_eq!(run.checkpoint(), Err(Cancelled));
+
+        drop(run);
+        assert!(!CONTROL.is_running());
+    }
+
+    #[test]
+    fn test_try_begin_refuses_while_running() {
+        static CONTROL: TestControl = TestControl::new();
+        let run = CONTROL.try_begin().unwrap();
+        assert!(CONTROL.try_begin().is_none());
+        assert!(!run.is_cancelled());
+
+        // panic 的测试也会注销令牌
+        let result = catch_panic(move || {
+            let _run = run;
+            panic!("磁盘已满");
+        });
+        assert_eq!(result, Err("测试异常退出: 磁盘已满".to_string()));
+        assert!(CONTROL.try_begin().is_some());
+    }
+
+    #[test]
+    fn test_stale_run_does_not_unregister_new_one() {
+        static CONTROL: TestControl = TestControl::new();
+        let old = CONTROL.begin();
+        let new = CONTROL.begin();
+        // 开始新的测试时旧令牌被取消
+        assert!(old.is_cancelled());
+        drop(old);
+        assert!(CONTROL.is_running());
+        assert!(new.checkpoint().is_ok());
+    }
+}
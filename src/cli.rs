@@ -0,0 +1,871 @@
+// src/cli.rs
+// 无界面命令行模式：复用各 handler 模块，结果输出到 stdout，进度输出到 stderr
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::cancel::CancelToken;
+use crate::handlers::{self, cpu_test, disk_test, dns_test, fio, iperf, memory_test, network_test, ping, port_manager, route_prefix, route_trace, system_info, unlock_check};
+use crate::handlers::network_test::{IpProtocol, PingTarget};
+use crate::handlers::dns_test::DnsResolver;
+use crate::handlers::iperf::IperfTarget;
+use crate::handlers::route_trace::RouteTrace;
+use crate::handlers::disk_test::{DiskIoMode, SyncMode};
+use crate::handlers::ping::{PingOptions, PingReply};
+use crate::handlers::port_manager::PortSpec;
+use crate::menu::MenuItem;
+use crate::report::{CpuReport, DiskReport, HostReport, MemoryReport, NetworkReport, Report};
+
+/// 退出码
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_INTERRUPTED: i32 = 130;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const NETWORK_INFO_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BenchTarget {
+    Disk,
+    Cpu,
+    Mem,
+    Net,
+    All,
+}
+
+impl BenchTarget {
+    fn includes(&self, target: BenchTarget) -> bool {
+        *self == BenchTarget::All || *self == target
+    }
+}
+
+/// 硬盘测试选项
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiskOptions {
+    pub dir: Option<PathBuf>,
+    pub io_mode: DiskIoMode,
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    SysInfo { json: bool },
+    Bench { target: BenchTarget, json: bool, disk: DiskOptions },
+    Port { open: bool, spec: PortSpec },
+    Ping { host: String, port: u16, count: usize },
+    Trace { host: String, rounds: usize },
+    Iperf { target: IperfTarget },
+    IperfServer { port: u16 },
+    Dns { resolvers: Vec<String> },
+    Unlock,
+    Guide(MenuItem),
+    Help,
+    Version,
+}
+
+const USAGE: &str = "\
+用法: onekey [命令] [选项]
+
+不带参数时启动交互式界面。
+
+命令:
+  sysinfo                  显示系统信息
+  bench disk               硬盘读写性能测试
+  bench cpu                CPU 性能测试
+  bench mem                内存带宽和延迟测试
+  bench net                网速测试
+  bench all                依次执行以上全部测试
+  port open <端口>         开放防火墙端口，如 8080/tcp、8000-9000/udp、443
+  port close <端口>        关闭防火墙端口
+  ping <主机[:端口]>       测量到主机的延迟和丢包，ICMP 不可用时改用 TCP 连接该端口（默认 443）
+  iperf <主机[:端口]>      用 iperf3 测量到主机的 TCP 下载/上传吞吐量、重传和 UDP 丢包（默认端口 5201）
+  iperf server             在本机前台运行 iperf3 服务端，供另一台机器测试
+  dns [解析器...]          测试系统和公共解析器的 UDP/TCP/DoH 解析延迟、失败率并检测污染，
+                           可指定解析器，如 1.1.1.1、9.9.9.9:53、https://dns.google/dns-query
+  unlock                   检测 Netflix、Disney+、YouTube Premium、ChatGPT 等服务的解锁情况和地区
+  trace <主机>             追踪到主机的路由，逐跳统计延迟和丢包并标注 ASN/运营商
+  history                  列出历史测试记录
+  gfw | k3s | k8s | tcp    显示对应功能的操作说明
+  help                     显示本帮助
+  version                  显示版本号
+
+选项:
+  --json                   sysinfo 和 bench 以 JSON 报告格式输出
+  --dir <目录>             硬盘测试使用的目录（默认 /var/tmp 所在的磁盘）
+  --buffered               硬盘测试使用缓存 I/O（默认 O_DIRECT 绕过页缓存）
+  --sync <方式>            硬盘测试每次写入后同步: fdatasync 或 fsync
+  --iodepth <n>            硬盘测试每个任务的队列深度，默认 1
+  --numjobs <n>            硬盘测试并发任务数，每个任务使用独立的测试文件，默认 1
+  --profile <名称>         硬盘测试配置: builtin、fio 或配置目录 fio/ 下的 .fio 文件名
+  --port <n>               iperf server 监听的端口，默认 5201
+  --count <n>              ping 的探测次数，默认 10；trace 的探测轮数，默认 10
+
+退出码: 0 成功, 1 执行失败, 2 参数错误, 130 被 Ctrl+C 中断
+";
+
+/// 解析命令行参数（不含程序名），没有参数时返回 None 表示进入界面模式
+pub fn parse_args(args: &[String]) -> Result<Option<Command>, String> {
+    let mut args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let json = take_flag(&mut args, "--json");
+    let buffered = take_flag(&mut args, "--buffered");
+    let dir = take_value(&mut args, "--dir")?;
+    let sync = take_value(&mut args, "--sync")?;
+    let profile = take_value(&mut args, "--profile")?;
+    let queue_depth = take_value(&mut args, "--iodepth")?;
+    let jobs = take_value(&mut args, "--numjobs")?;
+    let count = take_value(&mut args, "--count")?;
+    let port = take_value(&mut args, "--port")?;
+
+    let has_disk_options = buffered
+        || dir.is_some()
+        || sync.is_some()
+        || profile.is_some()
+        || queue_depth.is_some()
+        || jobs.is_some();
+    let disk = DiskOptions {
+        dir: dir.map(PathBuf::from),
+        io_mode: DiskIoMode {
+            direct: !buffered,
+            sync: match sync {
+                None | Some("none") => SyncMode::None,
+                Some("fdatasync") => SyncMode::Fdatasync,
+                Some("fsync") => SyncMode::Fsync,
+                Some(other) => return Err(format!("未知的同步方式: {}", other)),
+            },
+            queue_depth: parse_count(queue_depth, "--iodepth", 256)?,
+            jobs: parse_count(jobs, "--numjobs", 64)?,
+        },
+        profile: profile.map(str::to_string),
+    };
+
+    let command = match args.as_slice() {
+        [] if json => return Err("--json 需要配合 sysinfo 或 bench 使用".to_string()),
+        [] => return Ok(None),
+        ["help"] | ["-h"] | ["--help"] => Command::Help,
+        ["version"] | ["-V"] | ["--version"] => Command::Version,
+        ["sysinfo"] => Command::SysInfo { json },
+        ["bench", target] => Command::Bench {
+            target: match *target {
+                "disk" => BenchTarget::Disk,
+                "cpu" => BenchTarget::Cpu,
+                "mem" => BenchTarget::Mem,
+                "net" => BenchTarget::Net,
+                "all" => BenchTarget::All,
+                other => return Err(format!("未知的测试项目: {}", other)),
+            },
+            json,
+            disk: disk.clone(),
+        },
+        ["port", action, spec] => {
+            let open = match *action {
+                "open" => true,
+                "close" => false,
+                other => return Err(format!("未知的端口操作: {}", other)),
+            };
+            Command::Port { open, spec: PortSpec::parse(spec)? }
+        }
+        ["ping", target] => {
+            let (host, port) = network_test::split_host_port(target, PingOptions::default().port)?;
+            let count = match count {
+                Some(_) => parse_count(count, "--count", 1000)? as usize,
+                None => PingOptions::default().count,
+            };
+            Command::Ping { host, port, count }
+        }
+        ["iperf", "server"] => {
+            let port = match port {
+                Some(value) => value.parse::<u16>().ok().filter(|&p| p > 0).ok_or_else(|| format!("端口无效: {}", value))?,
+                None => iperf::DEFAULT_PORT,
+            };
+            Command::IperfServer { port }
+        }
+        ["iperf", target] => Command::Iperf { target: IperfTarget::parse(target, None)? },
+        ["trace", target] => {
+            // 接受与 ping 相同的写法，端口对路由追踪没有意义
+            let (host, _) = network_test::split_host_port(target, 0)?;
+            let rounds = match count {
+                Some(_) => parse_count(count, "--count", 1000)? as usize,
+                None => route_trace::rounds(),
+            };
+            Command::Trace { host, rounds }
+        }
+        ["dns", resolvers @ ..] => {
+            for resolver in resolvers {
+                DnsResolver::parse(resolver)?;
+            }
+            Command::Dns { resolvers: resolvers.iter().map(|r| r.to_string()).collect() }
+        }
+        ["unlock"] => Command::Unlock,
+        ["gfw"] => Command::Guide(MenuItem::CrossGFW),
+        ["k3s"] => Command::Guide(MenuItem::K3s),
+        ["k8s"] => Command::Guide(MenuItem::K8s),
+        ["tcp"] => Command::Guide(MenuItem::TcpOptimization),
+        ["history"] => Command::Guide(MenuItem::History),
+        _ => return Err(format!("无法识别的参数: {}", args.join(" "))),
+    };
+
+    if json && !matches!(command, Command::SysInfo { .. } | Command::Bench { .. }) {
+        return Err("--json 只适用于 sysinfo 和 bench".to_string());
+    }
+    if has_disk_options && !matches!(command, Command::Bench { target, .. } if target.includes(BenchTarget::Disk)) {
+        return Err("硬盘测试选项只适用于 bench disk 和 bench all".to_string());
+    }
+    if count.is_some() && !matches!(command, Command::Ping { .. } | Command::Trace { .. }) {
+        return Err("--count 只适用于 ping 和 trace".to_string());
+    }
+    if port.is_some() && !matches!(command, Command::IperfServer { .. }) {
+        return Err("--port 只适用于 iperf server".to_string());
+    }
+
+    Ok(Some(command))
+}
+
+// 解析 1..=max 的计数参数，未指定时为 1
+fn parse_count(value: Option<&str>, name: &str, max: u32) -> Result<u32, String> {
+    match value {
+        None => Ok(1),
+        Some(value) => value
+            .parse::<u32>()
+            .ok()
+            .filter(|n| (1..=max).contains(n))
+            .ok_or_else(|| format!("{} 必须是 1 到 {} 之间的整数", name, max)),
+    }
+}
+
+// 取出开关参数
+fn take_flag(args: &mut Vec<&str>, name: &str) -> bool {
+    let before = args.len();
+    args.retain(|arg| *arg != name);
+    args.len() != before
+}
+
+// 取出带值的参数
+fn take_value<'a>(args: &mut Vec<&'a str>, name: &str) -> Result<Option<&'a str>, String> {
+    match args.iter().position(|arg| *arg == name) {
+        Some(index) if index + 1 < args.len() => {
+            let value = args.remove(index + 1);
+            args.remove(index);
+            Ok(Some(value))
+        }
+        Some(_) => Err(format!("{} 需要指定值", name)),
+        None => Ok(None),
+    }
+}
+
+/// 执行命令，返回进程退出码
+pub async fn run(command: Command) -> i32 {
+    match command {
+        Command::Help => {
+            print!("{}", USAGE);
+            EXIT_OK
+        }
+        Command::Version => {
+            println!("onekey {}", env!("CARGO_PKG_VERSION"));
+            EXIT_OK
+        }
+        Command::SysInfo { json } => {
+            let info = collect_system_info().await;
+            if json {
+                println!("{}", Report::new(HostReport::from_system_info(&info)).to_json());
+            } else {
+                print!("{}", system_info::format_system_info(&info));
+            }
+            EXIT_OK
+        }
+        Command::Bench { target, json, disk } => {
+            disk_test::set_io_mode(disk.io_mode);
+            if let Some(name) = disk.profile {
+                match fio::find_profile(&name) {
+                    Some(profile) => disk_test::set_profile(profile),
+                    None => {
+                        eprintln!("未找到硬盘测试配置: {}", name);
+                        return EXIT_FAILURE;
+                    }
+                }
+            }
+            if let Some(dir) = disk.dir {
+                if let Err(e) = disk_test::set_test_dir(&dir) {
+                    eprintln!("{}", e);
+                    return EXIT_FAILURE;
+                }
+            }
+            run_bench(target, json).await
+        }
+        Command::Port { open, spec } => match port_manager::apply_port_rule(&spec, open) {
+            Ok(log) => {
+                print!("{}", log);
+                EXIT_OK
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                EXIT_FAILURE
+            }
+        },
+        Command::Ping { host, port, count } => run_ping(&host, port, count).await,
+        Command::Trace { host, rounds } => run_trace(host, rounds).await,
+        Command::Iperf { target } => run_iperf(&target).await,
+        Command::IperfServer { port } => run_iperf_server(port).await,
+        Command::Dns { resolvers } => run_dns(&resolvers).await,
+        Command::Unlock => run_unlock().await,
+        Command::Guide(MenuItem::CrossGFW) => {
+            // 界面中该项只是占位，命令行直接输出 sing-box 和 xray 的说明
+            print!("{}\n{}", handlers::sing_box::get_info(), handlers::xray::get_info());
+            EXIT_OK
+        }
+        Command::Guide(item) => {
+            print!("{}", handlers::get_content(item));
+            EXIT_OK
+        }
+    }
+}
+
+/// 打印用法错误并返回对应退出码
+pub fn usage_error(message: &str) -> i32 {
+    eprintln!("错误: {}\n", message);
+    eprint!("{}", USAGE);
+    EXIT_USAGE
+}
+
+async fn collect_system_info() -> system_info::SystemInfo {
+    let start = Instant::now();
+    let mut info = system_info::SystemInfo::get_current();
+
+    // 公网 IP 等信息在后台线程获取，等待其完成
+    while info.network_loading && start.elapsed() < NETWORK_INFO_TIMEOUT {
+        tokio::time::sleep(POLL_INTERVAL).await;
+        info = system_info::SystemInfo::get_current();
+    }
+
+    info
+}
+
+/// 依次执行选中的测试，任一测试失败时返回失败退出码
+async fn run_bench(target: BenchTarget, json: bool) -> i32 {
+    // JSON 报告需要主机信息，提前在后台开始获取
+    let mut report = json.then(|| Report::new(HostReport::from_system_info(&system_info::SystemInfo::get_current())));
+    let mut exit_code = EXIT_OK;
+
+    // Ctrl+C 时取消正在运行的测试，等测试清理完临时文件后再退出
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let signal_watcher = {
+        let interrupted = interrupted.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                interrupted.store(true, Ordering::SeqCst);
+                eprintln!("正在取消测试...");
+                for item in [MenuItem::DiskTest, MenuItem::CpuTest, MenuItem::MemoryTest, MenuItem::NetworkSpeedTest] {
+                    if let Some(control) = handlers::test_control(item) {
+                        control.cancel();
+                    }
+                }
+            }
+        })
+    };
+    let is_interrupted = || interrupted.load(Ordering::SeqCst);
+
+    if target.includes(BenchTarget::Disk) && !is_interrupted() {
+        let info = run_disk_bench().await;
+        if info.error_message.is_some() {
+            exit_code = EXIT_FAILURE;
+        }
+        match report.as_mut() {
+            Some(report) => report.disk = Some(DiskReport::from_test_info(&info)),
+            None => print!("{}", disk_test::format_disk_test_info(&info)),
+        }
+    }
+
+    if target.includes(BenchTarget::Cpu) && !is_interrupted() {
+        let info = run_cpu_bench().await;
+        if info.error_message.is_some() || info.results.is_empty() {
+            exit_code = EXIT_FAILURE;
+        }
+        match report.as_mut() {
+            Some(report) => report.cpu = Some(CpuReport::from_test_info(&info)),
+            None => print!("{}", cpu_test::get_info()),
+        }
+    }
+
+    if target.includes(BenchTarget::Mem) && !is_interrupted() {
+        let info = run_mem_bench().await;
+        if !info.is_complete() {
+            exit_code = EXIT_FAILURE;
+        }
+        match report.as_mut() {
+            Some(report) => report.memory = Some(MemoryReport::from_test_info(&info)),
+            None => print!("{}", memory_test::get_info()),
+        }
+    }
+
+    if target.includes(BenchTarget::Net) && !is_interrupted() {
+        let info = run_net_bench().await;
+        let providers = network_test::get_network_providers().providers;
+
+        // 所有服务器都失败才视为执行失败
+        let any_completed = info
+            .results
+            .values()
+            .any(|result| result.status == network_test::TestStatus::Completed);
+        if !any_completed {
+            exit_code = EXIT_FAILURE;
+        }
+        match report.as_mut() {
+            Some(report) => report.network = Some(NetworkReport::from_test_info(&info, &providers)),
+            None => print!("{}", network_test::format_network_test_info(&info, &providers)),
+        }
+    }
+
+    signal_watcher.abort();
+    if is_interrupted() {
+        return EXIT_INTERRUPTED;
+    }
+
+    if let Some(mut report) = report {
+        // 测试期间公网信息通常已获取完毕，生成报告前刷新一次
+        report.host = HostReport::from_system_info(&collect_system_info().await);
+        println!("{}", report.to_json());
+    }
+
+    exit_code
+}
+
+async fn run_disk_bench() -> disk_test::DiskTestInfo {
+    let started = disk_test::start_disk_test();
+    let mut info = disk_test::get_current_test_info();
+    if let Err(e) = started {
+        info.error_message = Some(e);
+        return info;
+    }
+    if let Some(ref target) = info.target {
+        eprintln!("测试目标: {}", disk_test::describe_target(target));
+        if let Some(warning) = target.warning() {
+            eprintln!("警告: {}", warning);
+        }
+    }
+    let mut last_stage = String::new();
+
+    while info.is_testing || (info.results.is_empty() && info.error_message.is_none()) {
+        report_progress(&mut last_stage, &info.current_test, info.progress as f64);
+        tokio::time::sleep(POLL_INTERVAL).await;
+        info = disk_test::get_current_test_info();
+    }
+
+    if let Some(ref note) = info.io_mode_note {
+        eprintln!("警告: {}", note);
+    }
+    if let Some(ref note) = info.profile_note {
+        eprintln!("警告: {}", note);
+    }
+    info
+}
+
+async fn run_cpu_bench() -> cpu_test::CpuTestInfo {
+    let started = cpu_test::start_cpu_test();
+    let mut info = cpu_test::get_current_test_info();
+    if let Err(e) = started {
+        info.error_message = Some(e);
+        return info;
+    }
+    let mut last_stage = String::new();
+
+    while info.is_testing {
+        report_progress(&mut last_stage, &info.current_test, info.progress as f64);
+        tokio::time::sleep(POLL_INTERVAL).await;
+        info = cpu_test::get_current_test_info();
+    }
+
+    info
+}
+
+async fn run_mem_bench() -> memory_test::MemoryTestInfo {
+    let started = memory_test::start_memory_test();
+    let mut info = memory_test::get_current_test_info();
+    if let Err(e) = started {
+        info.error_message = Some(e);
+        return info;
+    }
+    let mut last_stage = String::new();
+
+    while info.is_testing {
+        report_progress(&mut last_stage, &info.current_test, info.progress as f64);
+        tokio::time::sleep(POLL_INTERVAL).await;
+        info = memory_test::get_current_test_info();
+    }
+
+    info
+}
+
+async fn run_net_bench() -> network_test::NetworkTestInfo {
+    let started = network_test::start_network_test();
+    let mut info = network_test::get_current_test_info();
+    if let Err(e) = started {
+        info.error_message = Some(e);
+        return info;
+    }
+    let mut last_stage = String::new();
+
+    while info.is_testing {
+        report_progress(&mut last_stage, &info.current_stage, info.overall_progress);
+        tokio::time::sleep(POLL_INTERVAL).await;
+        info = network_test::get_current_test_info();
+    }
+
+    info
+}
+
+/// 逐次输出探测结果，最后输出统计；全部丢包时返回失败退出码
+async fn run_ping(host: &str, port: u16, count: usize) -> i32 {
+    let options = PingOptions { count, port, ..Default::default() };
+    let cancel = CancelToken::new();
+    let signal_watcher = {
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                cancel.cancel();
+            }
+        })
+    };
+
+    println!("PING {}，共 {} 次", host, count);
+    let result = ping::ping(host, &options, &cancel, |reply: &PingReply| match reply.rtt_ms {
+        Some(rtt) => println!("[{}] seq={} 时间={:.1} ms", reply.method.name(), reply.seq + 1, rtt),
+        None => println!("[{}] seq={} 超时", reply.method.name(), reply.seq + 1),
+    })
+    .await;
+    signal_watcher.abort();
+
+    let stats = match result {
+        Ok(stats) => stats,
+        Err(_) if cancel.is_cancelled() => return EXIT_INTERRUPTED,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_FAILURE;
+        }
+    };
+
+    println!("\n--- {} ({}) 的统计 ---", host, stats.address);
+    match stats.fallback {
+        Some(ref reason) => println!("方式: TCP 连接 {} 端口（{}）", port, reason),
+        None => println!("方式: ICMP"),
+    }
+    println!("{}", stats.summary());
+    if stats.received > 0 {
+        EXIT_OK
+    } else {
+        EXIT_FAILURE
+    }
+}
+
+/// 依次运行 TCP 下载、TCP 上传和 UDP 下载测试，进度输出到 stderr，结果输出到 stdout
+async fn run_iperf(target: &IperfTarget) -> i32 {
+    let cancel = CancelToken::new();
+    let signal_watcher = {
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                cancel.cancel();
+            }
+        })
+    };
+
+    let ping_target = PingTarget { host: target.host.clone(), port: target.port, protocol: IpProtocol::Auto };
+    let config = network_test::speed_test_config();
+    let last_stage = std::sync::Mutex::new(String::new());
+    let result = network_test::measure_iperf(target, &ping_target, &config, &cancel, |stage, fraction, _| {
+        report_progress(&mut last_stage.lock().unwrap(), stage, fraction * 100.0);
+    })
+    .await;
+    signal_watcher.abort();
+
+    let result = match result {
+        Ok(result) => result,
+        Err(_) if cancel.is_cancelled() => return EXIT_INTERRUPTED,
+        Err(e) => {
+            eprintln!("{}", e);
+            return EXIT_FAILURE;
+        }
+    };
+
+    println!("iperf3 {}（{}）", target.label(), config.label());
+    match result.ping_method {
+        Some(method) => println!("延迟: {:.1} ms, 抖动 {:.1} ms, 丢包 {:.0}% ({})", result.ping, result.jitter, result.packet_loss, method.name()),
+        None => println!("延迟: 探测失败"),
+    }
+    println!("TCP 下载: {:.2} Mbps", result.download_speed);
+    println!("TCP 上传: {:.2} Mbps", result.upload_speed);
+    if let Some(ref stats) = result.iperf {
+        println!("{}", stats.summary());
+    }
+    EXIT_OK
+}
+
+/// 在前台运行 iperf3 服务端，直到 Ctrl+C
+async fn run_iperf_server(port: u16) -> i32 {
+    let mut command = tokio::process::Command::from(iperf::server_command(port));
+    let mut child = match command.kill_on_drop(true).spawn() {
+        Ok(child) => child,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            eprintln!("未安装 iperf3");
+            return EXIT_FAILURE;
+        }
+        Err(e) => {
+            eprintln!("无法启动 iperf3 服务端: {}", e);
+            return EXIT_FAILURE;
+        }
+    };
+
+    eprintln!("iperf3 服务端监听端口 {}，在另一台机器上运行 onekey iperf <本机地址>:{}，按 Ctrl+C 停止", port, port);
+    tokio::select! {
+        status = child.wait() => match status {
+            Ok(status) if status.success() => EXIT_OK,
+            _ => EXIT_FAILURE,
+        },
+        _ = tokio::signal::ctrl_c() => {
+            let _ = child.kill().await;
+            EXIT_INTERRUPTED
+        }
+    }
+}
+
+/// 并行查询各解析器，每个解析器的一种查询方式完成时向 stderr 输出进度
+async fn run_dns(resolvers: &[String]) -> i32 {
+    let plan = dns_test::load_test_plan(resolvers);
+    for error in &plan.errors {
+        eprintln!("{}", error);
+    }
+    if plan.resolvers.is_empty() {
+        eprintln!("没有可以测试的解析器");
+        return EXIT_FAILURE;
+    }
+
+    let cancel = CancelToken::new();
+    let signal_watcher = {
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                cancel.cancel();
+            }
+        })
+    };
+
+    let mut probes = dns_test::probes_for(&plan.resolvers, plan.domains.len());
+    let total = probes.len();
+    let full = plan.domains.len() * dns_test::QUERY_ROUNDS;
+    let finished = std::sync::Mutex::new((0usize, String::new()));
+    let result = dns_test::run_dns_test(&mut probes, &plan.domains, &cancel, |_, probe| {
+        let done = probe.queries == full || (probe.is_unreachable() && probe.queries == plan.domains.len());
+        if done {
+            let (count, last_stage) = &mut *finished.lock().unwrap();
+            *count += 1;
+            let stage = format!("{} {} 完成", probe.resolver, probe.transport.name());
+            report_progress(last_stage, &stage, *count as f64 / total as f64 * 100.0);
+        }
+    })
+    .await;
+    signal_watcher.abort();
+
+    if let Err(e) = result {
+        if cancel.is_cancelled() {
+            return EXIT_INTERRUPTED;
+        }
+        eprintln!("{}", e);
+        return EXIT_FAILURE;
+    }
+
+    println!("测试域名: {}（每个查询 {} 轮）", plan.domains.join(", "), dns_test::QUERY_ROUNDS);
+    print!("{}", dns_test::format_dns_results(&probes));
+    if probes.iter().all(|probe| probe.is_unreachable()) {
+        EXIT_FAILURE
+    } else {
+        EXIT_OK
+    }
+}
+
+/// 并行检测各服务，每个服务完成时向 stderr 输出进度
+async fn run_unlock() -> i32 {
+    let plan = unlock_check::load_rules();
+    for error in &plan.errors {
+        eprintln!("{}", error);
+    }
+
+    let cancel = CancelToken::new();
+    let signal_watcher = {
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                cancel.cancel();
+            }
+        })
+    };
+
+    let total = plan.services.len();
+    let finished = std::sync::Mutex::new((0usize, String::new()));
+    let result = unlock_check::run_checks(&plan.services, &cancel, |_, result| {
+        let (count, last_stage) = &mut *finished.lock().unwrap();
+        *count += 1;
+        report_progress(last_stage, &format!("{} 检测完成", result.service), *count as f64 / total as f64 * 100.0);
+    })
+    .await;
+    signal_watcher.abort();
+
+    match result {
+        Ok(results) => {
+            print!("{}", unlock_check::format_unlock_results(&results));
+            // 全部请求失败时多半是本机网络问题，而不是服务不可用
+            if results.iter().all(|result| result.status == unlock_check::UnlockStatus::Failed) {
+                EXIT_FAILURE
+            } else {
+                EXIT_OK
+            }
+        }
+        Err(_) if cancel.is_cancelled() => EXIT_INTERRUPTED,
+        Err(e) => {
+            eprintln!("{}", e);
+            EXIT_FAILURE
+        }
+    }
+}
+
+/// 逐轮探测，进度输出到 stderr，结束后输出逐跳统计；无法解析或探测失败时返回失败退出码
+async fn run_trace(host: String, rounds: usize) -> i32 {
+    let cancel = CancelToken::new();
+    let signal_watcher = {
+        let cancel = cancel.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                cancel.cancel();
+            }
+        })
+    };
+
+    let worker = {
+        let cancel = cancel.clone();
+        tokio::task::spawn_blocking(move || {
+            let (table, errors) = route_prefix::load_prefix_table();
+            for error in errors {
+                eprintln!("{}", error);
+            }
+            let target = PingTarget { host: host.clone(), port: 0, protocol: IpProtocol::Auto };
+            let mut traces = [RouteTrace::new(host, target)];
+            traces[0].prepare();
+            let mut last_stage = String::new();
+            for round in 0..rounds {
+                if !traces[0].is_ready() || cancel.is_cancelled() {
+                    break;
+                }
+                let stage = format!("第 {}/{} 轮探测", round + 1, rounds);
+                report_progress(&mut last_stage, &stage, round as f64 / rounds as f64 * 100.0);
+                let started = std::time::Instant::now();
+                route_trace::trace_round(&mut traces, &table);
+                if round + 1 < rounds {
+                    std::thread::sleep(route_trace::ROUND_INTERVAL.saturating_sub(started.elapsed()));
+                }
+            }
+            let [trace] = traces;
+            trace
+        })
+    };
+    let result = worker.await;
+    signal_watcher.abort();
+
+    let trace = match result {
+        Ok(trace) => trace,
+        Err(e) => {
+            eprintln!("路由追踪异常退出: {}", e);
+            return EXIT_FAILURE;
+        }
+    };
+    print!("{}", route_trace::format_route_trace(&trace));
+    if cancel.is_cancelled() {
+        EXIT_INTERRUPTED
+    } else if trace.error.is_some() {
+        EXIT_FAILURE
+    } else {
+        EXIT_OK
+    }
+}
+
+/// 阶段变化时向 stderr 输出一行进度，避免污染 stdout 中的结果
+fn report_progress(last_stage: &mut String, stage: &str, progress: f64) {
+    if !stage.is_empty() && stage != last_stage {
+        eprintln!("[{:>3.0}%] {}", progress.min(100.0), stage);
+        *last_stage = stage.to_string();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Result<Option<Command>, String> {
+        let args: Vec<String> = line.split_whitespace().map(String::from).collect();
+        parse_args(&args)
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(parse(""), Ok(None));
+        assert_eq!(parse("sysinfo"), Ok(Some(Command::SysInfo { json: false })));
+        assert_eq!(
+            parse("bench net --json"),
+            Ok(Some(Command::Bench { target: BenchTarget::Net, json: true, disk: DiskOptions::default() }))
+        );
+        assert_eq!(
+            parse("bench disk --dir /data --sync fdatasync --buffered --iodepth 32 --numjobs 4"),
+            Ok(Some(Command::Bench {
+                target: BenchTarget::Disk,
+                json: false,
+                disk: DiskOptions {
+                    dir: Some(PathBuf::from("/data")),
+                    io_mode: DiskIoMode { direct: false, sync: SyncMode::Fdatasync, queue_depth: 32, jobs: 4 },
+                    profile: None,
+                },
+            }))
+        );
+        assert_eq!(
+            parse("bench mem"),
+            Ok(Some(Command::Bench { target: BenchTarget::Mem, json: false, disk: DiskOptions::default() }))
+        );
+        assert_eq!(
+            parse("ping example.com --count 5"),
+            Ok(Some(Command::Ping { host: "example.com".to_string(), port: 443, count: 5 }))
+        );
+        assert_eq!(
+            parse("ping [2001:db8::1]:80"),
+            Ok(Some(Command::Ping { host: "2001:db8::1".to_string(), port: 80, count: 10 }))
+        );
+        assert_eq!(parse("k3s"), Ok(Some(Command::Guide(MenuItem::K3s))));
+        assert_eq!(
+            parse("port open 8080/tcp"),
+            Ok(Some(Command::Port { open: true, spec: PortSpec::parse("8080/tcp").unwrap() }))
+        );
+
+        assert!(parse("bench gpu").is_err());
+        assert!(parse("port drop 8080").is_err());
+        assert!(parse("port open abc").is_err());
+        assert!(parse("sysinfo extra").is_err());
+        assert!(parse("k3s --json").is_err());
+        assert!(parse("--json").is_err());
+        assert!(parse("bench disk --dir").is_err());
+        assert!(parse("bench cpu --dir /data").is_err());
+        assert!(parse("bench net --profile fio").is_err());
+        assert!(parse("bench disk --sync always").is_err());
+        assert!(parse("bench disk --iodepth 0").is_err());
+        assert!(parse("bench disk --numjobs many").is_err());
+        assert!(parse("ping example.com --count 0").is_err());
+        assert_eq!(
+            parse("trace [2001:db8::1] --count 20"),
+            Ok(Some(Command::Trace { host: "2001:db8::1".to_string(), rounds: 20 }))
+        );
+        assert!(parse("bench net --count 5").is_err());
+        assert_eq!(parse("iperf server --port 9000"), Ok(Some(Command::IperfServer { port: 9000 })));
+        assert!(matches!(parse("iperf 203.0.113.5"), Ok(Some(Command::Iperf { target })) if target.port == 5201));
+        assert!(parse("iperf 203.0.113.5 --port 9000").is_err());
+        assert_eq!(parse("dns"), Ok(Some(Command::Dns { resolvers: Vec::new() })));
+        assert!(matches!(parse("dns 1.1.1.1 https://dns.google/dns-query"), Ok(Some(Command::Dns { resolvers })) if resolvers.len() == 2));
+        assert!(parse("dns dns.example.com").is_err());
+        assert_eq!(parse("unlock"), Ok(Some(Command::Unlock)));
+        assert!(parse("unlock --count 3").is_err());
+    }
+}
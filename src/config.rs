@@ -0,0 +1,48 @@
+// src/config.rs
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+
+/// 配置目录：$ONEKEY_CONFIG_DIR > $XDG_CONFIG_HOME/onekey > ~/.config/onekey
+pub fn config_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("ONEKEY_CONFIG_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("onekey"));
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config").join("onekey"))
+}
+
+/// 在配置目录中按顺序查找第一个存在的文件
+pub fn find_config_file(names: &[&str]) -> Option<PathBuf> {
+    let dir = config_dir()?;
+    names.iter().map(|name| dir.join(name)).find(|path| path.is_file())
+}
+
+/// 读取 TOML 或 JSON 配置文件（按扩展名区分）
+pub fn load_config_file<T: DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("无法读取 {}: {}", path.display(), e))?;
+
+    let is_json = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("json"))
+        .unwrap_or(false);
+
+    if is_json {
+        serde_json::from_str(&content).map_err(|e| format!("{} 解析失败: {}", path.display(), e))
+    } else {
+        toml::from_str(&content).map_err(|e| format!("{} 解析失败: {}", path.display(), e))
+    }
+}
+
+/// 数据目录：$ONEKEY_DATA_DIR > $XDG_DATA_HOME/onekey > ~/.local/share/onekey
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("ONEKEY_DATA_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    if let Some(dir) = std::env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("onekey"));
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share").join("onekey"))
+}
@@ -9,7 +9,9 @@ pub fn handle_events(app: &mut App) -> Result<bool> {
     let poll_duration = match app.menu.selected_item() {
         crate::menu::MenuItem::DiskTest | 
         crate::menu::MenuItem::CpuTest | 
-        crate::menu::MenuItem::NetworkSpeedTest => {
+        crate::menu::MenuItem::NetworkSpeedTest |
+        crate::menu::MenuItem::MemoryTest |
+        crate::menu::MenuItem::RouteTrace => {
             Duration::from_millis(50)  // 测试时更频繁的更新
         },
         _ => Duration::from_millis(100) // 其他情况保持原有频率
@@ -53,6 +55,151 @@ fn handle_key_press(app: &mut App, key: KeyEvent) -> Result<bool> {
             Ok(true)
         }
         
+        // H 键快速打开历史记录
+        KeyCode::Char('h') | KeyCode::Char('H') if app.show_menu && app.focus_area == FocusArea::Menu => {
+            if app.menu.select_by_number('h') {
+                app.set_focus(FocusArea::Content);
+                app.handle_menu_selection();
+            }
+            Ok(true)
+        }
+        
+        // R 键快速打开内存测试
+        KeyCode::Char('r') | KeyCode::Char('R') if app.show_menu && app.focus_area == FocusArea::Menu => {
+            if app.menu.select_by_number('r') {
+                app.set_focus(FocusArea::Content);
+                app.handle_menu_selection();
+            }
+            Ok(true)
+        }
+        
+        // T 键快速打开路由追踪
+        KeyCode::Char('t') | KeyCode::Char('T') if app.show_menu && app.focus_area == FocusArea::Menu => {
+            if app.menu.select_by_number('t') {
+                app.set_focus(FocusArea::Content);
+                app.handle_menu_selection();
+            }
+            Ok(true)
+        }
+        
+        // U 键快速打开解锁检测
+        KeyCode::Char('u') | KeyCode::Char('U') if app.show_menu && app.focus_area == FocusArea::Menu => {
+            if app.menu.select_by_number('u') {
+                app.set_focus(FocusArea::Content);
+                app.handle_menu_selection();
+            }
+            Ok(true)
+        }
+        
+        // 历史记录界面：B 设置/取消对比基准，R 重新读取
+        KeyCode::Char('b') | KeyCode::Char('B') if is_history_content(app) => {
+            app.toggle_history_baseline();
+            Ok(true)
+        }
+        KeyCode::Char('r') | KeyCode::Char('R') if is_history_content(app) => {
+            crate::handlers::history::reload_history();
+            app.clear_cache();
+            Ok(true)
+        }
+        
+        // 磁盘测试界面：T 切换测试目标
+        KeyCode::Char('t') | KeyCode::Char('T') if is_content(app, crate::menu::MenuItem::DiskTest) => {
+            app.select_next_disk_target();
+            Ok(true)
+        }
+        
+        // 磁盘测试界面：P 切换测试配置
+        KeyCode::Char('p') | KeyCode::Char('P') if is_content(app, crate::menu::MenuItem::DiskTest) => {
+            app.select_next_disk_profile();
+            Ok(true)
+        }
+        
+        // 磁盘测试界面：I 开关直接 I/O，S 切换同步方式
+        KeyCode::Char('i') | KeyCode::Char('I') if is_content(app, crate::menu::MenuItem::DiskTest) => {
+            app.toggle_disk_direct_io();
+            Ok(true)
+        }
+        KeyCode::Char('s') | KeyCode::Char('S') if is_content(app, crate::menu::MenuItem::DiskTest) => {
+            app.cycle_disk_sync_mode();
+            Ok(true)
+        }
+        
+        // 磁盘测试界面：D 切换队列深度，J 切换并发任务数
+        KeyCode::Char('d') | KeyCode::Char('D') if is_content(app, crate::menu::MenuItem::DiskTest) => {
+            app.cycle_disk_queue_depth();
+            Ok(true)
+        }
+        KeyCode::Char('j') | KeyCode::Char('J') if is_content(app, crate::menu::MenuItem::DiskTest) => {
+            app.cycle_disk_jobs();
+            Ok(true)
+        }
+        
+        // 测试界面：U 切换测试时长，C 切换线程/连接数
+        KeyCode::Char('u') | KeyCode::Char('U') if is_test_content(app) => {
+            app.cycle_test_duration();
+            Ok(true)
+        }
+        KeyCode::Char('c') | KeyCode::Char('C') if is_test_content(app) => {
+            app.cycle_test_concurrency();
+            Ok(true)
+        }
+        
+        // 磁盘测试界面：F 切换读取测试文件大小
+        KeyCode::Char('f') | KeyCode::Char('F') if is_content(app, crate::menu::MenuItem::DiskTest) => {
+            app.cycle_disk_file_size();
+            Ok(true)
+        }
+        
+        // 网速测试界面：T 切换测速服务器
+        KeyCode::Char('t') | KeyCode::Char('T') if is_content(app, crate::menu::MenuItem::NetworkSpeedTest) => {
+            app.select_next_network_server();
+            Ok(true)
+        }
+        
+        // 路由追踪界面：T 切换显示的目标
+        KeyCode::Char('t') | KeyCode::Char('T') if is_content(app, crate::menu::MenuItem::RouteTrace) => {
+            app.select_next_route_trace();
+            Ok(true)
+        }
+        
+        // 系统信息界面：D 测试 DNS 解析
+        KeyCode::Char('d') | KeyCode::Char('D') if is_content(app, crate::menu::MenuItem::SystemInfo) => {
+            app.start_dns_test();
+            Ok(true)
+        }
+        
+        // 网速测试界面：S 启动/停止本机的 iperf3 服务端
+        KeyCode::Char('s') | KeyCode::Char('S') if is_content(app, crate::menu::MenuItem::NetworkSpeedTest) => {
+            app.toggle_iperf_server();
+            Ok(true)
+        }
+        
+        // 磁盘测试界面：L 切换延迟分布图显示的测试项
+        KeyCode::Char('l') | KeyCode::Char('L') if is_content(app, crate::menu::MenuItem::DiskTest) => {
+            app.select_next_disk_latency();
+            Ok(true)
+        }
+        
+        // 测试界面：Enter 开始，X 取消，空格暂停/继续，R 重新测试
+        KeyCode::Char('x') | KeyCode::Char('X') if is_test_content(app) => {
+            app.cancel_current_test();
+            Ok(true)
+        }
+        KeyCode::Char(' ') if is_test_content(app) => {
+            app.toggle_current_test_pause();
+            Ok(true)
+        }
+        KeyCode::Enter | KeyCode::Char('r') | KeyCode::Char('R') if is_test_content(app) => {
+            app.start_current_test();
+            Ok(true)
+        }
+        
+        // E 键导出测试报告
+        KeyCode::Char('e') | KeyCode::Char('E') => {
+            app.export_report();
+            Ok(true)
+        }
+        
         // 焦点切换
         KeyCode::Tab => {
             app.toggle_focus();
@@ -75,6 +222,8 @@ fn handle_key_press(app: &mut App, key: KeyEvent) -> Result<bool> {
         KeyCode::Up => {
             if app.focus_area == FocusArea::Menu && app.show_menu {
                 app.menu.previous();
+            } else if is_history_content(app) {
+                app.history_select_previous();
             } else {
                 app.move_up();
             }
@@ -83,6 +232,8 @@ fn handle_key_press(app: &mut App, key: KeyEvent) -> Result<bool> {
         KeyCode::Down => {
             if app.focus_area == FocusArea::Menu && app.show_menu {
                 app.menu.next();
+            } else if is_history_content(app) {
+                app.history_select_next();
             } else {
                 app.move_down();
             }
@@ -120,4 +271,19 @@ fn handle_key_press(app: &mut App, key: KeyEvent) -> Result<bool> {
         
         _ => Ok(true),
     }
-}
\ No newline at end of file
+}
+
+/// 焦点是否在历史记录内容区域
+fn is_history_content(app: &App) -> bool {
+    is_content(app, crate::menu::MenuItem::History)
+}
+
+/// 焦点是否在硬盘/CPU/网速测试的内容区域
+fn is_test_content(app: &App) -> bool {
+    app.focus_area == FocusArea::Content && crate::handlers::test_control(app.menu.selected_item()).is_some()
+}
+
+/// 焦点是否在指定菜单项的内容区域
+fn is_content(app: &App, item: crate::menu::MenuItem) -> bool {
+    app.focus_area == FocusArea::Content && app.menu.selected_item() == item
+}
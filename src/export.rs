@@ -0,0 +1,773 @@
+// src/export.rs
+// 导出可分享的测试报告：同一份 Report 渲染为 Markdown 和自带样式、图表的单文件 HTML
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::handlers::history::format_time;
+use crate::handlers::system_info::{format_bytes_gib, SystemInfo};
+use crate::handlers::cpu_topology::TopologyResult;
+use crate::handlers::crypto_bench::CryptoBenchResult;
+use crate::handlers::memory_test::{format_size, level_latencies};
+use crate::handlers::{cpu_test, disk_test, memory_test, network_test};
+use crate::latency::format_latency_us;
+use crate::report::{
+    CpuReport, DiskReport, DiskResultReport, HostReport, MemoryReport, NetworkReport, Report, SpeedSample,
+};
+
+// 与界面中读写图表一致：读取绿色，写入红色
+const READ_COLOR: &str = "#2e9d4f";
+const WRITE_COLOR: &str = "#d64541";
+
+// SVG 图表尺寸与边距
+const CHART_WIDTH: f64 = 720.0;
+const CHART_HEIGHT: f64 = 280.0;
+const CHART_LEFT: f64 = 64.0;
+const CHART_RIGHT: f64 = 20.0;
+const CHART_TOP: f64 = 36.0;
+const CHART_BOTTOM: f64 = 44.0;
+
+/// 报告中的一张表格
+struct Section {
+    title: &'static str,
+    note: Option<String>,
+    headers: Vec<&'static str>,
+    rows: Vec<Vec<String>>,
+}
+
+/// 汇总当前已完成的测试结果，尚未运行的测试不会被启动
+pub fn current_report() -> Report {
+    let mut report = Report::new(HostReport::from_system_info(&SystemInfo::get_current()));
+
+    if let Some(info) = disk_test::peek_test_info() {
+        if !info.is_testing && !info.results.is_empty() {
+            report.disk = Some(DiskReport::from_test_info(&info));
+        }
+    }
+
+    let info = cpu_test::get_current_test_info();
+    if !info.is_testing && !info.results.is_empty() {
+        report.cpu = Some(CpuReport::from_test_info(&info));
+    }
+
+    let info = memory_test::get_current_test_info();
+    if info.is_complete() {
+        report.memory = Some(MemoryReport::from_test_info(&info));
+    }
+
+    let info = network_test::get_current_test_info();
+    if !info.is_testing && !info.results.is_empty() {
+        let providers = network_test::get_network_providers().providers;
+        report.network = Some(NetworkReport::from_test_info(&info, &providers));
+    }
+
+    report
+}
+
+/// 导出当前结果到当前目录，返回 Markdown 和 HTML 文件路径
+pub fn export_current_report() -> Result<(PathBuf, PathBuf), String> {
+    export_to(Path::new("."), &current_report())
+}
+
+/// 把报告写入 dir 下的 onekey_report_<时间>.md / .html
+pub fn export_to(dir: &Path, report: &Report) -> Result<(PathBuf, PathBuf), String> {
+    let timestamp = chrono::Local::now().format("%Y%m%d_%H%M%S").to_string();
+    let markdown_path = dir.join(format!("onekey_report_{}.md", timestamp));
+    let html_path = dir.join(format!("onekey_report_{}.html", timestamp));
+
+    fs::write(&markdown_path, render_markdown(report))
+        .map_err(|e| format!("写入 {} 失败: {}", markdown_path.display(), e))?;
+    fs::write(&html_path, render_html(report))
+        .map_err(|e| format!("写入 {} 失败: {}", html_path.display(), e))?;
+
+    Ok((markdown_path, html_path))
+}
+
+pub fn render_markdown(report: &Report) -> String {
+    let mut output = String::from("# OneKey 测试报告\n\n");
+    let _ = writeln!(output, "- 主机: {}", report.host.hostname);
+    let _ = writeln!(output, "- 生成时间: {}", format_time(&report.generated_at));
+    let _ = writeln!(output, "- 版本: onekey {}", report.tool_version);
+
+    for section in sections(report) {
+        let _ = write!(output, "\n## {}\n\n", section.title);
+        if let Some(ref note) = section.note {
+            let _ = write!(output, "{}\n\n", note);
+        }
+        if section.rows.is_empty() {
+            continue;
+        }
+        let _ = writeln!(output, "| {} |", section.headers.join(" | "));
+        let _ = writeln!(output, "|{}", " --- |".repeat(section.headers.len()));
+        for row in &section.rows {
+            let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+            let _ = writeln!(output, "| {} |", cells.join(" | "));
+        }
+    }
+
+    output
+}
+
+pub fn render_html(report: &Report) -> String {
+    let mut output = String::new();
+    let _ = write!(
+        output,
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>OneKey 测试报告 - {}</title>\n<style>{}</style>\n</head>\n<body>\n",
+        escape_html(&report.host.hostname),
+        HTML_STYLE,
+    );
+    output.push_str("<h1>OneKey 测试报告</h1>\n");
+    let _ = writeln!(
+        output,
+        "<p class=\"meta\">{} · {} · onekey {}</p>",
+        escape_html(&report.host.hostname),
+        escape_html(&format_time(&report.generated_at)),
+        escape_html(&report.tool_version),
+    );
+
+    for section in sections(report) {
+        let _ = writeln!(output, "<h2>{}</h2>", section.title);
+        if let Some(ref note) = section.note {
+            for paragraph in note.split("\n\n") {
+                let _ = writeln!(output, "<p class=\"note\">{}</p>", escape_html(paragraph));
+            }
+        }
+        if !section.rows.is_empty() {
+            output.push_str("<table>\n<tr>");
+            for header in &section.headers {
+                let _ = write!(output, "<th>{}</th>", header);
+            }
+            output.push_str("</tr>\n");
+            for row in &section.rows {
+                output.push_str("<tr>");
+                for cell in row {
+                    let _ = write!(output, "<td>{}</td>", escape_html(cell));
+                }
+                output.push_str("</tr>\n");
+            }
+            output.push_str("</table>\n");
+        }
+
+        // 硬盘表格后附上读写图表
+        if section.title == DISK_TITLE {
+            if let Some(ref disk) = report.disk {
+                output.push_str(&disk_charts(disk));
+            }
+        }
+    }
+
+    output.push_str("</body>\n</html>\n");
+    output
+}
+
+const HTML_STYLE: &str = "\
+body{font-family:-apple-system,'Segoe UI','PingFang SC','Microsoft YaHei',sans-serif;\
+max-width:960px;margin:2em auto;padding:0 1em;color:#222;background:#fafafa}\
+h1{margin-bottom:.2em}h2{margin-top:1.6em;border-bottom:2px solid #17a2b8;padding-bottom:.2em}\
+.meta{color:#777}.note{color:#555}\
+table{border-collapse:collapse;width:100%;margin:.6em 0;background:#fff}\
+th,td{border:1px solid #ddd;padding:6px 10px;text-align:left}\
+th{background:#f0f4f5}tr:nth-child(even) td{background:#fcfcfc}\
+svg{display:block;margin:1em 0;max-width:100%;height:auto;background:#fff;border:1px solid #ddd}";
+
+const DISK_TITLE: &str = "硬盘测试";
+
+fn sections(report: &Report) -> Vec<Section> {
+    let mut sections = vec![host_section(&report.host)];
+    if let Some(ref disk) = report.disk {
+        sections.push(disk_section(disk));
+        if disk.results.iter().any(|r| r.latency.is_some()) {
+            sections.push(disk_latency_section(disk));
+        }
+    }
+    if let Some(ref cpu) = report.cpu {
+        sections.push(cpu_section(cpu));
+        if let Some(ref crypto) = cpu.crypto {
+            sections.push(crypto_section(crypto));
+        }
+        if let Some(ref topology) = cpu.topology {
+            sections.push(topology_section(topology));
+        }
+    }
+    if let Some(ref memory) = report.memory {
+        sections.push(memory_bandwidth_section(memory));
+        sections.push(memory_latency_section(memory));
+    }
+    if let Some(ref network) = report.network {
+        sections.push(network_section(network));
+    }
+    sections
+}
+
+fn host_section(host: &HostReport) -> Section {
+    let mut rows = vec![
+        vec!["主机名".to_string(), host.hostname.clone()],
+        vec!["系统".to_string(), host.distro.clone()],
+        vec!["内核".to_string(), format!("{} ({})", host.kernel, host.arch)],
+        vec!["虚拟化".to_string(), host.virtualization.clone()],
+        vec![
+            "CPU".to_string(),
+            format!("{} ({} 核 @ {} MHz)", host.cpu_model, host.cpu_cores, host.cpu_frequency_mhz),
+        ],
+        vec![
+            "内存".to_string(),
+            format!("{} / {}", format_bytes_gib(host.memory_used_bytes), format_bytes_gib(host.memory_total_bytes)),
+        ],
+        vec![
+            "Swap".to_string(),
+            format!("{} / {}", format_bytes_gib(host.swap_used_bytes), format_bytes_gib(host.swap_total_bytes)),
+        ],
+        vec!["运行时间".to_string(), SystemInfo::format_uptime(host.uptime_seconds)],
+        vec![
+            "负载".to_string(),
+            format!("{:.2} {:.2} {:.2}", host.load_average[0], host.load_average[1], host.load_average[2]),
+        ],
+        vec!["TCP 拥塞控制".to_string(), host.tcp_congestion_control.clone()],
+    ];
+
+    for disk in &host.disks {
+        rows.push(vec![
+            format!("磁盘 {}", disk.mount_point),
+            format!(
+                "{} ({}), 可用 {} / {}",
+                disk.name,
+                disk.file_system,
+                format_bytes_gib(disk.available_bytes),
+                format_bytes_gib(disk.total_bytes)
+            ),
+        ]);
+    }
+
+    // 报告通常会公开发布，IP 只保留前半部分
+    if let Some(ref network) = host.public_network {
+        let fields = [
+            ("IPv4", network.ipv4.as_deref().map(mask_ip)),
+            ("IPv6", network.ipv6.as_deref().map(mask_ip)),
+            ("ISP", network.isp.clone()),
+            ("ASN", network.asn.clone()),
+            ("位置", network.location.clone()),
+        ];
+        for (label, value) in fields {
+            if let Some(value) = value {
+                rows.push(vec![label.to_string(), value]);
+            }
+        }
+    }
+
+    Section { title: "系统信息", note: None, headers: vec!["项目", "值"], rows }
+}
+
+fn disk_section(disk: &DiskReport) -> Section {
+    let rows = block_size_rows(disk)
+        .iter()
+        .map(|row| {
+            vec![
+                row.name.clone(),
+                format_metric(row.read_mb_s, 1),
+                format_metric(row.read_iops, 0),
+                format_metric(row.write_mb_s, 1),
+                format_metric(row.write_iops, 0),
+            ]
+        })
+        .collect();
+
+    let mut notes = Vec::new();
+    if let Some(ref target) = disk.target {
+        notes.push(format!("测试目录: {} ({}, {})", target.dir, target.device, target.file_system));
+        if let Some(ref warning) = target.warning {
+            notes.push(format!("⚠ {}", warning));
+        }
+    }
+    if let Some(ref profile) = disk.profile {
+        notes.push(format!("测试配置: {}", profile));
+    }
+    if let Some(mode) = disk.results.iter().find_map(|r| r.io_mode) {
+        notes.push(format!("I/O 模式: {}", mode.label()));
+    }
+    if let Some(ref error) = disk.error {
+        notes.push(format!("⚠ {}", error));
+    }
+
+    Section {
+        title: DISK_TITLE,
+        note: (!notes.is_empty()).then(|| notes.join("\n\n")),
+        headers: vec!["块大小", "读取 (MB/s)", "读取 IOPS", "写入 (MB/s)", "写入 IOPS"],
+        rows,
+    }
+}
+
+fn disk_latency_section(disk: &DiskReport) -> Section {
+    let rows = disk
+        .results
+        .iter()
+        .filter_map(|result| {
+            let latency = result.latency.as_ref()?;
+            let mut row = vec![result.name.clone(), latency.samples.to_string()];
+            row.extend(
+                [latency.mean_us, latency.p50_us, latency.p95_us, latency.p99_us, latency.p999_us, latency.max_us]
+                    .iter()
+                    .map(|&us| format_latency_us(us)),
+            );
+            Some(row)
+        })
+        .collect();
+
+    Section {
+        title: "硬盘延迟",
+        note: Some("单次 I/O 操作的延迟分布".to_string()),
+        headers: vec!["测试", "次数", "平均", "p50", "p95", "p99", "p99.9", "最大"],
+        rows,
+    }
+}
+
+fn cpu_section(cpu: &CpuReport) -> Section {
+    let mut summary = format!("综合评分: 单核 {} 分 / 多核 {} 分", cpu.single_core_score, cpu.multi_core_score);
+    if let Some(version) = cpu.score_version {
+        summary.push_str(&format!(" (评分版本 v{})", version));
+    }
+    if let Some(ref system) = cpu.system {
+        summary.push_str(&format!("\n\n测试期间: {}", system.label()));
+    }
+    let rows = cpu
+        .tests
+        .iter()
+        .map(|test| {
+            vec![
+                test.name.clone(),
+                test.single_core_score.to_string(),
+                test.multi_core_score.to_string(),
+                test.duration_ms.to_string(),
+            ]
+        })
+        .collect();
+
+    Section {
+        title: "CPU 测试",
+        note: Some(match cpu.error {
+            Some(ref e) => format!("{}\n\n⚠ {}", summary, e),
+            None => summary,
+        }),
+        headers: vec!["测试项目", "单核", "多核", "耗时 (ms)"],
+        rows,
+    }
+}
+
+fn crypto_section(crypto: &CryptoBenchResult) -> Section {
+    let rows = crypto
+        .algorithms
+        .iter()
+        .map(|throughput| {
+            vec![
+                throughput.algorithm.clone(),
+                format!("{:.0}", throughput.single_core_mb_s),
+                format!("{:.0}", throughput.multi_core_mb_s),
+            ]
+        })
+        .collect();
+
+    Section {
+        title: "加密吞吐量",
+        note: Some(format!("硬件加速: {}", crypto.hardware.label())),
+        headers: vec!["算法", "单核 (MB/s)", "多核 (MB/s)"],
+        rows,
+    }
+}
+
+fn topology_section(topology: &TopologyResult) -> Section {
+    let rows = topology
+        .scaling
+        .iter()
+        .map(|point| {
+            vec![
+                point.threads.to_string(),
+                point.score.to_string(),
+                format!("{:.2}x", point.speedup),
+                format!("{:.0}%", point.efficiency * 100.0),
+            ]
+        })
+        .collect();
+
+    let cores: Vec<String> = topology.cores.iter().map(|core| format!("CPU {}: {}", core.cpu, core.score)).collect();
+    let mut note = format!("{}\n\n逐核分数 ({}): {}", topology.topology.label(), topology.kernel, cores.join(", "));
+    if let Some(steal) = topology.steal_pct {
+        note.push_str(&format!("\n\nsteal 时间: {:.1}%", steal));
+    }
+    for warning in &topology.warnings {
+        note.push_str(&format!("\n\n⚠ {}", warning));
+    }
+
+    Section {
+        title: "多核扩展性",
+        note: Some(note),
+        headers: vec!["线程数", "分数", "加速比", "效率"],
+        rows,
+    }
+}
+
+fn memory_bandwidth_section(memory: &MemoryReport) -> Section {
+    let rows = memory
+        .bandwidth
+        .iter()
+        .map(|result| {
+            vec![result.kernel.clone(), format!("{:.0}", result.single_mb_s), format!("{:.0}", result.multi_mb_s)]
+        })
+        .collect();
+
+    Section {
+        title: "内存带宽",
+        note: Some(format!(
+            "STREAM 数组 3 × {}, 多线程 {} 线程。缓存: {}",
+            format_size(memory.array_bytes),
+            memory.threads,
+            memory.cache.label()
+        )),
+        headers: vec!["内核", "单线程 (MB/s)", "多线程 (MB/s)"],
+        rows,
+    }
+}
+
+fn memory_latency_section(memory: &MemoryReport) -> Section {
+    let rows = memory
+        .latency
+        .iter()
+        .map(|point| {
+            vec![
+                format_size(point.size_bytes),
+                format!("{:.1}", point.latency_ns),
+                point.level.map(|level| level.name().to_string()).unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+
+    let levels: Vec<String> = level_latencies(&memory.latency)
+        .iter()
+        .map(|(level, ns)| format!("{} {:.1} ns", level.name(), ns))
+        .collect();
+
+    Section {
+        title: "内存访问延迟",
+        note: (!levels.is_empty()).then(|| format!("各级延迟 (中位数): {}", levels.join(" / "))),
+        headers: vec!["工作集", "延迟 (ns)", "层级"],
+        rows,
+    }
+}
+
+fn network_section(network: &NetworkReport) -> Section {
+    let rows = network
+        .servers
+        .iter()
+        .filter(|server| server.status != "not_started")
+        .map(|server| {
+            let completed = server.status == "completed";
+            let status = match server.status.as_str() {
+                "completed" => "完成".to_string(),
+                "testing" => "测试中".to_string(),
+                _ => format!("失败: {}", server.error.as_deref().unwrap_or("未知错误")),
+            };
+            let value = |v: f64, precision: usize| if completed { format_metric(v, precision) } else { "-".to_string() };
+            vec![
+                server.name.clone(),
+                value(server.download_mbps, 1),
+                value(server.upload_mbps, 1),
+                value(server.ping_ms, 1),
+                value(server.jitter_ms, 1),
+                match server.ping_method {
+                    Some(method) if completed => format!("{} ({})", format_metric(server.packet_loss_pct, 0), method.name()),
+                    _ => "-".to_string(),
+                },
+                status,
+            ]
+        })
+        .collect();
+
+    Section {
+        title: "网速测试",
+        note: network.error.as_ref().map(|e| format!("⚠ {}", e)),
+        headers: vec!["服务器", "下载 (Mbps)", "上传 (Mbps)", "延迟 (ms)", "抖动 (ms)", "丢包 (%)", "状态"],
+        rows,
+    }
+}
+
+/// 按块大小合并读取/写入两条结果，总计行只在没有对应读写行时保留
+fn block_size_rows(disk: &DiskReport) -> Vec<DiskResultReport> {
+    let mut rows: Vec<DiskResultReport> = Vec::new();
+
+    for result in &disk.results {
+        let (key, part) = match result.name.rsplit_once(' ') {
+            Some((key, part @ ("读取" | "写入" | "总计"))) => (key, Some(part)),
+            _ => (result.name.as_str(), None),
+        };
+
+        match rows.iter_mut().find(|row| row.name == key) {
+            Some(_) if part == Some("总计") => {}
+            Some(row) => {
+                row.read_mb_s += result.read_mb_s;
+                row.write_mb_s += result.write_mb_s;
+                row.read_iops += result.read_iops;
+                row.write_iops += result.write_iops;
+            }
+            None => rows.push(DiskResultReport { name: key.to_string(), ..result.clone() }),
+        }
+    }
+
+    rows
+}
+
+fn disk_charts(disk: &DiskReport) -> String {
+    let mut output = String::new();
+
+    let rows = block_size_rows(disk);
+    if !rows.is_empty() {
+        let labels: Vec<String> = rows.iter().map(|row| row.name.clone()).collect();
+        let read: Vec<f64> = rows.iter().map(|row| row.read_mb_s).collect();
+        let write: Vec<f64> = rows.iter().map(|row| row.write_mb_s).collect();
+        output.push_str(&svg_bar_chart(
+            "各块大小读写速度 (MB/s)",
+            &labels,
+            &[("读取", READ_COLOR, &read), ("写入", WRITE_COLOR, &write)],
+        ));
+    }
+
+    if !disk.read_samples.is_empty() || !disk.write_samples.is_empty() {
+        output.push_str(&svg_line_chart(
+            "实时读写速度 (MB/s)",
+            &[("读取", READ_COLOR, &disk.read_samples), ("写入", WRITE_COLOR, &disk.write_samples)],
+        ));
+    }
+
+    output
+}
+
+fn svg_bar_chart(title: &str, labels: &[String], series: &[(&str, &str, &[f64])]) -> String {
+    let max = nice_max(series.iter().flat_map(|(_, _, values)| values.iter().copied()).fold(0.0, f64::max));
+    let mut svg = svg_frame(title, series.iter().map(|&(name, color, _)| (name, color)), max);
+
+    let plot_width = CHART_WIDTH - CHART_LEFT - CHART_RIGHT;
+    let plot_height = CHART_HEIGHT - CHART_TOP - CHART_BOTTOM;
+    let group_width = plot_width / labels.len().max(1) as f64;
+    let bar_width = group_width * 0.8 / series.len().max(1) as f64;
+
+    for (i, label) in labels.iter().enumerate() {
+        let group_x = CHART_LEFT + i as f64 * group_width;
+        for (j, (name, color, values)) in series.iter().enumerate() {
+            let value = values.get(i).copied().unwrap_or(0.0);
+            let height = value / max * plot_height;
+            let _ = write!(
+                svg,
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"{}\">\
+                 <title>{} {}: {:.1}</title></rect>",
+                group_x + group_width * 0.1 + j as f64 * bar_width,
+                CHART_TOP + plot_height - height,
+                bar_width,
+                height,
+                color,
+                escape_html(label),
+                name,
+                value,
+            );
+        }
+        let _ = write!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{}</text>",
+            group_x + group_width / 2.0,
+            CHART_HEIGHT - CHART_BOTTOM + 18.0,
+            escape_html(label),
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn svg_line_chart(title: &str, series: &[(&str, &str, &[SpeedSample])]) -> String {
+    let samples = || series.iter().flat_map(|(_, _, samples)| samples.iter());
+    let max = nice_max(samples().map(|s| s.mb_s).fold(0.0, f64::max));
+    let start = samples().map(|s| s.seconds).fold(f64::INFINITY, f64::min);
+    let end = samples().map(|s| s.seconds).fold(f64::NEG_INFINITY, f64::max);
+    let span = if end > start { end - start } else { 1.0 };
+
+    let mut svg = svg_frame(title, series.iter().map(|&(name, color, _)| (name, color)), max);
+
+    let plot_width = CHART_WIDTH - CHART_LEFT - CHART_RIGHT;
+    let plot_height = CHART_HEIGHT - CHART_TOP - CHART_BOTTOM;
+
+    for (_, color, samples) in series {
+        if samples.is_empty() {
+            continue;
+        }
+        let points: Vec<String> = samples
+            .iter()
+            .map(|s| {
+                format!(
+                    "{:.1},{:.1}",
+                    CHART_LEFT + (s.seconds - start) / span * plot_width,
+                    CHART_TOP + plot_height - s.mb_s / max * plot_height,
+                )
+            })
+            .collect();
+        let _ = write!(
+            svg,
+            "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"2\" points=\"{}\"/>",
+            color,
+            points.join(" "),
+        );
+    }
+
+    // 时间轴刻度
+    for i in 0..=4 {
+        let fraction = i as f64 / 4.0;
+        let _ = write!(
+            svg,
+            "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\">{:.0}s</text>",
+            CHART_LEFT + fraction * plot_width,
+            CHART_HEIGHT - CHART_BOTTOM + 18.0,
+            start + fraction * span,
+        );
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// 图表外框：标题、图例、Y 轴刻度和网格线，调用方负责补上 </svg>
+fn svg_frame<'a>(title: &str, legend: impl Iterator<Item = (&'a str, &'a str)>, max: f64) -> String {
+    let mut svg = String::new();
+    let _ = write!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {w} {h}\" width=\"{w}\" height=\"{h}\" \
+         font-size=\"12\" font-family=\"sans-serif\">",
+        w = CHART_WIDTH,
+        h = CHART_HEIGHT,
+    );
+    let _ = write!(
+        svg,
+        "<text x=\"{}\" y=\"22\" font-size=\"14\" font-weight=\"bold\">{}</text>",
+        CHART_LEFT,
+        escape_html(title),
+    );
+
+    for (i, (name, color)) in legend.enumerate() {
+        let x = CHART_WIDTH - CHART_RIGHT - 140.0 + i as f64 * 70.0;
+        let _ = write!(
+            svg,
+            "<rect x=\"{x:.1}\" y=\"12\" width=\"12\" height=\"12\" fill=\"{color}\"/>\
+             <text x=\"{:.1}\" y=\"22\">{}</text>",
+            x + 16.0,
+            escape_html(name),
+        );
+    }
+
+    let plot_height = CHART_HEIGHT - CHART_TOP - CHART_BOTTOM;
+    for i in 0..=4 {
+        let fraction = i as f64 / 4.0;
+        let y = CHART_TOP + plot_height - fraction * plot_height;
+        let _ = write!(
+            svg,
+            "<line x1=\"{:.1}\" y1=\"{y:.1}\" x2=\"{:.1}\" y2=\"{y:.1}\" stroke=\"{}\"/>\
+             <text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"end\">{}</text>",
+            CHART_LEFT,
+            CHART_WIDTH - CHART_RIGHT,
+            if i == 0 { "#999" } else { "#eee" },
+            CHART_LEFT - 6.0,
+            y + 4.0,
+            format_metric(max * fraction, 0),
+        );
+    }
+
+    svg
+}
+
+/// 取不小于 value 的 1/2/5 × 10^n，作为坐标轴上限
+fn nice_max(value: f64) -> f64 {
+    if !value.is_finite() || value <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(value.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|step| step * magnitude)
+        .find(|&max| max >= value)
+        .unwrap_or(10.0 * magnitude)
+}
+
+fn format_metric(value: f64, precision: usize) -> String {
+    format!("{:.*}", precision, value)
+}
+
+/// 隐藏 IP 地址的后半部分
+fn mask_ip(ip: &str) -> String {
+    if ip.contains(':') {
+        let groups: Vec<&str> = ip.split(':').filter(|g| !g.is_empty()).take(2).collect();
+        format!("{}:*:*", groups.join(":"))
+    } else {
+        let octets: Vec<&str> = ip.split('.').take(2).collect();
+        format!("{}.*.*", octets.join("."))
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_report() {
+        let host: HostReport = serde_json::from_value(serde_json::json!({
+            "hostname": "vps<1>", "distro": "Debian 12", "kernel": "6.1", "arch": "x86_64",
+            "virtualization": "KVM", "cpu_model": "EPYC", "cpu_cores": 2, "cpu_frequency_mhz": 2400,
+            "memory_total_bytes": 0, "memory_used_bytes": 0, "swap_total_bytes": 0, "swap_used_bytes": 0,
+            "uptime_seconds": 0, "load_average": [0.0, 0.0, 0.0], "tcp_congestion_control": "bbr",
+            "dns_servers": [], "disks": [],
+            "public_network": { "ipv4": "203.0.113.7" }
+        }))
+        .unwrap();
+        let disk: DiskReport = serde_json::from_value(serde_json::json!({
+            "results": [
+                { "name": "4K 读取", "read_mb_s": 120.0, "write_mb_s": 0.0, "read_iops": 30720.0, "write_iops": 0.0,
+                  "latency": { "samples": 1000, "mean_us": 95.0, "p50_us": 80.0, "p95_us": 150.0,
+                               "p99_us": 420.0, "p999_us": 1500.0, "max_us": 2600.0 } },
+                { "name": "4K 写入", "read_mb_s": 0.0, "write_mb_s": 80.0, "read_iops": 0.0, "write_iops": 20480.0 },
+                { "name": "4K 总计", "read_mb_s": 120.0, "write_mb_s": 80.0, "read_iops": 30720.0, "write_iops": 20480.0 }
+            ],
+            "read_samples": [{ "seconds": 0.5, "mb_s": 110.0 }, { "seconds": 1.0, "mb_s": 130.0 }]
+        }))
+        .unwrap();
+        let mut report = Report::new(host);
+        report.disk = Some(disk);
+
+        // 读写两行合并为一行，总计行不重复计入
+        let markdown = render_markdown(&report);
+        assert!(markdown.contains("| 4K | 120.0 | 30720 | 80.0 | 20480 |"));
+        assert!(markdown.contains("203.0.*.*") && !markdown.contains("203.0.113.7"));
+        assert!(markdown.contains("| 4K 读取 | 1000 | 95µs | 80µs | 150µs | 420µs | 1.50ms | 2.60ms |"));
+
+        let html = render_html(&report);
+        assert!(html.contains("vps&lt;1&gt;") && !html.contains("vps<1>"));
+        assert_eq!(html.matches("<svg").count(), 2);
+        assert!(html.contains("<polyline"));
+    }
+
+    #[test]
+    fn test_nice_max() {
+        assert_eq!(nice_max(0.0), 1.0);
+        assert_eq!(nice_max(120.0), 200.0);
+        assert_eq!(nice_max(500.0), 500.0);
+        assert_eq!(nice_max(5100.0), 10000.0);
+    }
+}
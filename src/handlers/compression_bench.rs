@@ -0,0 +1,213 @@
+// src/handlers/compression_bench.rs
+// 真实的压缩/解压吞吐量测试：zstd、deflate、lz4 以固定级别处理确定性的测试语料
+use serde::{Deserialize, Serialize};
+use std::hint::black_box;
+use std::io::{Read, Write};
+use std::sync::Arc;
+
+use super::cpu_score::Kernel;
+
+/// 测试语料总大小，每次计时压缩或解压整份语料
+pub const CORPUS_SIZE: usize = 4 * 1024 * 1024;
+// 每次处理的块大小
+const BLOCK_SIZE: usize = 128 * 1024;
+
+/// 测试的编码器，级别固定以便结果可比
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    Zstd,
+    Deflate,
+    Lz4,
+}
+
+impl Codec {
+    pub const ALL: [Codec; 3] = [Codec::Zstd, Codec::Deflate, Codec::Lz4];
+
+    const ZSTD_LEVEL: i32 = 3;
+    const DEFLATE_LEVEL: u32 = 6;
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Codec::Zstd => "zstd -3",
+            Codec::Deflate => "deflate -6",
+            Codec::Lz4 => "lz4",
+        }
+    }
+
+    // 参考机器上单线程 (压缩, 解压) 整份语料的中位耗时 (ms)
+    fn reference_ms(&self) -> (f64, f64) {
+        match self {
+            Codec::Zstd => (22.5, 4.3),
+            Codec::Deflate => (195.1, 17.8),
+            Codec::Lz4 => (23.6, 4.3),
+        }
+    }
+
+    pub fn compress(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Codec::Zstd => zstd::bulk::compress(data, Self::ZSTD_LEVEL).expect("zstd 压缩内存数据不会失败"),
+            Codec::Deflate => {
+                let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::new(Self::DEFLATE_LEVEL));
+                encoder.write_all(data).expect("写入内存不会失败");
+                encoder.finish().expect("写入内存不会失败")
+            }
+            Codec::Lz4 => lz4_flex::compress(data),
+        }
+    }
+
+    pub fn decompress(&self, data: &[u8], original_size: usize) -> Vec<u8> {
+        match self {
+            Codec::Zstd => zstd::bulk::decompress(data, original_size).expect("解压自己压缩的数据不会失败"),
+            Codec::Deflate => {
+                let mut output = Vec::with_capacity(original_size);
+                flate2::read::DeflateDecoder::new(data)
+                    .read_to_end(&mut output)
+                    .expect("解压自己压缩的数据不会失败");
+                output
+            }
+            Codec::Lz4 => lz4_flex::decompress(data, original_size).expect("解压自己压缩的数据不会失败"),
+        }
+    }
+}
+
+/// 编码器在测试语料上的压缩率
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CompressionRatio {
+    pub codec: String,
+    pub compression_ratio: f64,  // 原始大小 / 压缩后大小
+}
+
+/// 一份分块后的测试语料及各编码器的预压缩结果
+struct PreparedCorpus {
+    blocks: Vec<Vec<u8>>,
+    compressed: Vec<Vec<Vec<u8>>>, // 按 Codec::ALL 的顺序
+}
+
+/// 各编码器的压缩率（原始大小 / 压缩后大小）和计时任务
+///
+/// 每个编码器有压缩、解压两个任务，各处理整份语料，吞吐量按未压缩数据量计算
+pub fn kernels() -> (Vec<CompressionRatio>, Vec<Kernel>) {
+    let blocks: Vec<Vec<u8>> = test_corpus(CORPUS_SIZE).chunks(BLOCK_SIZE).map(<[u8]>::to_vec).collect();
+    let compressed: Vec<Vec<Vec<u8>>> = Codec::ALL
+        .iter()
+        .map(|codec| blocks.iter().map(|block| codec.compress(block)).collect())
+        .collect();
+    let ratios = Codec::ALL
+        .iter()
+        .zip(&compressed)
+        .map(|(codec, blocks)| CompressionRatio {
+            codec: codec.name().to_string(),
+            compression_ratio: CORPUS_SIZE as f64 / blocks.iter().map(Vec::len).sum::<usize>() as f64,
+        })
+        .collect();
+
+    let corpus = Arc::new(PreparedCorpus { blocks, compressed });
+    let mut kernels = Vec::new();
+    for (index, &codec) in Codec::ALL.iter().enumerate() {
+        let (compress_ms, decompress_ms) = codec.reference_ms();
+        let data = Arc::clone(&corpus);
+        kernels.push(
+            Kernel::new(format!("{} 压缩", codec.name()), compress_ms, move || {
+                for block in &data.blocks {
+                    black_box(codec.compress(black_box(block)));
+                }
+            })
+            .with_bytes(CORPUS_SIZE),
+        );
+        let data = Arc::clone(&corpus);
+        kernels.push(
+            Kernel::new(format!("{} 解压", codec.name()), decompress_ms, move || {
+                for (block, compressed) in data.blocks.iter().zip(&data.compressed[index]) {
+                    black_box(codec.decompress(black_box(compressed), block.len()));
+                }
+            })
+            .with_bytes(CORPUS_SIZE),
+        );
+    }
+    (ratios, kernels)
+}
+
+/// 确定性的测试语料：日志、JSON 和文本混合，另有约 1/8 不可压缩的随机数据
+///
+/// 固定的伪随机序列保证每次运行、每台机器上的数据完全相同
+pub fn test_corpus(size: usize) -> Vec<u8> {
+    const WORDS: [&str; 24] = [
+        "server", "request", "connection", "timeout", "client", "status", "error", "latency", "upstream",
+        "proxy", "session", "the", "of", "and", "to", "in", "is", "for", "with", "data", "cache", "user",
+        "config", "network",
+    ];
+    const LEVELS: [&str; 4] = ["INFO", "WARN", "DEBUG", "ERROR"];
+
+    // 线性同余生成器
+    let mut state = 0x2545_f491_4f6c_dd1du64;
+    let mut next = move || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 33) as usize
+    };
+
+    let mut corpus = Vec::with_capacity(size + 1024);
+    while corpus.len() < size {
+        match next() % 8 {
+            0..=2 => {
+                let line = format!(
+                    "2024-01-{:02} {:02}:{:02}:{:02} {} {} {} {}ms\n",
+                    next() % 28 + 1,
+                    next() % 24,
+                    next() % 60,
+                    next() % 60,
+                    LEVELS[next() % LEVELS.len()],
+                    WORDS[next() % WORDS.len()],
+                    WORDS[next() % WORDS.len()],
+                    next() % 5000
+                );
+                corpus.extend_from_slice(line.as_bytes());
+            }
+            3..=4 => {
+                let line = format!(
+                    "{{\"id\":{},\"user\":\"{}{}\",\"bytes\":{},\"ok\":{}}}\n",
+                    next() % 1_000_000,
+                    WORDS[next() % WORDS.len()],
+                    next() % 1000,
+                    next() % 100_000,
+                    next() % 2 == 0
+                );
+                corpus.extend_from_slice(line.as_bytes());
+            }
+            5..=6 => {
+                for _ in 0..12 {
+                    corpus.extend_from_slice(WORDS[next() % WORDS.len()].as_bytes());
+                    corpus.push(b' ');
+                }
+                corpus.push(b'\n');
+            }
+            _ => corpus.extend((0..96).map(|_| next() as u8)),
+        }
+    }
+    corpus.truncate(size);
+    corpus
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codecs_round_trip() {
+        let corpus = test_corpus(256 * 1024);
+        assert_eq!(corpus, test_corpus(256 * 1024));
+        for codec in Codec::ALL {
+            let compressed = codec.compress(&corpus);
+            assert!(compressed.len() < corpus.len(), "{}", codec.name());
+            assert_eq!(codec.decompress(&compressed, corpus.len()), corpus, "{}", codec.name());
+        }
+    }
+
+    #[test]
+    fn test_compression_kernels() {
+        let (ratios, kernels) = kernels();
+        assert_eq!(ratios.len(), Codec::ALL.len());
+        assert!(ratios.iter().all(|ratio| ratio.compression_ratio > 1.0), "{:?}", ratios);
+        assert_eq!(kernels.len(), Codec::ALL.len() * 2);
+        assert!(kernels.iter().all(|k| k.reference_ms > 0.0 && k.bytes == Some(CORPUS_SIZE)));
+    }
+}
@@ -0,0 +1,294 @@
+// src/handlers/cpu_score.rs
+// CPU 评分模型：每个工作负载使用固定大小的确定性输入，多次运行取中位数，
+// 与参考机器上记录的耗时相比得出分数，不同版本、不同主机之间的分数可以直接比较
+use std::collections::HashMap;
+use std::time::Instant;
+
+use super::cpu_test::CpuTestResult;
+
+/// 评分模型版本：工作负载、输入大小、参考耗时或权重变化时递增，不同版本的分数不可比较
+pub const CPU_SCORE_VERSION: u32 = 1;
+
+/// 参考机器：在它上面单线程运行的分数为 BASELINE_SCORE
+pub const BASELINE_MACHINE: &str = "Intel Xeon 2.1 GHz 云主机 (KVM, 1 vCPU), release 构建";
+pub const BASELINE_SCORE: f64 = 1000.0;
+
+/// 每个工作负载正式计时的次数，之前另有一次预热不计入
+pub const MEASURED_RUNS: usize = 5;
+
+pub const INTEGER: &str = "整数运算基准";
+pub const FLOATING_POINT: &str = "浮点运算基准";
+pub const SIMD: &str = "SIMD矢量计算";
+pub const CRYPTO: &str = "加密哈希运算";
+pub const COMPRESSION: &str = "数据压缩算法";
+pub const MEMORY: &str = "内存带宽测试";
+pub const MULTITHREADING: &str = "多线程并发";
+pub const OVERALL: &str = "综合评分";
+
+/// 各测试项在综合评分中的权重，按名称精确匹配
+pub const WEIGHTS: [(&str, f64); 7] = [
+    (INTEGER, 0.20),
+    (FLOATING_POINT, 0.20),
+    (SIMD, 0.15),
+    (CRYPTO, 0.15),
+    (COMPRESSION, 0.10),
+    (MEMORY, 0.10),
+    (MULTITHREADING, 0.10),
+];
+
+/// 一个固定输入的计算任务
+pub struct Kernel {
+    pub name: String,
+    pub reference_ms: f64,     // 参考机器上单线程运行一次的中位耗时
+    pub bytes: Option<usize>,  // 每次处理的数据量，用于换算吞吐量
+    run: Box<dyn Fn() + Send + Sync>,
+}
+
+impl Kernel {
+    pub fn new(name: impl Into<String>, reference_ms: f64, run: impl Fn() + Send + Sync + 'static) -> Self {
+        Self { name: name.into(), reference_ms, bytes: None, run: Box::new(run) }
+    }
+
+    /// 处理 bytes 字节数据的任务，结果中附带吞吐量
+    pub fn with_bytes(mut self, bytes: usize) -> Self {
+        self.bytes = Some(bytes);
+        self
+    }
+
+    /// 在当前线程上运行一次
+    pub fn run_once(&self) {
+        (self.run)()
+    }
+}
+
+/// 多次运行的耗时统计
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    pub median_ms: f64,
+    pub stddev_ms: f64,
+    pub runs: usize,
+}
+
+impl Measurement {
+    pub fn from_samples(samples: &[f64]) -> Self {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let n = sorted.len();
+        let median_ms = match n {
+            0 => 0.0,
+            _ if n % 2 == 1 => sorted[n / 2],
+            _ => (sorted[n / 2 - 1] + sorted[n / 2]) / 2.0,
+        };
+        let mean = sorted.iter().sum::<f64>() / n.max(1) as f64;
+        let variance = sorted.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n.max(1) as f64;
+        Self { median_ms, stddev_ms: variance.sqrt(), runs: n }
+    }
+
+    // 相对标准差，衡量结果是否稳定
+    fn relative_stddev_pct(&self) -> f64 {
+        if self.median_ms > 0.0 {
+            self.stddev_ms / self.median_ms * 100.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 单个任务单核、多核的测量结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct KernelResult {
+    pub name: String,
+    pub bytes: Option<usize>,
+    pub threads: usize,
+    pub single: Measurement,
+    pub multi: Measurement,
+    pub single_score: f64,
+    pub multi_score: f64,
+}
+
+impl KernelResult {
+    /// 单核吞吐量 (MB/s)
+    pub fn single_mb_s(&self) -> Option<f64> {
+        self.bytes.map(|bytes| throughput_mb_s(bytes, self.single.median_ms))
+    }
+
+    /// 多核吞吐量 (MB/s)，所有线程处理的数据量之和
+    pub fn multi_mb_s(&self) -> Option<f64> {
+        self.bytes.map(|bytes| throughput_mb_s(bytes * self.threads, self.multi.median_ms))
+    }
+}
+
+fn throughput_mb_s(bytes: usize, ms: f64) -> f64 {
+    if ms > 0.0 {
+        bytes as f64 / (1024.0 * 1024.0) / (ms / 1000.0)
+    } else {
+        0.0
+    }
+}
+
+/// 计时运行 f：先预热一次，再运行 runs 次
+pub fn measure(runs: usize, mut f: impl FnMut()) -> Measurement {
+    f();
+    let samples: Vec<f64> = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed().as_secs_f64() * 1000.0
+        })
+        .collect();
+    Measurement::from_samples(&samples)
+}
+
+/// 按参考耗时计算分数：copies 份相同任务同时运行，用时与参考机器单份相同时得 copies * BASELINE_SCORE
+pub fn score(reference_ms: f64, measurement: &Measurement, copies: usize) -> f64 {
+    if measurement.median_ms > 0.0 {
+        BASELINE_SCORE * reference_ms * copies as f64 / measurement.median_ms
+    } else {
+        0.0
+    }
+}
+
+/// 加权几何平均，权重之和不必为 1
+pub fn weighted_geometric_mean(values: &[(f64, f64)]) -> f64 {
+    let values: Vec<_> = values.iter().filter(|(value, weight)| *value > 0.0 && *weight > 0.0).collect();
+    let total_weight: f64 = values.iter().map(|(_, weight)| weight).sum();
+    if total_weight == 0.0 {
+        return 0.0;
+    }
+    (values.iter().map(|(value, weight)| value.ln() * weight).sum::<f64>() / total_weight).exp()
+}
+
+/// 运行任务：单核在当前线程上，多核由当前 rayon 线程池的每个线程同时各运行一份
+pub fn run_kernel(kernel: &Kernel) -> KernelResult {
+    let threads = rayon::current_num_threads();
+    let single = measure(MEASURED_RUNS, || kernel.run_once());
+    let multi = measure(MEASURED_RUNS, || {
+        rayon::broadcast(|_| kernel.run_once());
+    });
+    KernelResult {
+        name: kernel.name.clone(),
+        bytes: kernel.bytes,
+        threads,
+        single_score: score(kernel.reference_ms, &single, 1),
+        multi_score: score(kernel.reference_ms, &multi, threads),
+        single,
+        multi,
+    }
+}
+
+/// 运行一个测试项的全部任务，分数为各任务分数的几何平均，每个任务的耗时统计随结果保存
+pub fn run_workload(test_name: &str, kernels: &[Kernel]) -> CpuTestResult {
+    let start_time = Instant::now();
+    let results: Vec<KernelResult> = kernels.iter().map(run_kernel).collect();
+
+    let mean = |pick: fn(&KernelResult) -> f64| {
+        weighted_geometric_mean(&results.iter().map(|r| (pick(r), 1.0)).collect::<Vec<_>>()).round() as u32
+    };
+
+    CpuTestResult {
+        test_name: test_name.to_string(),
+        single_core_score: mean(|r| r.single_score),
+        multi_core_score: mean(|r| r.multi_score),
+        duration_ms: start_time.elapsed().as_millis() as u64,
+        details: HashMap::new(),
+        kernels: results,
+    }
+}
+
+/// 任务单核、多核结果的显示文本
+pub fn describe_kernel(result: &KernelResult) -> [(String, String); 2] {
+    [
+        (format!("{} 单核", result.name), describe(&result.single, result.single_mb_s())),
+        (
+            format!("{} 多核", result.name),
+            describe(&result.multi, result.multi_mb_s()) + &format!(", {} 线程", result.threads),
+        ),
+    ]
+}
+
+// 例如 "中位 12.30 ms ±1.2%, 1302 MB/s"
+fn describe(measurement: &Measurement, mb_s: Option<f64>) -> String {
+    let mut text = format!("中位 {:.2} ms ±{:.1}%", measurement.median_ms, measurement.relative_stddev_pct());
+    if let Some(mb_s) = mb_s {
+        text.push_str(&format!(", {:.0} MB/s", mb_s));
+    }
+    text
+}
+
+/// 综合评分：按 WEIGHTS 对各测试项分数做加权几何平均，未知名称的测试项不参与
+pub fn overall(results: &[CpuTestResult]) -> CpuTestResult {
+    let weighted = |pick: fn(&CpuTestResult) -> u32| {
+        let values: Vec<(f64, f64)> = results
+            .iter()
+            .filter_map(|result| {
+                let (_, weight) = WEIGHTS.iter().find(|(name, _)| *name == result.test_name)?;
+                Some((pick(result) as f64, *weight))
+            })
+            .collect();
+        weighted_geometric_mean(&values).round() as u32
+    };
+
+    let mut details = HashMap::new();
+    details.insert("评分版本".to_string(), format!("v{}", CPU_SCORE_VERSION));
+    details.insert("参考机器".to_string(), format!("{} = {} 分", BASELINE_MACHINE, BASELINE_SCORE));
+
+    CpuTestResult {
+        test_name: OVERALL.to_string(),
+        single_core_score: weighted(|r| r.single_core_score),
+        multi_core_score: weighted(|r| r.multi_core_score),
+        duration_ms: results.iter().map(|r| r.duration_ms).sum(),
+        details,
+        kernels: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measurement_statistics() {
+        let m = Measurement::from_samples(&[12.0, 10.0, 30.0, 11.0, 10.0]);
+        assert_eq!(m.median_ms, 11.0);
+        assert_eq!(m.runs, 5);
+        assert!((m.stddev_ms - 7.7356).abs() < 0.001);
+        assert_eq!(Measurement::from_samples(&[1.0, 3.0]).median_ms, 2.0);
+    }
+
+    #[test]
+    fn test_score_against_reference() {
+        let m = Measurement { median_ms: 50.0, stddev_ms: 0.0, runs: 5 };
+        // 用时为参考的一半，分数翻倍；4 份同时运行用时相同，分数乘 4
+        assert_eq!(score(100.0, &m, 1), 2000.0);
+        assert_eq!(score(50.0, &m, 4), 4000.0);
+    }
+
+    #[test]
+    fn test_workload_keeps_kernel_measurements() {
+        let kernels = [Kernel::new("空任务", 1.0, || {}).with_bytes(1024)];
+        let result = run_workload(INTEGER, &kernels);
+        assert!(result.details.is_empty());
+        assert_eq!(result.kernels.len(), 1);
+        assert_eq!(result.kernels[0].single.runs, MEASURED_RUNS);
+        let [(single, _), (multi, text)] = describe_kernel(&result.kernels[0]);
+        assert_eq!((single.as_str(), multi.as_str()), ("空任务 单核", "空任务 多核"));
+        assert!(text.ends_with(&format!(", {} 线程", result.kernels[0].threads)));
+    }
+
+    #[test]
+    fn test_overall_uses_exact_names_and_weights() {
+        let result = |name: &str, score: u32| CpuTestResult {
+            test_name: name.to_string(),
+            single_core_score: score,
+            multi_core_score: score * 2,
+            duration_ms: 0,
+            details: HashMap::new(),
+            kernels: Vec::new(),
+        };
+        let all = overall(&[result(INTEGER, 1000), result(CRYPTO, 4000), result("整数运算基准 (旧)", 1)]);
+        // 权重 0.20 与 0.15 的加权几何平均
+        let expected = (0.2 * 1000f64.ln() + 0.15 * 4000f64.ln()) / 0.35;
+        assert_eq!(all.single_core_score, expected.exp().round() as u32);
+        assert_eq!(all.multi_core_score, (expected.exp() * 2.0).round() as u32);
+    }
+}
@@ -1,10 +1,18 @@
 use std::sync::Mutex;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::thread;
 use std::time::Instant;
 use std::collections::HashMap;
 use rayon::prelude::*;
 
+use crate::cancel::{catch_panic, CancelToken, Cancelled, TestControl};
+use super::compression_bench;
+use super::cpu_score::{self, Kernel, KernelResult};
+use super::cpu_topology::{self, TopologyResult};
+use super::system_monitor::{SystemMonitor, SystemSample, SystemSummary};
+use super::crypto_bench::{self, CryptoBenchResult};
+use super::compression_bench::CompressionRatio;
+
 // CPU 测试结果结构
 #[derive(Debug, Clone)]
 pub struct CpuTestResult {
@@ -13,6 +21,7 @@ pub struct CpuTestResult {
     pub multi_core_score: u32,
     pub duration_ms: u64,
     pub details: HashMap<String, String>,
+    pub kernels: Vec<KernelResult>,  // 评分任务的耗时统计，旧版测试项为空
 }
 
 // CPU 测试信息状态
@@ -34,6 +43,12 @@ pub struct CpuTestInfo {
     pub multi_core_current_score: u32,
     pub estimated_single_core: u32,
     pub estimated_multi_core: u32,
+    pub threads: usize,  // 多核测试使用的线程数
+    pub crypto: Option<CryptoBenchResult>,  // 加密吞吐量和硬件加速检测结果
+    pub compression: Vec<CompressionRatio>,  // 各编码器的压缩率
+    pub topology: Option<TopologyResult>,  // 逐核分数、扩展曲线和 steal 时间
+    pub system_samples: Vec<SystemSample>,  // 测试期间每秒的 steal、iowait 和 CPU 频率
+    pub system_summary: Option<SystemSummary>,  // 测试完成时由 system_samples 汇总
 }
 
 impl Default for CpuTestInfo {
@@ -55,6 +70,12 @@ impl Default for CpuTestInfo {
             multi_core_current_score: 0,
             estimated_single_core: 0,
             estimated_multi_core: 0,
+            threads: thread_count(),
+            crypto: None,
+            compression: Vec::new(),
+            topology: None,
+            system_samples: Vec::new(),
+            system_summary: None,
         }
     }
 }
@@ -62,7 +83,10 @@ impl Default for CpuTestInfo {
 // 全局状态管理
 static CPU_TEST_INFO: Mutex<Option<CpuTestInfo>> = Mutex::new(None);
 static NEEDS_UI_REFRESH: AtomicBool = AtomicBool::new(false);
-static CPU_TEST_STARTED: AtomicBool = AtomicBool::new(false);
+static CONTROL: TestControl = TestControl::new();
+
+// 用户选择的多核测试线程数，0 表示使用全部逻辑核心
+static THREADS: AtomicUsize = AtomicUsize::new(0);
 
 /// 获取CPU测试信息
 pub fn get_info() -> String {
@@ -72,24 +96,11 @@ pub fn get_info() -> String {
         }
     }
     
-    // 首次创建CPU测试信息时，自动启动测试
-    let mut info = CpuTestInfo::default();
-    info.cpu_info = get_cpu_info();
-    
-    let formatted = format_cpu_test_info(&info);
-    
-    // 保存到全局状态
-    if let Ok(mut global_info) = CPU_TEST_INFO.lock() {
-        *global_info = Some(info);
-    }
-    
-    // 自动启动CPU测试
-    start_cpu_test();
-    
-    formatted
+    // 尚未开始时显示准备状态，不会启动测试
+    format_cpu_test_info(&CpuTestInfo::default())
 }
 
-/// 获取当前CPU测试状态
+/// 获取当前CPU测试状态，尚未开始时返回带当前设置的准备状态
 pub fn get_current_test_info() -> CpuTestInfo {
     if let Ok(global_info) = CPU_TEST_INFO.lock() {
         if let Some(info) = global_info.as_ref() {
@@ -99,11 +110,11 @@ pub fn get_current_test_info() -> CpuTestInfo {
     CpuTestInfo::default()
 }
 
-/// 启动CPU测试
-pub fn start_cpu_test() {
-    if CPU_TEST_STARTED.swap(true, Ordering::Relaxed) {
-        return; // 测试已在进行中
-    }
+/// 按当前设置开始CPU测试，上一次的结果会被清除
+pub fn start_cpu_test() -> Result<(), String> {
+    let Some(run) = CONTROL.try_begin() else {
+        return Err("CPU测试进行中，完成或按 X 取消后才能重新测试".to_string());
+    };
     
     // 初始化测试状态
     if let Ok(mut global_info) = CPU_TEST_INFO.lock() {
@@ -113,10 +124,45 @@ pub fn start_cpu_test() {
         *global_info = Some(info);
     }
     
-    // 在新线程中运行测试
-    thread::spawn(|| {
-        run_async_cpu_tests();
+    // 在新线程中运行测试，run 在线程结束时释放
+    thread::spawn(move || {
+        let _monitor = SystemMonitor::start(push_system_sample);
+        if let Err(e) = catch_panic(|| run_async_cpu_tests(&run)) {
+            update_test_status_with_error(e);
+        }
     });
+    Ok(())
+}
+
+/// 取消/暂停正在运行的CPU测试
+pub fn test_control() -> &'static TestControl {
+    &CONTROL
+}
+
+/// 多核测试使用的线程数
+pub fn thread_count() -> usize {
+    match THREADS.load(Ordering::Relaxed) {
+        0 => num_cpus::get(),
+        threads => threads,
+    }
+}
+
+/// 切换多核测试线程数（1、2、4... 直到全部逻辑核心），回到准备状态
+pub fn cycle_thread_count() -> Result<usize, String> {
+    if CONTROL.is_running() {
+        return Err("测试进行中，完成或按 X 取消后才能修改测试设置".to_string());
+    }
+    let cores = num_cpus::get();
+    let next = match THREADS.load(Ordering::Relaxed) {
+        0 => 1,
+        threads if threads * 2 < cores => threads * 2,
+        _ => 0,
+    };
+    THREADS.store(next, Ordering::Relaxed);
+    if let Ok(mut global_info) = CPU_TEST_INFO.lock() {
+        *global_info = None;
+    }
+    Ok(thread_count())
 }
 
 /// 检查是否需要刷新UI
@@ -139,7 +185,7 @@ pub fn update_animation_frame() {
 
 // 内部函数实现
 
-fn run_async_cpu_tests() {
+fn run_async_cpu_tests(cancel: &CancelToken) {
     // 更严谨的测试阶段定义
     let test_phases = vec![
         ("🔢 整数运算基准", 10),
@@ -148,26 +194,62 @@ fn run_async_cpu_tests() {
         ("🔐 加密哈希运算", 55),
         ("📦 数据压缩算法", 70),
         ("💾 内存带宽测试", 85),
-        ("🎯 多线程并发", 95),
+        ("🎯 多线程并发", 90),
+        ("🧭 核心拓扑与扩展性", 95),
     ];
     
     let mut all_results = Vec::new();
     
+    // 多核测试在指定线程数的线程池中运行
+    let pool = match rayon::ThreadPoolBuilder::new().num_threads(thread_count()).build() {
+        Ok(pool) => pool,
+        Err(e) => {
+            update_test_status_with_error(format!("无法创建测试线程池: {}", e));
+            return;
+        }
+    };
+    
     for (i, (phase_name, progress)) in test_phases.iter().enumerate() {
+        // 单项测试是固定工作量，取消和暂停在测试项之间生效
+        if checkpoint(cancel).is_err() {
+            update_test_cancelled();
+            return;
+        }
+        
         update_test_status(phase_name, *progress, true);
         update_test_phase(phase_name, i);
         
+        if i == 7 {
+            // 拓扑与扩展性测试不计入综合评分，逐核和各线程数之间可以取消
+            match cpu_topology::run_topology_benchmark(&cpu_benchmarks::topology_kernel(), thread_count(), cancel) {
+                Ok(topology) => update_topology_result(topology),
+                Err(Cancelled) => {
+                    update_test_cancelled();
+                    return;
+                }
+            }
+            continue;
+        }
+        
         // 执行严谨的基准测试
-        let result = match i {
+        let result = pool.install(|| match i {
             0 => cpu_benchmarks::run_rigorous_integer_benchmark(),
             1 => cpu_benchmarks::run_rigorous_floating_point_benchmark(),
             2 => cpu_benchmarks::run_rigorous_simd_benchmark(),
-            3 => cpu_benchmarks::run_rigorous_cryptographic_benchmark(),
-            4 => cpu_benchmarks::run_rigorous_compression_benchmark(),
+            3 => {
+                let (result, crypto) = cpu_benchmarks::run_rigorous_cryptographic_benchmark();
+                update_crypto_result(crypto);
+                Some(result)
+            }
+            4 => {
+                let (result, compression) = cpu_benchmarks::run_rigorous_compression_benchmark();
+                update_compression_result(compression);
+                Some(result)
+            }
             5 => cpu_benchmarks::run_rigorous_memory_benchmark(),
             6 => cpu_benchmarks::run_rigorous_multithreading_benchmark(),
             _ => None,
-        };
+        });
         
         if let Some(test_result) = result {
             all_results.push(test_result);
@@ -177,14 +259,14 @@ fn run_async_cpu_tests() {
         update_estimated_scores(&all_results);
     }
     
-    // 计算最终分数（使用Geekbench风格的加权）
-    let final_result = cpu_benchmarks::calculate_geekbench_scores(&all_results);
+    // 按评分模型的权重计算综合分数
+    let final_result = cpu_score::overall(&all_results);
     all_results.push(final_result);
     
     // 完成测试
     update_test_status("CPU测试完成", 100, false);
     update_test_results(all_results);
-    CPU_TEST_STARTED.store(false, Ordering::Relaxed);
+    super::history::record_cpu_run(&get_current_test_info());
 }
 
 fn update_test_status(current_test: &str, progress: u8, is_testing: bool) {
@@ -222,6 +304,43 @@ fn update_test_results(results: Vec<CpuTestResult>) {
     if let Ok(mut global_info) = CPU_TEST_INFO.lock() {
         if let Some(ref mut info) = global_info.as_mut() {
             info.results = results;
+            info.system_summary = SystemSummary::from_samples(&info.system_samples);
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+fn update_crypto_result(crypto: CryptoBenchResult) {
+    if let Ok(mut global_info) = CPU_TEST_INFO.lock() {
+        if let Some(ref mut info) = global_info.as_mut() {
+            info.crypto = Some(crypto);
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+fn update_compression_result(compression: Vec<CompressionRatio>) {
+    if let Ok(mut global_info) = CPU_TEST_INFO.lock() {
+        if let Some(ref mut info) = global_info.as_mut() {
+            info.compression = compression;
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+fn push_system_sample(sample: SystemSample) {
+    if let Ok(mut global_info) = CPU_TEST_INFO.lock() {
+        if let Some(ref mut info) = global_info.as_mut() {
+            info.system_samples.push(sample);
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+fn update_topology_result(topology: TopologyResult) {
+    if let Ok(mut global_info) = CPU_TEST_INFO.lock() {
+        if let Some(ref mut info) = global_info.as_mut() {
+            info.topology = Some(topology);
             NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
         }
     }
@@ -263,7 +382,33 @@ fn update_test_status_with_error(error: String) {
             NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
         }
     }
-    CPU_TEST_STARTED.store(false, Ordering::Relaxed);
+}
+
+// 测试被取消：保留已完成测试项的估计分数，不写入历史记录
+fn update_test_cancelled() {
+    if let Ok(mut global_info) = CPU_TEST_INFO.lock() {
+        if let Some(ref mut info) = global_info.as_mut() {
+            info.current_test = "测试已取消".to_string();
+            info.current_test_phase = info.current_test.clone();
+            info.error_message = Some(Cancelled.to_string());
+            info.is_testing = false;
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+// 测试项之间检查暂停和取消，暂停期间在界面上显示提示
+fn checkpoint(cancel: &CancelToken) -> Result<(), Cancelled> {
+    if cancel.is_paused() {
+        if let Ok(mut global_info) = CPU_TEST_INFO.lock() {
+            if let Some(ref mut info) = global_info.as_mut() {
+                info.current_test = "已暂停，按空格继续".to_string();
+                info.current_test_phase = info.current_test.clone();
+                NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+    cancel.checkpoint()
 }
 
 fn calculate_final_scores(results: &[CpuTestResult]) -> CpuTestResult {
@@ -313,6 +458,7 @@ fn calculate_final_scores(results: &[CpuTestResult]) -> CpuTestResult {
         multi_core_score: final_multi,
         duration_ms: results.iter().map(|r| r.duration_ms).sum(),
         details,
+        kernels: Vec::new(),
     }
 }
 
@@ -394,7 +540,11 @@ fn format_cpu_test_info(info: &CpuTestInfo) -> String {
         if let Some(final_result) = info.results.iter().find(|r| r.test_name == "综合评分") {
             output.push_str(&format!("🏆 综合评分:\n"));
             output.push_str(&format!("   单核心: {} 分\n", final_result.single_core_score));
-            output.push_str(&format!("   多核心: {} 分\n\n", final_result.multi_core_score));
+            output.push_str(&format!("   多核心: {} 分\n", final_result.multi_core_score));
+            for (key, value) in sorted_details(final_result) {
+                output.push_str(&format!("   {}: {}\n", key, value));
+            }
+            output.push('\n');
         }
         
         // 显示详细测试结果
@@ -405,16 +555,28 @@ fn format_cpu_test_info(info: &CpuTestInfo) -> String {
         for result in &info.results {
             if result.test_name != "综合评分" {
                 output.push_str(&format!("{}:\n", result.test_name));
-                output.push_str(&format!("  单核: {} 分 | 多核: {} 分 | 耗时: {}ms\n\n", 
+                output.push_str(&format!("  单核: {} 分 | 多核: {} 分 | 耗时: {}ms\n", 
                     result.single_core_score, 
                     result.multi_core_score,
                     result.duration_ms
                 ));
+                for (key, value) in sorted_details(result) {
+                    output.push_str(&format!("  {}: {}\n", key, value));
+                }
+                output.push('\n');
             }
         }
         
+        if let Some(ref topology) = info.topology {
+            output.push_str(&format_topology(topology));
+        }
+        
+        if let Some(ref summary) = info.system_summary {
+            output.push_str(&format!("\n系统监控 ({} 次采样): {}\n", summary.samples, summary.label()));
+        }
+        
         output.push_str("\n💡 说明: 分数越高表示性能越好。\n");
-        output.push_str("这些分数可以与其他设备进行对比。\n");
+        output.push_str("评分版本相同的分数可以与其他设备进行对比。\n");
     } else {
         output.push_str("准备开始CPU测试...\n\n");
         output.push_str(&info.cpu_info);
@@ -423,7 +585,7 @@ fn format_cpu_test_info(info: &CpuTestInfo) -> String {
         output.push_str("• 整数运算性能\n");
         output.push_str("• 浮点运算性能\n");
         output.push_str("• 矢量运算性能\n");
-        output.push_str("• 加密算法性能\n");
+        output.push_str("• 加密算法性能 (SHA-256 / AES-128-GCM / ChaCha20-Poly1305)\n");
         output.push_str("• 压缩算法性能\n");
         output.push_str("• 内存带宽测试\n");
         output.push_str("• 综合性能评估\n\n");
@@ -433,10 +595,46 @@ fn format_cpu_test_info(info: &CpuTestInfo) -> String {
     output
 }
 
+// 拓扑、逐核分数和扩展曲线
+fn format_topology(topology: &TopologyResult) -> String {
+    let mut output = String::from("核心拓扑与扩展性:\n");
+    output.push_str(&format!("  拓扑: {}\n", topology.topology.label()));
+    if let Some(steal) = topology.steal_pct {
+        output.push_str(&format!("  steal 时间: {:.1}%\n", steal));
+    }
+    output.push_str(&format!("  逐核分数 ({}):\n", topology.kernel));
+    for core in &topology.cores {
+        output.push_str(&format!("    CPU {:<3} {:>5} 分  {:.2} ms\n", core.cpu, core.score, core.median_ms));
+    }
+    output.push_str("  扩展曲线:\n");
+    for point in &topology.scaling {
+        output.push_str(&format!(
+            "    {:>3} 线程 {:>6} 分  {:.2}x  效率 {:.0}%\n",
+            point.threads,
+            point.score,
+            point.speedup,
+            point.efficiency * 100.0
+        ));
+    }
+    for warning in &topology.warnings {
+        output.push_str(&format!("  ⚠ {}\n", warning));
+    }
+    output
+}
+
+/// 按名称排序的测试详情：各任务的耗时和吞吐量，以及压缩率等附加信息
+pub fn sorted_details(result: &CpuTestResult) -> Vec<(String, String)> {
+    let mut details: Vec<_> = result.kernels.iter().flat_map(cpu_score::describe_kernel).collect();
+    details.extend(result.details.iter().map(|(key, value)| (key.clone(), value.clone())));
+    details.sort();
+    details
+}
+
 // CPU基准测试模块
 mod cpu_benchmarks {
     use super::*;
     use std::f64::consts::PI;
+    use std::hint::black_box;
     use rand::prelude::*;
     
     pub fn run_integer_test() -> Option<CpuTestResult> {
@@ -481,6 +679,7 @@ mod cpu_benchmarks {
             multi_core_score: multi_score,
             duration_ms: start_time.elapsed().as_millis() as u64,
             details: HashMap::new(),
+            kernels: Vec::new(),
         })
     }
     
@@ -525,6 +724,7 @@ mod cpu_benchmarks {
             multi_core_score: multi_score,
             duration_ms: start_time.elapsed().as_millis() as u64,
             details: HashMap::new(),
+            kernels: Vec::new(),
         })
     }
     
@@ -575,6 +775,7 @@ mod cpu_benchmarks {
             multi_core_score: multi_score,
             duration_ms: start_time.elapsed().as_millis() as u64,
             details: HashMap::new(),
+            kernels: Vec::new(),
         })
     }
     
@@ -624,47 +825,7 @@ mod cpu_benchmarks {
             multi_core_score: multi_score,
             duration_ms: start_time.elapsed().as_millis() as u64,
             details: HashMap::new(),
-        })
-    }
-    
-    pub fn run_compression_test() -> Option<CpuTestResult> {
-        let start_time = Instant::now();
-        
-        // 模拟压缩算法（字符串压缩）
-        let test_data = "a".repeat(10000) + &"b".repeat(10000) + &"c".repeat(10000);
-        
-        let single_score = {
-            let iterations = 1000;
-            let start = Instant::now();
-            
-            for _ in 0..iterations {
-                let _compressed = simple_compress(&test_data);
-            }
-            
-            let duration = start.elapsed().as_millis() as f64;
-            ((iterations as f64 / duration) * 25.0) as u32
-        };
-        
-        // 多核心压缩测试
-        let multi_score = {
-            let iterations = 1000;
-            let start = Instant::now();
-            
-            let _results: Vec<String> = (0..iterations)
-                .into_par_iter()
-                .map(|_| simple_compress(&test_data))
-                .collect();
-            
-            let duration = start.elapsed().as_millis() as f64;
-            ((iterations as f64 / duration) * 25.0 * num_cpus::get() as f64) as u32
-        };
-        
-        Some(CpuTestResult {
-            test_name: "压缩算法测试".to_string(),
-            single_core_score: single_score,
-            multi_core_score: multi_score,
-            duration_ms: start_time.elapsed().as_millis() as u64,
-            details: HashMap::new(),
+            kernels: Vec::new(),
         })
     }
     
@@ -712,6 +873,7 @@ mod cpu_benchmarks {
             multi_core_score: multi_score,
             duration_ms: start_time.elapsed().as_millis() as u64,
             details: HashMap::new(),
+            kernels: Vec::new(),
         })
     }
     
@@ -761,6 +923,7 @@ mod cpu_benchmarks {
             multi_core_score: multi_score,
             duration_ms: start_time.elapsed().as_millis() as u64,
             details: HashMap::new(),
+            kernels: Vec::new(),
         })
     }
     
@@ -785,431 +948,99 @@ mod cpu_benchmarks {
         1
     }
     
-    fn simple_compress(data: &str) -> String {
-        // 简单的RLE压缩
-        let mut result = String::new();
-        let chars: Vec<char> = data.chars().collect();
-        if chars.is_empty() { return result; }
-        
-        let mut current_char = chars[0];
-        let mut count = 1;
-        
-        for &ch in &chars[1..] {
-            if ch == current_char {
-                count += 1;
-            } else {
-                result.push_str(&format!("{}{}", count, current_char));
-                current_char = ch;
-                count = 1;
-            }
-        }
-        result.push_str(&format!("{}{}", count, current_char));
-        result
-    }
-    
     // ============ 严谨的CPU基准测试算法 ============
     
-    /// 严谨的整数运算基准测试（基于素数筛选和斐波那契数列）
+    // 每个测试项由若干固定输入的任务组成，评分方法见 cpu_score 模块。
+    // 任务的参数和参考耗时属于评分模型的一部分，修改后需要递增 CPU_SCORE_VERSION
+
+    /// 整数运算基准：埃拉托斯特尼筛法 + 递归斐波那契数列
     pub fn run_rigorous_integer_benchmark() -> Option<CpuTestResult> {
-        let start_time = Instant::now();
-        
-        // 单核测试：埃拉托斯特尼筛法 + 斐波那契数列
-        let single_core_score = {
-            let test_start = Instant::now();
-            let _primes_count = sieve_of_eratosthenes(1_000_000); // 增加到100万
-            let _fib_result = fibonacci_recursive(40); // 增加到40
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            // 基于GeekBench 5的计分标准：基准分1000分，基准时间约5秒
-            let base_time_ms = 5000.0; // 基准时间5秒
-            let base_score = 1000.0;   // 基准分数1000分
-            
-            // 分数与时间成反比，时间越短分数越高
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                1000
-            }
-        };
-        
-        // 多核测试：并行素数筛选
-        let multi_core_score = {
-            use rayon::prelude::*;
-            let test_start = Instant::now();
-            
-            // 增加计算量，让测试更有挑战性
-            let ranges: Vec<_> = (0..16).map(|i| (i * 62500, (i + 1) * 62500)).collect();
-            let _total_primes: usize = ranges.par_iter()
-                .map(|&(start, end)| count_primes_in_range(start, end))
-                .sum();
-                
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            // 多核基准分数通常是单核的4-8倍
-            let base_time_ms = 2000.0; // 多核基准时间2秒
-            let base_score = 4000.0;   // 多核基准分数4000分
-            
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                4000
-            }
-        };
-        
-        Some(CpuTestResult {
-            test_name: "整数运算基准".to_string(),
-            single_core_score,
-            multi_core_score,
-            duration_ms: start_time.elapsed().as_millis() as u64,
-            details: HashMap::new(),
+        let kernels = [
+            Kernel::new("素数筛 400 万", 38.3, || {
+                black_box(sieve_of_eratosthenes(black_box(4_000_000)));
+            }),
+            topology_kernel(),
+        ];
+        Some(cpu_score::run_workload(cpu_score::INTEGER, &kernels))
+    }
+    
+    /// 纯计算、几乎不访问内存的任务，用于逐核和扩展性测试，核心之间的差异只来自 CPU 本身
+    pub fn topology_kernel() -> Kernel {
+        Kernel::new("斐波那契 fib(33)", 21.2, || {
+            black_box(fibonacci_recursive(black_box(33)));
         })
     }
     
-    /// 严谨的浮点运算基准测试（科学计算和三角函数）
+    /// 浮点运算基准：蒙特卡洛法估算π + 三角函数
     pub fn run_rigorous_floating_point_benchmark() -> Option<CpuTestResult> {
-        let start_time = Instant::now();
-        
-        // 单核测试：蒙特卡洛方法计算π + 复杂三角函数
-        let single_core_score = {
-            let test_start = Instant::now();
-            let _pi_estimate = monte_carlo_pi(5_000_000); // 增加到500万次
-            let _trig_sum = complex_trigonometric_operations(1_000_000); // 增加到100万次
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            // 浮点运算基准分数
-            let base_time_ms = 4000.0; // 基准时间4秒
-            let base_score = 1200.0;   // 基准分数1200分
-            
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                1200
-            }
-        };
-        
-        // 多核测试：并行蒙特卡洛
-        let multi_score = {
-            use rayon::prelude::*;
-            let test_start = Instant::now();
-            
-            let _pi_estimates: Vec<f64> = (0..8).into_par_iter()
-                .map(|_| monte_carlo_pi(1_250_000)) // 每个线程125万次
-                .collect();
-            
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            let base_time_ms = 1500.0; // 多核基准时间1.5秒
-            let base_score = 5000.0;   // 多核基准分数5000分
-            
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                5000
-            }
-        };
-        
-        Some(CpuTestResult {
-            test_name: "浮点运算基准".to_string(),
-            single_core_score,
-            multi_core_score: multi_score,
-            duration_ms: start_time.elapsed().as_millis() as u64,
-            details: HashMap::new(),
-        })
+        let kernels = [
+            Kernel::new("蒙特卡洛 π 800 万次", 46.4, || {
+                black_box(monte_carlo_pi(black_box(8_000_000)));
+            }),
+            Kernel::new("三角函数 100 万次", 50.7, || {
+                black_box(complex_trigonometric_operations(black_box(1_000_000)));
+            }),
+        ];
+        Some(cpu_score::run_workload(cpu_score::FLOATING_POINT, &kernels))
     }
     
-    /// 严谨的SIMD矢量计算基准测试
+    /// SIMD矢量计算基准：向量点积 + 矩阵乘法
     pub fn run_rigorous_simd_benchmark() -> Option<CpuTestResult> {
-        let start_time = Instant::now();
-        
-        // 模拟SIMD操作：向量点积和矩阵乘法
-        let single_core_score = {
-            let test_start = Instant::now();
-            let _vector_ops = vector_dot_product_operations(500_000); // 增加到50万
-            let _matrix_ops = matrix_multiplication_benchmark(200); // 增加到200x200矩阵
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            let base_time_ms = 3000.0; // 基准时间3秒
-            let base_score = 900.0;    // 基准分数900分
-            
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                900
-            }
-        };
-        
-        // 多核SIMD测试
-        let multi_score = {
-            use rayon::prelude::*;
-            let test_start = Instant::now();
-            
-            let _results: Vec<f64> = (0..8).into_par_iter()
-                .map(|_| vector_dot_product_operations(125_000) + matrix_multiplication_benchmark(100))
-                .collect();
-            
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            let base_time_ms = 1200.0; // 多核基准时间1.2秒
-            let base_score = 4500.0;   // 多核基准分数4500分
-            
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                4500
-            }
-        };
-        
-        Some(CpuTestResult {
-            test_name: "SIMD矢量计算".to_string(),
-            single_core_score,
-            multi_core_score: multi_score,
-            duration_ms: start_time.elapsed().as_millis() as u64,
-            details: HashMap::new(),
-        })
+        let kernels = [
+            Kernel::new("向量点积 400 万", 20.6, || {
+                black_box(vector_dot_product_operations(black_box(4_000_000)));
+            }),
+            Kernel::new("矩阵乘法 192x192", 43.4, || {
+                black_box(matrix_multiplication_benchmark(black_box(192)));
+            }),
+        ];
+        Some(cpu_score::run_workload(cpu_score::SIMD, &kernels))
     }
     
-    /// 严谨的加密哈希运算基准测试
-    pub fn run_rigorous_cryptographic_benchmark() -> Option<CpuTestResult> {
-        let start_time = Instant::now();
-        
-        // 单核测试：SHA-256模拟 + 简单加密操作
-        let single_core_score = {
-            let test_start = Instant::now();
-            let _hash_ops = sha256_simulation_benchmark(100_000); // 增加到10万次
-            let _encrypt_ops = simple_encryption_benchmark(500_000); // 增加到50万次
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            let base_time_ms = 3500.0; // 基准时间3.5秒
-            let base_score = 800.0;    // 基准分数800分
-            
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                800
-            }
-        };
-        
-        // 多核测试：并行哈希计算
-        let multi_score = {
-            use rayon::prelude::*;
-            let test_start = Instant::now();
-            
-            let _results: Vec<f64> = (0..8).into_par_iter()
-                .map(|_| sha256_simulation_benchmark(25_000) + simple_encryption_benchmark(125_000) as f64)
-                .collect();
-            
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            let base_time_ms = 1800.0; // 多核基准时间1.8秒
-            let base_score = 3800.0;   // 多核基准分数3800分
-            
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                3800
-            }
-        };
-        
-        Some(CpuTestResult {
-            test_name: "加密哈希运算".to_string(),
-            single_core_score,
-            multi_core_score: multi_score,
-            duration_ms: start_time.elapsed().as_millis() as u64,
-            details: HashMap::new(),
-        })
+    /// 加密哈希运算基准：真实的 SHA-256、AES-128-GCM、ChaCha20-Poly1305 吞吐量
+    pub fn run_rigorous_cryptographic_benchmark() -> (CpuTestResult, CryptoBenchResult) {
+        let mut result = cpu_score::run_workload(cpu_score::CRYPTO, &crypto_bench::kernels());
+        let crypto = crypto_bench::summarize(&result.kernels);
+        result.details.insert("硬件加速".to_string(), crypto.hardware.label());
+        (result, crypto)
     }
     
-    /// 严谨的数据压缩算法基准测试
-    pub fn run_rigorous_compression_benchmark() -> Option<CpuTestResult> {
-        let start_time = Instant::now();
-        
-        // 单核测试：LZ77风格压缩 + 霍夫曼编码模拟
-        let single_core_score = {
-            let test_start = Instant::now();
-            let _compression_ratio = lz77_compression_simulation(100_000); // 增加到10万
-            let _huffman_ops = huffman_encoding_simulation(200_000); // 增加到20万
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            let base_time_ms = 4500.0; // 基准时间4.5秒
-            let base_score = 700.0;    // 基准分数700分
-            
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                700
-            }
-        };
-        
-        // 多核测试：并行数据压缩
-        let multi_score = {
-            use rayon::prelude::*;
-            let test_start = Instant::now();
-            
-            let _results: Vec<f64> = (0..8).into_par_iter()
-                .map(|_| lz77_compression_simulation(25_000) + huffman_encoding_simulation(50_000))
-                .collect();
-            
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            let base_time_ms = 2000.0; // 多核基准时间2秒
-            let base_score = 3200.0;   // 多核基准分数3200分
-            
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                3200
-            }
-        };
-        
-        Some(CpuTestResult {
-            test_name: "数据压缩算法".to_string(),
-            single_core_score,
-            multi_core_score: multi_score,
-            duration_ms: start_time.elapsed().as_millis() as u64,
-            details: HashMap::new(),
-        })
+    /// 数据压缩算法基准：zstd、deflate、lz4 处理固定语料的压缩和解压吞吐量
+    pub fn run_rigorous_compression_benchmark() -> (CpuTestResult, Vec<CompressionRatio>) {
+        let (ratios, kernels) = compression_bench::kernels();
+        let mut result = cpu_score::run_workload(cpu_score::COMPRESSION, &kernels);
+        for ratio in &ratios {
+            result.details.insert(format!("{} 压缩率", ratio.codec), format!("{:.2}x", ratio.compression_ratio));
+        }
+        (result, ratios)
     }
     
-    /// 严谨的内存带宽基准测试
+    /// 内存带宽基准：随机交换 + 顺序/随机读取
     pub fn run_rigorous_memory_benchmark() -> Option<CpuTestResult> {
-        let start_time = Instant::now();
-        
-        // 单核测试：内存密集型操作
-        let single_core_score = {
-            let test_start = Instant::now();
-            let _memory_ops = memory_intensive_operations(1_000_000); // 增加到100万
-            let _cache_performance = cache_performance_test(500_000); // 增加到50万
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            let base_time_ms = 6000.0; // 基准时间6秒
-            let base_score = 600.0;    // 基准分数600分
-            
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                600
-            }
-        };
-        
-        // 多核测试：并行内存访问
-        let multi_score = {
-            use rayon::prelude::*;
-            let test_start = Instant::now();
-            
-            let _results: Vec<f64> = (0..8).into_par_iter()
-                .map(|_| memory_intensive_operations(250_000) + cache_performance_test(125_000))
-                .collect();
-            
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            let base_time_ms = 2500.0; // 多核基准时间2.5秒
-            let base_score = 2800.0;   // 多核基准分数2800分
-            
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                2800
-            }
-        };
-        
-        Some(CpuTestResult {
-            test_name: "内存带宽测试".to_string(),
-            single_core_score,
-            multi_core_score: multi_score,
-            duration_ms: start_time.elapsed().as_millis() as u64,
-            details: HashMap::new(),
-        })
+        let kernels = [
+            Kernel::new("随机交换 200 万", 17.4, || {
+                black_box(memory_intensive_operations(black_box(2_000_000)));
+            })
+            .with_bytes(2_000_000 * std::mem::size_of::<f64>()),
+            Kernel::new("顺序+随机读取 400 万", 29.7, || {
+                black_box(cache_performance_test(black_box(4_000_000)));
+            })
+            .with_bytes(4_000_000 * std::mem::size_of::<f64>()),
+        ];
+        Some(cpu_score::run_workload(cpu_score::MEMORY, &kernels))
     }
     
-    /// 严谨的多线程并发基准测试
+    /// 多线程并发基准：每个线程各自运行一份浮点计算，衡量多核扩展性
     pub fn run_rigorous_multithreading_benchmark() -> Option<CpuTestResult> {
-        let start_time = Instant::now();
-        
-        // 单核分数（作为基准）
-        let single_core_score = {
-            let test_start = Instant::now();
-            let _sequential_ops = sequential_processing_benchmark(100_000); // 增加到10万
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            let base_time_ms = 5000.0; // 基准时间5秒
-            let base_score = 500.0;    // 基准分数500分
-            
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                500
-            }
-        };
-        
-        // 多核测试：真正的并发性能测试
-        let multi_core_score = {
-            use rayon::prelude::*;
-            let test_start = Instant::now();
-            
-            // 模拟生产者-消费者模式和并发计算
-            let _concurrent_results: Vec<f64> = (0..16).into_par_iter()
-                .map(|_| concurrent_processing_benchmark(20_000)) // 增加到2万
-                .collect();
-            
-            let elapsed_ms = test_start.elapsed().as_millis() as f64;
-            
-            let base_time_ms = 1500.0; // 多核基准时间1.5秒
-            let base_score = 6000.0;   // 多核基准分数6000分
-            
-            if elapsed_ms > 0.0 {
-                (base_score * base_time_ms / elapsed_ms) as u32
-            } else {
-                6000
-            }
-        };
-        
-        Some(CpuTestResult {
-            test_name: "多线程并发".to_string(),
-            single_core_score,
-            multi_core_score,
-            duration_ms: start_time.elapsed().as_millis() as u64,
-            details: HashMap::new(),
-        })
-    }
-    
-    /// 计算GeekBench风格的加权综合分数
-    pub fn calculate_geekbench_scores(results: &[CpuTestResult]) -> CpuTestResult {
-        // GeekBench 5的权重分配（近似）
-        let weights = [
-            ("整数运算基准", 0.20),
-            ("浮点运算基准", 0.20),
-            ("SIMD矢量计算", 0.15),
-            ("加密哈希运算", 0.15),
-            ("数据压缩算法", 0.10),
-            ("内存带宽测试", 0.10),
-            ("多线程并发", 0.10),
+        let kernels = [
+            Kernel::new("顺序计算 100 万次", 10.1, || {
+                black_box(sequential_processing_benchmark(black_box(1_000_000)));
+            }),
+            Kernel::new("并发计算 50 万次", 15.5, || {
+                black_box(concurrent_processing_benchmark(black_box(500_000)));
+            }),
         ];
-        
-        let mut weighted_single = 0.0;
-        let mut weighted_multi = 0.0;
-        let mut total_weight = 0.0;
-        
-        for result in results {
-            if let Some((_, weight)) = weights.iter().find(|(name, _)| result.test_name.contains(name)) {
-                weighted_single += result.single_core_score as f64 * weight;
-                weighted_multi += result.multi_core_score as f64 * weight;
-                total_weight += weight;
-            }
-        }
-        
-        // 确保权重归一化
-        if total_weight > 0.0 {
-            weighted_single /= total_weight;
-            weighted_multi /= total_weight;
-        }
-        
-        CpuTestResult {
-            test_name: "综合评分".to_string(),
-            single_core_score: weighted_single as u32,
-            multi_core_score: weighted_multi as u32,
-            duration_ms: 0,
-            details: HashMap::new(),
-        }
+        Some(cpu_score::run_workload(cpu_score::MULTITHREADING, &kernels))
     }
     
     // ============ 基准测试辅助函数 ============
@@ -1231,23 +1062,6 @@ mod cpu_benchmarks {
         is_prime.iter().filter(|&&x| x).count()
     }
     
-    /// 计算指定范围内的素数个数
-    fn count_primes_in_range(start: usize, end: usize) -> usize {
-        (start..end).filter(|&n| is_prime(n)).count()
-    }
-    
-    /// 判断是否为素数
-    fn is_prime(n: usize) -> bool {
-        if n < 2 { return false; }
-        if n == 2 { return true; }
-        if n % 2 == 0 { return false; }
-        
-        for i in (3..=((n as f64).sqrt() as usize)).step_by(2) {
-            if n % i == 0 { return false; }
-        }
-        true
-    }
-    
     /// 递归斐波那契数列（CPU密集型）
     fn fibonacci_recursive(n: u32) -> u64 {
         match n {
@@ -1310,97 +1124,6 @@ mod cpu_benchmarks {
         result
     }
     
-    /// SHA-256模拟基准测试
-    fn sha256_simulation_benchmark(iterations: usize) -> f64 {
-        let mut hash_sum = 0u64;
-        for i in 0..iterations {
-            // 模拟SHA-256的部分计算过程
-            let mut h = i as u64;
-            for _ in 0..64 {
-                h = h.wrapping_mul(1103515245).wrapping_add(12345);
-                h ^= h >> 16;
-                h = h.wrapping_mul(0x85ebca6b);
-                h ^= h >> 13;
-                h = h.wrapping_mul(0xc2b2ae35);
-                h ^= h >> 16;
-            }
-            hash_sum = hash_sum.wrapping_add(h);
-        }
-        hash_sum as f64
-    }
-    
-    /// 简单加密操作基准测试
-    fn simple_encryption_benchmark(iterations: usize) -> u64 {
-        let mut encrypted_sum = 0u64;
-        let key = 0x1234567890ABCDEFu64;
-        
-        for i in 0..iterations {
-            let data = i as u64;
-            let encrypted = data ^ key;
-            let rotated = encrypted.rotate_left(13);
-            encrypted_sum = encrypted_sum.wrapping_add(rotated);
-        }
-        encrypted_sum
-    }
-    
-    /// LZ77压缩算法模拟
-    fn lz77_compression_simulation(data_size: usize) -> f64 {
-        let data: Vec<u8> = (0..data_size).map(|i| (i % 256) as u8).collect();
-        let mut compressed_size = 0;
-        let mut i = 0;
-        
-        while i < data.len() {
-            let mut best_length = 0;
-            let mut _best_distance = 0;
-            
-            // 查找最长匹配
-            for distance in 1..=i.min(255) {
-                if i >= distance {
-                    let mut length = 0;
-                    while length < 255 && i + length < data.len() && 
-                          data[i + length] == data[i - distance + length] {
-                        length += 1;
-                    }
-                    if length > best_length {
-                        best_length = length;
-                        _best_distance = distance;
-                    }
-                }
-            }
-            
-            if best_length >= 3 {
-                compressed_size += 3; // 距离+长度+标志
-                i += best_length;
-            } else {
-                compressed_size += 1; // 原始字节
-                i += 1;
-            }
-        }
-        
-        data_size as f64 / compressed_size as f64 // 压缩率
-    }
-    
-    /// 霍夫曼编码模拟
-    fn huffman_encoding_simulation(data_size: usize) -> f64 {
-        let data: Vec<u8> = (0..data_size).map(|i| (i % 256) as u8).collect();
-        
-        // 统计频率
-        let mut freq = [0u32; 256];
-        for &byte in &data {
-            freq[byte as usize] += 1;
-        }
-        
-        // 模拟霍夫曼树构建的计算复杂度
-        let mut complexity = 0.0;
-        for f in freq.iter() {
-            if *f > 0 {
-                complexity += (*f as f64) * (*f as f64).log2();
-            }
-        }
-        
-        complexity
-    }
-    
     /// 内存密集型操作基准
     fn memory_intensive_operations(size: usize) -> f64 {
         let mut data: Vec<f64> = (0..size).map(|i| i as f64).collect();
@@ -1453,4 +1176,4 @@ mod cpu_benchmarks {
         }
         result
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,351 @@
+// src/handlers/cpu_topology.rs
+// CPU 拓扑与扩展性测试：逐个逻辑核心绑定运行同一任务、1..N 线程的扩展曲线，
+// 并检测 SMT 兄弟线程和 /proc/stat 中的 steal 时间，用于发现多核扩展性崩溃的超售 vCPU
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::thread;
+
+use crate::cancel::{CancelToken, Cancelled};
+use super::cpu_score::{self, Kernel};
+use super::system_monitor::CpuTimes;
+
+// 每个核心、每个线程数的计时次数，测量点较多，比评分测试少
+const RUNS: usize = 3;
+
+// 核心分数低于全部核心中位数的该比例时标记为慢核心
+const SLOW_CORE_RATIO: f64 = 0.8;
+
+// 扩展效率（按物理核心数计）低于该值时认为多核扩展性崩溃
+const COLLAPSE_EFFICIENCY: f64 = 0.6;
+
+// 测试期间 steal 时间占比超过该值 (%) 时提示宿主机资源争用
+const HIGH_STEAL_PCT: f64 = 5.0;
+
+// 逻辑核心不超过该数量时逐个线程数测试，否则按 2 的幂取点
+const FULL_SCALING_MAX_THREADS: usize = 8;
+
+/// 本进程可用的逻辑 CPU 及其 SMT 分组
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CpuTopology {
+    pub logical_cpus: Vec<usize>,
+    pub smt_groups: Vec<Vec<usize>>, // 共享同一物理核心的逻辑 CPU，只包含可用的 CPU
+}
+
+impl CpuTopology {
+    pub fn detect() -> Self {
+        let logical_cpus = allowed_cpus();
+        let smt_groups = group_siblings(&logical_cpus, |cpu| {
+            let path = format!("/sys/devices/system/cpu/cpu{}/topology/thread_siblings_list", cpu);
+            fs::read_to_string(path).ok().map(|list| parse_cpu_list(&list))
+        });
+        Self { logical_cpus, smt_groups }
+    }
+
+    pub fn physical_cores(&self) -> usize {
+        self.smt_groups.len().max(1)
+    }
+
+    pub fn smt_enabled(&self) -> bool {
+        self.smt_groups.iter().any(|group| group.len() > 1)
+    }
+
+    /// 例如 "4 逻辑核心 / 2 物理核心，SMT 兄弟线程: 0,2 1,3"
+    pub fn label(&self) -> String {
+        let mut text = format!("{} 逻辑核心 / {} 物理核心", self.logical_cpus.len(), self.physical_cores());
+        if self.smt_enabled() {
+            let groups: Vec<String> = self
+                .smt_groups
+                .iter()
+                .filter(|group| group.len() > 1)
+                .map(|group| group.iter().map(usize::to_string).collect::<Vec<_>>().join(","))
+                .collect();
+            text.push_str(&format!("，SMT 兄弟线程: {}", groups.join(" ")));
+        } else {
+            text.push_str("，未检测到 SMT");
+        }
+        text
+    }
+}
+
+/// 绑定在单个逻辑核心上的测试结果
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoreScore {
+    pub cpu: usize,
+    pub score: u32,
+    pub median_ms: f64,
+    pub pinned: bool, // 绑定失败时线程由调度器决定运行位置
+}
+
+/// 扩展曲线上的一个点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScalingPoint {
+    pub threads: usize,
+    pub score: u32,
+    pub speedup: f64,    // 相对单线程的总吞吐量倍数
+    pub efficiency: f64, // speedup / 线程数
+}
+
+/// 拓扑与扩展性测试的完整结果，不计入综合评分
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TopologyResult {
+    pub topology: CpuTopology,
+    pub kernel: String,
+    pub cores: Vec<CoreScore>,
+    pub scaling: Vec<ScalingPoint>,
+    pub steal_pct: Option<f64>, // 测试期间 steal 时间占全部 CPU 时间的比例，无法读取 /proc/stat 时为 None
+    pub warnings: Vec<String>,
+}
+
+impl TopologyResult {
+    /// 分数低于中位数 SLOW_CORE_RATIO 的核心
+    pub fn slow_cores(&self) -> Vec<usize> {
+        let mut scores: Vec<u32> = self.cores.iter().map(|core| core.score).collect();
+        scores.sort_unstable();
+        let Some(&median) = scores.get(scores.len() / 2) else {
+            return Vec::new();
+        };
+        self.cores
+            .iter()
+            .filter(|core| (core.score as f64) < median as f64 * SLOW_CORE_RATIO)
+            .map(|core| core.cpu)
+            .collect()
+    }
+}
+
+/// 依次在每个逻辑核心上绑定运行 kernel，再测量 1..max_threads 线程的扩展曲线
+pub fn run_topology_benchmark(kernel: &Kernel, max_threads: usize, cancel: &CancelToken) -> Result<TopologyResult, Cancelled> {
+    let topology = CpuTopology::detect();
+    let before = CpuTimes::read();
+
+    let mut cores = Vec::new();
+    for &cpu in &topology.logical_cpus {
+        cancel.check()?;
+        let (pinned, measurement) = thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let pinned = pin_current_thread(cpu).is_ok();
+                    (pinned, cpu_score::measure(RUNS, || kernel.run_once()))
+                })
+                .join()
+                .expect("核心测试线程不会 panic")
+        });
+        cores.push(CoreScore {
+            cpu,
+            score: cpu_score::score(kernel.reference_ms, &measurement, 1).round() as u32,
+            median_ms: measurement.median_ms,
+            pinned,
+        });
+    }
+
+    let mut scaling: Vec<ScalingPoint> = Vec::new();
+    let mut single_ms = 0.0;
+    for threads in scaling_steps(max_threads) {
+        cancel.check()?;
+        let Ok(pool) = rayon::ThreadPoolBuilder::new().num_threads(threads).build() else {
+            continue;
+        };
+        let measurement = cpu_score::measure(RUNS, || {
+            pool.broadcast(|_| kernel.run_once());
+        });
+        if threads == 1 {
+            single_ms = measurement.median_ms;
+        }
+        let speedup = if measurement.median_ms > 0.0 {
+            single_ms * threads as f64 / measurement.median_ms
+        } else {
+            0.0
+        };
+        scaling.push(ScalingPoint {
+            threads,
+            score: cpu_score::score(kernel.reference_ms, &measurement, threads).round() as u32,
+            speedup,
+            efficiency: speedup / threads as f64,
+        });
+    }
+
+    let steal_pct = before.zip(CpuTimes::read()).map(|(before, after)| after.steal_pct_since(&before));
+    let mut result = TopologyResult {
+        topology,
+        kernel: kernel.name.clone(),
+        cores,
+        scaling,
+        steal_pct,
+        warnings: Vec::new(),
+    };
+    result.warnings = warnings(&result);
+    Ok(result)
+}
+
+// 扩展性崩溃、慢核心、steal 时间高、绑定失败等提示
+fn warnings(result: &TopologyResult) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    if let Some(last) = result.scaling.last().filter(|point| point.threads > 1) {
+        // SMT 兄弟线程共享执行单元，按物理核心数估计期望的加速比
+        let expected = last.threads.min(result.topology.physical_cores()) as f64;
+        if last.speedup < expected * COLLAPSE_EFFICIENCY {
+            warnings.push(format!(
+                "{} 线程只达到单线程的 {:.1} 倍（{} 个物理核心），多核扩展性差，vCPU 可能超售",
+                last.threads,
+                last.speedup,
+                result.topology.physical_cores()
+            ));
+        }
+    }
+
+    let slow = result.slow_cores();
+    if !slow.is_empty() {
+        let list: Vec<String> = slow.iter().map(usize::to_string).collect();
+        warnings.push(format!(
+            "CPU {} 的单核分数低于中位数的 {:.0}%，可能与其他负载共享或被降频",
+            list.join(","),
+            SLOW_CORE_RATIO * 100.0
+        ));
+    }
+
+    if let Some(steal) = result.steal_pct.filter(|steal| *steal > HIGH_STEAL_PCT) {
+        warnings.push(format!("测试期间 steal 时间占 {:.1}%，宿主机 CPU 争用明显", steal));
+    }
+
+    if result.cores.iter().any(|core| !core.pinned) {
+        warnings.push("无法把线程绑定到指定核心，逐核结果可能不准确".to_string());
+    }
+
+    warnings
+}
+
+/// 扩展曲线测量的线程数：核心较少时 1..=max，否则取 2 的幂并包含 max
+pub fn scaling_steps(max_threads: usize) -> Vec<usize> {
+    let max_threads = max_threads.max(1);
+    if max_threads <= FULL_SCALING_MAX_THREADS {
+        return (1..=max_threads).collect();
+    }
+    let mut steps: Vec<usize> = std::iter::successors(Some(1usize), |n| Some(n * 2))
+        .take_while(|&n| n < max_threads)
+        .collect();
+    steps.push(max_threads);
+    steps
+}
+
+/// 解析 "0-3,8,10-11" 形式的 CPU 列表
+pub fn parse_cpu_list(list: &str) -> Vec<usize> {
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',').filter(|part| !part.is_empty()) {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                if let (Ok(start), Ok(end)) = (start.parse::<usize>(), end.parse::<usize>()) {
+                    cpus.extend(start..=end);
+                }
+            }
+            None => cpus.extend(part.parse::<usize>().ok()),
+        }
+    }
+    cpus
+}
+
+// 按兄弟线程列表把可用 CPU 分组，读不到拓扑信息的 CPU 单独成组
+fn group_siblings(cpus: &[usize], siblings: impl Fn(usize) -> Option<Vec<usize>>) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    for &cpu in cpus {
+        if groups.iter().any(|group| group.contains(&cpu)) {
+            continue;
+        }
+        let mut group: Vec<usize> = siblings(cpu)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|sibling| cpus.contains(sibling))
+            .collect();
+        if !group.contains(&cpu) {
+            group.push(cpu);
+        }
+        group.sort_unstable();
+        groups.push(group);
+    }
+    groups
+}
+
+// 本进程允许运行的逻辑 CPU（受 taskset、cgroup cpuset 限制）
+#[cfg(target_os = "linux")]
+fn allowed_cpus() -> Vec<usize> {
+    // SAFETY: cpu_set_t 是普通的位图结构，全零是合法值
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    // SAFETY: 传入的大小与 set 一致，pid 0 表示当前线程
+    let ok = unsafe { libc::sched_getaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &mut set) } == 0;
+    if ok {
+        // SAFETY: CPU_ISSET 只读取 set 中的位
+        let cpus: Vec<usize> = (0..libc::CPU_SETSIZE as usize).filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) }).collect();
+        if !cpus.is_empty() {
+            return cpus;
+        }
+    }
+    (0..num_cpus::get()).collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn allowed_cpus() -> Vec<usize> {
+    (0..num_cpus::get()).collect()
+}
+
+/// 把当前线程绑定到指定逻辑 CPU
+#[cfg(target_os = "linux")]
+pub fn pin_current_thread(cpu: usize) -> Result<(), String> {
+    // SAFETY: cpu_set_t 是普通的位图结构，全零是合法值
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    // SAFETY: CPU_SET 只修改 set 中的位，cpu 超出范围时由 libc 忽略
+    unsafe { libc::CPU_SET(cpu, &mut set) };
+    // SAFETY: 传入的大小与 set 一致，pid 0 表示当前线程
+    if unsafe { libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) } == 0 {
+        Ok(())
+    } else {
+        Err(format!("无法绑定到 CPU {}: {}", cpu, std::io::Error::last_os_error()))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin_current_thread(_cpu: usize) -> Result<(), String> {
+    Err("当前系统不支持绑定线程到指定核心".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cpu_list_and_siblings() {
+        assert_eq!(parse_cpu_list("0-3,8,10-11\n"), vec![0, 1, 2, 3, 8, 10, 11]);
+        assert_eq!(parse_cpu_list(""), Vec::<usize>::new());
+
+        // 0/2、1/3 是兄弟线程，3 不在可用列表中
+        let siblings = |cpu: usize| Some(if [0, 2].contains(&cpu) { vec![0, 2] } else { vec![1, 3] });
+        let groups = group_siblings(&[0, 1, 2], siblings);
+        assert_eq!(groups, vec![vec![0, 2], vec![1]]);
+        assert_eq!(group_siblings(&[5], |_| None), vec![vec![5]]);
+    }
+
+    #[test]
+    fn test_scaling_steps_and_collapse_warning() {
+        assert_eq!(scaling_steps(4), vec![1, 2, 3, 4]);
+        assert_eq!(scaling_steps(24), vec![1, 2, 4, 8, 16, 24]);
+
+        let point = |threads: usize, speedup: f64| ScalingPoint {
+            threads,
+            score: 0,
+            speedup,
+            efficiency: speedup / threads as f64,
+        };
+        let core = |cpu: usize, score: u32| CoreScore { cpu, score, median_ms: 0.0, pinned: true };
+        let mut result = TopologyResult {
+            topology: CpuTopology { logical_cpus: vec![0, 1, 2, 3], smt_groups: vec![vec![0], vec![1], vec![2], vec![3]] },
+            cores: vec![core(0, 1000), core(1, 990), core(2, 600), core(3, 1010)],
+            scaling: vec![point(1, 1.0), point(4, 1.5)],
+            ..Default::default()
+        };
+        assert_eq!(result.slow_cores(), vec![2]);
+        assert_eq!(warnings(&result).len(), 2);
+
+        // 2 个物理核心各带一个 SMT 兄弟线程，4 线程 1.5 倍不算崩溃
+        result.topology.smt_groups = vec![vec![0, 2], vec![1, 3]];
+        result.cores[2].score = 1000;
+        assert!(warnings(&result).is_empty());
+    }
+}
@@ -0,0 +1,175 @@
+// src/handlers/crypto_bench.rs
+// 真实的加密/哈希吞吐量测试：SHA-256、AES-128-GCM、ChaCha20-Poly1305
+// 按 TLS 记录大小分块处理，使用 rustls 同款的 ring 实现，结果接近代理服务实际能达到的加解密速度
+use ring::aead::{self, Aad, LessSafeKey, Nonce, UnboundKey};
+use ring::digest;
+use serde::{Deserialize, Serialize};
+use std::hint::black_box;
+
+use super::cpu_score::{Kernel, KernelResult};
+
+// 每次处理的数据块大小，等于 TLS 单条记录的最大明文长度
+const RECORD_SIZE: usize = 16 * 1024;
+
+/// 每次计时处理的数据量
+pub const INPUT_SIZE: usize = 64 * 1024 * 1024;
+
+/// 测试的算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CryptoAlgorithm {
+    Sha256,
+    Aes128Gcm,
+    ChaCha20Poly1305,
+}
+
+impl CryptoAlgorithm {
+    pub const ALL: [CryptoAlgorithm; 3] = [
+        CryptoAlgorithm::Sha256,
+        CryptoAlgorithm::Aes128Gcm,
+        CryptoAlgorithm::ChaCha20Poly1305,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CryptoAlgorithm::Sha256 => "SHA-256",
+            CryptoAlgorithm::Aes128Gcm => "AES-128-GCM",
+            CryptoAlgorithm::ChaCha20Poly1305 => "ChaCha20-Poly1305",
+        }
+    }
+
+    // 参考机器上单线程处理 INPUT_SIZE 的中位耗时 (ms)
+    fn reference_ms(&self) -> f64 {
+        match self {
+            CryptoAlgorithm::Sha256 => 50.6,
+            CryptoAlgorithm::Aes128Gcm => 8.1,
+            CryptoAlgorithm::ChaCha20Poly1305 => 44.8,
+        }
+    }
+}
+
+/// CPU 提供的加密指令扩展
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HardwareFeatures {
+    pub aes: bool,   // AES-NI / ARMv8 AES
+    pub clmul: bool, // PCLMULQDQ / PMULL，用于 GCM 的 GHASH
+    pub sha: bool,   // SHA 扩展 / ARMv8 SHA2
+}
+
+impl HardwareFeatures {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    pub fn detect() -> Self {
+        Self {
+            aes: std::arch::is_x86_feature_detected!("aes"),
+            clmul: std::arch::is_x86_feature_detected!("pclmulqdq"),
+            sha: std::arch::is_x86_feature_detected!("sha"),
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    pub fn detect() -> Self {
+        Self {
+            aes: std::arch::is_aarch64_feature_detected!("aes"),
+            clmul: std::arch::is_aarch64_feature_detected!("pmull"),
+            sha: std::arch::is_aarch64_feature_detected!("sha2"),
+        }
+    }
+
+    #[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+    pub fn detect() -> Self {
+        Self::default()
+    }
+
+    pub fn label(&self) -> String {
+        let mark = |enabled: bool| if enabled { "✓" } else { "✗" };
+        let (aes, clmul, sha) = if cfg!(target_arch = "aarch64") {
+            ("AES", "PMULL", "SHA2")
+        } else {
+            ("AES-NI", "PCLMULQDQ", "SHA 扩展")
+        };
+        format!("{} {}  {} {}  {} {}", aes, mark(self.aes), clmul, mark(self.clmul), sha, mark(self.sha))
+    }
+}
+
+/// 单个算法的吞吐量
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CryptoThroughput {
+    pub algorithm: String,
+    pub single_core_mb_s: f64,
+    pub multi_core_mb_s: f64,
+}
+
+/// 加密测试的完整结果
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CryptoBenchResult {
+    pub hardware: HardwareFeatures,
+    pub algorithms: Vec<CryptoThroughput>,
+}
+
+/// 各算法的计时任务，每次处理 INPUT_SIZE 字节
+pub fn kernels() -> Vec<Kernel> {
+    CryptoAlgorithm::ALL
+        .iter()
+        .map(|&algorithm| {
+            Kernel::new(algorithm.name(), algorithm.reference_ms(), move || process(algorithm, INPUT_SIZE))
+                .with_bytes(INPUT_SIZE)
+        })
+        .collect()
+}
+
+/// 由计时结果换算各算法的吞吐量，并附上硬件加速检测结果
+pub fn summarize(results: &[KernelResult]) -> CryptoBenchResult {
+    let algorithms = results
+        .iter()
+        .map(|result| CryptoThroughput {
+            algorithm: result.name.clone(),
+            single_core_mb_s: result.single_mb_s().unwrap_or(0.0),
+            multi_core_mb_s: result.multi_mb_s().unwrap_or(0.0),
+        })
+        .collect();
+    CryptoBenchResult { hardware: HardwareFeatures::detect(), algorithms }
+}
+
+/// 在当前线程上按 TLS 记录大小处理 bytes 字节
+pub fn process(algorithm: CryptoAlgorithm, bytes: usize) {
+    let records = bytes.div_ceil(RECORD_SIZE);
+    match algorithm {
+        CryptoAlgorithm::Sha256 => {
+            let record = vec![0x5au8; RECORD_SIZE];
+            for _ in 0..records {
+                black_box(digest::digest(&digest::SHA256, &record));
+            }
+        }
+        CryptoAlgorithm::Aes128Gcm => seal_records(&aead::AES_128_GCM, records),
+        CryptoAlgorithm::ChaCha20Poly1305 => seal_records(&aead::CHACHA20_POLY1305, records),
+    }
+}
+
+// 原地加密记录，每条记录使用不同的 nonce
+fn seal_records(algorithm: &'static aead::Algorithm, records: usize) {
+    let key_bytes = vec![0x42u8; algorithm.key_len()];
+    let key = LessSafeKey::new(UnboundKey::new(algorithm, &key_bytes).expect("密钥长度与算法一致"));
+    let mut record = vec![0x5au8; RECORD_SIZE];
+    for counter in 0..records as u64 {
+        let mut nonce = [0u8; aead::NONCE_LEN];
+        nonce[4..].copy_from_slice(&counter.to_be_bytes());
+        let tag = key
+            .seal_in_place_separate_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut record)
+            .expect("明文长度不会超过 AEAD 上限");
+        black_box(&tag);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crypto_kernels() {
+        for algorithm in CryptoAlgorithm::ALL {
+            process(algorithm, 64 * 1024);
+        }
+        let kernels = kernels();
+        assert_eq!(kernels.len(), CryptoAlgorithm::ALL.len());
+        assert!(kernels.iter().all(|k| k.reference_ms > 0.0 && k.bytes == Some(INPUT_SIZE)));
+    }
+}
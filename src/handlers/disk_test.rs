@@ -1,13 +1,22 @@
+use serde::{Deserialize, Serialize};
 use std::process::Command;
 use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
 use std::thread;
 use std::time::{Instant, Duration};
 
+use super::fio::{self, DiskProfile, FioDirection, FioJobResult};
+use super::system_monitor::{SystemMonitor, SystemSample};
+use crate::cancel::{catch_panic, CancelToken, Cancelled, TestControl};
+use crate::latency::{format_latency_us, LatencyStats};
+
 // 全局刷新标志，用于通知UI更新
 static NEEDS_UI_REFRESH: AtomicBool = AtomicBool::new(false);
-static DISK_TEST_STARTED: AtomicBool = AtomicBool::new(false);
+
+// 正在运行的测试的取消令牌
+static CONTROL: TestControl = TestControl::new();
 
 // 磁盘测试结果
 #[derive(Debug, Clone)]
@@ -19,6 +28,151 @@ pub struct DiskTestResult {
     pub write_iops: String,
     pub total_speed: String,
     pub total_iops: String,
+    pub io_mode: Option<DiskIoMode>,  // 实际使用的 I/O 模式，未执行 I/O 的结果为 None
+    pub latency: Option<LatencyStats>,  // 单次操作延迟分布，只有按块大小的读/写测试才有
+    pub metrics: DiskMetrics,
+}
+
+// 磁盘测试的数值结果（用于导出），速度单位 MB/s
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DiskMetrics {
+    pub read_mb_s: f64,
+    pub write_mb_s: f64,
+    pub read_iops: f64,
+    pub write_iops: f64,
+}
+
+/// 磁盘测试的 I/O 模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DiskIoMode {
+    pub direct: bool,    // O_DIRECT，绕过页缓存
+    pub sync: SyncMode,  // 每次写入后的同步方式
+    #[serde(default = "default_one")]
+    pub queue_depth: u32,  // 每个任务同时在途的 I/O 数
+    #[serde(default = "default_one")]
+    pub jobs: u32,         // 并发任务数，每个任务使用独立的测试文件
+}
+
+fn default_one() -> u32 {
+    1
+}
+
+/// 界面中可切换的队列深度和任务数
+pub const QUEUE_DEPTHS: [u32; 5] = [1, 4, 16, 32, 64];
+pub const JOB_COUNTS: [u32; 4] = [1, 2, 4, 8];
+
+/// 每次写入后的同步方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncMode {
+    None,
+    Fdatasync,
+    Fsync,
+}
+
+impl Default for DiskIoMode {
+    // 默认绕过页缓存，否则读取测到的是内存速度
+    fn default() -> Self {
+        Self { direct: true, sync: SyncMode::None, queue_depth: 1, jobs: 1 }
+    }
+}
+
+impl DiskIoMode {
+    pub fn label(&self) -> String {
+        let base = if self.direct { "O_DIRECT" } else { "缓存 I/O" };
+        let mut label = match self.sync {
+            SyncMode::None => base.to_string(),
+            SyncMode::Fdatasync => format!("{} + fdatasync", base),
+            SyncMode::Fsync => format!("{} + fsync", base),
+        };
+        label.push_str(&format!(", QD{}", self.queue_depth));
+        if self.jobs > 1 {
+            label.push_str(&format!(" × {} 任务", self.jobs));
+        }
+        label
+    }
+
+    /// 同时在途的 I/O 总数
+    pub fn concurrency(&self) -> usize {
+        (self.queue_depth.max(1) * self.jobs.max(1)) as usize
+    }
+
+    pub fn next_queue_depth(&self) -> u32 {
+        next_value(&QUEUE_DEPTHS, self.queue_depth)
+    }
+
+    pub fn next_jobs(&self) -> u32 {
+        next_value(&JOB_COUNTS, self.jobs)
+    }
+}
+
+// 列表中比当前值大的第一个值，已是最大时回到第一个
+fn next_value(values: &[u32], current: u32) -> u32 {
+    values.iter().copied().find(|&v| v > current).unwrap_or(values[0])
+}
+
+impl SyncMode {
+    pub fn next(&self) -> Self {
+        match self {
+            SyncMode::None => SyncMode::Fdatasync,
+            SyncMode::Fdatasync => SyncMode::Fsync,
+            SyncMode::Fsync => SyncMode::None,
+        }
+    }
+}
+
+/// 内置引擎的测试参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskTestParams {
+    pub duration_secs: u64,  // 每个测试项的时长
+    pub file_size_mb: u64,   // 读取测试文件大小，0 表示按块大小自动选择
+}
+
+/// 界面中可切换的测试时长（秒）和文件大小（MB，0 为自动）
+pub const DURATIONS: [u64; 4] = [3, 5, 10, 30];
+pub const FILE_SIZES: [u64; 4] = [0, 256, 1024, 4096];
+
+impl Default for DiskTestParams {
+    fn default() -> Self {
+        Self { duration_secs: 5, file_size_mb: 0 }
+    }
+}
+
+impl DiskTestParams {
+    pub fn label(&self) -> String {
+        let size = match self.file_size_mb {
+            0 => "按块大小自动".to_string(),
+            mb if mb >= 1024 => format!("{} GB", mb / 1024),
+            mb => format!("{} MB", mb),
+        };
+        format!("每项 {} 秒, 读取文件 {}", self.duration_secs, size)
+    }
+
+    pub fn next_duration(&self) -> u64 {
+        DURATIONS.iter().copied().find(|&v| v > self.duration_secs).unwrap_or(DURATIONS[0])
+    }
+
+    pub fn next_file_size(&self) -> u64 {
+        FILE_SIZES.iter().copied().find(|&v| v > self.file_size_mb).unwrap_or(FILE_SIZES[0])
+    }
+}
+
+/// 磁盘测试目标：测试文件所在目录及其挂载点信息
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestTarget {
+    pub dir: PathBuf,
+    pub device: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+impl TestTarget {
+    /// 测试结果可能不代表真实磁盘性能时给出原因
+    pub fn warning(&self) -> Option<&'static str> {
+        filesystem_warning(&self.file_system)
+    }
 }
 
 // 实时性能数据点
@@ -57,6 +211,13 @@ pub struct DiskTestInfo {
     pub chart_data_points: Vec<(f64, f64)>, // 折线图数据点 (时间, 值)
     pub read_chart_data: Vec<(f64, f64)>,   // 读取速度图表数据
     pub write_chart_data: Vec<(f64, f64)>,  // 写入速度图表数据
+    pub target: Option<TestTarget>,         // 测试目标
+    pub io_mode: DiskIoMode,                // 实际使用的 I/O 模式
+    pub io_mode_note: Option<String>,       // 请求的模式不可用时的说明
+    pub profile: DiskProfile,               // 实际使用的测试配置
+    pub profile_note: Option<String>,       // 配置无法使用时的说明
+    pub params: DiskTestParams,             // 内置引擎的测试参数
+    pub system_samples: Vec<SystemSample>,  // 测试期间每秒的 steal、iowait 和 CPU 频率
 }
 
 impl Default for DiskTestInfo {
@@ -85,6 +246,13 @@ impl Default for DiskTestInfo {
             chart_data_points: Vec::new(),
             read_chart_data: Vec::new(),
             write_chart_data: Vec::new(),
+            target: None,
+            io_mode: DiskIoMode::default(),
+            io_mode_note: None,
+            profile: DiskProfile::Builtin,
+            profile_note: None,
+            params: DiskTestParams::default(),
+            system_samples: Vec::new(),
         }
     }
 }
@@ -92,6 +260,32 @@ impl Default for DiskTestInfo {
 // 全局磁盘测试状态
 static DISK_TEST_INFO: Mutex<Option<DiskTestInfo>> = Mutex::new(None);
 
+// 用户选择的 I/O 模式
+static IO_MODE: Mutex<DiskIoMode> = Mutex::new(DiskIoMode { direct: true, sync: SyncMode::None, queue_depth: 1, jobs: 1 });
+
+// 用户选择的测试参数
+static PARAMS: Mutex<DiskTestParams> = Mutex::new(DiskTestParams { duration_secs: 5, file_size_mb: 0 });
+
+// 用户选择的测试目标，未选择时使用默认目录所在的挂载点
+static SELECTED_TARGET: Mutex<Option<TestTarget>> = Mutex::new(None);
+
+// 用户选择的测试配置，未选择时使用内置引擎
+static SELECTED_PROFILE: Mutex<Option<DiskProfile>> = Mutex::new(None);
+
+// 默认测试目录：/var/tmp 通常位于系统盘上，而 /tmp 经常是 tmpfs
+const DEFAULT_TEST_DIR: &str = "/var/tmp/onekey_disk_test";
+
+// 测试目录名，位于所选挂载点下
+const TEST_DIR_NAME: &str = ".onekey_disk_test";
+
+// 测试使用的块大小
+const BLOCK_SIZES: [(usize, &str); 4] = [
+    (4 * 1024, "4K"),
+    (64 * 1024, "64K"),
+    (512 * 1024, "512K"),
+    (1024 * 1024, "1M"),
+];
+
 pub fn get_info() -> String {
     // 获取当前磁盘测试状态
     let test_info = get_current_test_info();
@@ -99,7 +293,7 @@ pub fn get_info() -> String {
     format_disk_test_info(&test_info)
 }
 
-// 获取当前磁盘测试信息
+// 获取当前磁盘测试信息，尚未开始时返回带当前设置的准备状态（不会启动测试）
 pub fn get_current_test_info() -> DiskTestInfo {
     if let Ok(mut global_info) = DISK_TEST_INFO.lock() {
         if let Some(ref mut info) = global_info.as_mut() {
@@ -113,9 +307,10 @@ pub fn get_current_test_info() -> DiskTestInfo {
         new_info.has_dd = check_command_exists("dd");
         new_info.disk_info = get_disk_info();
         new_info.disk_usage = get_disk_usage_info();
-        
-        // 启动异步测试
-        start_disk_test();
+        new_info.target = selected_target();
+        new_info.io_mode = io_mode();
+        new_info.profile = selected_profile();
+        new_info.params = test_params();
         
         *global_info = Some(new_info.clone());
         new_info
@@ -124,40 +319,295 @@ pub fn get_current_test_info() -> DiskTestInfo {
     }
 }
 
-// 启动异步磁盘测试
-fn start_disk_test() {
-    // 检查是否已经启动过测试
-    if DISK_TEST_STARTED.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_ok() {
-        thread::spawn(|| {
-            run_async_disk_tests();
+/// 获取当前磁盘测试状态，尚未开始时返回 None（不会启动测试）
+pub fn peek_test_info() -> Option<DiskTestInfo> {
+    DISK_TEST_INFO.lock().ok().and_then(|info| info.clone())
+}
+
+/// 按当前设置开始磁盘测试，上一次的结果会被清除
+pub fn start_disk_test() -> Result<(), String> {
+    let Some(run) = CONTROL.try_begin() else {
+        return Err("磁盘测试进行中，完成或按 X 取消后才能重新测试".to_string());
+    };
+    if let Ok(mut global_info) = DISK_TEST_INFO.lock() {
+        *global_info = None;
+    }
+    get_current_test_info();
+    update_test_status("准备磁盘测试", 0, true);
+    
+    thread::spawn(move || {
+        let _monitor = SystemMonitor::start(push_system_sample);
+        if let Err(e) = catch_panic(|| run_async_disk_tests(&run)) {
+            update_test_status_with_error(e);
+        }
+    });
+    Ok(())
+}
+
+/// 取消/暂停正在运行的磁盘测试
+pub fn test_control() -> &'static TestControl {
+    &CONTROL
+}
+
+
+/// 当前测试目标
+pub fn selected_target() -> Option<TestTarget> {
+    let mut selected = SELECTED_TARGET.lock().unwrap();
+    if selected.is_none() {
+        *selected = target_for_dir(Path::new(DEFAULT_TEST_DIR), &all_mounts());
+    }
+    selected.clone()
+}
+
+/// 指定测试目录（命令行 --dir），测试文件放在其下的独立子目录中
+pub fn set_test_dir(dir: &Path) -> Result<TestTarget, String> {
+    let dir = dir.canonicalize().map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let target = target_for_dir(&dir.join(TEST_DIR_NAME), &all_mounts())
+        .ok_or_else(|| format!("无法确定 {} 所在的挂载点", dir.display()))?;
+    *SELECTED_TARGET.lock().unwrap() = Some(target.clone());
+    Ok(target)
+}
+
+/// 请求的 I/O 模式（目标不支持 O_DIRECT 时实际测试会回退）
+pub fn io_mode() -> DiskIoMode {
+    *IO_MODE.lock().unwrap()
+}
+
+pub fn set_io_mode(mode: DiskIoMode) {
+    *IO_MODE.lock().unwrap() = mode;
+}
+
+/// 内置引擎的测试参数
+pub fn test_params() -> DiskTestParams {
+    *PARAMS.lock().unwrap()
+}
+
+/// 修改测试参数，回到准备状态
+pub fn change_test_params(update: impl FnOnce(&mut DiskTestParams)) -> Result<DiskTestParams, String> {
+    ensure_not_testing()?;
+    let mut params = test_params();
+    update(&mut params);
+    *PARAMS.lock().unwrap() = params;
+    reset_disk_test();
+    Ok(params)
+}
+
+/// 修改 I/O 模式，回到准备状态
+pub fn change_io_mode(update: impl FnOnce(&mut DiskIoMode)) -> Result<DiskIoMode, String> {
+    ensure_not_testing()?;
+    let mut mode = io_mode();
+    update(&mut mode);
+    set_io_mode(mode);
+    reset_disk_test();
+    Ok(mode)
+}
+
+// 以测试线程是否退出为准，取消后要等线程清理完测试文件
+fn ensure_not_testing() -> Result<(), String> {
+    if CONTROL.is_running() {
+        return Err("测试进行中，完成或按 X 取消后才能修改测试设置".to_string());
+    }
+    Ok(())
+}
+
+/// 切换到下一个挂载点，回到准备状态
+pub fn select_next_target() -> Result<TestTarget, String> {
+    ensure_not_testing()?;
+
+    let targets = discover_targets();
+    if targets.is_empty() {
+        return Err("未发现可用的挂载点".to_string());
+    }
+    let current = selected_target();
+    let next = current
+        .and_then(|current| targets.iter().position(|t| t.mount_point == current.mount_point))
+        .map(|index| (index + 1) % targets.len())
+        .unwrap_or(0);
+
+    let target = targets[next].clone();
+    *SELECTED_TARGET.lock().unwrap() = Some(target.clone());
+    reset_disk_test();
+    Ok(target)
+}
+
+/// 当前测试配置
+pub fn selected_profile() -> DiskProfile {
+    SELECTED_PROFILE.lock().unwrap().clone().unwrap_or(DiskProfile::Builtin)
+}
+
+pub fn set_profile(profile: DiskProfile) {
+    *SELECTED_PROFILE.lock().unwrap() = Some(profile);
+}
+
+/// 切换到下一个测试配置，回到准备状态；配置目录中新增的 .fio 文件会在这里被发现
+pub fn select_next_profile() -> Result<DiskProfile, String> {
+    ensure_not_testing()?;
+
+    let profiles = fio::available_profiles();
+    let current = selected_profile();
+    let next = profiles
+        .iter()
+        .position(|profile| *profile == current)
+        .map(|index| (index + 1) % profiles.len())
+        .unwrap_or(0);
+
+    let profile = profiles[next].clone();
+    set_profile(profile.clone());
+    reset_disk_test();
+    Ok(profile)
+}
+
+/// 发现可作为测试目标的挂载点，跳过伪文件系统和系统目录
+pub fn discover_targets() -> Vec<TestTarget> {
+    all_mounts().into_iter().filter(is_test_candidate).collect()
+}
+
+fn all_mounts() -> Vec<TestTarget> {
+    match Command::new("df").args(["-B1", "-T", "-P"]).output() {
+        Ok(output) if output.status.success() => parse_df_mounts(&String::from_utf8_lossy(&output.stdout)),
+        _ => Vec::new(),
+    }
+}
+
+fn is_test_candidate(target: &TestTarget) -> bool {
+    !matches!(target.file_system.as_str(), "devtmpfs" | "squashfs" | "efivarfs")
+        && !["/proc", "/sys", "/dev", "/run", "/boot"].iter().any(|p| target.mount_point.starts_with(p))
+}
+
+// 解析 df -B1 -T -P 输出
+fn parse_df_mounts(output: &str) -> Vec<TestTarget> {
+    let mut targets: Vec<TestTarget> = Vec::new();
+
+    for line in output.lines().skip(1) {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        if parts.len() < 7 {
+            continue;
+        }
+        let file_system = parts[1];
+        let mount_point = parts[6..].join(" ");
+        if targets.iter().any(|t| t.mount_point == mount_point) {
+            continue;
+        }
+
+        let dir = if mount_point == "/" {
+            PathBuf::from(DEFAULT_TEST_DIR)
+        } else {
+            Path::new(&mount_point).join(TEST_DIR_NAME)
+        };
+        targets.push(TestTarget {
+            dir,
+            device: parts[0].to_string(),
+            mount_point,
+            file_system: file_system.to_string(),
+            total_bytes: parts[2].parse().unwrap_or(0),
+            available_bytes: parts[4].parse().unwrap_or(0),
         });
     }
+
+    targets
+}
+
+// 目录所在的挂载点（最长前缀匹配），应传入全部挂载点
+fn target_for_dir(dir: &Path, targets: &[TestTarget]) -> Option<TestTarget> {
+    targets
+        .iter()
+        .filter(|t| dir.starts_with(&t.mount_point))
+        .max_by_key(|t| t.mount_point.len())
+        .map(|t| TestTarget { dir: dir.to_path_buf(), ..t.clone() })
 }
 
-// 重置测试状态，用于重新开始测试
-#[allow(dead_code)]
-pub fn reset_disk_test() {
-    DISK_TEST_STARTED.store(false, Ordering::SeqCst);
+/// 测试目标的一行描述
+pub fn describe_target(target: &TestTarget) -> String {
+    format!(
+        "{} ({}, {}, 可用 {})",
+        target.dir.display(),
+        target.device,
+        target.file_system,
+        super::system_info::format_bytes_gib(target.available_bytes)
+    )
+}
+
+/// 内存、叠加或网络文件系统上的测试结果不代表本地磁盘性能
+pub fn filesystem_warning(file_system: &str) -> Option<&'static str> {
+    match file_system {
+        "tmpfs" | "ramfs" => Some("内存文件系统，测试的是内存速度而不是磁盘"),
+        "overlay" | "aufs" => Some("容器叠加文件系统，结果可能与底层磁盘不同"),
+        "nfs" | "nfs4" | "cifs" | "smb3" | "smbfs" | "9p" | "ceph" | "glusterfs" => {
+            Some("网络文件系统，结果受网络影响")
+        }
+        fs if fs.starts_with("fuse") => Some("FUSE 文件系统，结果可能受用户态驱动影响"),
+        _ => None,
+    }
+}
+
+// 清除测试状态，下次获取时按新设置回到准备状态
+fn reset_disk_test() {
     if let Ok(mut global_info) = DISK_TEST_INFO.lock() {
         *global_info = None;
     }
 }
 
 // FIO风格的多块大小磁盘测试
-fn run_async_disk_tests() {
-    let test_dir = "/tmp/disk_test";
+fn run_async_disk_tests(cancel: &CancelToken) {
+    let Some(target) = selected_target() else {
+        update_test_status_with_error("未发现可用的测试目录".to_string());
+        return;
+    };
+    let test_dir_path = target.dir.clone();
+    let test_dir = test_dir_path.to_string_lossy();
+    let test_dir = test_dir.as_ref();
     if let Err(e) = fs::create_dir_all(test_dir) {
-        update_test_status_with_error(format!("无法创建测试目录: {}", e));
+        update_test_status_with_error(format!("无法创建测试目录 {}: {}", test_dir, e));
+        return;
+    }
+    // 无论正常结束、取消还是 panic，都移除空的测试目录，不动目录中的其他文件
+    let _test_dir_guard = rust_disk_test::TestDir(test_dir_path.clone());
+    
+    // fio 配置交给 fio 执行，未安装 fio 时回退到内置引擎
+    let mut profile = selected_profile();
+    if profile.uses_fio() && !check_command_exists("fio") {
+        update_profile(DiskProfile::Builtin, Some(format!("未安装 fio，{} 已改用内置引擎", profile.name())));
+        profile = DiskProfile::Builtin;
+    }
+    
+    // 开始前按实际运行的配置确认空间足够，避免测试中途写满磁盘
+    let block_sizes = BLOCK_SIZES;
+    let params = test_params();
+    let required_space = match profile {
+        DiskProfile::Builtin => {
+            Some(rust_disk_test::required_space(block_sizes.iter().map(|(size, _)| *size), io_mode().jobs, params))
+        }
+        DiskProfile::FioStandard => Some(fio::builtin_job_bytes() + rust_disk_test::SPACE_MARGIN),
+        // 用户任务文件的文件大小由任务自己决定，无法预先计算
+        DiskProfile::FioFile { .. } => {
+            update_profile(profile.clone(), Some("无法预估任务文件所需空间，未检查可用空间".to_string()));
+            None
+        }
+    };
+    if let Some(required_space) = required_space {
+        if !rust_disk_test::check_available_space(test_dir, required_space) {
+            update_test_status_with_error(format!(
+                "{} 可用空间不足，至少需要 {} MB",
+                target.mount_point,
+                required_space / 1024 / 1024
+            ));
+            return;
+        }
+    }
+    
+    // 目标文件系统不支持 O_DIRECT（如 tmpfs）时回退到缓存 I/O
+    let requested_mode = io_mode();
+    let mode = rust_disk_test::effective_io_mode(&test_dir_path, requested_mode);
+    update_io_mode(mode, (mode != requested_mode).then(|| {
+        format!("{} 不支持 O_DIRECT，已改用缓存 I/O，读取结果可能包含页缓存", target.file_system)
+    }));
+    
+    if profile.uses_fio() {
+        run_fio_disk_test(&profile, &test_dir_path, mode, cancel);
         return;
     }
     
     let mut all_results = Vec::new();
-    let block_sizes = vec![
-        (4 * 1024, "4K"),
-        (64 * 1024, "64K"),
-        (512 * 1024, "512K"),
-        (1024 * 1024, "1M"),
-    ];
     
     let total_phases = block_sizes.len() * 2 + 1; // 每个块大小有读写两个阶段，加上准备阶段
     let mut current_phase = 0;
@@ -170,6 +620,11 @@ fn run_async_disk_tests() {
     
     // 对每种块大小进行测试
     for (i, (block_size, block_name)) in block_sizes.iter().enumerate() {
+        if checkpoint(cancel).is_err() {
+            update_test_cancelled();
+            return;
+        }
+        
         // 重新计算进度，确保不会超过100%
         let read_progress = 10 + (i * 80) / block_sizes.len() + (40 / block_sizes.len()) / 2;
         let write_progress = 10 + (i * 80) / block_sizes.len() + (40 / block_sizes.len());
@@ -183,9 +638,13 @@ fn run_async_disk_tests() {
         update_test_phase(&format!("{} 读取测试", block_name), current_phase);
         
         let read_result = match std::panic::catch_unwind(|| {
-            rust_disk_test::run_block_size_read_test(test_dir, *block_size, block_name)
+            rust_disk_test::run_block_size_read_test(test_dir, *block_size, block_name, mode, params, cancel)
         }) {
-            Ok(result) => result,
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => {
+                update_test_status_with_error(format!("{} 读取测试错误: {}", block_name, e));
+                return;
+            }
             Err(e) => {
                 let error_msg = if let Some(s) = e.downcast_ref::<String>() {
                     format!("{} 读取测试错误: {}", block_name, s)
@@ -202,14 +661,23 @@ fn run_async_disk_tests() {
         }
         current_phase += 1;
         
+        if checkpoint(cancel).is_err() {
+            update_test_cancelled();
+            return;
+        }
+        
         // 写入测试
         update_test_status(&format!("正在测试 {} 写入性能", block_name), write_progress, true);
         update_test_phase(&format!("{} 写入测试", block_name), current_phase);
         
         let write_result = match std::panic::catch_unwind(|| {
-            rust_disk_test::run_block_size_write_test(test_dir, *block_size, block_name)
+            rust_disk_test::run_block_size_write_test(test_dir, *block_size, block_name, mode, params, cancel)
         }) {
-            Ok(result) => result,
+            Ok(Ok(result)) => result,
+            Ok(Err(e)) => {
+                update_test_status_with_error(format!("{} 写入测试错误: {}", block_name, e));
+                return;
+            }
             Err(e) => {
                 let error_msg = if let Some(s) = e.downcast_ref::<String>() {
                     format!("{} 写入测试错误: {}", block_name, s)
@@ -227,17 +695,97 @@ fn run_async_disk_tests() {
         current_phase += 1;
     }
     
+    // 最后一项测试中途取消时结果不完整，不计入
+    if cancel.is_cancelled() {
+        update_test_cancelled();
+        return;
+    }
+    
     // 计算总计性能
     let total_results = rust_disk_test::calculate_total_performance(&all_results);
     all_results.extend(total_results);
     
-    // 清理测试文件目录
-    let _ = fs::remove_dir_all(test_dir);
-    
     // 完成测试
     update_test_status("FIO风格测试完成", 100, false);
     update_test_phase("完成", current_phase);
     update_test_results(all_results);
+    super::history::record_disk_run(&get_current_test_info());
+}
+
+// 使用 fio 运行测试配置
+fn run_fio_disk_test(profile: &DiskProfile, test_dir: &Path, mode: DiskIoMode, cancel: &CancelToken) {
+    update_test_status(&format!("正在运行 {}", profile.name()), 10, true);
+    update_test_phase("fio 测试", 1);
+    
+    let jobs = fio::run_profile(profile, test_dir, mode, cancel);
+    if cancel.is_cancelled() {
+        update_test_cancelled();
+        return;
+    }
+    
+    match jobs {
+        Ok(jobs) => {
+            // 内置任务的 I/O 模式由我们指定，用户任务以任务文件为准
+            let io_mode = (*profile == DiskProfile::FioStandard).then_some(mode);
+            let results = jobs.iter().flat_map(|job| fio_job_results(job, io_mode)).collect();
+            update_test_status(&format!("{} 测试完成", profile.name()), 100, false);
+            update_test_phase("完成", 2);
+            update_test_results(results);
+            super::history::record_disk_run(&get_current_test_info());
+        }
+        Err(e) => update_test_status_with_error(e),
+    }
+}
+
+/// 把 fio 任务结果转换为每个方向一条的测试结果，命名与内置引擎一致（"任务名 读取/写入"）
+fn fio_job_results(job: &FioJobResult, io_mode: Option<DiskIoMode>) -> Vec<DiskTestResult> {
+    let result = |direction: &FioDirection, is_read: bool| {
+        let speed = rust_disk_test::format_speed_with_unit(direction.mb_s());
+        let iops = format!("{:.1}k", direction.iops / 1000.0);
+        let na = || "N/A".to_string();
+        DiskTestResult {
+            test_name: format!("{} {}", job.name, if is_read { "读取" } else { "写入" }),
+            read_speed: if is_read { speed.clone() } else { na() },
+            write_speed: if is_read { na() } else { speed.clone() },
+            read_iops: if is_read { iops.clone() } else { na() },
+            write_iops: if is_read { na() } else { iops.clone() },
+            total_speed: speed,
+            total_iops: iops,
+            io_mode,
+            latency: direction.latency.clone(),
+            metrics: if is_read {
+                DiskMetrics { read_mb_s: direction.mb_s(), read_iops: direction.iops, ..Default::default() }
+            } else {
+                DiskMetrics { write_mb_s: direction.mb_s(), write_iops: direction.iops, ..Default::default() }
+            },
+        }
+    };
+
+    job.read.iter().map(|read| result(read, true))
+        .chain(job.write.iter().map(|write| result(write, false)))
+        .collect()
+}
+
+// 记录实际使用的测试配置
+fn update_profile(profile: DiskProfile, note: Option<String>) {
+    if let Ok(mut global_info) = DISK_TEST_INFO.lock() {
+        if let Some(ref mut info) = global_info.as_mut() {
+            info.profile = profile;
+            info.profile_note = note;
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+// 记录实际使用的 I/O 模式
+fn update_io_mode(mode: DiskIoMode, note: Option<String>) {
+    if let Ok(mut global_info) = DISK_TEST_INFO.lock() {
+        if let Some(ref mut info) = global_info.as_mut() {
+            info.io_mode = mode;
+            info.io_mode_note = note;
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
 }
 
 // 更新测试阶段
@@ -254,6 +802,15 @@ fn update_test_phase(phase_name: &str, phase_index: usize) {
     }
 }
 
+fn push_system_sample(sample: SystemSample) {
+    if let Ok(mut global_info) = DISK_TEST_INFO.lock() {
+        if let Some(ref mut info) = global_info.as_mut() {
+            info.system_samples.push(sample);
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
 // 更新测试状态
 fn update_test_status(current_test: &str, progress: u8, is_testing: bool) {
     if let Ok(mut global_info) = DISK_TEST_INFO.lock() {
@@ -297,6 +854,34 @@ fn update_test_status_with_error(error_message: String) {
     }
 }
 
+// 测试被取消：保留已完成阶段的状态，不写入历史记录
+fn update_test_cancelled() {
+    if let Ok(mut global_info) = DISK_TEST_INFO.lock() {
+        if let Some(ref mut info) = global_info.as_mut() {
+            info.current_test = "测试已取消".to_string();
+            info.current_test_phase = info.current_test.clone();
+            info.is_testing = false;
+            info.error_message = Some(Cancelled.to_string());
+            info.last_update = Instant::now();
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+// 测试项之间检查暂停和取消，暂停期间在界面上显示提示
+fn checkpoint(cancel: &CancelToken) -> Result<(), Cancelled> {
+    if cancel.is_paused() {
+        if let Ok(mut global_info) = DISK_TEST_INFO.lock() {
+            if let Some(ref mut info) = global_info.as_mut() {
+                info.current_test = "已暂停，按空格继续".to_string();
+                info.current_test_phase = info.current_test.clone();
+                NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+    cancel.checkpoint()
+}
+
 // 更新测试结果
 fn update_test_results(results: Vec<DiskTestResult>) {
     if let Ok(mut global_info) = DISK_TEST_INFO.lock() {
@@ -377,6 +962,22 @@ pub fn format_disk_test_info(info: &DiskTestInfo) -> String {
     
     output.push_str("=== 磁盘性能测试 ===\n\n");
     
+    if let Some(ref target) = info.target {
+        output.push_str(&format!("测试目标: {}\n", describe_target(target)));
+        if let Some(warning) = target.warning() {
+            output.push_str(&format!("警告: {}\n", warning));
+        }
+        output.push_str(&format!("I/O 模式: {}\n", info.io_mode.label()));
+        if let Some(ref note) = info.io_mode_note {
+            output.push_str(&format!("警告: {}\n", note));
+        }
+        output.push_str(&format!("测试配置: {}\n", info.profile.name()));
+        if let Some(ref note) = info.profile_note {
+            output.push_str(&format!("警告: {}\n", note));
+        }
+        output.push('\n');
+    }
+    
     if info.is_testing {
         let clamped_progress = info.progress.min(100); // 确保进度不超过100%
         output.push_str(&format!("状态: {}\n", info.current_test));
@@ -410,6 +1011,16 @@ pub fn format_disk_test_info(info: &DiskTestInfo) -> String {
             if result.total_speed != "N/A" {
                 output.push_str(&format!("总计:  {} ({} IOPS)\n", result.total_speed, result.total_iops));
             }
+            if let Some(ref latency) = result.latency {
+                output.push_str(&format!(
+                    "延迟:  p50 {} / p95 {} / p99 {} / p99.9 {} / 最大 {}\n",
+                    format_latency_us(latency.p50_us),
+                    format_latency_us(latency.p95_us),
+                    format_latency_us(latency.p99_us),
+                    format_latency_us(latency.p999_us),
+                    format_latency_us(latency.max_us),
+                ));
+            }
             output.push_str("\n");
         }
     } else {
@@ -421,7 +1032,7 @@ pub fn format_disk_test_info(info: &DiskTestInfo) -> String {
         output.push_str("\n");
         
         if info.has_fio {
-            output.push_str("✓ FIO: 已安装 (将使用 FIO 进行专业测试)\n");
+            output.push_str("✓ FIO: 已安装 (可选择 fio 测试配置)\n");
         } else if info.has_dd {
             output.push_str("✗ FIO: 未安装\n");
             output.push_str("✓ DD: 已安装 (将使用 DD 进行基础测试)\n");
@@ -486,84 +1097,6 @@ fn check_command_exists(cmd: &str) -> bool {
         .unwrap_or(false)
 }
 
-fn run_fio_4k_mixed_async(test_dir: &str) -> Option<DiskTestResult> {
-    let output = Command::new("fio")
-        .arg("--name=4k_mixed")
-        .arg("--directory")
-        .arg(test_dir)
-        .arg("--rw=randrw")
-        .arg("--rwmixread=50")
-        .arg("--bs=4k")
-        .arg("--size=100M")
-        .arg("--numjobs=4")
-        .arg("--time_based")
-        .arg("--runtime=10")
-        .arg("--group_reporting")
-        .arg("--ioengine=libaio")
-        .arg("--direct=1")
-        .arg("--unlink=1")
-        .output()
-        .ok()?;
-    
-    if output.status.success() {
-        parse_fio_output_to_result(&String::from_utf8_lossy(&output.stdout), "4K 随机读写测试")
-    } else {
-        None
-    }
-}
-
-fn run_fio_64k_mixed_async(test_dir: &str) -> Option<DiskTestResult> {
-    let output = Command::new("fio")
-        .arg("--name=64k_mixed")
-        .arg("--directory")
-        .arg(test_dir)
-        .arg("--rw=randrw")
-        .arg("--rwmixread=50")
-        .arg("--bs=64k")
-        .arg("--size=200M")
-        .arg("--numjobs=2")
-        .arg("--time_based")
-        .arg("--runtime=10")
-        .arg("--group_reporting")
-        .arg("--ioengine=libaio")
-        .arg("--direct=1")
-        .arg("--unlink=1")
-        .output()
-        .ok()?;
-    
-    if output.status.success() {
-        parse_fio_output_to_result(&String::from_utf8_lossy(&output.stdout), "64K 随机读写测试")
-    } else {
-        None
-    }
-}
-
-fn run_fio_1m_seq_async(test_dir: &str) -> Option<DiskTestResult> {
-    let output = Command::new("fio")
-        .arg("--name=1m_seq")
-        .arg("--directory")
-        .arg(test_dir)
-        .arg("--rw=rw")
-        .arg("--rwmixread=50")
-        .arg("--bs=1m")
-        .arg("--size=500M")
-        .arg("--numjobs=1")
-        .arg("--time_based")
-        .arg("--runtime=15")
-        .arg("--group_reporting")
-        .arg("--ioengine=libaio")
-        .arg("--direct=1")
-        .arg("--unlink=1")
-        .output()
-        .ok()?;
-    
-    if output.status.success() {
-        parse_fio_output_to_result(&String::from_utf8_lossy(&output.stdout), "1M 顺序读写测试")
-    } else {
-        None
-    }
-}
-
 fn run_dd_write_test_async() -> Option<String> {
     let output = Command::new("dd")
         .arg("if=/dev/zero")
@@ -613,100 +1146,6 @@ fn run_dd_read_test_async() -> Option<String> {
     }
 }
 
-fn parse_fio_output_to_result(output: &str, test_name: &str) -> Option<DiskTestResult> {
-    let mut read_bw = "N/A".to_string();
-    let mut write_bw = "N/A".to_string();
-    let mut read_iops = "N/A".to_string();
-    let mut write_iops = "N/A".to_string();
-    
-    for line in output.lines() {
-        if line.contains("read: IOPS=") {
-            if let Some(bw_start) = line.find("BW=") {
-                if let Some(bw_end) = line[bw_start..].find("(") {
-                    read_bw = line[bw_start + 3..bw_start + bw_end].trim().to_string();
-                }
-            }
-            if let Some(iops_start) = line.find("IOPS=") {
-                if let Some(iops_end) = line[iops_start..].find(",") {
-                    read_iops = line[iops_start + 5..iops_start + iops_end].trim().to_string();
-                }
-            }
-        } else if line.contains("write: IOPS=") {
-            if let Some(bw_start) = line.find("BW=") {
-                if let Some(bw_end) = line[bw_start..].find("(") {
-                    write_bw = line[bw_start + 3..bw_start + bw_end].trim().to_string();
-                }
-            }
-            if let Some(iops_start) = line.find("IOPS=") {
-                if let Some(iops_end) = line[iops_start..].find(",") {
-                    write_iops = line[iops_start + 5..iops_start + iops_end].trim().to_string();
-                }
-            }
-        }
-    }
-    
-    // 计算总计（如果可能）
-    let (total_speed, total_iops) = calculate_totals(&read_bw, &write_bw, &read_iops, &write_iops);
-    
-    Some(DiskTestResult {
-        test_name: test_name.to_string(),
-        read_speed: read_bw,
-        write_speed: write_bw,
-        read_iops,
-        write_iops,
-        total_speed,
-        total_iops,
-    })
-}
-
-fn calculate_totals(read_bw: &str, write_bw: &str, read_iops: &str, write_iops: &str) -> (String, String) {
-    // 简化的总计计算，实际中可能需要更复杂的解析
-    if read_bw != "N/A" && write_bw != "N/A" {
-        return (format!("{} + {}", read_bw, write_bw), format!("{} + {}", read_iops, write_iops));
-    }
-    ("N/A".to_string(), "N/A".to_string())
-}
-
-fn parse_fio_output(output: &str, block_size: &str) -> Option<String> {
-    let mut result = String::new();
-    let mut read_bw = "N/A".to_string();
-    let mut write_bw = "N/A".to_string();
-    let mut read_iops = "N/A".to_string();
-    let mut write_iops = "N/A".to_string();
-    
-    for line in output.lines() {
-        if line.contains("read: IOPS=") {
-            if let Some(bw_start) = line.find("BW=") {
-                if let Some(bw_end) = line[bw_start..].find("(") {
-                    read_bw = line[bw_start + 3..bw_start + bw_end].trim().to_string();
-                }
-            }
-            if let Some(iops_start) = line.find("IOPS=") {
-                if let Some(iops_end) = line[iops_start..].find(",") {
-                    read_iops = line[iops_start + 5..iops_start + iops_end].trim().to_string();
-                }
-            }
-        } else if line.contains("write: IOPS=") {
-            if let Some(bw_start) = line.find("BW=") {
-                if let Some(bw_end) = line[bw_start..].find("(") {
-                    write_bw = line[bw_start + 3..bw_start + bw_end].trim().to_string();
-                }
-            }
-            if let Some(iops_start) = line.find("IOPS=") {
-                if let Some(iops_end) = line[iops_start..].find(",") {
-                    write_iops = line[iops_start + 5..iops_start + iops_end].trim().to_string();
-                }
-            }
-        }
-    }
-    
-    result.push_str(&format!("Block Size: {} \n", block_size));
-    result.push_str(&format!("Read:  {} ({} IOPS)\n", read_bw, read_iops));
-    result.push_str(&format!("Write: {} ({} IOPS)\n", write_bw, write_iops));
-    
-    Some(result)
-}
-
 fn run_dd_tests() -> String {
     let mut result = String::from("DD 磁盘性能测试结果:\n");
     result.push_str("=" .repeat(30).as_str());
@@ -848,9 +1287,15 @@ fn get_disk_usage_info() -> String {
 
 // 纯 Rust 实现的专业磁盘性能测试
 mod rust_disk_test {
-    use super::DiskTestResult;
-    use std::fs::OpenOptions;
+    use super::{DiskIoMode, DiskMetrics, DiskTestParams, DiskTestResult, SyncMode};
+    use crate::cancel::CancelToken;
+    use crate::latency::LatencyHistogram;
+    use std::alloc::{self, Layout};
+    use std::fs::{File, OpenOptions};
+    use std::ops::{Deref, DerefMut};
+    use std::path::Path;
     use std::io::{Write, Read, Seek, SeekFrom};
+    use std::os::unix::fs::FileExt;
     use std::time::{Instant, Duration};
     use std::path::PathBuf;
     use std::thread;
@@ -858,6 +1303,9 @@ mod rust_disk_test {
     use std::sync::atomic::{AtomicU64, Ordering};
     use rand::Rng;
 
+    // 随机写入测试覆盖的文件范围
+    const WRITE_AREA_SIZE: usize = 50 * 1024 * 1024;
+
     // 优化后的测试配置 - 减少测试时间
     const TEST_DURATION_SECS: u64 = 3;  // 减少到3秒
     const WARMUP_DURATION_SECS: u64 = 1; // 减少预热时间
@@ -876,6 +1324,9 @@ mod rust_disk_test {
                 write_iops: "N/A".to_string(),
                 total_speed: "N/A".to_string(),
                 total_iops: "需要至少50MB空间".to_string(),
+                io_mode: None,
+                latency: None,
+                metrics: DiskMetrics::default(),
             }];
         }
         
@@ -888,11 +1339,11 @@ mod rust_disk_test {
     }
 
     /// 运行指定块大小的读取测试
-    pub fn run_block_size_read_test(test_dir: &str, block_size: usize, block_name: &str) -> Option<DiskTestResult> {
+    pub fn run_block_size_read_test(test_dir: &str, block_size: usize, block_name: &str, mode: DiskIoMode, params: DiskTestParams, cancel: &CancelToken) -> Result<Option<DiskTestResult>, String> {
         // 检查磁盘空间
         let required_space = (block_size * 1000).max(50 * 1024 * 1024); // 至少50MB
         if !check_available_space(test_dir, required_space as u64) {
-            return Some(DiskTestResult {
+            return Ok(Some(DiskTestResult {
                 test_name: format!("{} 读取测试错误", block_name),
                 read_speed: "错误".to_string(),
                 write_speed: "N/A".to_string(),
@@ -900,44 +1351,57 @@ mod rust_disk_test {
                 write_iops: "N/A".to_string(),
                 total_speed: "N/A".to_string(),
                 total_iops: "磁盘空间不足".to_string(),
-            });
+                io_mode: None,
+                latency: None,
+                metrics: DiskMetrics::default(),
+            }));
         }
         
-        let file_size = calculate_optimal_file_size(block_size);
-        let test_file = PathBuf::from(test_dir).join(format!("read_test_{}.bin", block_name));
+        let file_size = read_file_size(block_size, params);
+        let test_files = TestFiles(job_files(test_dir, "read_test", block_name, mode));
         
-        // 创建测试文件
-        if !create_test_file(&test_file, file_size, block_size) {
-            return None;
+        // 创建测试文件，每个任务一个
+        for test_file in test_files.iter() {
+            if cancel.is_cancelled() || !create_test_file(test_file, file_size, block_size) {
+                return Ok(None);
+            }
+            // 刚写入的测试文件还在页缓存中，读取前先清除
+            drop_file_cache(test_file);
         }
         
-        let test_duration = Duration::from_secs(5); // 5秒测试
-        let result = run_io_test_internal(&test_file, block_size, true, true, 5);
+        let result = if mode.concurrency() > 1 {
+            run_concurrent_io_test(&test_files, block_size, true, params.duration_secs, mode, cancel)?
+        } else {
+            run_io_test_internal(&test_files[0], block_size, true, true, params.duration_secs, mode, cancel)
+        };
         
         // 清理测试文件
-        let _ = std::fs::remove_file(&test_file);
-        
-        if let Some((speed, iops, latency)) = result {
-            Some(DiskTestResult {
-                test_name: format!("{} 读取", block_name),
-                read_speed: format_speed_with_unit(speed),
-                write_speed: "N/A".to_string(),
-                read_iops: format!("{:.1}k", iops / 1000.0),
-                write_iops: "N/A".to_string(),
-                total_speed: format_speed_with_unit(speed),
-                total_iops: format!("{:.1}k", iops / 1000.0),
-            })
-        } else {
-            None
-        }
+        drop(test_files);
+        
+        Ok(result.map(|IoStats { speed, iops, latency, .. }| DiskTestResult {
+            test_name: format!("{} 读取", block_name),
+            read_speed: format_speed_with_unit(speed),
+            write_speed: "N/A".to_string(),
+            read_iops: format!("{:.1}k", iops / 1000.0),
+            write_iops: "N/A".to_string(),
+            total_speed: format_speed_with_unit(speed),
+            total_iops: format!("{:.1}k", iops / 1000.0),
+            io_mode: Some(mode),
+            latency: Some(latency.stats()),
+            metrics: DiskMetrics {
+                read_mb_s: speed,
+                read_iops: iops,
+                ..Default::default()
+            },
+        }))
     }
 
     /// 运行指定块大小的写入测试
-    pub fn run_block_size_write_test(test_dir: &str, block_size: usize, block_name: &str) -> Option<DiskTestResult> {
+    pub fn run_block_size_write_test(test_dir: &str, block_size: usize, block_name: &str, mode: DiskIoMode, params: DiskTestParams, cancel: &CancelToken) -> Result<Option<DiskTestResult>, String> {
         // 检查磁盘空间
         let required_space = (block_size * 1000).max(50 * 1024 * 1024); // 至少50MB
         if !check_available_space(test_dir, required_space as u64) {
-            return Some(DiskTestResult {
+            return Ok(Some(DiskTestResult {
                 test_name: format!("{} 写入测试错误", block_name),
                 read_speed: "N/A".to_string(),
                 write_speed: "错误".to_string(),
@@ -945,29 +1409,73 @@ mod rust_disk_test {
                 write_iops: "N/A".to_string(),
                 total_speed: "N/A".to_string(),
                 total_iops: "磁盘空间不足".to_string(),
-            });
+                io_mode: None,
+                latency: None,
+                metrics: DiskMetrics::default(),
+            }));
         }
         
-        let test_file = PathBuf::from(test_dir).join(format!("write_test_{}.bin", block_name));
+        let test_files = TestFiles(job_files(test_dir, "write_test", block_name, mode));
         
-        let test_duration = Duration::from_secs(5); // 5秒测试
-        let result = run_io_test_internal(&test_file, block_size, false, true, 5);
+        let result = if mode.concurrency() > 1 {
+            run_concurrent_io_test(&test_files, block_size, false, params.duration_secs, mode, cancel)?
+        } else {
+            run_io_test_internal(&test_files[0], block_size, false, true, params.duration_secs, mode, cancel)
+        };
         
         // 清理测试文件
-        let _ = std::fs::remove_file(&test_file);
-        
-        if let Some((speed, iops, latency)) = result {
-            Some(DiskTestResult {
-                test_name: format!("{} 写入", block_name),
-                read_speed: "N/A".to_string(),
-                write_speed: format_speed_with_unit(speed),
-                read_iops: "N/A".to_string(),
-                write_iops: format!("{:.1}k", iops / 1000.0),
-                total_speed: format_speed_with_unit(speed),
-                total_iops: format!("{:.1}k", iops / 1000.0),
-            })
-        } else {
-            None
+        drop(test_files);
+        
+        Ok(result.map(|IoStats { speed, iops, latency, .. }| DiskTestResult {
+            test_name: format!("{} 写入", block_name),
+            read_speed: "N/A".to_string(),
+            write_speed: format_speed_with_unit(speed),
+            read_iops: "N/A".to_string(),
+            write_iops: format!("{:.1}k", iops / 1000.0),
+            total_speed: format_speed_with_unit(speed),
+            total_iops: format!("{:.1}k", iops / 1000.0),
+            io_mode: Some(mode),
+            latency: Some(latency.stats()),
+            metrics: DiskMetrics {
+                write_mb_s: speed,
+                write_iops: iops,
+                ..Default::default()
+            },
+        }))
+    }
+
+    // 每个任务一个测试文件
+    fn job_files(test_dir: &str, prefix: &str, block_name: &str, mode: DiskIoMode) -> Vec<PathBuf> {
+        (0..mode.jobs.max(1))
+            .map(|job| PathBuf::from(test_dir).join(format!("{}_{}.{}.bin", prefix, block_name, job)))
+            .collect()
+    }
+
+    /// 测试文件，离开作用域时删除（包括测试被取消或 panic 时）
+    struct TestFiles(Vec<PathBuf>);
+
+    impl Deref for TestFiles {
+        type Target = [PathBuf];
+
+        fn deref(&self) -> &[PathBuf] {
+            &self.0
+        }
+    }
+
+    impl Drop for TestFiles {
+        fn drop(&mut self) {
+            for file in &self.0 {
+                let _ = std::fs::remove_file(file);
+            }
+        }
+    }
+
+    /// 测试目录，离开作用域时移除；目录非空（含用户原有文件）时保留
+    pub struct TestDir(pub PathBuf);
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir(&self.0);
         }
     }
 
@@ -981,10 +1489,10 @@ mod rust_disk_test {
             let write_result = results.iter().find(|r| r.test_name == format!("{} 写入", block_size));
             
             if let (Some(read), Some(write)) = (read_result, write_result) {
-                let read_speed_val = parse_speed_value(&read.read_speed);
-                let write_speed_val = parse_speed_value(&write.write_speed);
-                let read_iops_val = parse_iops_value(&read.read_iops);
-                let write_iops_val = parse_iops_value(&write.write_iops);
+                let read_speed_val = read.metrics.read_mb_s;
+                let write_speed_val = write.metrics.write_mb_s;
+                let read_iops_val = read.metrics.read_iops;
+                let write_iops_val = write.metrics.write_iops;
                 
                 let total_speed = read_speed_val + write_speed_val;
                 let total_iops = read_iops_val + write_iops_val;
@@ -997,6 +1505,14 @@ mod rust_disk_test {
                     write_iops: write.write_iops.clone(),
                     total_speed: format_speed_with_unit(total_speed),
                     total_iops: format!("{:.1}k", total_iops / 1000.0),
+                    io_mode: read.io_mode,
+                    latency: None,
+                    metrics: DiskMetrics {
+                        read_mb_s: read_speed_val,
+                        write_mb_s: write_speed_val,
+                        read_iops: read_iops_val,
+                        write_iops: write_iops_val,
+                    },
                 });
             }
         }
@@ -1004,6 +1520,23 @@ mod rust_disk_test {
         total_results
     }
 
+    /// 测试文件之外预留的空间
+    pub const SPACE_MARGIN: u64 = 64 * 1024 * 1024;
+
+    /// 依次测试这些块大小所需的磁盘空间（每个任务一个最大的测试文件，再加上余量）
+    pub fn required_space(block_sizes: impl Iterator<Item = usize>, jobs: u32, params: DiskTestParams) -> u64 {
+        let largest = block_sizes.map(|block_size| read_file_size(block_size, params)).max().unwrap_or(0);
+        largest as u64 * jobs.max(1) as u64 + SPACE_MARGIN
+    }
+
+    // 读取测试文件大小：用户指定时使用指定值，否则按块大小选择
+    fn read_file_size(block_size: usize, params: DiskTestParams) -> usize {
+        match params.file_size_mb {
+            0 => calculate_optimal_file_size(block_size),
+            mb => mb as usize * 1024 * 1024,
+        }
+    }
+
     /// 根据块大小计算最优文件大小
     fn calculate_optimal_file_size(block_size: usize) -> usize {
         match block_size {
@@ -1016,7 +1549,7 @@ mod rust_disk_test {
     }
 
     /// 格式化速度值（自动选择MB/s或GB/s）
-    fn format_speed_with_unit(speed_mbps: f64) -> String {
+    pub fn format_speed_with_unit(speed_mbps: f64) -> String {
         if speed_mbps >= 1024.0 {
             format!("{:.2} GB/s", speed_mbps / 1024.0)
         } else {
@@ -1024,36 +1557,6 @@ mod rust_disk_test {
         }
     }
 
-    /// 解析速度值（转换为MB/s）
-    fn parse_speed_value(speed_str: &str) -> f64 {
-        if speed_str == "N/A" || speed_str == "错误" {
-            return 0.0;
-        }
-        
-        if let Some(gb_pos) = speed_str.find("GB/s") {
-            let value_str = &speed_str[..gb_pos].trim();
-            value_str.parse::<f64>().unwrap_or(0.0) * 1024.0
-        } else if let Some(mb_pos) = speed_str.find("MB/s") {
-            let value_str = &speed_str[..mb_pos].trim();
-            value_str.parse::<f64>().unwrap_or(0.0)
-        } else {
-            0.0
-        }
-    }
-
-    /// 解析IOPS值
-    fn parse_iops_value(iops_str: &str) -> f64 {
-        if iops_str == "N/A" || iops_str == "错误" {
-            return 0.0;
-        }
-        
-        if let Some(k_pos) = iops_str.find('k') {
-            let value_str = &iops_str[..k_pos].trim();
-            value_str.parse::<f64>().unwrap_or(0.0) * 1000.0
-        } else {
-            iops_str.parse::<f64>().unwrap_or(0.0)
-        }
-    }
     pub fn run_read_only_test(test_dir: &str) -> Vec<DiskTestResult> {
         let mut results = Vec::new();
         
@@ -1067,6 +1570,9 @@ mod rust_disk_test {
                 write_iops: "N/A".to_string(),
                 total_speed: "N/A".to_string(),
                 total_iops: "需要至少30MB空间".to_string(),
+                io_mode: None,
+                latency: None,
+                metrics: DiskMetrics::default(),
             }];
         }
         
@@ -1092,6 +1598,9 @@ mod rust_disk_test {
                 write_iops: "N/A".to_string(),
                 total_speed: "N/A".to_string(),
                 total_iops: "需要至少30MB空间".to_string(),
+                io_mode: None,
+                latency: None,
+                metrics: DiskMetrics::default(),
             }];
         }
         
@@ -1151,6 +1660,9 @@ mod rust_disk_test {
                 write_iops: format!("{:.0}", write_iops),
                 total_speed: format!("{:.2} MB/s", read_speed + write_speed),
                 total_iops: format!("{:.0}", read_iops + write_iops),
+                io_mode: None,
+                latency: None,
+                metrics: DiskMetrics::default(),
             })
         } else {
             None
@@ -1183,6 +1695,9 @@ mod rust_disk_test {
                 write_iops: "N/A".to_string(),
                 total_speed: format!("{:.2} MB/s", speed),
                 total_iops: format!("{:.0}", iops),
+                io_mode: None,
+                latency: None,
+                metrics: DiskMetrics::default(),
             })
         } else {
             None
@@ -1209,6 +1724,9 @@ mod rust_disk_test {
                 write_iops: format!("{:.0}", iops),
                 total_speed: format!("{:.2} MB/s", speed),
                 total_iops: format!("{:.0}", iops),
+                io_mode: None,
+                latency: None,
+                metrics: DiskMetrics::default(),
             })
         } else {
             None
@@ -1386,6 +1904,9 @@ mod rust_disk_test {
                 write_iops: "N/A".to_string(),
                 total_speed: "N/A".to_string(),
                 total_iops: "需要至少50MB空间".to_string(),
+                io_mode: None,
+                latency: None,
+                metrics: DiskMetrics::default(),
             }];
         }
         
@@ -1435,6 +1956,9 @@ mod rust_disk_test {
             write_iops: format!("{:.0}", write_metrics.1),
             total_speed: format!("{:.2} MB/s", read_metrics.0 + write_metrics.0),
             total_iops: format!("{:.0}", read_metrics.1 + write_metrics.1),
+            io_mode: None,
+            latency: None,
+            metrics: DiskMetrics::default(),
         })
     }
 
@@ -1460,6 +1984,9 @@ mod rust_disk_test {
                 write_iops: "N/A".to_string(),
                 total_speed: format!("{:.2} MB/s", speed),
                 total_iops: format!("{:.0}", iops),
+                io_mode: None,
+                latency: None,
+                metrics: DiskMetrics::default(),
             })
         } else {
             None
@@ -1483,6 +2010,9 @@ mod rust_disk_test {
                 write_iops: format!("{:.0}", iops),
                 total_speed: format!("{:.2} MB/s", speed),
                 total_iops: format!("{:.0}", iops),
+                io_mode: None,
+                latency: None,
+                metrics: DiskMetrics::default(),
             })
         } else {
             None
@@ -1514,6 +2044,9 @@ mod rust_disk_test {
                 write_iops: format!("{:.0}", write_iops),
                 total_speed: format!("{:.2} MB/s", (read_speed + write_speed) / 2.0),
                 total_iops: format!("{:.0}", (read_iops + write_iops) / 2.0),
+                io_mode: None,
+                latency: None,
+                metrics: DiskMetrics::default(),
             })
         } else {
             None
@@ -1543,6 +2076,9 @@ mod rust_disk_test {
             write_iops: format!("{:.0}", write_iops),
             total_speed: format!("{:.2} MB/s", (read_speed + write_speed) / 2.0),
             total_iops: format!("{:.0}", (read_iops + write_iops) / 2.0),
+            io_mode: None,
+            latency: None,
+            metrics: DiskMetrics::default(),
         })
     }
 
@@ -1605,6 +2141,9 @@ mod rust_disk_test {
             write_iops: format!("{:.0}", write_iops),
             total_speed: format!("{:.2} MB/s", read_speed + write_speed),
             total_iops: format!("{:.0}", read_iops + write_iops),
+            io_mode: None,
+            latency: None,
+            metrics: DiskMetrics::default(),
         })
     }
 
@@ -1630,6 +2169,9 @@ mod rust_disk_test {
                 write_iops: format!("{:.0}", write_iops),
                 total_speed: format!("{:.2} MB/s", read_speed + write_speed),
                 total_iops: format!("{:.0}", read_iops + write_iops),
+                io_mode: None,
+                latency: None,
+                metrics: DiskMetrics::default(),
             })
         } else {
             None
@@ -1637,7 +2179,7 @@ mod rust_disk_test {
     }
 
     /// 检查可用磁盘空间
-    fn check_available_space(test_dir: &str, required_bytes: u64) -> bool {
+    pub fn check_available_space(test_dir: &str, required_bytes: u64) -> bool {
         use std::fs;
         
         // 尝试创建测试目录
@@ -1680,35 +2222,51 @@ mod rust_disk_test {
 
     /// 核心 IO 基准测试函数
     fn run_io_benchmark(
-        file_path: &PathBuf,
+        file_path: &Path,
         block_size: usize,
         is_read: bool,
         random_access: bool,
         duration_secs: u64,
     ) -> Option<(f64, f64, f64)> {
+        // 这些综合测试沿用缓存 I/O，不支持取消
+        let mode = DiskIoMode { direct: false, ..DiskIoMode::default() };
+        let cancel = CancelToken::new();
+        
         // 预热阶段
         if WARMUP_DURATION_SECS > 0 {
-            let _ = run_io_test_internal(file_path, block_size, is_read, random_access, WARMUP_DURATION_SECS);
+            let _ = run_io_test_internal(file_path, block_size, is_read, random_access, WARMUP_DURATION_SECS, mode, &cancel);
         }
         
         // 正式测试
-        run_io_test_internal(file_path, block_size, is_read, random_access, duration_secs)
+        run_io_test_internal(file_path, block_size, is_read, random_access, duration_secs, mode, &cancel)
+            .map(|stats| (stats.speed, stats.iops, stats.latency.stats().mean_us / 1_000_000.0))
+    }
+
+    /// 单次 IO 测试的统计结果
+    struct IoStats {
+        speed: f64,        // MB/s
+        iops: f64,
+        latency: LatencyHistogram,
     }
 
     /// 内部 IO 测试实现 - 增强错误处理和实时反馈
     fn run_io_test_internal(
-        file_path: &PathBuf,
+        file_path: &Path,
         block_size: usize,
         is_read: bool,
         random_access: bool,
         duration_secs: u64,
-    ) -> Option<(f64, f64, f64)> {
+        mode: DiskIoMode,
+        cancel: &CancelToken,
+    ) -> Option<IoStats> {
         let test_duration = Duration::from_secs(duration_secs);
         let start_time = Instant::now();
         
         // 增强的文件打开错误处理
         let mut file = if is_read {
-            match OpenOptions::new().read(true).open(file_path) {
+            let mut options = OpenOptions::new();
+            options.read(true);
+            match open_with_mode(&mut options, file_path, mode) {
                 Ok(f) => f,
                 Err(e) => {
                     eprintln!("无法打开文件进行读取: {}", e);
@@ -1716,12 +2274,9 @@ mod rust_disk_test {
                 }
             }
         } else {
-            match OpenOptions::new()
-                .create(true)
-                .write(true)
-                .truncate(true)
-                .open(file_path)
-            {
+            let mut options = OpenOptions::new();
+            options.create(true).write(true).truncate(true);
+            match open_with_mode(&mut options, file_path, mode) {
                 Ok(f) => f,
                 Err(e) => {
                     eprintln!("无法打开文件进行写入: {}", e);
@@ -1730,15 +2285,15 @@ mod rust_disk_test {
             }
         };
         
-        let mut buffer = if is_read {
-            vec![0u8; block_size]
-        } else {
-            create_test_data(block_size)
-        };
+        // O_DIRECT 要求缓冲区地址按页对齐
+        let mut buffer = AlignedBuffer::new(block_size);
+        if !is_read {
+            buffer.copy_from_slice(&create_test_data(block_size));
+        }
         
         let mut bytes_processed = 0usize;
         let mut operations = 0u64;
-        let mut total_latency = 0.0;
+        let mut latency = LatencyHistogram::new();
         let mut rng = rand::thread_rng();
         let mut consecutive_errors = 0;
         const MAX_CONSECUTIVE_ERRORS: u32 = 10;
@@ -1756,17 +2311,18 @@ mod rust_disk_test {
                 Err(_) => return None,
             }
         } else {
-            50 * 1024 * 1024 // 减少默认最大文件大小到50MB
+            WRITE_AREA_SIZE
         };
         
-        while start_time.elapsed() < test_duration && consecutive_errors < MAX_CONSECUTIVE_ERRORS {
+        while start_time.elapsed() < test_duration && consecutive_errors < MAX_CONSECUTIVE_ERRORS && !cancel.is_cancelled() {
             let op_start = Instant::now();
             
             // 随机定位（如果需要）
             if random_access && operations > 0 {
                 let max_offset = file_size.saturating_sub(block_size);
                 if max_offset > 0 {
-                    let offset = rng.gen_range(0..max_offset);
+                    // 偏移按块大小对齐，O_DIRECT 下未对齐的访问会失败
+                    let offset = rng.gen_range(0..max_offset) / block_size * block_size;
                     if let Err(_) = file.seek(SeekFrom::Start(offset as u64)) {
                         consecutive_errors += 1;
                         continue;
@@ -1781,16 +2337,15 @@ mod rust_disk_test {
                     Err(_) => false,
                 }
             } else {
-                // 写操作，减少同步频率
+                // 写操作，同步方式由 I/O 模式决定
                 match file.write_all(&buffer) {
-                    Ok(_) => {
+                    Ok(_) => match mode.sync {
+                        SyncMode::Fdatasync => file.sync_data().is_ok(),
+                        SyncMode::Fsync => file.sync_all().is_ok(),
                         // 每32次操作同步一次，减少性能损耗
-                        if operations > 0 && operations % 32 == 0 {
-                            file.flush().is_ok()
-                        } else {
-                            true
-                        }
-                    }
+                        SyncMode::None if operations > 0 && operations % 32 == 0 => file.flush().is_ok(),
+                        SyncMode::None => true,
+                    },
                     Err(_) => false,
                 }
             };
@@ -1798,7 +2353,7 @@ mod rust_disk_test {
             if success {
                 bytes_processed += block_size;
                 operations += 1;
-                total_latency += op_start.elapsed().as_secs_f64();
+                latency.record(op_start.elapsed());
                 consecutive_errors = 0; // 重置错误计数
                 
                 // 实时数据更新 - 增强版本，更频繁更新
@@ -1842,7 +2397,6 @@ mod rust_disk_test {
         if elapsed_secs > 0.0 && operations > 0 {
             let speed_mbps = (bytes_processed as f64) / (1024.0 * 1024.0) / elapsed_secs;
             let iops = operations as f64 / elapsed_secs;
-            let avg_latency = total_latency / operations as f64;
             
             // 最终更新一次实时数据
             if is_read {
@@ -1851,12 +2405,157 @@ mod rust_disk_test {
                 super::update_realtime_data(0.0, speed_mbps, 0.0, iops);
             }
             
-            Some((speed_mbps, iops, avg_latency))
+            Some(IoStats { speed: speed_mbps, iops, latency })
         } else {
             None
         }
     }
 
+    /// 多任务、多队列深度的 IO 测试：每个任务一个文件，每个文件上 queue_depth 个线程同时发起随机同步 I/O，
+    /// 在途 I/O 总数为 queue_depth × jobs；无法打开测试文件时返回错误
+    fn run_concurrent_io_test(
+        files: &[PathBuf],
+        block_size: usize,
+        is_read: bool,
+        duration_secs: u64,
+        mode: DiskIoMode,
+        cancel: &CancelToken,
+    ) -> Result<Option<IoStats>, String> {
+        const MB: f64 = 1024.0 * 1024.0;
+        let start_time = Instant::now();
+        let deadline = start_time + Duration::from_secs(duration_secs);
+        
+        // 写入测试的文件先由这里创建，工作线程只负责写
+        if !is_read {
+            for file in files {
+                let mut options = OpenOptions::new();
+                options.create(true).write(true).truncate(true);
+                options.open(file).map_err(|e| format!("无法创建测试文件 {}: {}", file.display(), e))?;
+            }
+        }
+        
+        let bytes_counter = AtomicU64::new(0);
+        let ops_counter = AtomicU64::new(0);
+        let (bytes, ops) = (&bytes_counter, &ops_counter);
+        
+        let histograms: Vec<Result<LatencyHistogram, String>> = thread::scope(|scope| {
+            let workers: Vec<_> = files
+                .iter()
+                .flat_map(|file| (0..mode.queue_depth.max(1)).map(move |_| file))
+                .map(|file| scope.spawn(move || io_worker(file, block_size, is_read, deadline, mode, bytes, ops, cancel)))
+                .collect();
+            
+            // 工作线程运行期间每 100ms 刷新一次实时数据
+            let mut last_update = Instant::now();
+            let (mut last_bytes, mut last_ops) = (0, 0);
+            while workers.iter().any(|worker| !worker.is_finished()) {
+                thread::sleep(Duration::from_millis(100));
+                let elapsed = last_update.elapsed().as_secs_f64();
+                let (current_bytes, current_ops) = (bytes.load(Ordering::Relaxed), ops.load(Ordering::Relaxed));
+                let speed = (current_bytes - last_bytes) as f64 / MB / elapsed;
+                let iops = (current_ops - last_ops) as f64 / elapsed;
+                if is_read {
+                    super::update_realtime_data(speed, 0.0, iops, 0.0);
+                } else {
+                    super::update_realtime_data(0.0, speed, 0.0, iops);
+                }
+                last_update = Instant::now();
+                last_bytes = current_bytes;
+                last_ops = current_ops;
+            }
+            
+            workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap_or_else(|_| Err("I/O 线程异常退出".to_string())))
+                .collect()
+        });
+        let histograms = histograms.into_iter().collect::<Result<Vec<_>, _>>()?;
+        
+        let elapsed_secs = start_time.elapsed().as_secs_f64();
+        let operations = ops_counter.load(Ordering::Relaxed);
+        if histograms.is_empty() || operations == 0 || elapsed_secs <= 0.0 {
+            return Ok(None);
+        }
+        
+        let mut latency = LatencyHistogram::new();
+        for histogram in &histograms {
+            latency.merge(histogram);
+        }
+        let speed = bytes_counter.load(Ordering::Relaxed) as f64 / MB / elapsed_secs;
+        let iops = operations as f64 / elapsed_secs;
+        if is_read {
+            super::update_realtime_data(speed, 0.0, iops, 0.0);
+        } else {
+            super::update_realtime_data(0.0, speed, 0.0, iops);
+        }
+        
+        Ok(Some(IoStats { speed, iops, latency }))
+    }
+    
+    /// 单个工作线程：用独立的文件句柄在随机的对齐偏移处读写，直到截止时间或测试被取消
+    #[allow(clippy::too_many_arguments)]
+    fn io_worker(
+        file_path: &Path,
+        block_size: usize,
+        is_read: bool,
+        deadline: Instant,
+        mode: DiskIoMode,
+        bytes: &AtomicU64,
+        ops: &AtomicU64,
+        cancel: &CancelToken,
+    ) -> Result<LatencyHistogram, String> {
+        const MAX_CONSECUTIVE_ERRORS: u32 = 10;
+        
+        let mut options = OpenOptions::new();
+        if is_read {
+            options.read(true);
+        } else {
+            options.write(true);
+        }
+        let open_error = |e: std::io::Error| format!("无法打开测试文件 {}: {}", file_path.display(), e);
+        let file = open_with_mode(&mut options, file_path, mode).map_err(open_error)?;
+        
+        let area = if is_read { file.metadata().map_err(open_error)?.len() as usize } else { WRITE_AREA_SIZE };
+        let blocks = (area / block_size).max(1);
+        
+        // O_DIRECT 要求缓冲区地址按页对齐
+        let mut buffer = AlignedBuffer::new(block_size);
+        if !is_read {
+            buffer.copy_from_slice(&create_test_data(block_size));
+        }
+        
+        let mut rng = rand::thread_rng();
+        let mut latency = LatencyHistogram::new();
+        let mut consecutive_errors = 0;
+        
+        while Instant::now() < deadline && consecutive_errors < MAX_CONSECUTIVE_ERRORS && !cancel.is_cancelled() {
+            let offset = (rng.gen_range(0..blocks) * block_size) as u64;
+            let op_start = Instant::now();
+            
+            let success = if is_read {
+                file.read_exact_at(&mut buffer, offset).is_ok()
+            } else {
+                file.write_all_at(&buffer, offset).is_ok()
+                    && match mode.sync {
+                        SyncMode::Fdatasync => file.sync_data().is_ok(),
+                        SyncMode::Fsync => file.sync_all().is_ok(),
+                        SyncMode::None => true,
+                    }
+            };
+            
+            if success {
+                latency.record(op_start.elapsed());
+                bytes.fetch_add(block_size as u64, Ordering::Relaxed);
+                ops.fetch_add(1, Ordering::Relaxed);
+                consecutive_errors = 0;
+            } else {
+                consecutive_errors += 1;
+            }
+        }
+        
+        Ok(latency)
+    }
+
     /// 混合读写测试（指定读取百分比）- 增强版
     fn run_mixed_io_test(
         file_path: &PathBuf,
@@ -2018,6 +2717,102 @@ mod rust_disk_test {
         }
     }
 
+    /// 按页对齐的 I/O 缓冲区
+    struct AlignedBuffer {
+        ptr: *mut u8,
+        layout: Layout,
+    }
+
+    // 缓冲区独占所指向的内存
+    unsafe impl Send for AlignedBuffer {}
+
+    impl AlignedBuffer {
+        const ALIGNMENT: usize = 4096;
+
+        fn new(size: usize) -> Self {
+            let layout = Layout::from_size_align(size.max(1), Self::ALIGNMENT).expect("无效的缓冲区大小");
+            // SAFETY: layout 大小不为 0
+            let ptr = unsafe { alloc::alloc_zeroed(layout) };
+            if ptr.is_null() {
+                alloc::handle_alloc_error(layout);
+            }
+            Self { ptr, layout }
+        }
+    }
+
+    impl Deref for AlignedBuffer {
+        type Target = [u8];
+
+        fn deref(&self) -> &[u8] {
+            // SAFETY: ptr 指向 layout.size() 字节已初始化的内存
+            unsafe { std::slice::from_raw_parts(self.ptr, self.layout.size()) }
+        }
+    }
+
+    impl DerefMut for AlignedBuffer {
+        fn deref_mut(&mut self) -> &mut [u8] {
+            // SAFETY: 同上，且 &mut self 保证独占访问
+            unsafe { std::slice::from_raw_parts_mut(self.ptr, self.layout.size()) }
+        }
+    }
+
+    impl Drop for AlignedBuffer {
+        fn drop(&mut self) {
+            // SAFETY: ptr 由同一 layout 分配
+            unsafe { alloc::dealloc(self.ptr, self.layout) }
+        }
+    }
+
+    /// 按 I/O 模式打开文件
+    fn open_with_mode(options: &mut OpenOptions, path: &Path, mode: DiskIoMode) -> std::io::Result<File> {
+        #[cfg(target_os = "linux")]
+        if mode.direct {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.custom_flags(libc::O_DIRECT);
+        }
+        #[cfg(not(target_os = "linux"))]
+        let _ = mode;
+        options.open(path)
+    }
+
+    /// 在测试目录中试探 O_DIRECT 是否可用，不可用时回退到缓存 I/O
+    pub fn effective_io_mode(test_dir: &Path, requested: DiskIoMode) -> DiskIoMode {
+        if !requested.direct {
+            return requested;
+        }
+
+        let probe = test_dir.join("direct_io_probe.tmp");
+        let mut options = OpenOptions::new();
+        options.create(true).write(true).truncate(true);
+        let supported = open_with_mode(&mut options, &probe, requested)
+            .and_then(|mut file| file.write_all(&AlignedBuffer::new(AlignedBuffer::ALIGNMENT)))
+            .is_ok();
+        let _ = std::fs::remove_file(&probe);
+
+        if supported {
+            requested
+        } else {
+            DiskIoMode { direct: false, ..requested }
+        }
+    }
+
+    /// 把文件写回磁盘并从页缓存中移除，使随后的读取真正访问磁盘
+    pub fn drop_file_cache(path: &Path) {
+        let Ok(file) = File::open(path) else {
+            return;
+        };
+        let _ = file.sync_all();
+
+        #[cfg(target_os = "linux")]
+        {
+            use std::os::unix::io::AsRawFd;
+            // SAFETY: fd 在 file 的生命周期内有效
+            unsafe {
+                libc::posix_fadvise(file.as_raw_fd(), 0, 0, libc::POSIX_FADV_DONTNEED);
+            }
+        }
+    }
+
     /// 创建测试数据（防止压缩优化）
     fn create_test_data(size: usize) -> Vec<u8> {
         let mut data = Vec::with_capacity(size);
@@ -2035,4 +2830,40 @@ mod rust_disk_test {
     pub fn run_rust_disk_tests(test_dir: &str) -> Vec<DiskTestResult> {
         run_professional_disk_tests(test_dir)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_df_mounts() {
+        let output = "\
+Filesystem     Type      1024-blocks       Used  Available Capacity Mounted on
+/dev/vda1      ext4      42140479488 8589934592 31406948352      22% /
+tmpfs          tmpfs      1073741824          0  1073741824       0% /tmp
+devtmpfs       devtmpfs     10485760          0    10485760       0% /dev
+tmpfs          tmpfs       209715200    1048576   208666624       1% /run
+/dev/vdb1      xfs      107374182400 1073741824 106300440576       1% /data disk
+server:/export nfs4     536870912000          0 536870912000       0% /mnt/nfs
+";
+        let mounts = parse_df_mounts(output);
+        let targets: Vec<TestTarget> = mounts.iter().filter(|t| is_test_candidate(t)).cloned().collect();
+        let names: Vec<&str> = targets.iter().map(|t| t.mount_point.as_str()).collect();
+        assert_eq!(names, ["/", "/tmp", "/data disk", "/mnt/nfs"]);
+        assert_eq!(targets[0].dir, PathBuf::from(DEFAULT_TEST_DIR));
+        assert_eq!(targets[2].dir, PathBuf::from("/data disk").join(TEST_DIR_NAME));
+        assert_eq!(targets[2].available_bytes, 106_300_440_576);
+
+        assert_eq!(targets[0].warning(), None);
+        assert!(targets[1].warning().is_some());
+        assert!(targets[3].warning().is_some());
+
+        // 目录按最长前缀归属到挂载点
+        let target = target_for_dir(Path::new("/tmp/bench"), &mounts).unwrap();
+        assert_eq!(target.mount_point, "/tmp");
+        assert_eq!(target.dir, PathBuf::from("/tmp/bench"));
+        assert_eq!(target_for_dir(Path::new("/var/tmp"), &mounts).unwrap().mount_point, "/");
+        assert_eq!(target_for_dir(Path::new("/run/user"), &mounts).unwrap().file_system, "tmpfs");
+    }
+}
@@ -0,0 +1,827 @@
+// src/handlers/dns_test.rs
+// DNS 解析测试：向系统配置的和用户指定的解析器分别通过 UDP、TCP、DoH 查询一组域名，
+// 统计延迟和失败率，并交叉比对各解析器的应答找出疑似被污染的结果
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+
+use crate::cancel::{CancelToken, Cancelled, TestControl};
+use crate::config;
+use super::network_test;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 单次查询的超时
+pub const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 每个域名的查询轮数，第一轮通常需要解析器递归查询，之后命中缓存
+pub const QUERY_ROUNDS: usize = 3;
+
+const DNS_PORT: u16 = 53;
+const TYPE_A: u16 = 1;
+const TYPE_AAAA: u16 = 28;
+const CLASS_IN: u16 = 1;
+
+// 内置测试域名：国内和海外常用站点各一半
+const DEFAULT_DOMAINS: &[&str] = &[
+    "www.baidu.com",
+    "www.qq.com",
+    "www.taobao.com",
+    "www.google.com",
+    "www.youtube.com",
+    "github.com",
+];
+
+// 内置公共解析器：名称、地址、DoH 地址（用 IP 形式，不依赖其他解析器解析 DoH 主机名）
+const PUBLIC_RESOLVERS: &[(&str, &str, &str)] = &[
+    ("Cloudflare", "1.1.1.1", "https://1.1.1.1/dns-query"),
+    ("Google", "8.8.8.8", "https://8.8.8.8/dns-query"),
+    ("阿里 DNS", "223.5.5.5", "https://223.5.5.5/dns-query"),
+    ("腾讯 DNSPod", "119.29.29.29", "https://1.12.12.12/dns-query"),
+];
+
+/// 查询方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DnsTransport {
+    Udp,
+    Tcp,
+    Doh,
+}
+
+impl DnsTransport {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DnsTransport::Udp => "UDP",
+            DnsTransport::Tcp => "TCP",
+            DnsTransport::Doh => "DoH",
+        }
+    }
+
+    /// 加密传输的应答在途中无法被篡改，作为比对的参照
+    pub fn is_encrypted(&self) -> bool {
+        *self == DnsTransport::Doh
+    }
+}
+
+/// 解析器
+#[derive(Debug, Clone, PartialEq)]
+pub struct DnsResolver {
+    pub name: String,
+    pub address: Option<SocketAddr>,  // UDP/TCP 查询地址
+    pub doh: Option<String>,          // DoH 地址
+}
+
+impl DnsResolver {
+    /// 解析命令行中的解析器：IP[:端口] 通过 UDP/TCP 查询（内置的公共解析器同时使用其 DoH），
+    /// https:// 开头的地址只通过 DoH 查询
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        if value.starts_with("https://") {
+            let name = parse_doh_url(value)?;
+            return Ok(Self { name, address: None, doh: Some(value.to_string()) });
+        }
+
+        let address = parse_resolver_address(value)?;
+        let public = PUBLIC_RESOLVERS
+            .iter()
+            .find(|(_, ip, _)| address.port() == DNS_PORT && ip.parse() == Ok(address.ip()));
+        Ok(match public {
+            Some(&(name, _, doh)) => Self { name: name.to_string(), address: Some(address), doh: Some(doh.to_string()) },
+            None => Self { name: value.to_string(), address: Some(address), doh: None },
+        })
+    }
+
+    pub fn transports(&self) -> Vec<DnsTransport> {
+        let mut transports = Vec::new();
+        if self.address.is_some() {
+            transports.extend([DnsTransport::Udp, DnsTransport::Tcp]);
+        }
+        if self.doh.is_some() {
+            transports.push(DnsTransport::Doh);
+        }
+        transports
+    }
+}
+
+// 解析器必须是 IP 地址，否则查询它之前还要先解析它自己
+fn parse_resolver_address(value: &str) -> Result<SocketAddr, String> {
+    let (host, port) = network_test::split_host_port(value, DNS_PORT)?;
+    let ip: IpAddr = host.parse().map_err(|_| format!("解析器地址必须是 IP: {}", value))?;
+    Ok(SocketAddr::new(ip, port))
+}
+
+// 返回 DoH 地址的主机名作为默认名称
+fn parse_doh_url(value: &str) -> Result<String, String> {
+    let url = url::Url::parse(value).map_err(|_| format!("DoH 地址无效: {}", value))?;
+    match url.host_str() {
+        Some(host) if url.scheme() == "https" => Ok(host.to_string()),
+        _ => Err(format!("DoH 地址必须以 https:// 开头: {}", value)),
+    }
+}
+
+/// 读取 /etc/resolv.conf 中的 nameserver
+pub fn system_nameservers() -> Vec<String> {
+    std::fs::read_to_string("/etc/resolv.conf")
+        .map(|content| parse_resolv_conf(&content))
+        .unwrap_or_default()
+}
+
+pub fn parse_resolv_conf(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            match (parts.next(), parts.next()) {
+                (Some("nameserver"), Some(address)) => Some(address.to_string()),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+// 配置文件格式，所有字段可选以便逐项给出校验错误
+#[derive(Debug, Default, Deserialize)]
+struct DnsConfigFile {
+    #[serde(default)]
+    domains: Vec<String>,
+    #[serde(default)]
+    resolvers: Vec<ResolverEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ResolverEntry {
+    name: Option<String>,
+    address: Option<String>,  // IP[:端口]，通过 UDP/TCP 查询
+    doh: Option<String>,      // DoH 地址
+}
+
+fn validate_resolver_entry(entry: ResolverEntry) -> Result<DnsResolver, String> {
+    let address = entry.address.as_deref().map(parse_resolver_address).transpose()?;
+    let doh_host = entry.doh.as_deref().map(parse_doh_url).transpose()?;
+    let name = entry
+        .name
+        .filter(|name| !name.trim().is_empty())
+        .or_else(|| address.map(|address| address.ip().to_string()))
+        .or(doh_host)
+        .ok_or("需要 address 或 doh")?;
+    Ok(DnsResolver { name, address, doh: entry.doh })
+}
+
+/// 本次测试的解析器和域名，以及读取配置时遇到的错误
+#[derive(Debug, Clone, Default)]
+pub struct DnsTestPlan {
+    pub resolvers: Vec<DnsResolver>,
+    pub domains: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+/// 系统解析器排在最前，其后是 resolvers（命令行指定）、配置目录中 dns.toml / dns.json 的解析器
+/// 或内置的公共解析器，三者取第一个非空的；测试域名取自配置文件，未配置时使用内置域名
+pub fn load_test_plan(resolvers: &[String]) -> DnsTestPlan {
+    let mut plan = DnsTestPlan::default();
+    for address in system_nameservers() {
+        match parse_resolver_address(&address) {
+            Ok(address) => plan.resolvers.push(DnsResolver {
+                name: format!("系统 {}", address.ip()),
+                address: Some(address),
+                doh: None,
+            }),
+            Err(e) => plan.errors.push(format!("/etc/resolv.conf: {}", e)),
+        }
+    }
+
+    let file = match config::find_config_file(&["dns.toml", "dns.json"]) {
+        Some(path) => config::load_config_file::<DnsConfigFile>(&path).unwrap_or_else(|e| {
+            plan.errors.push(e);
+            DnsConfigFile::default()
+        }),
+        None => DnsConfigFile::default(),
+    };
+
+    let mut extra = Vec::new();
+    if !resolvers.is_empty() {
+        for value in resolvers {
+            match DnsResolver::parse(value) {
+                Ok(resolver) => extra.push(resolver),
+                Err(e) => plan.errors.push(e),
+            }
+        }
+    } else {
+        for (index, entry) in file.resolvers.into_iter().enumerate() {
+            match validate_resolver_entry(entry) {
+                Ok(resolver) => extra.push(resolver),
+                Err(e) => plan.errors.push(format!("dns 配置第 {} 项: {}", index + 1, e)),
+            }
+        }
+    }
+    if extra.is_empty() && resolvers.is_empty() {
+        extra = PUBLIC_RESOLVERS
+            .iter()
+            .map(|&(name, address, doh)| DnsResolver {
+                name: name.to_string(),
+                address: Some(SocketAddr::new(address.parse().expect("内置解析器地址正确"), DNS_PORT)),
+                doh: Some(doh.to_string()),
+            })
+            .collect();
+    }
+    plan.resolvers.extend(extra);
+
+    for domain in file.domains {
+        match build_query(0, &domain, TYPE_A) {
+            Ok(_) => plan.domains.push(domain),
+            Err(e) => plan.errors.push(e),
+        }
+    }
+    if plan.domains.is_empty() {
+        plan.domains = DEFAULT_DOMAINS.iter().map(|domain| domain.to_string()).collect();
+    }
+    plan
+}
+
+/// 构造递归查询报文
+pub fn build_query(id: u16, domain: &str, qtype: u16) -> Result<Vec<u8>, String> {
+    let domain = domain.trim().trim_end_matches('.');
+    let mut packet = Vec::with_capacity(domain.len() + 18);
+    packet.extend_from_slice(&id.to_be_bytes());
+    packet.extend_from_slice(&0x0100u16.to_be_bytes()); // RD：请求递归
+    packet.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]); // 1 个问题
+    for label in domain.split('.') {
+        if label.is_empty() || label.len() > 63 || !label.is_ascii() {
+            return Err(format!("域名无效: {}", domain));
+        }
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+    if packet.len() - 12 > 255 {
+        return Err(format!("域名过长: {}", domain));
+    }
+    packet.extend_from_slice(&qtype.to_be_bytes());
+    packet.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(packet)
+}
+
+/// 解析应答报文，返回其中的 A/AAAA 地址（CNAME 链上的地址一并返回）；
+/// 只有 NOERROR 算成功，域名存在但没有地址时返回空列表
+pub fn parse_response(id: u16, packet: &[u8]) -> Result<Vec<IpAddr>, String> {
+    let truncated = || "应答报文不完整".to_string();
+    if packet.len() < 12 {
+        return Err(truncated());
+    }
+    let read_u16 = |pos: usize| packet.get(pos..pos + 2).map(|b| u16::from_be_bytes([b[0], b[1]]));
+    if read_u16(0) != Some(id) {
+        return Err("应答 ID 不匹配".to_string());
+    }
+    let flags = read_u16(2).ok_or_else(truncated)?;
+    if flags & 0x8000 == 0 {
+        return Err("收到的不是应答报文".to_string());
+    }
+    match flags & 0x000f {
+        0 => {}
+        2 => return Err("SERVFAIL".to_string()),
+        3 => return Err("NXDOMAIN（域名不存在）".to_string()),
+        5 => return Err("REFUSED（拒绝查询）".to_string()),
+        rcode => return Err(format!("RCODE {}", rcode)),
+    }
+
+    let questions = read_u16(4).ok_or_else(truncated)?;
+    let answers = read_u16(6).ok_or_else(truncated)?;
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = skip_name(packet, pos).ok_or_else(truncated)? + 4;
+    }
+
+    let mut addresses = Vec::new();
+    for _ in 0..answers {
+        pos = skip_name(packet, pos).ok_or_else(truncated)?;
+        let rtype = read_u16(pos).ok_or_else(truncated)?;
+        let rdlength = read_u16(pos + 8).ok_or_else(truncated)? as usize;
+        let rdata = packet.get(pos + 10..pos + 10 + rdlength).ok_or_else(truncated)?;
+        match (rtype, rdata.len()) {
+            (TYPE_A, 4) => addresses.push(IpAddr::V4(Ipv4Addr::new(rdata[0], rdata[1], rdata[2], rdata[3]))),
+            (TYPE_AAAA, 16) => {
+                let octets: [u8; 16] = rdata.try_into().expect("长度已检查");
+                addresses.push(IpAddr::V6(Ipv6Addr::from(octets)));
+            }
+            _ => {}
+        }
+        pos += 10 + rdlength;
+    }
+    Ok(addresses)
+}
+
+// 跳过报文中的域名，返回其后的位置；遇到压缩指针时域名在指针处结束
+fn skip_name(packet: &[u8], mut pos: usize) -> Option<usize> {
+    loop {
+        let len = *packet.get(pos)? as usize;
+        match len {
+            0 => return Some(pos + 1),
+            _ if len & 0xc0 == 0xc0 => return packet.get(pos + 1).map(|_| pos + 2),
+            _ => pos += 1 + len,
+        }
+    }
+}
+
+/// 应答中不可能是公网站点的地址：内网、回环、保留地址等
+pub fn is_bogon(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_private()
+                || ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || a >= 240
+                || (a == 100 && (64..128).contains(&b))
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            ip.is_loopback() || ip.is_unspecified() || first & 0xfe00 == 0xfc00 || first & 0xffc0 == 0xfe80
+        }
+    }
+}
+
+// 比对时按网段比较，同一 CDN 节点的多个地址通常在同一 /24（IPv6 为 /48）内
+fn network_of(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V4(ip) => IpAddr::V4(Ipv4Addr::from(u32::from(ip) & 0xffff_ff00)),
+        IpAddr::V6(ip) => IpAddr::V6(Ipv6Addr::from(u128::from(ip) & !((1u128 << 80) - 1))),
+    }
+}
+
+/// 一个解析器通过一种方式查询的统计
+#[derive(Debug, Clone)]
+pub struct DnsProbe {
+    pub resolver: String,
+    pub transport: DnsTransport,
+    pub endpoint: String,              // 1.1.1.1:53 或 DoH 地址
+    pub latencies_ms: Vec<f64>,        // 成功查询的耗时
+    pub queries: usize,
+    pub failures: usize,
+    pub answers: Vec<Vec<IpAddr>>,     // 按域名汇总各轮查询得到的地址
+    pub suspicious: Vec<String>,       // 应答疑似被污染的域名
+    pub last_error: Option<String>,
+}
+
+impl DnsProbe {
+    pub fn new(resolver: &DnsResolver, transport: DnsTransport, domains: usize) -> Self {
+        let endpoint = match transport {
+            DnsTransport::Doh => resolver.doh.clone().unwrap_or_default(),
+            _ => resolver.address.map(|address| address.to_string()).unwrap_or_default(),
+        };
+        Self {
+            resolver: resolver.name.clone(),
+            transport,
+            endpoint,
+            latencies_ms: Vec::new(),
+            queries: 0,
+            failures: 0,
+            answers: vec![Vec::new(); domains],
+            suspicious: Vec::new(),
+            last_error: None,
+        }
+    }
+
+    pub fn avg_ms(&self) -> Option<f64> {
+        (!self.latencies_ms.is_empty()).then(|| self.latencies_ms.iter().sum::<f64>() / self.latencies_ms.len() as f64)
+    }
+
+    pub fn median_ms(&self) -> Option<f64> {
+        let mut sorted = self.latencies_ms.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let mid = sorted.len() / 2;
+        match sorted.len() {
+            0 => None,
+            len if len % 2 == 0 => Some((sorted[mid - 1] + sorted[mid]) / 2.0),
+            _ => Some(sorted[mid]),
+        }
+    }
+
+    pub fn failure_pct(&self) -> f64 {
+        if self.queries == 0 { 0.0 } else { self.failures as f64 / self.queries as f64 * 100.0 }
+    }
+
+    /// 是否所有查询都失败
+    pub fn is_unreachable(&self) -> bool {
+        self.queries > 0 && self.failures == self.queries
+    }
+
+    fn record(&mut self, domain: usize, started: Instant, result: Result<Vec<IpAddr>, String>) {
+        self.queries += 1;
+        match result {
+            Ok(addresses) => {
+                self.latencies_ms.push(started.elapsed().as_secs_f64() * 1000.0);
+                for address in addresses {
+                    if !self.answers[domain].contains(&address) {
+                        self.answers[domain].push(address);
+                    }
+                }
+            }
+            Err(e) => {
+                self.failures += 1;
+                self.last_error = Some(e);
+            }
+        }
+    }
+}
+
+/// 为每个解析器的每种查询方式生成一项统计
+pub fn probes_for(resolvers: &[DnsResolver], domains: usize) -> Vec<DnsProbe> {
+    resolvers
+        .iter()
+        .flat_map(|resolver| resolver.transports().into_iter().map(move |transport| DnsProbe::new(resolver, transport, domains)))
+        .collect()
+}
+
+async fn query(probe: &DnsProbe, client: &reqwest::Client, domain: &str) -> Result<Vec<IpAddr>, BoxError> {
+    // DoH 建议 ID 固定为 0，以便 HTTP 缓存
+    let id = if probe.transport == DnsTransport::Doh { 0 } else { rand::random() };
+    let packet = build_query(id, domain, TYPE_A)?;
+    match probe.transport {
+        DnsTransport::Udp => query_udp(probe.endpoint.parse()?, id, &packet).await,
+        DnsTransport::Tcp => query_tcp(probe.endpoint.parse()?, id, &packet).await,
+        DnsTransport::Doh => query_doh(client, &probe.endpoint, &packet).await,
+    }
+}
+
+async fn query_udp(server: SocketAddr, id: u16, packet: &[u8]) -> Result<Vec<IpAddr>, BoxError> {
+    let local: SocketAddr = match server {
+        SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+        SocketAddr::V6(_) => (Ipv6Addr::UNSPECIFIED, 0).into(),
+    };
+    let socket = UdpSocket::bind(local).await?;
+    socket.connect(server).await?;
+    socket.send(packet).await?;
+
+    let mut buf = [0u8; 4096];
+    loop {
+        let len = socket.recv(&mut buf).await?;
+        // 与普通的系统解析器一样采用最先到达的应答，途中注入的伪造应答也会被采用
+        if buf[..len].starts_with(&id.to_be_bytes()) {
+            return Ok(parse_response(id, &buf[..len])?);
+        }
+    }
+}
+
+async fn query_tcp(server: SocketAddr, id: u16, packet: &[u8]) -> Result<Vec<IpAddr>, BoxError> {
+    let mut stream = TcpStream::connect(server).await?;
+    // TCP 上的报文前有两字节长度
+    let mut message = (packet.len() as u16).to_be_bytes().to_vec();
+    message.extend_from_slice(packet);
+    stream.write_all(&message).await?;
+
+    let len = stream.read_u16().await? as usize;
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf).await?;
+    Ok(parse_response(id, &buf)?)
+}
+
+async fn query_doh(client: &reqwest::Client, url: &str, packet: &[u8]) -> Result<Vec<IpAddr>, BoxError> {
+    let response = client
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/dns-message")
+        .header(reqwest::header::ACCEPT, "application/dns-message")
+        .body(packet.to_vec())
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()).into());
+    }
+    let body = response.bytes().await?;
+    Ok(parse_response(0, &body)?)
+}
+
+/// 依次查询每个域名 QUERY_ROUNDS 轮，每次查询后回调；
+/// 第一轮全部失败时不再继续，避免不可达的解析器拖慢整个测试
+pub async fn run_probe<F>(
+    probe: &mut DnsProbe,
+    domains: &[String],
+    client: &reqwest::Client,
+    cancel: &CancelToken,
+    on_update: F,
+) -> Result<(), Cancelled>
+where
+    F: Fn(&DnsProbe),
+{
+    for round in 0..QUERY_ROUNDS {
+        for (index, domain) in domains.iter().enumerate() {
+            cancel.checkpoint_async().await?;
+            let started = Instant::now();
+            let result = match tokio::time::timeout(QUERY_TIMEOUT, query(probe, client, domain)).await {
+                Ok(result) => result.map_err(|e| e.to_string()),
+                Err(_) => Err("超时".to_string()),
+            };
+            probe.record(index, started, result);
+            on_update(probe);
+        }
+        if round == 0 && probe.is_unreachable() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// 所有解析器和查询方式并行测试，完成后交叉比对应答
+pub async fn run_dns_test<F>(probes: &mut [DnsProbe], domains: &[String], cancel: &CancelToken, on_update: F) -> Result<(), String>
+where
+    F: Fn(usize, &DnsProbe),
+{
+    let client = reqwest::Client::builder()
+        .timeout(QUERY_TIMEOUT)
+        .build()
+        .map_err(|e| format!("无法创建 DoH 客户端: {}", e))?;
+
+    let on_update = &on_update;
+    let client = &client;
+    let runs = probes.iter_mut().enumerate().map(|(index, probe)| async move {
+        run_probe(probe, domains, client, cancel, |probe| on_update(index, probe)).await
+    });
+    let results = futures_util::future::join_all(runs).await;
+    if let Some(Err(e)) = results.into_iter().find(Result::is_err) {
+        return Err(e.to_string());
+    }
+
+    flag_suspicious(probes, domains);
+    Ok(())
+}
+
+/// 交叉比对各解析器的应答，标记疑似被污染的结果：应答中含有内网、保留地址等，
+/// 或者与参照应答没有任何网段重合。参照优先取 DoH 应答，没有 DoH 应答时取至少两个其他解析器的应答。
+/// CDN 站点在不同解析器上的应答本就可能不同，结果只作提示
+pub fn flag_suspicious(probes: &mut [DnsProbe], domains: &[String]) {
+    for (index, domain) in domains.iter().enumerate() {
+        let flags: Vec<bool> = (0..probes.len()).map(|probe| is_suspicious(probes, probe, index)).collect();
+        for (probe, flagged) in probes.iter_mut().zip(flags) {
+            if flagged && !probe.suspicious.contains(domain) {
+                probe.suspicious.push(domain.clone());
+            }
+        }
+    }
+}
+
+fn is_suspicious(probes: &[DnsProbe], probe: usize, domain: usize) -> bool {
+    let answer = &probes[probe].answers[domain];
+    if answer.iter().any(|&ip| is_bogon(ip)) {
+        return true;
+    }
+    if answer.is_empty() || probes[probe].transport.is_encrypted() {
+        return false;
+    }
+
+    let others: Vec<&DnsProbe> = probes
+        .iter()
+        .enumerate()
+        .filter(|&(index, other)| index != probe && !other.answers[domain].is_empty())
+        .map(|(_, other)| other)
+        .collect();
+    let encrypted: Vec<&DnsProbe> = others.iter().copied().filter(|other| other.transport.is_encrypted()).collect();
+    let reference = match encrypted.is_empty() {
+        false => encrypted,
+        true if others.len() >= 2 => others,
+        true => return false,
+    };
+
+    let networks: HashSet<IpAddr> = answer.iter().map(|&ip| network_of(ip)).collect();
+    !reference
+        .iter()
+        .flat_map(|other| other.answers[domain].iter())
+        .any(|&ip| networks.contains(&network_of(ip)))
+}
+
+/// DNS 测试状态
+#[derive(Debug, Clone, Default)]
+pub struct DnsTestInfo {
+    pub is_testing: bool,
+    pub current_stage: String,
+    pub domains: Vec<String>,
+    pub probes: Vec<DnsProbe>,
+    pub error_message: Option<String>,
+    pub start_time: Option<Instant>,
+}
+
+impl DnsTestInfo {
+    /// 已完成的查询数和总查询数（不可达的解析器提前结束，按完成计）
+    pub fn progress(&self) -> (usize, usize) {
+        let full = self.domains.len() * QUERY_ROUNDS;
+        let done = self
+            .probes
+            .iter()
+            .map(|probe| if probe.is_unreachable() && probe.queries >= self.domains.len() { full } else { probe.queries })
+            .sum();
+        (done, full * self.probes.len())
+    }
+}
+
+// 全局状态
+static DNS_TEST_INFO: Mutex<Option<DnsTestInfo>> = Mutex::new(None);
+static NEEDS_UI_REFRESH: AtomicBool = AtomicBool::new(false);
+static CONTROL: TestControl = TestControl::new();
+
+/// 获取当前测试状态
+pub fn get_current_test_info() -> DnsTestInfo {
+    DNS_TEST_INFO.lock().ok().and_then(|info| info.clone()).unwrap_or_default()
+}
+
+/// 开始 DNS 测试，每次重新读取解析器配置
+pub fn start_dns_test() -> Result<(), String> {
+    let Some(run) = CONTROL.try_begin() else {
+        return Err("DNS 测试进行中，完成或按 X 取消后才能重新测试".to_string());
+    };
+
+    let plan = load_test_plan(&[]);
+    if plan.resolvers.is_empty() {
+        return Err("没有可以测试的解析器".to_string());
+    }
+    let mut probes = probes_for(&plan.resolvers, plan.domains.len());
+    if let Ok(mut info) = DNS_TEST_INFO.lock() {
+        *info = Some(DnsTestInfo {
+            is_testing: true,
+            current_stage: "正在查询...".to_string(),
+            domains: plan.domains.clone(),
+            probes: probes.clone(),
+            error_message: (!plan.errors.is_empty()).then(|| plan.errors.join("; ")),
+            start_time: Some(Instant::now()),
+        });
+    }
+
+    tokio::spawn(async move {
+        let result = run_dns_test(&mut probes, &plan.domains, &run, |index, probe| {
+            update_info(|info| info.probes[index] = probe.clone());
+        })
+        .await;
+        update_info(|info| {
+            info.is_testing = false;
+            info.current_stage = match result {
+                Ok(()) => {
+                    info.probes = probes;
+                    "测试完成".to_string()
+                }
+                Err(e) => {
+                    // 保留读取配置时的错误
+                    info.error_message = Some(match info.error_message.take() {
+                        Some(previous) => format!("{}; {}", e, previous),
+                        None => e.to_string(),
+                    });
+                    e.to_string()
+                }
+            };
+        });
+    });
+    Ok(())
+}
+
+/// 取消/暂停正在运行的 DNS 测试
+pub fn test_control() -> &'static TestControl {
+    &CONTROL
+}
+
+/// 检查是否需要刷新UI
+pub fn check_needs_refresh() -> bool {
+    NEEDS_UI_REFRESH.swap(false, Ordering::Relaxed)
+}
+
+fn update_info(update: impl FnOnce(&mut DnsTestInfo)) {
+    if let Ok(mut info) = DNS_TEST_INFO.lock() {
+        if let Some(ref mut info) = info.as_mut() {
+            update(info);
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+pub fn format_ms(value: Option<f64>) -> String {
+    value.map(|ms| format!("{:.1}", ms)).unwrap_or_else(|| "-".to_string())
+}
+
+/// 格式化测试结果，用于命令行输出和导出报告
+pub fn format_dns_results(probes: &[DnsProbe]) -> String {
+    // 中文字符占两列，表头直接按列宽写出
+    let mut output = String::from("解析器                协议   平均ms   中位ms   失败  可疑\n");
+    for probe in probes {
+        output.push_str(&format!(
+            "{:<width$} {:<5} {:>7} {:>8} {:>5.0}% {:>5}\n",
+            probe.resolver,
+            probe.transport.name(),
+            format_ms(probe.avg_ms()),
+            format_ms(probe.median_ms()),
+            probe.failure_pct(),
+            probe.suspicious.len(),
+            width = 21usize.saturating_sub(probe.resolver.chars().filter(|c| !c.is_ascii()).count()),
+        ));
+    }
+    for probe in probes.iter().filter(|probe| !probe.suspicious.is_empty()) {
+        output.push_str(&format!("  {} {} 应答可疑: {}\n", probe.resolver, probe.transport.name(), probe.suspicious.join(", ")));
+    }
+    for probe in probes.iter().filter(|probe| probe.is_unreachable()) {
+        output.push_str(&format!(
+            "  {} {} 全部失败: {}\n",
+            probe.resolver,
+            probe.transport.name(),
+            probe.last_error.as_deref().unwrap_or("未知错误")
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 构造只含一个问题和若干 A 记录的应答，记录名使用指向问题的压缩指针
+    fn response(id: u16, rcode: u16, addresses: &[[u8; 4]]) -> Vec<u8> {
+        let mut packet = build_query(id, "www.example.com", TYPE_A).unwrap();
+        packet[2..4].copy_from_slice(&(0x8180 | rcode).to_be_bytes());
+        packet[6..8].copy_from_slice(&(addresses.len() as u16).to_be_bytes());
+        for address in addresses {
+            packet.extend_from_slice(&[0xc0, 12, 0, 1, 0, 1, 0, 0, 0, 60, 0, 4]);
+            packet.extend_from_slice(address);
+        }
+        packet
+    }
+
+    #[test]
+    fn test_build_and_parse() {
+        let query = build_query(0x1234, "www.example.com.", TYPE_A).unwrap();
+        assert_eq!(&query[..4], &[0x12, 0x34, 0x01, 0x00]);
+        assert_eq!(&query[12..17], b"\x03www\x07");
+        assert!(build_query(1, "bad..example", TYPE_A).is_err());
+
+        let ips = parse_response(7, &response(7, 0, &[[93, 184, 216, 34], [93, 184, 216, 35]])).unwrap();
+        assert_eq!(ips, vec!["93.184.216.34".parse::<IpAddr>().unwrap(), "93.184.216.35".parse().unwrap()]);
+        assert_eq!(parse_response(7, &response(7, 3, &[])).unwrap_err(), "NXDOMAIN（域名不存在）");
+        assert!(parse_response(8, &response(7, 0, &[])).is_err());
+        let mut cut = response(7, 0, &[[1, 2, 3, 4]]);
+        cut.truncate(cut.len() - 2);
+        assert!(parse_response(7, &cut).is_err());
+
+        assert_eq!(parse_resolv_conf("# x\nnameserver 127.0.0.53\noptions edns0\nnameserver ::1\n"), vec!["127.0.0.53", "::1"]);
+        let resolver = DnsResolver::parse("223.5.5.5").unwrap();
+        assert_eq!((resolver.name.as_str(), resolver.transports().len()), ("阿里 DNS", 3));
+        assert_eq!(DnsResolver::parse("[2001:db8::53]:5353").unwrap().transports(), vec![DnsTransport::Udp, DnsTransport::Tcp]);
+        assert!(DnsResolver::parse("dns.example.com").is_err());
+    }
+
+    #[test]
+    fn test_flag_suspicious() {
+        let domains = vec!["www.google.com".to_string(), "www.baidu.com".to_string()];
+        let resolver = |name: &str| DnsResolver { name: name.to_string(), address: None, doh: None };
+        let probe = |name: &str, transport: DnsTransport, answers: [&[&str]; 2]| DnsProbe {
+            answers: answers.iter().map(|ips| ips.iter().map(|ip| ip.parse().unwrap()).collect()).collect(),
+            ..DnsProbe::new(&resolver(name), transport, 2)
+        };
+        let mut probes = vec![
+            probe("系统", DnsTransport::Udp, [&["31.13.94.41"], &["110.242.68.66"]]),
+            probe("A", DnsTransport::Udp, [&["142.250.196.196"], &["127.0.0.1"]]),
+            probe("A", DnsTransport::Doh, [&["142.250.196.100"], &["110.242.68.3"]]),
+            probe("B", DnsTransport::Tcp, [&[], &["110.242.68.21"]]),
+        ];
+        flag_suspicious(&mut probes, &domains);
+
+        // 与 DoH 应答网段不同，或者应答是回环地址
+        assert_eq!(probes[0].suspicious, vec!["www.google.com"]);
+        assert_eq!(probes[1].suspicious, vec!["www.baidu.com"]);
+        assert!(probes[2].suspicious.is_empty() && probes[3].suspicious.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_udp_and_tcp_against_stand_in_resolver() {
+        let udp = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = udp.local_addr().unwrap();
+        let tcp = tokio::net::TcpListener::bind(addr).await.unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            while let Ok((_, peer)) = udp.recv_from(&mut buf).await {
+                let id = u16::from_be_bytes([buf[0], buf[1]]);
+                udp.send_to(&response(id, 0, &[[198, 18, 0, 1]]), peer).await.unwrap();
+            }
+        });
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = tcp.accept().await {
+                let len = stream.read_u16().await.unwrap() as usize;
+                let mut buf = vec![0u8; len];
+                stream.read_exact(&mut buf).await.unwrap();
+                let reply = response(u16::from_be_bytes([buf[0], buf[1]]), 0, &[[198, 18, 0, 2]]);
+                stream.write_all(&(reply.len() as u16).to_be_bytes()).await.unwrap();
+                stream.write_all(&reply).await.unwrap();
+            }
+        });
+
+        let resolver = DnsResolver::parse(&addr.to_string()).unwrap();
+        let domains = vec!["www.example.com".to_string()];
+        let mut probes = probes_for(&[resolver], domains.len());
+        run_dns_test(&mut probes, &domains, &CancelToken::new(), |_, _| {}).await.unwrap();
+
+        assert_eq!(probes.len(), 2);
+        for (probe, expected) in probes.iter().zip(["198.18.0.1", "198.18.0.2"]) {
+            assert_eq!((probe.queries, probe.failures), (QUERY_ROUNDS, 0));
+            assert_eq!(probe.answers[0], vec![expected.parse::<IpAddr>().unwrap()]);
+        }
+    }
+}
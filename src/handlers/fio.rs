@@ -0,0 +1,439 @@
+// src/handlers/fio.rs
+// fio 集成：以 JSON 格式运行任务文件并解析结果，支持配置目录中用户自定义的 .fio 任务
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use crate::cancel::{CancelToken, Cancelled};
+use crate::config;
+use crate::handlers::disk_test::{DiskIoMode, SyncMode};
+use crate::latency::LatencyStats;
+
+// 用户任务文件所在的子目录：<配置目录>/fio/*.fio
+const PROFILE_DIR_NAME: &str = "fio";
+
+// 等待 fio 退出时检查取消请求的间隔
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 磁盘测试配置
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiskProfile {
+    Builtin,                                // 内置 Rust 引擎，按块大小测试
+    FioStandard,                            // 内置 fio 任务
+    FioFile { name: String, path: PathBuf }, // 用户的 .fio 任务文件
+}
+
+impl DiskProfile {
+    pub fn name(&self) -> String {
+        match self {
+            DiskProfile::Builtin => "内置引擎".to_string(),
+            DiskProfile::FioStandard => "fio 标准".to_string(),
+            DiskProfile::FioFile { name, .. } => format!("fio: {}", name),
+        }
+    }
+
+    pub fn uses_fio(&self) -> bool {
+        !matches!(self, DiskProfile::Builtin)
+    }
+}
+
+/// 可选的测试配置：内置引擎、内置 fio 任务，以及配置目录中按文件名排序的 .fio 文件
+pub fn available_profiles() -> Vec<DiskProfile> {
+    let mut profiles = vec![DiskProfile::Builtin, DiskProfile::FioStandard];
+    if let Some(dir) = profile_dir() {
+        profiles.extend(load_profiles_from(&dir));
+    }
+    profiles
+}
+
+/// 用户任务文件目录
+pub fn profile_dir() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join(PROFILE_DIR_NAME))
+}
+
+fn load_profiles_from(dir: &Path) -> Vec<DiskProfile> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().map(|ext| ext == "fio").unwrap_or(false))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().into_owned();
+            Some(DiskProfile::FioFile { name, path })
+        })
+        .collect()
+}
+
+/// 按名称查找配置，名称可以是显示名称或 .fio 文件名（不含扩展名）
+pub fn find_profile(name: &str) -> Option<DiskProfile> {
+    available_profiles().into_iter().find(|profile| {
+        profile.name() == name
+            || match profile {
+                DiskProfile::Builtin => name == "builtin",
+                DiskProfile::FioStandard => name == "fio",
+                DiskProfile::FioFile { name: file_name, .. } => file_name == name,
+            }
+    })
+}
+
+/// fio 单个任务（使用 group_reporting 时为一组）的结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct FioJobResult {
+    pub name: String,
+    pub read: Option<FioDirection>,
+    pub write: Option<FioDirection>,
+}
+
+/// 单个方向的结果，没有产生 I/O 的方向不会出现
+#[derive(Debug, Clone, PartialEq)]
+pub struct FioDirection {
+    pub bw_bytes: f64,  // 字节/秒
+    pub iops: f64,
+    pub latency: Option<LatencyStats>,  // 完成延迟 (clat)
+}
+
+impl FioDirection {
+    pub fn mb_s(&self) -> f64 {
+        self.bw_bytes / (1024.0 * 1024.0)
+    }
+}
+
+// fio --output-format=json 的输出，只取用到的字段
+#[derive(Deserialize)]
+struct FioOutput {
+    jobs: Vec<FioJob>,
+}
+
+#[derive(Deserialize)]
+struct FioJob {
+    jobname: String,
+    #[serde(default)]
+    error: i64,
+    read: Option<FioIo>,
+    write: Option<FioIo>,
+}
+
+#[derive(Deserialize)]
+struct FioIo {
+    #[serde(default)]
+    io_bytes: u64,
+    bw_bytes: Option<f64>,  // fio 3.x
+    #[serde(default)]
+    bw: f64,                // KiB/s
+    #[serde(default)]
+    iops: f64,
+    clat_ns: Option<FioLatency>,  // fio 3.x，纳秒
+    clat: Option<FioLatency>,     // fio 2.x，微秒
+}
+
+#[derive(Deserialize)]
+struct FioLatency {
+    #[serde(default)]
+    max: f64,
+    #[serde(default)]
+    mean: f64,
+    #[serde(default, rename = "N")]
+    samples: u64,
+    #[serde(default)]
+    percentile: BTreeMap<String, f64>,
+}
+
+/// 解析 fio 的 JSON 输出；fio 可能在 JSON 前打印警告，从第一个 `{` 开始解析
+pub fn parse_json_output(output: &str) -> Result<Vec<FioJobResult>, String> {
+    let start = output.find('{').ok_or("fio 没有输出 JSON 结果")?;
+    let mut deserializer = serde_json::Deserializer::from_str(&output[start..]);
+    let parsed = FioOutput::deserialize(&mut deserializer).map_err(|e| format!("fio 输出解析失败: {}", e))?;
+
+    if let Some(job) = parsed.jobs.iter().find(|job| job.error != 0) {
+        return Err(format!("fio 任务 {} 出错 (错误码 {})", job.jobname, job.error));
+    }
+
+    Ok(parsed
+        .jobs
+        .into_iter()
+        .map(|job| FioJobResult {
+            name: job.jobname,
+            read: job.read.and_then(direction),
+            write: job.write.and_then(direction),
+        })
+        .collect())
+}
+
+fn direction(io: FioIo) -> Option<FioDirection> {
+    if io.io_bytes == 0 {
+        return None;
+    }
+    let latency = match (io.clat_ns, io.clat) {
+        (Some(clat), _) => Some(latency_stats(&clat, 1000.0)),
+        (None, Some(clat)) => Some(latency_stats(&clat, 1.0)),
+        (None, None) => None,
+    };
+    Some(FioDirection {
+        bw_bytes: io.bw_bytes.unwrap_or(io.bw * 1024.0),
+        iops: io.iops,
+        latency,
+    })
+}
+
+// per_us：每微秒对应的原始单位数
+fn latency_stats(clat: &FioLatency, per_us: f64) -> LatencyStats {
+    let percentile = |p: f64| {
+        clat.percentile
+            .iter()
+            .find(|(key, _)| key.parse::<f64>().map(|k| (k - p).abs() < 1e-6).unwrap_or(false))
+            .map(|(_, &value)| value / per_us)
+            .unwrap_or(0.0)
+    };
+    LatencyStats {
+        samples: clat.samples,
+        mean_us: clat.mean / per_us,
+        p50_us: percentile(50.0),
+        p95_us: percentile(95.0),
+        p99_us: percentile(99.0),
+        p999_us: percentile(99.9),
+        max_us: clat.max / per_us,
+        distribution: Vec::new(),
+    }
+}
+
+// 内置任务各组每个任务的文件大小 (MiB) 和任务数，与 builtin_job_file 中的 size、numjobs 一致
+const BUILTIN_JOB_SIZES: [(u64, u64); 3] = [(100, 4), (200, 2), (500, 1)];
+
+/// 内置 fio 任务创建的测试文件总大小（字节）
+pub fn builtin_job_bytes() -> u64 {
+    BUILTIN_JOB_SIZES.iter().map(|(size_mb, jobs)| size_mb * jobs * 1024 * 1024).sum()
+}
+
+/// 内置 fio 任务：4K/64K 随机混合读写和 1M 顺序读写依次运行，直接 I/O、同步方式和队列深度跟随 I/O 模式
+pub fn builtin_job_file(mode: DiskIoMode) -> String {
+    let sync = match mode.sync {
+        SyncMode::None => "",
+        SyncMode::Fdatasync => "fdatasync=1\n",
+        SyncMode::Fsync => "fsync=1\n",
+    };
+    format!(
+        "[global]
+ioengine=libaio
+direct={direct}
+iodepth={queue_depth}
+{sync}time_based
+group_reporting
+unlink=1
+
+[4K-randrw]
+rw=randrw
+rwmixread=50
+bs=4k
+size=100M
+numjobs=4
+runtime=10
+
+[64K-randrw]
+stonewall
+rw=randrw
+rwmixread=50
+bs=64k
+size=200M
+numjobs=2
+runtime=10
+
+[1M-rw]
+stonewall
+rw=rw
+rwmixread=50
+bs=1m
+size=500M
+numjobs=1
+runtime=15
+",
+        direct = mode.direct as u8,
+        queue_depth = mode.queue_depth.max(1),
+        sync = sync,
+    )
+}
+
+/// 在测试目录中运行 fio 配置，结束后删除任务留在目录中的文件
+pub fn run_profile(profile: &DiskProfile, test_dir: &Path, mode: DiskIoMode, cancel: &CancelToken) -> Result<Vec<FioJobResult>, String> {
+    let mut command = Command::new("fio");
+    command
+        .arg("--output-format=json")
+        .current_dir(test_dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    // 内置任务从标准输入读取
+    let stdin_job = match profile {
+        DiskProfile::Builtin => return Err("内置引擎不使用 fio".to_string()),
+        DiskProfile::FioStandard => {
+            command.arg("-").stdin(Stdio::piped());
+            Some(builtin_job_file(mode))
+        }
+        DiskProfile::FioFile { path, .. } => {
+            command.arg(path).stdin(Stdio::null());
+            None
+        }
+    };
+
+    // 先于子进程创建，返回（包括取消和 panic）时子进程已结束，再清理它留下的文件
+    let _cleanup = NewFilesGuard { dir: test_dir, existing: list_files(test_dir) };
+
+    let mut child = command.spawn().map_err(|e| format!("无法启动 fio: {}", e))?;
+    if let (Some(job), Some(mut stdin)) = (stdin_job, child.stdin.take()) {
+        if let Err(e) = stdin.write_all(job.as_bytes()) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("无法写入 fio 任务: {}", e));
+        }
+    }
+
+    // 在后台读取输出，避免管道写满时 fio 阻塞
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if cancel.is_cancelled() => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(Cancelled.to_string());
+            }
+            Ok(None) => thread::sleep(WAIT_POLL_INTERVAL),
+            Err(e) => {
+                let _ = child.kill();
+                return Err(format!("fio 运行失败: {}", e));
+            }
+        }
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    if !status.success() {
+        let stderr = String::from_utf8_lossy(&stderr);
+        let reason = stderr.lines().find(|line| !line.trim().is_empty()).unwrap_or("未知错误");
+        return Err(format!("fio 退出码 {}: {}", status.code().unwrap_or(-1), reason.trim()));
+    }
+
+    parse_json_output(&String::from_utf8_lossy(&stdout))
+}
+
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        output
+    })
+}
+
+/// 离开作用域时删除 fio 运行期间新出现的文件
+struct NewFilesGuard<'a> {
+    dir: &'a Path,
+    existing: HashSet<PathBuf>,
+}
+
+impl Drop for NewFilesGuard<'_> {
+    fn drop(&mut self) {
+        remove_new_files(self.dir, &self.existing);
+    }
+}
+
+fn list_files(dir: &Path) -> HashSet<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| entries.filter_map(|entry| entry.ok().map(|e| e.path())).collect())
+        .unwrap_or_default()
+}
+
+// 只删除运行期间新出现的普通文件，不动目录中原有的内容
+fn remove_new_files(dir: &Path, existing: &HashSet<PathBuf>) {
+    for path in list_files(dir) {
+        if !existing.contains(&path) && path.is_file() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_output() {
+        // fio 3.x 的输出（已删减），前面带一行警告
+        let output = r#"note: both iodepth >= 1 and synchronous I/O engine are selected, queue depth will be capped at 1
+{
+  "fio version" : "fio-3.33",
+  "jobs" : [
+    {
+      "jobname" : "4K-randrw",
+      "groupid" : 0,
+      "error" : 0,
+      "read" : {
+        "io_bytes" : 524288000, "bw_bytes" : 52428800, "bw" : 51200, "iops" : 12800.5,
+        "clat_ns" : {
+          "min" : 20000, "max" : 9000000, "mean" : 75000.0, "N" : 128005,
+          "percentile" : { "50.000000" : 60000, "95.000000" : 150000, "99.000000" : 400000, "99.900000" : 2000000 }
+        }
+      },
+      "write" : { "io_bytes" : 0, "bw_bytes" : 0, "bw" : 0, "iops" : 0.0 }
+    },
+    {
+      "jobname" : "1M-rw",
+      "groupid" : 1,
+      "error" : 0,
+      "write" : {
+        "io_bytes" : 1048576000, "bw" : 102400, "iops" : 100.0,
+        "clat" : { "min" : 900, "max" : 50000, "mean" : 9500.0, "N" : 1000, "percentile" : { "99.000000" : 30000 } }
+      }
+    }
+  ]
+}
+"#;
+        let jobs = parse_json_output(output).unwrap();
+        assert_eq!(jobs.len(), 2);
+
+        let read = jobs[0].read.as_ref().unwrap();
+        assert_eq!(read.mb_s(), 50.0);
+        assert_eq!(read.iops, 12800.5);
+        let latency = read.latency.as_ref().unwrap();
+        assert_eq!(latency.samples, 128005);
+        assert_eq!((latency.mean_us, latency.p50_us, latency.p99_us, latency.p999_us), (75.0, 60.0, 400.0, 2000.0));
+        assert_eq!(latency.max_us, 9000.0);
+        assert!(jobs[0].write.is_none());
+
+        // 旧版 fio：没有 bw_bytes，clat 单位为微秒
+        let write = jobs[1].write.as_ref().unwrap();
+        assert_eq!(write.mb_s(), 100.0);
+        assert_eq!(write.latency.as_ref().unwrap().p99_us, 30000.0);
+        assert!(jobs[1].read.is_none());
+    }
+
+    #[test]
+    fn test_builtin_job_bytes_match_job_file() {
+        let job_file = builtin_job_file(DiskIoMode::default());
+        for (size_mb, jobs) in BUILTIN_JOB_SIZES {
+            assert!(job_file.contains(&format!("size={}M\nnumjobs={}\n", size_mb, jobs)), "{}", job_file);
+        }
+        assert_eq!(builtin_job_bytes(), 1300 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_parse_json_output_errors() {
+        assert!(parse_json_output("fio: pid=0, err=2/file:filesetup.c").is_err());
+        let failed = r#"{"jobs": [{"jobname": "bad", "error": 5}]}"#;
+        assert_eq!(parse_json_output(failed).unwrap_err(), "fio 任务 bad 出错 (错误码 5)");
+    }
+}
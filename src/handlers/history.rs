@@ -0,0 +1,195 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::handlers::cpu_test::CpuTestInfo;
+use crate::handlers::disk_test::DiskTestInfo;
+use crate::handlers::memory_test::MemoryTestInfo;
+use crate::handlers::network_test::{NetworkProvider, NetworkTestInfo, TestStatus};
+use crate::handlers::system_info::SystemInfo;
+use crate::history::{self, RunKind};
+use crate::report::{CpuReport, DiskReport, HostReport, MemoryReport, NetworkReport, Report};
+
+// 全局刷新标志，用于通知UI更新
+static NEEDS_UI_REFRESH: AtomicBool = AtomicBool::new(false);
+
+// 历史记录缓存，写入新记录后失效
+static HISTORY_CACHE: Mutex<Option<Arc<HistoryData>>> = Mutex::new(None);
+
+// 最近一次写入失败的原因
+static LAST_RECORD_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+/// 已加载的历史记录
+#[derive(Debug, Default)]
+pub struct HistoryData {
+    pub path: Option<PathBuf>,
+    pub runs: Vec<Report>,  // 按写入顺序
+    pub errors: Vec<String>,
+}
+
+impl HistoryData {
+    /// 列表显示顺序：按主机分组，组内最新的在前
+    pub fn display_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.runs.len()).collect();
+        order.sort_by(|&a, &b| {
+            self.runs[a]
+                .host
+                .hostname
+                .cmp(&self.runs[b].host.hostname)
+                .then(b.cmp(&a))
+        });
+        order
+    }
+
+    pub fn find(&self, generated_at: &str) -> Option<usize> {
+        self.runs.iter().position(|run| run.generated_at == generated_at)
+    }
+}
+
+/// 获取历史记录（带缓存）
+pub fn get_history() -> Arc<HistoryData> {
+    let mut cache = HISTORY_CACHE.lock().unwrap();
+    if let Some(ref data) = *cache {
+        return data.clone();
+    }
+
+    let data = Arc::new(load_history());
+    *cache = Some(data.clone());
+    data
+}
+
+/// 丢弃缓存，下次访问时重新读取文件
+pub fn reload_history() {
+    *HISTORY_CACHE.lock().unwrap() = None;
+    NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+}
+
+fn load_history() -> HistoryData {
+    let path = history::history_path();
+    let mut data = HistoryData { path: path.clone(), ..Default::default() };
+
+    match path {
+        Some(path) => match history::load_from(&path) {
+            Ok((runs, errors)) => {
+                data.runs = runs;
+                data.errors = errors;
+            }
+            Err(e) => data.errors.push(e),
+        },
+        None => data.errors.push("无法确定数据目录 (HOME 未设置)".to_string()),
+    }
+
+    if let Some(ref e) = *LAST_RECORD_ERROR.lock().unwrap() {
+        data.errors.push(format!("保存记录失败: {}", e));
+    }
+
+    data
+}
+
+/// 保存一次测试结果
+fn record(report: Report) {
+    let result = history::history_path()
+        .ok_or_else(|| "无法确定数据目录 (HOME 未设置)".to_string())
+        .and_then(|path| history::append_to(&path, &report));
+
+    *LAST_RECORD_ERROR.lock().unwrap() = result.err();
+    reload_history();
+}
+
+fn new_report() -> Report {
+    Report::new(HostReport::from_system_info(&SystemInfo::get_current()))
+}
+
+/// 磁盘测试完成后调用，失败的测试不记录
+pub fn record_disk_run(info: &DiskTestInfo) {
+    if info.error_message.is_some() || info.results.is_empty() {
+        return;
+    }
+    let mut report = new_report();
+    report.disk = Some(DiskReport::from_test_info(info));
+    record(report);
+}
+
+/// CPU 测试完成后调用
+pub fn record_cpu_run(info: &CpuTestInfo) {
+    if info.error_message.is_some() || info.results.is_empty() {
+        return;
+    }
+    let mut report = new_report();
+    report.cpu = Some(CpuReport::from_test_info(info));
+    record(report);
+}
+
+/// 内存测试完成后调用
+pub fn record_memory_run(info: &MemoryTestInfo) {
+    if !info.is_complete() {
+        return;
+    }
+    let mut report = new_report();
+    report.memory = Some(MemoryReport::from_test_info(info));
+    record(report);
+}
+
+/// 网速测试完成后调用，全部服务器失败时不记录
+pub fn record_network_run(info: &NetworkTestInfo, providers: &[NetworkProvider]) {
+    if !info.results.values().any(|r| r.status == TestStatus::Completed) {
+        return;
+    }
+    let mut report = new_report();
+    report.network = Some(NetworkReport::from_test_info(info, providers));
+    record(report);
+}
+
+/// 选中记录的对比对象：指定的基准（可对比时）优先，否则为同主机上一次可对比的测试
+pub fn comparison_target(data: &HistoryData, index: usize, baseline: Option<&str>) -> Option<usize> {
+    let current = data.runs.get(index)?;
+    if let Some(base) = baseline.and_then(|b| data.find(b)) {
+        if base != index && history::comparable(&data.runs[base], current) {
+            return Some(base);
+        }
+    }
+    history::previous_run(&data.runs, index)
+}
+
+/// 格式化历史记录
+pub fn get_info() -> String {
+    let data = get_history();
+    let mut output = String::from("=== 历史记录 ===\n\n");
+
+    if let Some(ref path) = data.path {
+        output.push_str(&format!("文件: {}\n\n", path.display()));
+    }
+
+    let mut last_host = None;
+    for index in data.display_order() {
+        let run = &data.runs[index];
+        if last_host != Some(&run.host.hostname) {
+            output.push_str(&format!("--- {} ---\n", run.host.hostname));
+            last_host = Some(&run.host.hostname);
+        }
+        let kind = RunKind::of(run).map(|k| k.name()).unwrap_or("-");
+        output.push_str(&format!("{}  {:<4}  {}\n", format_time(&run.generated_at), kind, history::summary(run)));
+    }
+
+    if data.runs.is_empty() {
+        output.push_str("暂无记录，完成一次硬盘/CPU/网速测试后会自动保存。\n");
+    }
+
+    for error in &data.errors {
+        output.push_str(&format!("错误: {}\n", error));
+    }
+
+    output
+}
+
+/// 把 RFC 3339 时间格式化为本地时间
+pub fn format_time(generated_at: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(generated_at)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_else(|_| generated_at.to_string())
+}
+
+/// 检查是否需要刷新UI
+pub fn check_needs_refresh() -> bool {
+    NEEDS_UI_REFRESH.swap(false, Ordering::Relaxed)
+}
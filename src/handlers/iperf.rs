@@ -0,0 +1,352 @@
+// src/handlers/iperf.rs
+// iperf3 集成：以 --json 运行 iperf3 客户端测量到指定主机的点对点吞吐量，不依赖第三方测速服务器；
+// 另一台 VPS 上用本程序启动 iperf3 服务端作为对端
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::cancel::CancelToken;
+use super::network_test::split_host_port;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// iperf3 默认端口
+pub const DEFAULT_PORT: u16 = 5201;
+
+/// UDP 测试的默认发送速率（每个连接）
+pub const DEFAULT_UDP_BITRATE: &str = "100M";
+
+// 等待 iperf3 退出时检查取消请求、更新进度的间隔
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// iperf3 测试目标
+#[derive(Debug, Clone, PartialEq)]
+pub struct IperfTarget {
+    pub host: String,
+    pub port: u16,
+    pub udp_bitrate: String,  // iperf3 -b 的写法，如 100M
+}
+
+impl IperfTarget {
+    /// 解析 host、host:port 或 [v6]:port，缺省端口为 5201
+    pub fn parse(value: &str, udp_bitrate: Option<&str>) -> Result<Self, String> {
+        let (host, port) = split_host_port(value.trim(), DEFAULT_PORT)?;
+        let udp_bitrate = udp_bitrate.map(str::trim).unwrap_or(DEFAULT_UDP_BITRATE).to_string();
+        if !is_valid_bitrate(&udp_bitrate) {
+            return Err(format!("UDP 速率必须是数字加可选的 K/M/G 单位，如 100M，而不是 \"{}\"", udp_bitrate));
+        }
+        Ok(Self { host, port, udp_bitrate })
+    }
+
+    /// host:port，IPv6 地址加方括号
+    pub fn label(&self) -> String {
+        if self.host.contains(':') {
+            format!("[{}]:{}", self.host, self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+}
+
+fn is_valid_bitrate(value: &str) -> bool {
+    let digits = value.strip_suffix(['K', 'M', 'G', 'k', 'm', 'g']).unwrap_or(value);
+    !digits.is_empty() && digits.parse::<f64>().is_ok_and(|rate| rate > 0.0)
+}
+
+/// 一次 iperf3 测试的协议和方向，下载方向由服务端发送（-R）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IperfTest {
+    TcpDownload,
+    TcpUpload,
+    UdpDownload,
+}
+
+impl IperfTest {
+    pub fn name(&self) -> &'static str {
+        match self {
+            IperfTest::TcpDownload => "TCP 下载",
+            IperfTest::TcpUpload => "TCP 上传",
+            IperfTest::UdpDownload => "UDP 下载",
+        }
+    }
+
+    fn args(&self, target: &IperfTarget) -> Vec<String> {
+        match self {
+            IperfTest::TcpDownload => vec!["-R".to_string()],
+            IperfTest::TcpUpload => Vec::new(),
+            IperfTest::UdpDownload => vec!["-R".to_string(), "-u".to_string(), "-b".to_string(), target.udp_bitrate.clone()],
+        }
+    }
+}
+
+/// 一次测试的结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct IperfSummary {
+    pub mbps: f64,  // 接收端吞吐量
+    /// TCP 发送端的重传次数，发送端不是 Linux 时没有
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retransmits: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jitter_ms: Option<f64>,  // UDP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lost_pct: Option<f64>,   // UDP
+}
+
+/// 网速测试中 iperf3 节点的补充结果，吞吐量记入 SpeedTestResult 的下载/上传速度
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct IperfStats {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_retransmits: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_retransmits: Option<u64>,
+    /// UDP 下载的吞吐量、抖动和丢包率
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub udp: Option<IperfSummary>,
+    /// UDP 测试失败（例如防火墙只放行 TCP）不影响 TCP 结果
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub udp_error: Option<String>,
+}
+
+impl IperfStats {
+    /// 例如 "重传 下载 37 / 上传 5 | UDP 99.8 Mbps 抖动 0.08 ms 丢包 0.10%"
+    pub fn summary(&self) -> String {
+        let count = |value: Option<u64>| value.map(|v| v.to_string()).unwrap_or_else(|| "-".to_string());
+        let udp = match (&self.udp, &self.udp_error) {
+            (Some(udp), _) => format!(
+                "UDP {:.1} Mbps 抖动 {:.2} ms 丢包 {:.2}%",
+                udp.mbps,
+                udp.jitter_ms.unwrap_or(0.0),
+                udp.lost_pct.unwrap_or(0.0)
+            ),
+            (None, Some(error)) => format!("UDP 失败: {}", error),
+            (None, None) => "UDP -".to_string(),
+        };
+        format!(
+            "重传 下载 {} / 上传 {} | {}",
+            count(self.download_retransmits),
+            count(self.upload_retransmits),
+            udp
+        )
+    }
+}
+
+// iperf3 --json 的输出，只取用到的字段
+#[derive(Deserialize)]
+struct IperfOutput {
+    #[serde(default)]
+    end: IperfEnd,
+    error: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+struct IperfEnd {
+    sum_sent: Option<IperfSum>,
+    sum_received: Option<IperfSum>,
+    sum: Option<IperfSum>,  // UDP
+}
+
+#[derive(Deserialize)]
+struct IperfSum {
+    #[serde(default)]
+    bits_per_second: f64,
+    retransmits: Option<u64>,
+    jitter_ms: Option<f64>,
+    lost_percent: Option<f64>,
+}
+
+/// 解析 iperf3 --json 的输出；出错时 iperf3 同样输出 JSON，错误信息在 error 字段
+pub fn parse_json_output(output: &str) -> Result<IperfSummary, String> {
+    let start = output.find('{').ok_or("iperf3 没有输出 JSON 结果")?;
+    let mut deserializer = serde_json::Deserializer::from_str(&output[start..]);
+    let parsed = IperfOutput::deserialize(&mut deserializer).map_err(|e| format!("iperf3 输出解析失败: {}", e))?;
+
+    if let Some(error) = parsed.error {
+        return Err(format!("iperf3: {}", error));
+    }
+
+    let end = parsed.end;
+    // UDP 的丢包和抖动由接收端统计，旧版本只在 sum 中给出
+    let udp = end.sum.as_ref().filter(|sum| sum.lost_percent.is_some());
+    let received = end.sum_received.as_ref().or(udp).ok_or("iperf3 输出中没有测试结果")?;
+    Ok(IperfSummary {
+        mbps: received.bits_per_second / 1_000_000.0,
+        retransmits: end.sum_sent.as_ref().and_then(|sum| sum.retransmits).filter(|_| udp.is_none()),
+        jitter_ms: udp.and_then(|sum| sum.jitter_ms),
+        lost_pct: udp.and_then(|sum| sum.lost_percent),
+    })
+}
+
+/// 运行一次 iperf3 客户端测试：测量 duration，另加 warmup 的预热（-O，不计入结果），streams 个并行连接
+pub async fn run_client<F>(
+    target: &IperfTarget,
+    test: IperfTest,
+    warmup: Duration,
+    duration: Duration,
+    streams: usize,
+    cancel: &CancelToken,
+    on_progress: F,
+) -> Result<IperfSummary, BoxError>
+where
+    F: Fn(f64),
+{
+    let mut command = tokio::process::Command::new("iperf3");
+    command
+        .args(["-c", &target.host, "-p", &target.port.to_string(), "--json"])
+        .args(["-t", &duration.as_secs().max(1).to_string()])
+        .args(["-O", &warmup.as_secs().to_string()])
+        .args(["-P", &streams.max(1).to_string()])
+        .args(test.args(target))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        // 取消时丢弃等待中的 future，子进程随之被结束
+        .kill_on_drop(true);
+
+    let child = command.spawn().map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => "未安装 iperf3".to_string(),
+        _ => format!("无法启动 iperf3: {}", e),
+    })?;
+
+    let start = Instant::now();
+    let total = (warmup + duration).as_secs_f64();
+    let output = child.wait_with_output();
+    tokio::pin!(output);
+    let mut ticker = tokio::time::interval(WAIT_POLL_INTERVAL);
+    let output = loop {
+        tokio::select! {
+            result = &mut output => break result.map_err(|e| format!("iperf3 运行失败: {}", e))?,
+            _ = ticker.tick() => {
+                cancel.check()?;
+                on_progress((start.elapsed().as_secs_f64() / total).min(1.0));
+            }
+        }
+    };
+
+    match parse_json_output(&String::from_utf8_lossy(&output.stdout)) {
+        Ok(summary) => Ok(summary),
+        Err(e) if output.status.success() => Err(e.into()),
+        // 没有 JSON 输出时（例如参数不被支持）取 stderr 的第一行
+        Err(e) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            match stderr.lines().find(|line| !line.trim().is_empty()) {
+                Some(reason) if !e.starts_with("iperf3: ") => Err(format!("iperf3 退出码 {}: {}", output.status.code().unwrap_or(-1), reason.trim()).into()),
+                _ => Err(e.into()),
+            }
+        }
+    }
+}
+
+// 本机后台运行的 iperf3 服务端及其端口
+static SERVER: Mutex<Option<(Child, u16)>> = Mutex::new(None);
+
+/// 本机 iperf3 服务端的命令，命令行模式在前台运行
+pub fn server_command(port: u16) -> Command {
+    let mut command = Command::new("iperf3");
+    command.args(["-s", "-p", &port.to_string()]);
+    command
+}
+
+/// 正在运行的 iperf3 服务端端口，服务端已退出时返回 None
+pub fn server_port() -> Option<u16> {
+    let mut server = SERVER.lock().ok()?;
+    let exited = match server.as_mut() {
+        Some((child, _)) => !matches!(child.try_wait(), Ok(None)),
+        None => return None,
+    };
+    if exited {
+        *server = None;
+    }
+    server.as_ref().map(|(_, port)| *port)
+}
+
+/// 启动或停止后台的 iperf3 服务端，返回启动后的端口，停止时返回 None
+pub fn toggle_server(port: u16) -> Result<Option<u16>, String> {
+    if server_port().is_some() {
+        stop_server();
+        return Ok(None);
+    }
+
+    let mut child = server_command(port)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => "未安装 iperf3，无法启动服务端".to_string(),
+            _ => format!("无法启动 iperf3 服务端: {}", e),
+        })?;
+
+    // 端口被占用等错误会让 iperf3 立即退出
+    std::thread::sleep(Duration::from_millis(300));
+    if let Ok(Some(status)) = child.try_wait() {
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            let _ = io::Read::read_to_string(&mut pipe, &mut stderr);
+        }
+        let reason = stderr.lines().find(|line| !line.trim().is_empty()).unwrap_or("未知错误");
+        return Err(format!("iperf3 服务端退出 (退出码 {}): {}", status.code().unwrap_or(-1), reason.trim()));
+    }
+
+    // 之后不再读取 stderr，关闭管道避免写满后阻塞
+    drop(child.stderr.take());
+    if let Ok(mut server) = SERVER.lock() {
+        *server = Some((child, port));
+    }
+    Ok(Some(port))
+}
+
+/// 停止后台的 iperf3 服务端，退出界面时调用
+pub fn stop_server() {
+    if let Some((mut child, _)) = SERVER.lock().ok().and_then(|mut server| server.take()) {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_output() {
+        let tcp = r#"{
+            "start": {"test_start": {"protocol": "TCP", "num_streams": 4, "reverse": 1}},
+            "intervals": [],
+            "end": {
+                "streams": [],
+                "sum_sent": {"start": 0, "end": 10.0, "seconds": 10.0, "bytes": 1180000000, "bits_per_second": 944000000.0, "retransmits": 37, "sender": true},
+                "sum_received": {"start": 0, "end": 10.04, "seconds": 10.04, "bytes": 1170000000, "bits_per_second": 932500000.0, "sender": true}
+            }
+        }"#;
+        let summary = parse_json_output(tcp).unwrap();
+        assert!((summary.mbps - 932.5).abs() < 1e-9);
+        assert_eq!(summary.retransmits, Some(37));
+        assert_eq!((summary.jitter_ms, summary.lost_pct), (None, None));
+
+        // 旧版本的 UDP 结果只有 sum
+        let udp = r#"warning: UDP block size 1460 exceeds TCP MSS 1448, may result in fragmentation / drops
+        {
+            "start": {"test_start": {"protocol": "UDP"}},
+            "end": {
+                "sum": {"start": 0, "end": 10, "seconds": 10, "bytes": 125000000, "bits_per_second": 99800000.0,
+                        "jitter_ms": 0.083, "lost_packets": 86, "packets": 85600, "lost_percent": 0.1005, "sender": true}
+            }
+        }"#;
+        let summary = parse_json_output(udp).unwrap();
+        assert!((summary.mbps - 99.8).abs() < 1e-9);
+        assert_eq!(summary.retransmits, None);
+        assert_eq!((summary.jitter_ms, summary.lost_pct), (Some(0.083), Some(0.1005)));
+
+        let refused = r#"{"start": {"connected": []}, "intervals": [], "end": {}, "error": "unable to connect to server: Connection refused"}"#;
+        assert_eq!(parse_json_output(refused).unwrap_err(), "iperf3: unable to connect to server: Connection refused");
+        assert!(parse_json_output(r#"{"end": {}}"#).is_err());
+        assert!(parse_json_output("iperf3: error").is_err());
+
+        let target = IperfTarget::parse("[2001:db8::1]", None).unwrap();
+        assert_eq!((target.port, target.label().as_str()), (DEFAULT_PORT, "[2001:db8::1]:5201"));
+        assert!(IperfTarget::parse("vps.example.com:9000", Some("1.5G")).is_ok());
+        assert!(IperfTarget::parse("vps.example.com", Some("fast")).is_err());
+    }
+}
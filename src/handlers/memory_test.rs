@@ -0,0 +1,645 @@
+// src/handlers/memory_test.rs
+// 内存测试：STREAM 风格的 copy/scale/add/triad 带宽，以及不同工作集大小下的指针追逐延迟
+// 延迟随工作集增大依次落在 L1、L2、L3 和内存上，曲线上的台阶对应各级缓存的容量
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Instant;
+
+use crate::cancel::{catch_panic, CancelToken, TestControl};
+use super::cpu_score;
+
+/// STREAM 每个数组的大小范围：至少为末级缓存的 4 倍，受可用内存限制
+const MIN_ARRAY_BYTES: usize = 8 * 1024 * 1024;
+const MAX_ARRAY_BYTES: usize = 128 * 1024 * 1024;
+// 未检测到 L3 时按 32 MiB 估计
+const DEFAULT_LLC_BYTES: usize = 32 * 1024 * 1024;
+
+/// 延迟测试的工作集从 4 KiB 开始逐级翻倍
+const MIN_WORKING_SET: usize = 4 * 1024;
+const MAX_WORKING_SET: usize = 1024 * 1024 * 1024;
+
+// 每个节点占一个缓存行，相邻访问不会落在同一行上
+const CACHE_LINE: usize = 64;
+const NODE_STRIDE: usize = CACHE_LINE / std::mem::size_of::<usize>();
+
+/// 每次计时的指针追逐步数
+const CHASE_STEPS: usize = 1 << 21;
+
+/// 带宽和延迟各自正式计时的次数，取中位数
+const STREAM_RUNS: usize = 5;
+const LATENCY_RUNS: usize = 3;
+
+// STREAM 的标量和数组初值
+const SCALAR: f64 = 3.0;
+
+// 并行时每个线程每次处理的元素数
+const PAR_CHUNK: usize = 64 * 1024;
+
+/// STREAM 的四个内核
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamKernel {
+    Copy,
+    Scale,
+    Add,
+    Triad,
+}
+
+impl StreamKernel {
+    pub const ALL: [StreamKernel; 4] = [StreamKernel::Copy, StreamKernel::Scale, StreamKernel::Add, StreamKernel::Triad];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            StreamKernel::Copy => "Copy",
+            StreamKernel::Scale => "Scale",
+            StreamKernel::Add => "Add",
+            StreamKernel::Triad => "Triad",
+        }
+    }
+
+    pub fn formula(&self) -> &'static str {
+        match self {
+            StreamKernel::Copy => "c = a",
+            StreamKernel::Scale => "b = s·c",
+            StreamKernel::Add => "c = a + b",
+            StreamKernel::Triad => "a = b + s·c",
+        }
+    }
+
+    // 每个元素读写的数组个数，与 STREAM 的计数方式一致
+    fn arrays_touched(&self) -> usize {
+        match self {
+            StreamKernel::Copy | StreamKernel::Scale => 2,
+            StreamKernel::Add | StreamKernel::Triad => 3,
+        }
+    }
+
+    fn apply(&self, a: &mut [f64], b: &mut [f64], c: &mut [f64]) {
+        match self {
+            StreamKernel::Copy => c.copy_from_slice(a),
+            StreamKernel::Scale => {
+                for (b, c) in b.iter_mut().zip(c.iter()) {
+                    *b = SCALAR * c;
+                }
+            }
+            StreamKernel::Add => {
+                for ((c, a), b) in c.iter_mut().zip(a.iter()).zip(b.iter()) {
+                    *c = a + b;
+                }
+            }
+            StreamKernel::Triad => {
+                for ((a, b), c) in a.iter_mut().zip(b.iter()).zip(c.iter()) {
+                    *a = b + SCALAR * c;
+                }
+            }
+        }
+    }
+}
+
+/// 存储层级
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MemoryLevel {
+    L1,
+    L2,
+    L3,
+    Dram,
+}
+
+impl MemoryLevel {
+    pub const ALL: [MemoryLevel; 4] = [MemoryLevel::L1, MemoryLevel::L2, MemoryLevel::L3, MemoryLevel::Dram];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MemoryLevel::L1 => "L1",
+            MemoryLevel::L2 => "L2",
+            MemoryLevel::L3 => "L3",
+            MemoryLevel::Dram => "内存",
+        }
+    }
+}
+
+/// 各级数据缓存容量（字节），读不到的为 None
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheSizes {
+    pub l1d_bytes: Option<usize>,
+    pub l2_bytes: Option<usize>,
+    pub l3_bytes: Option<usize>,
+}
+
+impl CacheSizes {
+    /// 读取 cpu0 的缓存信息，L3 为所在 CPU 组共享的总容量
+    pub fn detect() -> Self {
+        let Ok(entries) = fs::read_dir("/sys/devices/system/cpu/cpu0/cache") else {
+            return Self::default();
+        };
+        let mut sizes = Self::default();
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let read = |name: &str| fs::read_to_string(entry.path().join(name)).ok();
+            if let (Some(level), Some(kind), Some(size)) = (read("level"), read("type"), read("size")) {
+                sizes.insert(level.trim(), kind.trim(), size.trim());
+            }
+        }
+        sizes
+    }
+
+    // 记录一个 index* 目录的内容，指令缓存不计入
+    fn insert(&mut self, level: &str, kind: &str, size: &str) {
+        if kind == "Instruction" {
+            return;
+        }
+        let size = parse_cache_size(size);
+        match level {
+            "1" => self.l1d_bytes = size,
+            "2" => self.l2_bytes = size,
+            "3" => self.l3_bytes = size,
+            _ => {}
+        }
+    }
+
+    /// 工作集能完整放下的最小一级，没有任何缓存信息时返回 None
+    pub fn level_of(&self, bytes: usize) -> Option<MemoryLevel> {
+        let levels = [
+            (MemoryLevel::L1, self.l1d_bytes),
+            (MemoryLevel::L2, self.l2_bytes),
+            (MemoryLevel::L3, self.l3_bytes),
+        ];
+        if levels.iter().all(|(_, size)| size.is_none()) {
+            return None;
+        }
+        levels
+            .iter()
+            .find(|(_, size)| size.is_some_and(|size| bytes <= size))
+            .map(|(level, _)| *level)
+            .or(Some(MemoryLevel::Dram))
+    }
+
+    /// 例如 "L1d 48K / L2 2M / L3 300M"
+    pub fn label(&self) -> String {
+        let size = |bytes: Option<usize>| bytes.map(format_size).unwrap_or_else(|| "?".to_string());
+        format!("L1d {} / L2 {} / L3 {}", size(self.l1d_bytes), size(self.l2_bytes), size(self.l3_bytes))
+    }
+}
+
+// 解析 "48K"、"2048K"、"8M" 这样的容量
+fn parse_cache_size(size: &str) -> Option<usize> {
+    let (digits, unit) = match size.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => size.split_at(index),
+        None => (size, ""),
+    };
+    let multiplier = match unit {
+        "" => 1,
+        "K" => 1024,
+        "M" => 1024 * 1024,
+        "G" => 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    digits.parse::<usize>().ok().map(|n| n * multiplier)
+}
+
+/// 按二进制单位格式化容量，例如 4K、256M、1G
+pub fn format_size(bytes: usize) -> String {
+    const UNITS: [(usize, &str); 3] = [(1024 * 1024 * 1024, "G"), (1024 * 1024, "M"), (1024, "K")];
+    for (unit, suffix) in UNITS {
+        if bytes >= unit {
+            let value = bytes as f64 / unit as f64;
+            return if value.fract() == 0.0 {
+                format!("{}{}", value, suffix)
+            } else {
+                format!("{:.1}{}", value, suffix)
+            };
+        }
+    }
+    format!("{}B", bytes)
+}
+
+/// 一个 STREAM 内核的带宽
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BandwidthResult {
+    pub kernel: String,
+    pub single_mb_s: f64,
+    pub multi_mb_s: f64,
+}
+
+/// 一个工作集大小下每次访问的平均延迟
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LatencyPoint {
+    pub size_bytes: usize,
+    pub latency_ns: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<MemoryLevel>,
+}
+
+/// 每个层级的代表延迟：落在该层级的各点延迟的中位数
+pub fn level_latencies(points: &[LatencyPoint]) -> Vec<(MemoryLevel, f64)> {
+    MemoryLevel::ALL
+        .iter()
+        .filter_map(|&level| {
+            let mut values: Vec<f64> = points.iter().filter(|p| p.level == Some(level)).map(|p| p.latency_ns).collect();
+            values.sort_by(|a, b| a.total_cmp(b));
+            values.get(values.len() / 2).map(|&median| (level, median))
+        })
+        .collect()
+}
+
+// 内存测试信息状态
+#[derive(Debug, Clone)]
+pub struct MemoryTestInfo {
+    pub is_testing: bool,
+    pub current_test: String,
+    pub progress: u8,  // 0-100
+    pub error_message: Option<String>,
+    pub test_start_time: Option<Instant>,
+    pub threads: usize,  // 多线程带宽测试使用的线程数
+    pub array_bytes: usize,  // STREAM 每个数组的大小
+    pub working_sets: Vec<usize>,  // 延迟测试的工作集大小
+    pub cache: CacheSizes,
+    pub bandwidth: Vec<BandwidthResult>,
+    pub latency: Vec<LatencyPoint>,
+}
+
+impl Default for MemoryTestInfo {
+    fn default() -> Self {
+        let cache = CacheSizes::detect();
+        let available = available_memory_bytes();
+        Self {
+            is_testing: false,
+            current_test: String::new(),
+            progress: 0,
+            error_message: None,
+            test_start_time: None,
+            threads: num_cpus::get(),
+            array_bytes: stream_array_bytes(&cache, available),
+            working_sets: working_sets(&cache, available),
+            cache,
+            bandwidth: Vec::new(),
+            latency: Vec::new(),
+        }
+    }
+}
+
+impl MemoryTestInfo {
+    /// 测试已完成且有结果
+    pub fn is_complete(&self) -> bool {
+        !self.is_testing && self.error_message.is_none() && !self.bandwidth.is_empty() && !self.latency.is_empty()
+    }
+}
+
+// 全局状态管理
+static MEMORY_TEST_INFO: Mutex<Option<MemoryTestInfo>> = Mutex::new(None);
+static NEEDS_UI_REFRESH: AtomicBool = AtomicBool::new(false);
+static CONTROL: TestControl = TestControl::new();
+
+/// 获取内存测试信息
+pub fn get_info() -> String {
+    format_memory_test_info(&get_current_test_info())
+}
+
+/// 获取当前内存测试状态，尚未开始时返回准备状态
+pub fn get_current_test_info() -> MemoryTestInfo {
+    if let Ok(mut global_info) = MEMORY_TEST_INFO.lock() {
+        // 首次创建准备状态，缓存大小和可用内存只读取一次
+        return global_info.get_or_insert_with(MemoryTestInfo::default).clone();
+    }
+    MemoryTestInfo::default()
+}
+
+/// 开始内存测试，上一次的结果会被清除
+pub fn start_memory_test() -> Result<(), String> {
+    let Some(run) = CONTROL.try_begin() else {
+        return Err("内存测试进行中，完成或按 X 取消后才能重新测试".to_string());
+    };
+
+    let info = MemoryTestInfo { is_testing: true, test_start_time: Some(Instant::now()), ..Default::default() };
+    let (array_bytes, working_sets, threads, cache) = (info.array_bytes, info.working_sets.clone(), info.threads, info.cache);
+    if let Ok(mut global_info) = MEMORY_TEST_INFO.lock() {
+        *global_info = Some(info);
+    }
+
+    thread::spawn(move || {
+        let result = catch_panic(|| run_memory_tests(array_bytes, &working_sets, threads, &cache, &run)).and_then(|result| result);
+        finish(result);
+    });
+    Ok(())
+}
+
+/// 取消/暂停正在运行的内存测试
+pub fn test_control() -> &'static TestControl {
+    &CONTROL
+}
+
+/// 检查是否需要刷新UI
+pub fn check_needs_refresh() -> bool {
+    NEEDS_UI_REFRESH.swap(false, Ordering::Relaxed)
+}
+
+// 内部函数实现
+
+fn run_memory_tests(
+    array_bytes: usize,
+    working_sets: &[usize],
+    threads: usize,
+    cache: &CacheSizes,
+    cancel: &CancelToken,
+) -> Result<(), String> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .map_err(|e| format!("创建线程池失败: {}", e))?;
+
+    // 带宽测试占前一半进度，延迟测试占后一半
+    let elements = array_bytes / std::mem::size_of::<f64>();
+    let mut arrays = StreamArrays::new(elements, &pool);
+    let steps = StreamKernel::ALL.len() * 2;
+    for (index, kernel) in StreamKernel::ALL.iter().enumerate() {
+        checkpoint(cancel)?;
+        update_status(&format!("STREAM {} 单线程", kernel.name()), (index * 2 * 50 / steps) as u8);
+        let single_mb_s = arrays.bandwidth(*kernel, None);
+        checkpoint(cancel)?;
+        update_status(&format!("STREAM {} {} 线程", kernel.name(), threads), ((index * 2 + 1) * 50 / steps) as u8);
+        let multi_mb_s = arrays.bandwidth(*kernel, Some(&pool));
+        push_bandwidth(BandwidthResult { kernel: kernel.name().to_string(), single_mb_s, multi_mb_s });
+    }
+    drop(arrays);
+
+    for (index, &size) in working_sets.iter().enumerate() {
+        checkpoint(cancel)?;
+        update_status(
+            &format!("延迟 {} 工作集", format_size(size)),
+            (50 + index * 50 / working_sets.len()) as u8,
+        );
+        let latency_ns = chase_latency_ns(size);
+        push_latency(LatencyPoint { size_bytes: size, latency_ns, level: cache.level_of(size) });
+    }
+    Ok(())
+}
+
+// 测试之间检查暂停和取消，暂停期间在界面上显示提示
+fn checkpoint(cancel: &CancelToken) -> Result<(), String> {
+    if cancel.is_paused() {
+        update_status("已暂停，按空格继续", get_current_test_info().progress);
+    }
+    cancel.checkpoint().map_err(|e| e.to_string())
+}
+
+/// STREAM 的三个数组
+struct StreamArrays {
+    a: Vec<f64>,
+    b: Vec<f64>,
+    c: Vec<f64>,
+}
+
+impl StreamArrays {
+    // 在线程池中初始化，多路服务器上每段内存分配在之后访问它的线程所在的节点
+    fn new(elements: usize, pool: &rayon::ThreadPool) -> Self {
+        let init = |value: f64| pool.install(|| (0..elements).into_par_iter().map(|_| value).collect::<Vec<f64>>());
+        Self { a: init(1.0), b: init(2.0), c: init(0.0) }
+    }
+
+    // 运行 kernel 并返回带宽 (MB/s)，pool 为 None 时在当前线程上运行
+    fn bandwidth(&mut self, kernel: StreamKernel, pool: Option<&rayon::ThreadPool>) -> f64 {
+        let bytes = kernel.arrays_touched() * self.a.len() * std::mem::size_of::<f64>();
+        let measurement = cpu_score::measure(STREAM_RUNS, || {
+            let (a, b, c) = (&mut self.a, &mut self.b, &mut self.c);
+            match pool {
+                None => kernel.apply(a, b, c),
+                Some(pool) => pool.install(|| {
+                    a.par_chunks_mut(PAR_CHUNK)
+                        .zip(b.par_chunks_mut(PAR_CHUNK))
+                        .zip(c.par_chunks_mut(PAR_CHUNK))
+                        .for_each(|((a, b), c)| kernel.apply(a, b, c));
+                }),
+            }
+            black_box((&self.a, &self.b, &self.c));
+        });
+        if measurement.median_ms > 0.0 {
+            bytes as f64 / (1024.0 * 1024.0) / (measurement.median_ms / 1000.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 工作集为 size 字节时，每次依赖访问的平均延迟 (ns)
+///
+/// 节点按随机单环顺序相连，每次访问的地址取决于上一次读到的值，无法被预取或并行；
+/// 大工作集的结果包含 TLB 未命中的开销
+pub fn chase_latency_ns(size: usize) -> f64 {
+    let buffer = chase_buffer(size / CACHE_LINE);
+    let measurement = cpu_score::measure(LATENCY_RUNS, || {
+        black_box(chase(&buffer, CHASE_STEPS));
+    });
+    measurement.median_ms * 1_000_000.0 / CHASE_STEPS as f64
+}
+
+/// nodes 个节点组成一个随机单环，节点 i 位于下标 i * NODE_STRIDE，存放下一个节点的下标
+///
+/// 使用 Sattolo 算法打乱，保证从任意节点出发都会走遍全部节点
+fn chase_buffer(nodes: usize) -> Vec<usize> {
+    let nodes = nodes.max(1);
+    let mut buffer = vec![0usize; nodes * NODE_STRIDE];
+    for i in 0..nodes {
+        buffer[i * NODE_STRIDE] = i * NODE_STRIDE;
+    }
+    // 固定种子的 xorshift，每次运行的访问顺序相同
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    for i in (1..nodes).rev() {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        let j = (state % i as u64) as usize;
+        buffer.swap(i * NODE_STRIDE, j * NODE_STRIDE);
+    }
+    buffer
+}
+
+fn chase(buffer: &[usize], steps: usize) -> usize {
+    let mut index = 0;
+    for _ in 0..steps {
+        index = buffer[index];
+    }
+    index
+}
+
+/// STREAM 数组大小：末级缓存的 4 倍，在 MIN_ARRAY_BYTES 与 MAX_ARRAY_BYTES 之间，三个数组最多占可用内存的一半
+fn stream_array_bytes(cache: &CacheSizes, available: Option<usize>) -> usize {
+    let wanted = cache.l3_bytes.or(cache.l2_bytes).unwrap_or(DEFAULT_LLC_BYTES).saturating_mul(4);
+    let limit = available.map(|bytes| bytes / 6).unwrap_or(MAX_ARRAY_BYTES);
+    wanted.clamp(MIN_ARRAY_BYTES, MAX_ARRAY_BYTES).min(limit).max(MIN_ARRAY_BYTES)
+}
+
+/// 延迟测试的工作集：从 4 KiB 翻倍到末级缓存的 2 倍以上，最多占可用内存的 1/4
+fn working_sets(cache: &CacheSizes, available: Option<usize>) -> Vec<usize> {
+    let llc = cache.l3_bytes.or(cache.l2_bytes).unwrap_or(DEFAULT_LLC_BYTES);
+    let mut max = llc.saturating_mul(2).next_power_of_two().clamp(64 * 1024 * 1024, MAX_WORKING_SET);
+    if let Some(available) = available {
+        max = max.min(available / 4);
+    }
+    std::iter::successors(Some(MIN_WORKING_SET), |size| Some(size * 2))
+        .take_while(|&size| size <= max.max(MIN_WORKING_SET))
+        .collect()
+}
+
+// /proc/meminfo 中的 MemAvailable
+fn available_memory_bytes() -> Option<usize> {
+    let meminfo = fs::read_to_string("/proc/meminfo").ok()?;
+    let line = meminfo.lines().find(|line| line.starts_with("MemAvailable:"))?;
+    let kib: usize = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+fn update_status(current_test: &str, progress: u8) {
+    if let Ok(mut global_info) = MEMORY_TEST_INFO.lock() {
+        if let Some(ref mut info) = global_info.as_mut() {
+            info.current_test = current_test.to_string();
+            info.progress = progress.min(100);
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+fn push_bandwidth(result: BandwidthResult) {
+    if let Ok(mut global_info) = MEMORY_TEST_INFO.lock() {
+        if let Some(ref mut info) = global_info.as_mut() {
+            info.bandwidth.push(result);
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+fn push_latency(point: LatencyPoint) {
+    if let Ok(mut global_info) = MEMORY_TEST_INFO.lock() {
+        if let Some(ref mut info) = global_info.as_mut() {
+            info.latency.push(point);
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+// 测试结束：成功时写入历史记录，取消或失败时保留已完成的部分结果
+fn finish(result: Result<(), String>) {
+    if let Ok(mut global_info) = MEMORY_TEST_INFO.lock() {
+        if let Some(ref mut info) = global_info.as_mut() {
+            info.is_testing = false;
+            match result {
+                Ok(()) => {
+                    info.current_test = "测试完成".to_string();
+                    info.progress = 100;
+                }
+                Err(ref e) => {
+                    info.current_test = e.clone();
+                    info.error_message = Some(e.clone());
+                }
+            }
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+    if result.is_ok() {
+        super::history::record_memory_run(&get_current_test_info());
+    }
+}
+
+fn format_memory_test_info(info: &MemoryTestInfo) -> String {
+    let mut output = String::new();
+    output.push_str("━━━━━━━━━━━━━━━━ 内存测试 ━━━━━━━━━━━━━━━━\n");
+    output.push_str(&format!("缓存: {}\n", info.cache.label()));
+    output.push_str(&format!(
+        "STREAM 数组: 3 × {}, 多线程 {} 线程\n",
+        format_size(info.array_bytes),
+        info.threads
+    ));
+    if let Some(ref error) = info.error_message {
+        output.push_str(&format!("错误: {}\n", error));
+    }
+
+    if !info.bandwidth.is_empty() {
+        output.push_str("\n━━━━━━━━━━━━━━━━ 内存带宽 ━━━━━━━━━━━━━━━━\n");
+        // 中文字符占两列，表头直接按列宽写出
+        output.push_str("内核    公式                  单线程        多线程\n");
+        for result in &info.bandwidth {
+            let formula = StreamKernel::ALL
+                .iter()
+                .find(|kernel| kernel.name() == result.kernel)
+                .map(|kernel| kernel.formula())
+                .unwrap_or("");
+            output.push_str(&format!(
+                "{:<8}{:<14}{:>9.0} MB/s{:>9.0} MB/s\n",
+                result.kernel, formula, result.single_mb_s, result.multi_mb_s
+            ));
+        }
+    }
+
+    if !info.latency.is_empty() {
+        output.push_str("\n━━━━━━━━━━━━━━━━ 访问延迟 ━━━━━━━━━━━━━━━━\n");
+        for point in &info.latency {
+            output.push_str(&format!(
+                "{:>6}: {:>7.1} ns  {}\n",
+                format_size(point.size_bytes),
+                point.latency_ns,
+                point.level.map(|level| level.name()).unwrap_or("")
+            ));
+        }
+        let levels = level_latencies(&info.latency);
+        if !levels.is_empty() {
+            let summary: Vec<String> =
+                levels.iter().map(|(level, ns)| format!("{} {:.1} ns", level.name(), ns)).collect();
+            output.push_str(&format!("各级延迟: {}\n", summary.join(" / ")));
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chase_buffer_is_single_cycle() {
+        for nodes in [1, 2, 7, 1000] {
+            let buffer = chase_buffer(nodes);
+            let mut index = 0;
+            let mut visited = vec![false; nodes];
+            for _ in 0..nodes {
+                assert!(!visited[index / NODE_STRIDE], "nodes = {}", nodes);
+                visited[index / NODE_STRIDE] = true;
+                index = buffer[index];
+            }
+            assert_eq!(index, 0);
+            assert!(visited.iter().all(|&v| v));
+        }
+        assert!(chase_latency_ns(16 * 1024) > 0.0);
+    }
+
+    #[test]
+    fn test_cache_sizes_and_levels() {
+        let mut cache = CacheSizes::default();
+        assert_eq!(cache.level_of(4096), None);
+        cache.insert("1", "Data", "48K");
+        cache.insert("1", "Instruction", "32K");
+        cache.insert("2", "Unified", "2048K");
+        cache.insert("3", "Unified", "32M");
+        assert_eq!(cache, CacheSizes { l1d_bytes: Some(48 * 1024), l2_bytes: Some(2 << 20), l3_bytes: Some(32 << 20) });
+        assert_eq!(cache.level_of(32 * 1024), Some(MemoryLevel::L1));
+        assert_eq!(cache.level_of(64 * 1024), Some(MemoryLevel::L2));
+        assert_eq!(cache.level_of(32 << 20), Some(MemoryLevel::L3));
+        assert_eq!(cache.level_of(64 << 20), Some(MemoryLevel::Dram));
+        assert_eq!(parse_cache_size("1x"), None);
+        assert_eq!(format_size(48 * 1024), "48K");
+        assert_eq!(format_size(1536 * 1024), "1.5M");
+
+        let sets = working_sets(&cache, Some(usize::MAX));
+        assert_eq!(sets.first(), Some(&MIN_WORKING_SET));
+        assert_eq!(sets.last(), Some(&(64 << 20)));
+        assert_eq!(working_sets(&cache, Some(64 << 20)).last(), Some(&(16 << 20)));
+        assert_eq!(stream_array_bytes(&cache, None), MAX_ARRAY_BYTES);
+
+        let point = |size: usize, ns: f64| LatencyPoint { size_bytes: size, latency_ns: ns, level: cache.level_of(size) };
+        let levels = level_latencies(&[point(4096, 1.0), point(8192, 1.2), point(16384, 1.1), point(1 << 20, 4.0), point(128 << 20, 90.0)]);
+        assert_eq!(levels, vec![(MemoryLevel::L1, 1.1), (MemoryLevel::L2, 4.0), (MemoryLevel::Dram, 90.0)]);
+    }
+}
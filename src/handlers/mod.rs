@@ -1,15 +1,31 @@
 pub mod command;
+pub mod compression_bench;
+pub mod cpu_score;
 pub mod cpu_test;
+pub mod cpu_topology;
+pub mod crypto_bench;
 pub mod disk_test;
+pub mod dns_test;
+pub mod fio;
+pub mod history;
+pub mod iperf;
 pub mod k3s;
 pub mod k8s;
+pub mod memory_test;
 pub mod network_test;
+pub mod ping;
 pub mod port_manager;
+pub mod resource_monitor;
+pub mod route_prefix;
+pub mod route_trace;
 pub mod sing_box;
 pub mod system_info;
+pub mod system_monitor;
 pub mod tcp_optimizer;
+pub mod unlock_check;
 pub mod xray;
 
+use crate::cancel::TestControl;
 use crate::menu::MenuItem;
 
 /// 根据菜单项获取对应的内容
@@ -25,5 +41,23 @@ pub fn get_content(item: MenuItem) -> String {
         MenuItem::K3s => k3s::get_info(),
         MenuItem::K8s => k8s::get_info(),
         MenuItem::TcpOptimization => tcp_optimizer::get_info(),
+        MenuItem::MemoryTest => memory_test::get_info(),
+        MenuItem::RouteTrace => route_trace::get_info(),
+        MenuItem::UnlockCheck => unlock_check::get_info(),
+        MenuItem::History => history::get_info(),
     }
-}
\ No newline at end of file
+}
+
+/// 菜单项对应测试的控制入口，非测试页面返回 None
+pub fn test_control(item: MenuItem) -> Option<&'static TestControl> {
+    match item {
+        MenuItem::SystemInfo => Some(dns_test::test_control()),
+        MenuItem::DiskTest => Some(disk_test::test_control()),
+        MenuItem::CpuTest => Some(cpu_test::test_control()),
+        MenuItem::NetworkSpeedTest => Some(network_test::test_control()),
+        MenuItem::MemoryTest => Some(memory_test::test_control()),
+        MenuItem::RouteTrace => Some(route_trace::test_control()),
+        MenuItem::UnlockCheck => Some(unlock_check::test_control()),
+        _ => None,
+    }
+}
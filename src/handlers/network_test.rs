@@ -1,26 +1,186 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use futures_util::StreamExt;
-use reqwest::Client;
+use bytes::Bytes;
+use futures_util::{future, stream, StreamExt};
+use rand::RngCore;
+use reqwest::{Body, Client};
+use serde::Deserialize;
+use tokio::task::JoinHandle;
 use tokio::time::timeout;
-use rand::Rng;
+
+use crate::cancel::{CancelToken, Cancelled, TestControl};
+use crate::config;
+use super::iperf::{self, IperfStats, IperfTarget, IperfTest};
+use super::ping::{self, PingMethod, PingOptions};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 测速时使用的 IP 协议族
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IpProtocol {
+    Auto,
+    Ipv4,
+    Ipv6,
+}
+
+impl IpProtocol {
+    fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "auto" | "" => Some(IpProtocol::Auto),
+            "ipv4" | "v4" | "4" => Some(IpProtocol::Ipv4),
+            "ipv6" | "v6" | "6" => Some(IpProtocol::Ipv6),
+            _ => None,
+        }
+    }
+
+    pub fn accepts(&self, addr: &SocketAddr) -> bool {
+        match self {
+            IpProtocol::Auto => true,
+            IpProtocol::Ipv4 => addr.is_ipv4(),
+            IpProtocol::Ipv6 => addr.is_ipv6(),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct NetworkProvider {
     pub name: String,
-    pub test_urls: Vec<String>,
-    pub color: &'static str,
+    pub download_url: String,
+    pub upload_url: String,
+    pub ping_host: String,   // host 或 host:port，缺省端口取下载地址的端口
+    pub protocol: IpProtocol,
+    pub color: String,       // #RRGGBB
+    pub iperf: Option<IperfTarget>,  // iperf3 节点，吞吐量由 iperf3 测量，没有 HTTP 地址
+}
+
+/// 延迟探测目标
+#[derive(Debug, Clone)]
+pub struct PingTarget {
+    pub host: String,
+    pub port: u16,
+    pub protocol: IpProtocol,
+}
+
+impl NetworkProvider {
+    pub fn ping_target(&self) -> Result<PingTarget, String> {
+        let default_port = match self.iperf {
+            Some(ref target) => target.port,
+            None => url::Url::parse(&self.download_url)
+                .ok()
+                .and_then(|u| u.port_or_known_default())
+                .unwrap_or(80),
+        };
+        let (host, port) = split_host_port(&self.ping_host, default_port)?;
+        Ok(PingTarget { host, port, protocol: self.protocol })
+    }
+}
+
+/// 测速服务器列表及其来源
+#[derive(Debug, Clone)]
+pub struct ProviderList {
+    pub providers: Vec<NetworkProvider>,
+    pub source: Option<PathBuf>, // None 表示使用内置列表
+    pub errors: Vec<String>,
+}
+
+// 服务器列表配置文件格式，所有字段可选以便逐项给出校验错误
+#[derive(Debug, Deserialize)]
+struct ServerListFile {
+    #[serde(default)]
+    servers: Vec<ServerEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ServerEntry {
+    name: Option<String>,
+    download_url: Option<String>,
+    upload_url: Option<String>,
+    ping_host: Option<String>,
+    protocol: Option<String>,
+    color: Option<String>,
+    iperf3: Option<String>,             // iperf3 服务端 host[:port]，设置后不需要 download_url/upload_url
+    iperf3_udp_bitrate: Option<String>,
+}
+
+static PROVIDER_LIST: Mutex<Option<ProviderList>> = Mutex::new(None);
+
+/// 测速参数
+#[derive(Debug, Clone)]
+pub struct SpeedTestConfig {
+    pub connections: usize,     // 并发连接数
+    pub warmup: Duration,       // 预热时间（不计入结果）
+    pub duration: Duration,     // 正式测量时间
+    pub ping_count: usize,      // 延迟探测次数
+    pub upload_size: usize,     // 单次上传请求的字节数
+}
+
+impl Default for SpeedTestConfig {
+    fn default() -> Self {
+        Self {
+            connections: 4,
+            warmup: Duration::from_secs(2),
+            duration: Duration::from_secs(8),
+            ping_count: 10,
+            upload_size: 8 * 1024 * 1024,
+        }
+    }
+}
+
+/// 界面中可切换的测量时长（秒）和并发连接数
+pub const MEASURE_DURATIONS: [u64; 4] = [5, 8, 15, 30];
+pub const CONNECTION_COUNTS: [usize; 5] = [1, 2, 4, 8, 16];
+
+impl SpeedTestConfig {
+    pub fn label(&self) -> String {
+        format!(
+            "下载/上传各 {} 秒（另加 {} 秒预热）, {} 个并发连接",
+            self.duration.as_secs(),
+            self.warmup.as_secs(),
+            self.connections
+        )
+    }
+
+    pub fn next_duration(&self) -> Duration {
+        let current = self.duration.as_secs();
+        let next = MEASURE_DURATIONS.iter().copied().find(|&v| v > current).unwrap_or(MEASURE_DURATIONS[0]);
+        Duration::from_secs(next)
+    }
+
+    pub fn next_connections(&self) -> usize {
+        CONNECTION_COUNTS.iter().copied().find(|&v| v > self.connections).unwrap_or(CONNECTION_COUNTS[0])
+    }
+}
+
+/// 延迟测量结果
+#[derive(Debug, Clone, Default)]
+pub struct LatencyStats {
+    pub ping_avg: f64,                // ICMP/TCP 探测平均延迟 ms
+    pub http_avg: f64,                // HTTP 请求平均耗时 ms
+    pub jitter: f64,                  // ms
+    pub packet_loss: f64,             // 探测丢包率 %
+    pub method: Option<PingMethod>,   // None 表示探测全部失败，延迟取自 HTTP 请求
 }
 
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(200);
+const UPLOAD_CHUNK_SIZE: usize = 256 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const PROBE_INTERVAL: Duration = Duration::from_millis(200);
+
 #[derive(Debug, Clone)]
 pub struct SpeedTestResult {
     pub provider: String,
     pub download_speed: f64, // Mbps
     pub upload_speed: f64,   // Mbps
     pub ping: f64,           // ms
+    pub http_latency: f64,   // ms
     pub jitter: f64,         // ms
     pub packet_loss: f64,    // %
+    pub ping_method: Option<PingMethod>,
+    pub iperf: Option<IperfStats>,  // 通过 iperf3 测试时的重传和 UDP 结果
     pub status: TestStatus,
     pub error_message: Option<String>,
 }
@@ -46,6 +206,13 @@ pub struct NetworkTestInfo {
 }
 
 static NETWORK_TEST_STATE: Mutex<Option<Arc<Mutex<NetworkTestInfo>>>> = Mutex::new(None);
+static CONTROL: TestControl = TestControl::new();
+
+// 用户修改过的测速参数，未修改时使用默认值
+static SPEED_TEST_CONFIG: Mutex<Option<SpeedTestConfig>> = Mutex::new(None);
+
+// 只测试指定名称的服务器，None 表示测试全部
+static SELECTED_SERVER: Mutex<Option<String>> = Mutex::new(None);
 
 impl Default for NetworkTestInfo {
     fn default() -> Self {
@@ -69,43 +236,187 @@ impl Default for SpeedTestResult {
             download_speed: 0.0,
             upload_speed: 0.0,
             ping: 0.0,
+            http_latency: 0.0,
             jitter: 0.0,
             packet_loss: 0.0,
+            ping_method: None,
+            iperf: None,
             status: TestStatus::NotStarted,
             error_message: None,
         }
     }
 }
 
-pub fn get_network_providers() -> Vec<NetworkProvider> {
+fn builtin_provider(name: &str, base_url: &str, color: &str) -> NetworkProvider {
+    let host = url::Url::parse(base_url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_string()))
+        .unwrap_or_default();
+    NetworkProvider {
+        name: name.to_string(),
+        download_url: format!("{}/speedtest/random4000x4000.jpg", base_url),
+        upload_url: format!("{}/speedtest/upload.php", base_url),
+        ping_host: host,
+        protocol: IpProtocol::Auto,
+        color: color.to_string(),
+        iperf: None,
+    }
+}
+
+pub fn builtin_network_providers() -> Vec<NetworkProvider> {
     vec![
-        NetworkProvider {
-            name: "中国移动".to_string(),
-            test_urls: vec![
-                "http://speedtest1.bmcc.com.cn:8080/speedtest/upload.php".to_string(),
-                "http://speedtest2.bmcc.com.cn:8080/speedtest/upload.php".to_string(),
-            ],
-            color: "#00D4AA",
-        },
-        NetworkProvider {
-            name: "中国联通".to_string(),
-            test_urls: vec![
-                "http://61.135.169.121:8080/speedtest/upload.php".to_string(),
-                "http://61.135.169.122:8080/speedtest/upload.php".to_string(),
-            ],
-            color: "#E60012",
-        },
-        NetworkProvider {
-            name: "中国电信".to_string(),
-            test_urls: vec![
-                "http://speedtest1.online.sh.cn:8080/speedtest/upload.php".to_string(),
-                "http://speedtest2.online.sh.cn:8080/speedtest/upload.php".to_string(),
-            ],
-            color: "#0052D9",
-        },
+        builtin_provider("中国移动", "http://speedtest1.bmcc.com.cn:8080", "#00D4AA"),
+        builtin_provider("中国联通", "http://61.135.169.121:8080", "#E60012"),
+        builtin_provider("中国电信", "http://speedtest1.online.sh.cn:8080", "#0052D9"),
     ]
 }
 
+/// 获取测速服务器列表（缓存，首次调用时加载）
+pub fn get_network_providers() -> ProviderList {
+    let mut cached = PROVIDER_LIST.lock().unwrap();
+    cached.get_or_insert_with(load_network_providers).clone()
+}
+
+/// 重新读取配置文件，每次开始测试时调用，使修改无需重启即可生效
+pub fn reload_network_providers() -> ProviderList {
+    let list = load_network_providers();
+    *PROVIDER_LIST.lock().unwrap() = Some(list.clone());
+    list
+}
+
+/// 从 $ONEKEY_SERVERS 或配置目录下的 servers.toml / servers.json 加载服务器列表，
+/// 文件不存在或没有任何有效条目时退回内置列表
+pub fn load_network_providers() -> ProviderList {
+    let path = std::env::var_os("ONEKEY_SERVERS")
+        .map(PathBuf::from)
+        .or_else(|| config::find_config_file(&["servers.toml", "servers.json"]));
+
+    let Some(path) = path else {
+        return ProviderList { providers: builtin_network_providers(), source: None, errors: Vec::new() };
+    };
+
+    let (providers, mut errors) = match config::load_config_file::<ServerListFile>(&path) {
+        Ok(file) => providers_from_entries(file.servers),
+        Err(e) => (Vec::new(), vec![e]),
+    };
+
+    if providers.is_empty() {
+        errors.push(format!("{} 中没有可用的服务器，已使用内置列表", path.display()));
+        return ProviderList { providers: builtin_network_providers(), source: None, errors };
+    }
+
+    ProviderList { providers, source: Some(path), errors }
+}
+
+fn providers_from_entries(entries: Vec<ServerEntry>) -> (Vec<NetworkProvider>, Vec<String>) {
+    let mut providers = Vec::new();
+    let mut errors = Vec::new();
+    let mut seen_names = HashSet::new();
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        let label = entry.name.clone().unwrap_or_else(|| "未命名".to_string());
+        match validate_server_entry(entry) {
+            Ok(provider) if !seen_names.insert(provider.name.clone()) => {
+                errors.push(format!("第 {} 项 ({}): 名称重复", index + 1, label));
+            }
+            Ok(provider) => providers.push(provider),
+            Err(e) => errors.push(format!("第 {} 项 ({}): {}", index + 1, label, e)),
+        }
+    }
+
+    (providers, errors)
+}
+
+fn validate_server_entry(entry: ServerEntry) -> Result<NetworkProvider, String> {
+    let name = entry.name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty())
+        .ok_or("缺少 name")?;
+
+    // iperf3 节点不使用 HTTP 地址
+    let iperf = match entry.iperf3.filter(|v| !v.trim().is_empty()) {
+        Some(value) => Some(IperfTarget::parse(&value, entry.iperf3_udp_bitrate.as_deref()).map_err(|e| format!("iperf3 {}", e))?),
+        None if entry.iperf3_udp_bitrate.is_some() => return Err("iperf3_udp_bitrate 需要配合 iperf3 使用".to_string()),
+        None => None,
+    };
+    let (download_url, upload_url) = match iperf {
+        Some(_) if entry.download_url.is_some() || entry.upload_url.is_some() => {
+            return Err("iperf3 节点不能同时设置 download_url/upload_url".to_string());
+        }
+        Some(_) => (String::new(), String::new()),
+        None => (
+            validate_http_url(entry.download_url, "download_url")?,
+            validate_http_url(entry.upload_url, "upload_url")?,
+        ),
+    };
+
+    let ping_host = match entry.ping_host.filter(|h| !h.trim().is_empty()) {
+        Some(host) => {
+            split_host_port(host.trim(), 80)?;
+            host.trim().to_string()
+        }
+        None => match iperf {
+            Some(ref target) => target.host.clone(),
+            None => url::Url::parse(&download_url)
+                .ok()
+                .and_then(|u| u.host_str().map(|h| h.to_string()))
+                .ok_or("无法从 download_url 推断 ping_host")?,
+        },
+    };
+
+    let protocol = match entry.protocol {
+        Some(value) => IpProtocol::parse(value.trim())
+            .ok_or_else(|| format!("protocol 只能是 auto/ipv4/ipv6，而不是 \"{}\"", value))?,
+        None => IpProtocol::Auto,
+    };
+
+    let color = entry.color.unwrap_or_else(|| "#FFFFFF".to_string());
+    if parse_hex_color(&color).is_none() {
+        return Err(format!("color 必须是 #RRGGBB 格式，而不是 \"{}\"", color));
+    }
+
+    Ok(NetworkProvider { name, download_url, upload_url, ping_host, protocol, color, iperf })
+}
+
+fn validate_http_url(value: Option<String>, field: &str) -> Result<String, String> {
+    let value = value.filter(|v| !v.trim().is_empty()).ok_or(format!("缺少 {}", field))?;
+    let parsed = url::Url::parse(value.trim()).map_err(|e| format!("{} 无效: {}", field, e))?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err(format!("{} 必须是 http 或 https 地址", field));
+    }
+    if parsed.host_str().is_none() {
+        return Err(format!("{} 缺少主机名", field));
+    }
+    Ok(value.trim().to_string())
+}
+
+/// 解析 host、host:port、[v6]:port 以及裸 IPv6 地址
+pub fn split_host_port(value: &str, default_port: u16) -> Result<(String, u16), String> {
+    if let Ok(addr) = value.parse::<SocketAddr>() {
+        return Ok((addr.ip().to_string(), addr.port()));
+    }
+    if value.parse::<IpAddr>().is_ok() {
+        return Ok((value.to_string(), default_port));
+    }
+    let host = value.trim_start_matches('[').trim_end_matches(']');
+    match value.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => {
+            let port = port.parse::<u16>().map_err(|_| format!("端口无效: {}", value))?;
+            Ok((host.to_string(), port))
+        }
+        _ if host.is_empty() || host.contains(char::is_whitespace) => Err(format!("主机名无效: {}", value)),
+        _ => Ok((host.to_string(), default_port)),
+    }
+}
+
+/// 解析 #RRGGBB 颜色
+pub fn parse_hex_color(value: &str) -> Option<(u8, u8, u8)> {
+    let hex = value.strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
 pub fn get_current_test_info() -> NetworkTestInfo {
     let state_guard = NETWORK_TEST_STATE.lock().unwrap();
     if let Some(ref state_arc) = *state_guard {
@@ -116,58 +427,115 @@ pub fn get_current_test_info() -> NetworkTestInfo {
     }
 }
 
-pub fn start_network_test() {
-    // 添加调试输出
-    println!("开始网速测试...");
-    
-    let state_guard = NETWORK_TEST_STATE.lock().unwrap();
-    let state_arc = if let Some(ref existing) = *state_guard {
-        existing.clone()
-    } else {
-        drop(state_guard);
-        let new_state = Arc::new(Mutex::new(NetworkTestInfo::default()));
-        *NETWORK_TEST_STATE.lock().unwrap() = Some(new_state.clone());
-        new_state
-    };
+/// 按当前设置开始网速测试，上一次的结果会被清除
+pub fn start_network_test() -> Result<(), String> {
+    ensure_not_testing()?;
 
-    // 检查是否已经在测试中
-    {
-        let mut state = state_arc.lock().unwrap();
-        if state.is_testing {
-            println!("网速测试已在进行中，跳过");
-            return;
+    // 每次测试前重新读取服务器列表，配置错误显示在界面上
+    let provider_list = reload_network_providers();
+    let providers: Vec<NetworkProvider> = match selected_server() {
+        Some(name) => {
+            let selected: Vec<_> = provider_list.providers.iter().filter(|p| p.name == name).cloned().collect();
+            if selected.is_empty() {
+                *SELECTED_SERVER.lock().unwrap() = None;
+                return Err(format!("服务器列表中已没有 {}，已改为测试全部服务器", name));
+            }
+            selected
         }
-        state.is_testing = true;
-        state.start_time = Some(Instant::now());
-        state.current_stage = "初始化测试...".to_string();
-        state.progress = 0.0;
-        state.overall_progress = 0.0;
-        state.results.clear();
-        state.error_message = None;
-        println!("网速测试状态已初始化");
+        None => provider_list.providers.clone(),
+    };
+
+    // 每次测试使用新的状态，上一次的结果随之清空
+    let mut state = NetworkTestInfo::default();
+    state.is_testing = true;
+    state.start_time = Some(Instant::now());
+    state.current_stage = "初始化测试...".to_string();
+    if !provider_list.errors.is_empty() {
+        state.error_message = Some(provider_list.errors.join("; "));
     }
+    let state_arc = Arc::new(Mutex::new(state));
+    *NETWORK_TEST_STATE.lock().unwrap() = Some(state_arc.clone());
 
-    let state_clone = state_arc.clone();
+    let config = speed_test_config();
+    let run = CONTROL.begin();
     tokio::spawn(async move {
-        println!("网速测试异步任务已启动");
-        run_network_tests(state_clone).await;
+        run_network_tests(state_arc, providers, config, &run).await;
     });
+    Ok(())
 }
 
-async fn run_network_tests(state: Arc<Mutex<NetworkTestInfo>>) {
-    println!("开始执行网速测试");
-    let providers = get_network_providers();
+/// 取消/暂停正在运行的网速测试
+pub fn test_control() -> &'static TestControl {
+    &CONTROL
+}
+
+fn ensure_not_testing() -> Result<(), String> {
+    if CONTROL.is_running() {
+        return Err("网速测试进行中，完成或按 X 取消后才能修改测试设置".to_string());
+    }
+    Ok(())
+}
+
+// 清除上一次的结果，界面回到准备状态
+fn reset_network_test() {
+    *NETWORK_TEST_STATE.lock().unwrap() = None;
+}
+
+/// 当前测速参数
+pub fn speed_test_config() -> SpeedTestConfig {
+    SPEED_TEST_CONFIG.lock().unwrap().clone().unwrap_or_default()
+}
+
+/// 修改测速参数，回到准备状态
+pub fn change_speed_test_config(update: impl FnOnce(&mut SpeedTestConfig)) -> Result<SpeedTestConfig, String> {
+    ensure_not_testing()?;
+    let mut config = speed_test_config();
+    update(&mut config);
+    *SPEED_TEST_CONFIG.lock().unwrap() = Some(config.clone());
+    reset_network_test();
+    Ok(config)
+}
+
+/// 只测试的服务器，None 表示全部
+pub fn selected_server() -> Option<String> {
+    SELECTED_SERVER.lock().unwrap().clone()
+}
+
+/// 依次切换为全部服务器、列表中的每一台，回到准备状态
+pub fn select_next_server() -> Result<Option<String>, String> {
+    ensure_not_testing()?;
+    let names: Vec<String> = get_network_providers().providers.into_iter().map(|p| p.name).collect();
+    let next = match selected_server().and_then(|name| names.iter().position(|n| *n == name)) {
+        None => names.first().cloned(),
+        Some(index) => names.get(index + 1).cloned(),
+    };
+    *SELECTED_SERVER.lock().unwrap() = next.clone();
+    reset_network_test();
+    Ok(next)
+}
+
+async fn run_network_tests(
+    state: Arc<Mutex<NetworkTestInfo>>,
+    providers: Vec<NetworkProvider>,
+    config: SpeedTestConfig,
+    cancel: &CancelToken,
+) {
     let total_providers = providers.len();
 
     for (index, provider) in providers.iter().enumerate() {
-        println!("测试运营商: {}", provider.name);
+        if cancel.is_paused() {
+            state.lock().unwrap().current_stage = "已暂停，按空格继续".to_string();
+        }
+        if cancel.checkpoint_async().await.is_err() {
+            break;
+        }
+
         {
             let mut state_lock = state.lock().unwrap();
             state_lock.current_provider = Some(provider.name.clone());
             state_lock.current_stage = format!("准备测试 {}...", provider.name);
             state_lock.overall_progress = (index as f64 / total_providers as f64) * 100.0;
             state_lock.progress = 0.0;
-            println!("总体进度: {:.1}%", state_lock.overall_progress);
             
             // 先创建一个初始的测试结果
             let initial_result = SpeedTestResult {
@@ -179,13 +547,16 @@ async fn run_network_tests(state: Arc<Mutex<NetworkTestInfo>>) {
         }
 
         // 执行测试
-        match test_provider_speed(provider, state.clone()).await {
+        let outcome = match provider.iperf {
+            Some(ref target) => test_iperf_speed(provider, target, state.clone(), &config, cancel).await,
+            None => test_provider_speed(provider, state.clone(), &config, cancel).await,
+        };
+        match outcome {
             Ok(test_result) => {
                 let mut state_lock = state.lock().unwrap();
                 let mut final_result = test_result;
                 final_result.status = TestStatus::Completed;
                 state_lock.results.insert(provider.name.clone(), final_result);
-                println!("运营商 {} 测试完成", provider.name);
             }
             Err(e) => {
                 let mut state_lock = state.lock().unwrap();
@@ -194,7 +565,6 @@ async fn run_network_tests(state: Arc<Mutex<NetworkTestInfo>>) {
                     result.error_message = Some(e.to_string());
                     state_lock.results.insert(provider.name.clone(), result);
                 }
-                println!("运营商 {} 测试失败: {}", provider.name, e);
             }
         }
 
@@ -202,16 +572,23 @@ async fn run_network_tests(state: Arc<Mutex<NetworkTestInfo>>) {
         {
             let mut state_lock = state.lock().unwrap();
             state_lock.overall_progress = ((index + 1) as f64 / total_providers as f64) * 100.0;
-            println!("更新进度: {:.1}%", state_lock.overall_progress);
         }
 
         // 测试间隔 - 增加间隔让用户能看到进度
         if index < total_providers - 1 { // 不是最后一个运营商
-            println!("等待1秒后开始下一个运营商测试...");
             tokio::time::sleep(Duration::from_secs(1)).await;
         }
     }
 
+    // 取消时保留已完成服务器的结果，不写入历史记录
+    if cancel.is_cancelled() {
+        let mut state_lock = state.lock().unwrap();
+        state_lock.is_testing = false;
+        state_lock.current_stage = Cancelled.to_string();
+        state_lock.current_provider = None;
+        return;
+    }
+
     // 测试完成
     {
         let mut state_lock = state.lock().unwrap();
@@ -220,13 +597,17 @@ async fn run_network_tests(state: Arc<Mutex<NetworkTestInfo>>) {
         state_lock.progress = 100.0;
         state_lock.overall_progress = 100.0;
         state_lock.current_provider = None;
-        println!("所有网速测试完成");
     }
+
+    let info = state.lock().unwrap().clone();
+    super::history::record_network_run(&info, &providers);
 }
 
 async fn test_provider_speed(
     provider: &NetworkProvider, 
-    state: Arc<Mutex<NetworkTestInfo>>
+    state: Arc<Mutex<NetworkTestInfo>>,
+    config: &SpeedTestConfig,
+    cancel: &CancelToken,
 ) -> Result<SpeedTestResult, Box<dyn std::error::Error + Send + Sync>> {
     let mut result = SpeedTestResult {
         provider: provider.name.clone(),
@@ -234,41 +615,50 @@ async fn test_provider_speed(
         ..Default::default()
     };
 
-    let client = Client::builder()
-        .timeout(Duration::from_secs(30))
-        .build()?;
+    // 只限制建连时间，测速请求本身需要持续传输数据
+    let builder = Client::builder().connect_timeout(Duration::from_secs(10));
+    let client = match provider.protocol {
+        IpProtocol::Auto => builder,
+        IpProtocol::Ipv4 => builder.local_address(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+        IpProtocol::Ipv6 => builder.local_address(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+    }
+    .build()?;
 
     // 阶段1: Ping测试 (33%)
     {
         let mut state_lock = state.lock().unwrap();
         state_lock.current_stage = format!("{} - 延迟测试", provider.name);
         state_lock.progress = 0.0;
-        println!("开始 {} 的延迟测试", provider.name);
     }
 
-    let ping_results = test_ping(provider, &client, state.clone()).await?;
-    result.ping = ping_results.0;
-    result.jitter = ping_results.1;
+    let latency = test_ping(provider, &client, config, state.clone(), cancel).await?;
+    result.ping = latency.ping_avg;
+    result.http_latency = latency.http_avg;
+    result.jitter = latency.jitter;
+    result.packet_loss = latency.packet_loss;
+    result.ping_method = latency.method;
 
     // 更新结果并显示ping完成
     {
         let mut state_lock = state.lock().unwrap();
         if let Some(current_result) = state_lock.results.get_mut(&provider.name) {
             current_result.ping = result.ping;
+            current_result.http_latency = result.http_latency;
             current_result.jitter = result.jitter;
+            current_result.packet_loss = result.packet_loss;
+            current_result.ping_method = result.ping_method;
         }
         state_lock.progress = 33.0;
-        println!("{} 延迟测试完成，ping: {:.1}ms, 进度: 33%", provider.name, result.ping);
     }
 
     // 阶段2: 下载测试 (33%)
+    cancel.checkpoint_async().await?;
     {
         let mut state_lock = state.lock().unwrap();
         state_lock.current_stage = format!("{} - 下载测试", provider.name);
-        println!("开始 {} 的下载测试", provider.name);
     }
 
-    result.download_speed = test_download_speed(provider, &client, state.clone()).await?;
+    result.download_speed = test_download_speed(provider, &client, config, state.clone(), cancel).await?;
 
     // 更新结果并显示下载完成
     {
@@ -277,17 +667,16 @@ async fn test_provider_speed(
             current_result.download_speed = result.download_speed;
         }
         state_lock.progress = 66.0;
-        println!("{} 下载测试完成，下载: {:.1} Mbps, 进度: 66%", provider.name, result.download_speed);
     }
 
     // 阶段3: 上传测试 (34%)
+    cancel.checkpoint_async().await?;
     {
         let mut state_lock = state.lock().unwrap();
         state_lock.current_stage = format!("{} - 上传测试", provider.name);
-        println!("开始 {} 的上传测试", provider.name);
     }
 
-    result.upload_speed = test_upload_speed(provider, &client, state.clone()).await?;
+    result.upload_speed = test_upload_speed(provider, &client, config, state.clone(), cancel).await?;
 
     // 更新结果并显示上传完成
     {
@@ -296,111 +685,404 @@ async fn test_provider_speed(
             current_result.upload_speed = result.upload_speed;
         }
         state_lock.progress = 100.0;
-        println!("{} 上传测试完成，上传: {:.1} Mbps, 进度: 100%", provider.name, result.upload_speed);
     }
 
     Ok(result)
 }
 
 async fn test_ping(
-    _provider: &NetworkProvider,
-    _client: &Client,
+    provider: &NetworkProvider,
+    client: &Client,
+    config: &SpeedTestConfig,
     state: Arc<Mutex<NetworkTestInfo>>,
-) -> Result<(f64, f64), Box<dyn std::error::Error + Send + Sync>> {
-    // 模拟ping测试 - 在实际实现中，你可能需要使用系统ping命令或ICMP
-    let mut ping_times = Vec::new();
-    
-    for i in 0..5 { // 减少到5次ping，加快速度
-        let start = Instant::now();
-        
-        // 模拟网络延迟（适度延迟让用户能看到进度）
-        let delay = 300 + (i * 100); // 300-700ms的延迟
-        tokio::time::sleep(Duration::from_millis(delay)).await;
-        
-        let elapsed = start.elapsed().as_millis() as f64;
-        ping_times.push(elapsed);
-        
-        // 更新ping测试的进度 (在33%内分布)
-        let ping_progress = ((i + 1) as f64 / 5.0) * 33.0;
-        {
-            let mut state_lock = state.lock().unwrap();
-            state_lock.progress = ping_progress;
-        }
-        
-        println!("Ping {}/5 完成，延迟: {:.1}ms，进度: {:.1}%", i + 1, elapsed, ping_progress);
-    }
-
-    let avg_ping = ping_times.iter().sum::<f64>() / ping_times.len() as f64;
-    let jitter = calculate_jitter(&ping_times);
-
-    Ok((avg_ping, jitter))
+    cancel: &CancelToken,
+) -> Result<LatencyStats, BoxError> {
+    let target = provider.ping_target()?;
+    measure_latency(client, &provider.download_url, &target, config.ping_count, cancel, |fraction| {
+        // 延迟测试占单个运营商进度的 33%
+        let mut state_lock = state.lock().unwrap();
+        state_lock.progress = fraction * 33.0;
+    })
+    .await
 }
 
 async fn test_download_speed(
-    _provider: &NetworkProvider,
-    _client: &Client,
+    provider: &NetworkProvider,
+    client: &Client,
+    config: &SpeedTestConfig,
     state: Arc<Mutex<NetworkTestInfo>>,
-) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-    // 模拟下载测试，显示进度
-    println!("开始下载速度测试...");
-    
-    // 分6个阶段模拟下载，每个阶段更新进度
-    for i in 0..6 {
-        tokio::time::sleep(Duration::from_millis(400)).await; // 每个阶段0.4秒
-        
-        let stage_progress = ((i + 1) as f64 / 6.0) * 33.0; // 下载占33%的进度
-        {
-            let mut state_lock = state.lock().unwrap();
-            state_lock.progress = 33.0 + stage_progress; // ping占33%，下载从33%开始
-            println!("下载进度: {:.1}%", state_lock.progress);
+    cancel: &CancelToken,
+) -> Result<f64, BoxError> {
+    measure_download(client, &provider.download_url, config, cancel, |fraction, current_mbps| {
+        let mut state_lock = state.lock().unwrap();
+        state_lock.progress = 33.0 + fraction * 33.0; // ping占33%，下载从33%开始
+        if let Some(current_result) = state_lock.results.get_mut(&provider.name) {
+            current_result.download_speed = current_mbps;
         }
-    }
-    
-    // 模拟下载速度结果
-    let download_speed = 30.0 + (rand::thread_rng().gen::<f64>() * 50.0); // 30-80 Mbps
-    println!("下载速度测试完成: {:.1} Mbps", download_speed);
-    
-    Ok(download_speed)
+    })
+    .await
 }
 
 async fn test_upload_speed(
-    _provider: &NetworkProvider,
-    _client: &Client,
+    provider: &NetworkProvider,
+    client: &Client,
+    config: &SpeedTestConfig,
     state: Arc<Mutex<NetworkTestInfo>>,
-) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
-    // 模拟上传测试，显示进度
-    println!("开始上传速度测试...");
-    
-    // 分6个阶段模拟上传，每个阶段更新进度  
-    for i in 0..6 {
-        tokio::time::sleep(Duration::from_millis(300)).await; // 每个阶段0.3秒
-        
-        let upload_progress = ((i + 1) as f64 / 6.0) * 34.0; // 上传占34%的进度
-        {
-            let mut state_lock = state.lock().unwrap();
-            state_lock.progress = 66.0 + upload_progress; // ping+下载占66%，上传从66%开始
-            println!("上传进度: {:.1}%", state_lock.progress);
+    cancel: &CancelToken,
+) -> Result<f64, BoxError> {
+    measure_upload(client, &provider.upload_url, config, cancel, |fraction, current_mbps| {
+        let mut state_lock = state.lock().unwrap();
+        state_lock.progress = 66.0 + fraction * 34.0; // ping+下载占66%，上传从66%开始
+        if let Some(current_result) = state_lock.results.get_mut(&provider.name) {
+            current_result.upload_speed = current_mbps;
+        }
+    })
+    .await
+}
+
+async fn test_iperf_speed(
+    provider: &NetworkProvider,
+    target: &IperfTarget,
+    state: Arc<Mutex<NetworkTestInfo>>,
+    config: &SpeedTestConfig,
+    cancel: &CancelToken,
+) -> Result<SpeedTestResult, BoxError> {
+    let ping_target = provider.ping_target()?;
+    let mut result = measure_iperf(target, &ping_target, config, cancel, |stage, fraction, current| {
+        let mut state_lock = state.lock().unwrap();
+        state_lock.current_stage = format!("{} - {}", provider.name, stage);
+        state_lock.progress = fraction * 100.0;
+        if let Some(current_result) = state_lock.results.get_mut(&provider.name) {
+            *current_result = SpeedTestResult {
+                provider: provider.name.clone(),
+                status: TestStatus::Testing,
+                ..current.clone()
+            };
+        }
+    })
+    .await?;
+    result.provider = provider.name.clone();
+    Ok(result)
+}
+
+/// 通过 iperf3 测量到节点的 TCP 下载/上传吞吐量和 UDP 下载的丢包、抖动，
+/// 延迟用 ICMP（不可用时用 TCP 建连到 iperf3 端口）探测；on_update 收到阶段名称、进度 (0-1) 和目前的结果
+pub async fn measure_iperf<F>(
+    target: &IperfTarget,
+    ping_target: &PingTarget,
+    config: &SpeedTestConfig,
+    cancel: &CancelToken,
+    on_update: F,
+) -> Result<SpeedTestResult, BoxError>
+where
+    F: Fn(&str, f64, &SpeedTestResult),
+{
+    let mut result = SpeedTestResult::default();
+    let mut stats = IperfStats::default();
+
+    // 延迟探测占 10%，TCP 下载和上传各占 35%，UDP 占 20%
+    let count = config.ping_count.max(1);
+    let options = PingOptions {
+        count,
+        interval: PROBE_INTERVAL,
+        timeout: CONNECT_TIMEOUT,
+        port: ping_target.port,
+        protocol: ping_target.protocol,
+    };
+    on_update("延迟测试", 0.0, &result);
+    let probes = ping::ping(&ping_target.host, &options, cancel, |reply| {
+        on_update("延迟测试", (reply.seq + 1) as f64 / count as f64 * 0.1, &result)
+    })
+    .await;
+    match probes {
+        Ok(probes) if probes.received > 0 => {
+            result.ping = probes.avg_ms;
+            result.jitter = probes.jitter_ms;
+            result.packet_loss = probes.loss_pct;
+            result.ping_method = Some(probes.method);
+        }
+        Err(e) if e.is::<Cancelled>() => return Err(e),
+        // 延迟探测失败不影响吞吐量测试
+        _ => {}
+    }
+
+    let runs = [
+        (IperfTest::TcpDownload, 0.1, 0.35),
+        (IperfTest::TcpUpload, 0.45, 0.35),
+        (IperfTest::UdpDownload, 0.8, 0.2),
+    ];
+    for (test, offset, share) in runs {
+        cancel.checkpoint_async().await?;
+        on_update(test.name(), offset, &result);
+        let outcome = iperf::run_client(target, test, config.warmup, config.duration, config.connections, cancel, |fraction| {
+            on_update(test.name(), offset + fraction * share, &result)
+        })
+        .await;
+        match (test, outcome) {
+            (IperfTest::TcpDownload, Ok(summary)) => {
+                result.download_speed = summary.mbps;
+                stats.download_retransmits = summary.retransmits;
+            }
+            (IperfTest::TcpUpload, Ok(summary)) => {
+                result.upload_speed = summary.mbps;
+                stats.upload_retransmits = summary.retransmits;
+            }
+            (IperfTest::UdpDownload, Ok(summary)) => stats.udp = Some(summary),
+            (_, Err(e)) if e.is::<Cancelled>() => return Err(e),
+            // 只放行 TCP 的防火墙很常见，UDP 失败时保留 TCP 结果
+            (IperfTest::UdpDownload, Err(e)) => stats.udp_error = Some(e.to_string()),
+            (_, Err(e)) => return Err(e),
+        }
+        result.iperf = Some(stats.clone());
+    }
+
+    on_update("测试完成", 1.0, &result);
+    Ok(result)
+}
+
+/// 用 ICMP（不可用时用 TCP 建连）探测目标的延迟和丢包，再测量到目标 URL 的 HTTP 请求延迟
+pub async fn measure_latency<F>(
+    client: &Client,
+    url: &str,
+    target: &PingTarget,
+    count: usize,
+    cancel: &CancelToken,
+    on_progress: F,
+) -> Result<LatencyStats, BoxError>
+where
+    F: Fn(f64),
+{
+    let count = count.max(1);
+    let options = PingOptions {
+        count,
+        interval: PROBE_INTERVAL,
+        timeout: CONNECT_TIMEOUT,
+        port: target.port,
+        protocol: target.protocol,
+    };
+
+    // 前一半进度为延迟探测，后一半为 HTTP 请求；ICMP 无回应改用 TCP 时进度重新计算
+    let probes = match ping::ping(&target.host, &options, cancel, |reply| {
+        on_progress((reply.seq + 1) as f64 / count as f64 / 2.0)
+    })
+    .await
+    {
+        Ok(stats) => Some(stats),
+        Err(e) if e.is::<Cancelled>() => return Err(e),
+        Err(_) => None,
+    };
+
+    // 预热一次，让 HTTP 连接进入连接池，避免首个请求把握手时间算进去
+    let _ = client.head(url).send().await;
+
+    let mut http_times = Vec::with_capacity(count);
+    for i in 0..count {
+        cancel.check()?;
+        // 使用 HEAD 请求，收到响应头即停止计时
+        let start = Instant::now();
+        if let Ok(Ok(_response)) = timeout(CONNECT_TIMEOUT, client.head(url).send()).await {
+            http_times.push(start.elapsed().as_secs_f64() * 1000.0);
         }
+        on_progress(0.5 + (i + 1) as f64 / count as f64 / 2.0);
+    }
+
+    let http_avg = if http_times.is_empty() {
+        0.0
+    } else {
+        http_times.iter().sum::<f64>() / http_times.len() as f64
+    };
+
+    match probes.filter(|stats| stats.received > 0) {
+        Some(stats) => Ok(LatencyStats {
+            ping_avg: stats.avg_ms,
+            http_avg,
+            jitter: stats.jitter_ms,
+            packet_loss: stats.loss_pct,
+            method: Some(stats.method),
+        }),
+        None if http_times.is_empty() => Err(format!("无法连接到 {}:{}", target.host, target.port).into()),
+        // 探测全部失败时（例如只能走代理），退回使用 HTTP 延迟，丢包率无从得知
+        None => Ok(LatencyStats {
+            ping_avg: http_avg,
+            http_avg,
+            jitter: ping::jitter(&http_times),
+            packet_loss: 0.0,
+            method: None,
+        }),
     }
-    
-    // 模拟上传速度结果
-    let upload_speed = 20.0 + (rand::thread_rng().gen::<f64>() * 30.0); // 20-50 Mbps
-    println!("上传速度测试完成: {:.1} Mbps", upload_speed);
-    
-    Ok(upload_speed)
 }
 
-fn calculate_jitter(ping_times: &[f64]) -> f64 {
-    if ping_times.len() < 2 {
-        return 0.0;
+/// 多连接并发下载，返回预热之后的平均吞吐量 (Mbps)
+pub async fn measure_download<F>(
+    client: &Client,
+    url: &str,
+    config: &SpeedTestConfig,
+    cancel: &CancelToken,
+    on_progress: F,
+) -> Result<f64, BoxError>
+where
+    F: Fn(f64, f64),
+{
+    let counter = Arc::new(AtomicU64::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let workers = (0..config.connections.max(1))
+        .map(|_| {
+            let client = client.clone();
+            let url = url.to_string();
+            let counter = counter.clone();
+            let stop = stop.clone();
+            tokio::spawn(async move {
+                // 文件下载完后重新请求，直到测量结束
+                while !stop.load(Ordering::Relaxed) {
+                    let response = client.get(&url).send().await?.error_for_status()?;
+                    let mut body = response.bytes_stream();
+                    while let Some(chunk) = body.next().await {
+                        counter.fetch_add(chunk?.len() as u64, Ordering::Relaxed);
+                        if stop.load(Ordering::Relaxed) {
+                            break;
+                        }
+                    }
+                }
+                Ok(())
+            })
+        })
+        .collect();
+
+    drive_transfer(counter, stop, workers, config, cancel, on_progress).await
+}
+
+/// 多连接并发上传，返回预热之后的平均吞吐量 (Mbps)
+pub async fn measure_upload<F>(
+    client: &Client,
+    url: &str,
+    config: &SpeedTestConfig,
+    cancel: &CancelToken,
+    on_progress: F,
+) -> Result<f64, BoxError>
+where
+    F: Fn(f64, f64),
+{
+    let counter = Arc::new(AtomicU64::new(0));
+    let stop = Arc::new(AtomicBool::new(false));
+
+    // 使用随机数据，避免链路上的压缩影响结果
+    let mut payload = vec![0u8; UPLOAD_CHUNK_SIZE];
+    rand::thread_rng().fill_bytes(&mut payload);
+    let payload = Bytes::from(payload);
+    let chunks_per_request = (config.upload_size / UPLOAD_CHUNK_SIZE).max(1);
+
+    let workers = (0..config.connections.max(1))
+        .map(|_| {
+            let client = client.clone();
+            let url = url.to_string();
+            let counter = counter.clone();
+            let stop = stop.clone();
+            let payload = payload.clone();
+            tokio::spawn(async move {
+                while !stop.load(Ordering::Relaxed) {
+                    let body_stop = stop.clone();
+                    let body_counter = counter.clone();
+                    let body_payload = payload.clone();
+                    let body = stream::iter(0..chunks_per_request)
+                        .take_while(move |_| future::ready(!body_stop.load(Ordering::Relaxed)))
+                        .map(move |_| {
+                            body_counter.fetch_add(body_payload.len() as u64, Ordering::Relaxed);
+                            Ok::<Bytes, std::io::Error>(body_payload.clone())
+                        });
+
+                    client
+                        .post(&url)
+                        .header(reqwest::header::CONTENT_TYPE, "application/octet-stream")
+                        .body(Body::wrap_stream(body))
+                        .send()
+                        .await?
+                        .error_for_status()?;
+                }
+                Ok(())
+            })
+        })
+        .collect();
+
+    drive_transfer(counter, stop, workers, config, cancel, on_progress).await
+}
+
+/// 采样传输计数器直到测量结束，丢弃预热阶段的数据
+async fn drive_transfer<F>(
+    counter: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    workers: Vec<JoinHandle<Result<(), reqwest::Error>>>,
+    config: &SpeedTestConfig,
+    cancel: &CancelToken,
+    on_progress: F,
+) -> Result<f64, BoxError>
+where
+    F: Fn(f64, f64),
+{
+    let start = Instant::now();
+    let total = config.warmup + config.duration;
+    let mut measure_from: Option<(Instant, u64)> = None;
+    let mut last_sample = (start, 0u64);
+    let mut ticker = tokio::time::interval(SAMPLE_INTERVAL);
+
+    loop {
+        ticker.tick().await;
+        let now = Instant::now();
+        let bytes = counter.load(Ordering::Relaxed);
+        let elapsed = now.duration_since(start);
+
+        if measure_from.is_none() && elapsed >= config.warmup {
+            measure_from = Some((now, bytes));
+        }
+
+        let window = now.duration_since(last_sample.0).as_secs_f64();
+        let current_mbps = if window > 0.0 {
+            to_mbps(bytes - last_sample.1, window)
+        } else {
+            0.0
+        };
+        last_sample = (now, bytes);
+
+        on_progress((elapsed.as_secs_f64() / total.as_secs_f64()).min(1.0), current_mbps);
+
+        // 所有连接都已退出说明发生了错误，没有必要继续等待
+        if elapsed >= total || workers.iter().all(|worker| worker.is_finished()) || cancel.is_cancelled() {
+            break;
+        }
+    }
+
+    stop.store(true, Ordering::Relaxed);
+    let end = (Instant::now(), counter.load(Ordering::Relaxed));
+
+    let mut first_error = None;
+    for worker in workers {
+        if worker.is_finished() {
+            if let Ok(Err(e)) = worker.await {
+                first_error.get_or_insert(e);
+            }
+        } else {
+            worker.abort();
+        }
     }
-    
-    let mut deltas = Vec::new();
-    for i in 1..ping_times.len() {
-        deltas.push((ping_times[i] - ping_times[i-1]).abs());
+    cancel.check()?;
+
+    // 预热阶段就失败时，退回使用全程数据
+    let (from_instant, from_bytes) = measure_from.unwrap_or((start, 0));
+    let bytes = end.1 - from_bytes;
+    let seconds = end.0.duration_since(from_instant).as_secs_f64();
+
+    if bytes == 0 || seconds <= 0.0 {
+        return Err(match first_error {
+            Some(e) => e.into(),
+            None => "测量期间没有传输任何数据".into(),
+        });
     }
-    
-    deltas.iter().sum::<f64>() / deltas.len() as f64
+
+    Ok(to_mbps(bytes, seconds))
+}
+
+fn to_mbps(bytes: u64, seconds: f64) -> f64 {
+    bytes as f64 * 8.0 / seconds / 1_000_000.0
 }
 
 /// 检查是否需要刷新UI
@@ -416,4 +1098,260 @@ pub fn check_needs_refresh() -> bool {
 
 pub fn get_info() -> String {
     format!("网速测试功能已集成到UI中，请在UI界面中查看测试结果。")
-}
\ No newline at end of file
+}
+
+/// 格式化网速测试结果，按服务器列表顺序输出
+pub fn format_network_test_info(info: &NetworkTestInfo, providers: &[NetworkProvider]) -> String {
+    let mut output = String::from("=== 网速测试 ===\n\n");
+
+    if let Some(ref error) = info.error_message {
+        output.push_str(&format!("配置错误: {}\n\n", error));
+    }
+
+    output.push_str(&format!(
+        "{:<14} {:>12} {:>12} {:>10} {:>10} {:>10} {:>8}\n",
+        "服务器", "下载(Mbps)", "上传(Mbps)", "延迟(ms)", "HTTP(ms)", "抖动(ms)", "丢包"
+    ));
+    output.push_str(&"-".repeat(83));
+    output.push('\n');
+
+    for provider in providers {
+        match info.results.get(&provider.name) {
+            Some(result) if result.status == TestStatus::Failed => {
+                output.push_str(&format!(
+                    "{:<14} 测试失败: {}\n",
+                    provider.name,
+                    result.error_message.as_deref().unwrap_or("未知错误")
+                ));
+            }
+            Some(result) => {
+                let loss = match result.ping_method {
+                    Some(method) => format!("{:.0}% {}", result.packet_loss, method.name()),
+                    None => "-".to_string(),
+                };
+                output.push_str(&format!(
+                    "{:<14} {:>12.2} {:>12.2} {:>10.1} {:>10.1} {:>10.1} {:>8}\n",
+                    provider.name,
+                    result.download_speed,
+                    result.upload_speed,
+                    result.ping,
+                    result.http_latency,
+                    result.jitter,
+                    loss
+                ));
+                if let Some(ref stats) = result.iperf {
+                    output.push_str(&format!("{:<14} iperf3 {}\n", "", stats.summary()));
+                }
+            }
+            None => output.push_str(&format!("{:<14} 未测试\n", provider.name)),
+        }
+    }
+
+    output
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::{TcpListener, TcpStream};
+
+    const BODY_SIZE: usize = 1024 * 1024;
+
+    /// 本地 HTTP 测速替身：GET 返回固定大小的数据，POST 读完请求体后返回 200
+    async fn spawn_stand_in_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(serve_connection(socket));
+            }
+        });
+
+        format!("http://{}/speedtest", addr)
+    }
+
+    async fn serve_connection(socket: TcpStream) -> std::io::Result<()> {
+        let (reader, mut writer) = socket.into_split();
+        let mut reader = BufReader::new(reader);
+
+        loop {
+            let mut request_line = String::new();
+            if reader.read_line(&mut request_line).await? == 0 {
+                return Ok(());
+            }
+
+            let mut content_length = 0usize;
+            let mut chunked = false;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).await?;
+                let header = header.trim_end().to_ascii_lowercase();
+                if header.is_empty() {
+                    break;
+                }
+                if let Some(value) = header.strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap_or(0);
+                }
+                if header.starts_with("transfer-encoding:") && header.contains("chunked") {
+                    chunked = true;
+                }
+            }
+
+            if chunked {
+                loop {
+                    let mut size_line = String::new();
+                    reader.read_line(&mut size_line).await?;
+                    let size = usize::from_str_radix(size_line.trim(), 16).unwrap_or(0);
+                    let mut discard = vec![0u8; size + 2];
+                    reader.read_exact(&mut discard).await?;
+                    if size == 0 {
+                        break;
+                    }
+                }
+            } else if content_length > 0 {
+                let mut discard = vec![0u8; content_length];
+                reader.read_exact(&mut discard).await?;
+            }
+
+            if request_line.starts_with("GET") {
+                let header = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", BODY_SIZE);
+                writer.write_all(header.as_bytes()).await?;
+                writer.write_all(&vec![0u8; BODY_SIZE]).await?;
+            } else {
+                writer.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n").await?;
+            }
+        }
+    }
+
+    fn quick_config() -> SpeedTestConfig {
+        SpeedTestConfig {
+            connections: 2,
+            warmup: Duration::from_millis(200),
+            duration: Duration::from_millis(600),
+            ping_count: 3,
+            upload_size: 1024 * 1024,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_against_stand_in_server() {
+        let url = spawn_stand_in_server().await;
+        let client = Client::new();
+
+        let speed = measure_download(&client, &url, &quick_config(), &CancelToken::new(), |_, _| {}).await.unwrap();
+        assert!(speed > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_upload_against_stand_in_server() {
+        let url = spawn_stand_in_server().await;
+        let client = Client::new();
+
+        let speed = measure_upload(&client, &url, &quick_config(), &CancelToken::new(), |_, _| {}).await.unwrap();
+        assert!(speed > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_latency_against_stand_in_server() {
+        let url = spawn_stand_in_server().await;
+        let client = Client::new();
+
+        let addr = url::Url::parse(&url).unwrap();
+        let target = PingTarget {
+            host: addr.host_str().unwrap().to_string(),
+            port: addr.port().unwrap(),
+            protocol: IpProtocol::Ipv4,
+        };
+
+        let latency = measure_latency(&client, &url, &target, 3, &CancelToken::new(), |_| {}).await.unwrap();
+        assert!(latency.ping_avg > 0.0 && latency.ping_avg < 1000.0);
+        assert!(latency.http_avg > 0.0);
+        assert!(latency.method.is_some());
+        assert_eq!(latency.packet_loss, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_download_stops_early() {
+        let url = spawn_stand_in_server().await;
+        let client = Client::new();
+        let cancel = CancelToken::new();
+        cancel.cancel();
+
+        let start = Instant::now();
+        let result = measure_download(&client, &url, &quick_config(), &cancel, |_, _| {}).await;
+        assert!(result.unwrap_err().downcast_ref::<Cancelled>().is_some());
+        assert!(start.elapsed() < quick_config().warmup + quick_config().duration);
+    }
+
+    #[tokio::test]
+    async fn test_download_fails_when_server_is_unreachable() {
+        // 绑定后立即释放端口，保证没有服务在监听
+        let addr = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+        let client = Client::new();
+
+        let result = measure_download(&client, &format!("http://{}/", addr), &quick_config(), &CancelToken::new(), |_, _| {}).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_server_list_validation() {
+        let file: ServerListFile = toml::from_str(r##"
+            [[servers]]
+            name = "Tokyo"
+            download_url = "https://tokyo.example.com/100MB.bin"
+            upload_url = "https://tokyo.example.com/upload"
+            ping_host = "tokyo.example.com:443"
+            protocol = "ipv6"
+            color = "#00D4AA"
+
+            [[servers]]
+            name = "Broken"
+            download_url = "ftp://broken.example.com/file"
+            upload_url = "https://broken.example.com/upload"
+
+            [[servers]]
+            name = "Tokyo"
+            download_url = "https://tokyo2.example.com/100MB.bin"
+            upload_url = "https://tokyo2.example.com/upload"
+            color = "green"
+
+            [[servers]]
+            name = "VPS"
+            iperf3 = "vps.example.com:5202"
+            iperf3_udp_bitrate = "50M"
+
+            [[servers]]
+            name = "Mixed"
+            iperf3 = "mixed.example.com"
+            download_url = "https://mixed.example.com/100MB.bin"
+        "##).unwrap();
+
+        let (providers, errors) = providers_from_entries(file.servers);
+        assert_eq!(providers.len(), 2);
+        assert_eq!(providers[0].protocol, IpProtocol::Ipv6);
+
+        let target = providers[0].ping_target().unwrap();
+        assert_eq!((target.host.as_str(), target.port), ("tokyo.example.com", 443));
+
+        // iperf3 节点默认探测 iperf3 端口
+        let iperf = providers[1].iperf.as_ref().unwrap();
+        assert_eq!((iperf.port, iperf.udp_bitrate.as_str()), (5202, "50M"));
+        let target = providers[1].ping_target().unwrap();
+        assert_eq!((target.host.as_str(), target.port), ("vps.example.com", 5202));
+
+        assert_eq!(errors.len(), 3);
+        assert!(errors[0].contains("Broken") && errors[0].contains("download_url"));
+        assert!(errors[1].contains("color"));
+        assert!(errors[2].contains("Mixed"));
+    }
+
+    #[test]
+    fn test_split_host_port() {
+        assert_eq!(split_host_port("example.com", 80).unwrap(), ("example.com".to_string(), 80));
+        assert_eq!(split_host_port("1.2.3.4:8080", 80).unwrap(), ("1.2.3.4".to_string(), 8080));
+        assert_eq!(split_host_port("[2001:db8::1]:443", 80).unwrap(), ("2001:db8::1".to_string(), 443));
+        assert_eq!(split_host_port("2001:db8::1", 80).unwrap(), ("2001:db8::1".to_string(), 80));
+        assert!(split_host_port("example.com:http", 80).is_err());
+    }
+}
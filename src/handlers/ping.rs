@@ -0,0 +1,357 @@
+// src/handlers/ping.rs
+// 延迟探测：优先使用无需 root 的 ICMP 数据报套接字（受 net.ipv4.ping_group_range 限制），
+// 套接字不可用或目标不回应 ICMP 时改为测量 TCP 建连耗时，统计平均/最小/最大延迟、抖动和丢包率
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::os::fd::FromRawFd;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::time::timeout;
+
+use crate::cancel::CancelToken;
+use super::network_test::IpProtocol;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+// ICMP 回显请求/应答的类型值
+const ICMP_ECHO_REQUEST: u8 = 8;
+const ICMP_ECHO_REPLY: u8 = 0;
+const ICMPV6_ECHO_REQUEST: u8 = 128;
+const ICMPV6_ECHO_REPLY: u8 = 129;
+
+// 与系统 ping 相同的 56 字节负载
+const PAYLOAD_SIZE: usize = 56;
+
+/// 探测方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PingMethod {
+    Icmp,
+    Tcp,
+}
+
+impl PingMethod {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PingMethod::Icmp => "ICMP",
+            PingMethod::Tcp => "TCP",
+        }
+    }
+}
+
+/// 探测参数
+#[derive(Debug, Clone)]
+pub struct PingOptions {
+    pub count: usize,        // 探测次数
+    pub interval: Duration,  // 相邻两次探测开始的间隔
+    pub timeout: Duration,   // 单次探测的等待时间，超时计为丢包
+    pub port: u16,           // 改用 TCP 探测时连接的端口
+    pub protocol: IpProtocol,
+}
+
+impl Default for PingOptions {
+    fn default() -> Self {
+        Self {
+            count: 10,
+            interval: Duration::from_secs(1),
+            timeout: Duration::from_secs(2),
+            port: 443,
+            protocol: IpProtocol::Auto,
+        }
+    }
+}
+
+/// 一次探测的结果，rtt_ms 为 None 表示超时或出错
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PingReply {
+    pub method: PingMethod,
+    pub seq: usize,
+    pub rtt_ms: Option<f64>,
+}
+
+/// 一组探测的统计
+#[derive(Debug, Clone, PartialEq)]
+pub struct PingStats {
+    pub method: PingMethod,
+    pub address: IpAddr,
+    pub fallback: Option<String>,  // 改用 TCP 探测的原因
+    pub rtts: Vec<Option<f64>>,    // 每次探测的往返时间 ms
+    pub sent: usize,
+    pub received: usize,
+    pub min_ms: f64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    pub jitter_ms: f64,
+    pub loss_pct: f64,
+}
+
+impl PingStats {
+    /// 由每次探测的往返时间统计，没有收到任何回应时延迟均为 0
+    pub fn from_rtts(method: PingMethod, address: IpAddr, rtts: Vec<Option<f64>>) -> Self {
+        let times: Vec<f64> = rtts.iter().flatten().copied().collect();
+        let sent = rtts.len();
+        let received = times.len();
+        let avg_ms = if received > 0 { times.iter().sum::<f64>() / received as f64 } else { 0.0 };
+        Self {
+            method,
+            address,
+            fallback: None,
+            sent,
+            received,
+            min_ms: times.iter().copied().reduce(f64::min).unwrap_or(0.0),
+            avg_ms,
+            max_ms: times.iter().copied().reduce(f64::max).unwrap_or(0.0),
+            jitter_ms: jitter(&times),
+            loss_pct: if sent > 0 { (sent - received) as f64 / sent as f64 * 100.0 } else { 0.0 },
+            rtts,
+        }
+    }
+
+    /// 例如 "10 次探测, 收到 9 次, 丢包 10.0%, 延迟 最小/平均/最大 = 1.2/3.4/8.9 ms, 抖动 0.8 ms"
+    pub fn summary(&self) -> String {
+        let mut text = format!(
+            "{} 次探测, 收到 {} 次, 丢包 {:.1}%",
+            self.sent, self.received, self.loss_pct
+        );
+        if self.received > 0 {
+            text.push_str(&format!(
+                ", 延迟 最小/平均/最大 = {:.1}/{:.1}/{:.1} ms, 抖动 {:.1} ms",
+                self.min_ms, self.avg_ms, self.max_ms, self.jitter_ms
+            ));
+        }
+        text
+    }
+}
+
+/// 抖动：相邻两次延迟之差的平均值
+pub fn jitter(times: &[f64]) -> f64 {
+    if times.len() < 2 {
+        return 0.0;
+    }
+    times.windows(2).map(|pair| (pair[1] - pair[0]).abs()).sum::<f64>() / (times.len() - 1) as f64
+}
+
+/// 按协议族解析主机名，IP 地址直接返回
+pub async fn resolve(host: &str, protocol: IpProtocol) -> Result<IpAddr, BoxError> {
+    let mut addrs = tokio::net::lookup_host((host, 0))
+        .await
+        .map_err(|e| format!("无法解析 {}: {}", host, e))?;
+    addrs
+        .find(|addr| protocol.accepts(addr))
+        .map(|addr| addr.ip())
+        .ok_or_else(|| format!("{} 没有符合协议要求的地址", host).into())
+}
+
+/// 向主机发送 options.count 次探测，每次探测完成后调用 on_reply
+///
+/// ICMP 套接字不可用或所有 ICMP 探测都没有回应时，改用 TCP 重新探测；
+/// 全部丢包不算错误，只有解析失败和取消时返回 Err
+pub async fn ping<F>(host: &str, options: &PingOptions, cancel: &CancelToken, mut on_reply: F) -> Result<PingStats, BoxError>
+where
+    F: FnMut(&PingReply),
+{
+    let address = resolve(host, options.protocol).await?;
+
+    let fallback = match IcmpSocket::open(address) {
+        Ok(socket) => {
+            let prober = Prober::Icmp(socket);
+            let rtts = run_probes(&prober, options, cancel, &mut on_reply).await?;
+            if rtts.iter().any(Option::is_some) {
+                return Ok(PingStats::from_rtts(PingMethod::Icmp, address, rtts));
+            }
+            "目标没有回应 ICMP".to_string()
+        }
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            "ICMP 套接字不可用（net.ipv4.ping_group_range 不包含当前用户组）".to_string()
+        }
+        Err(e) => format!("ICMP 套接字不可用: {}", e),
+    };
+
+    let prober = Prober::Tcp(SocketAddr::new(address, options.port));
+    let rtts = run_probes(&prober, options, cancel, &mut on_reply).await?;
+    Ok(PingStats {
+        fallback: Some(fallback),
+        ..PingStats::from_rtts(PingMethod::Tcp, address, rtts)
+    })
+}
+
+enum Prober {
+    Icmp(IcmpSocket),
+    Tcp(SocketAddr),
+}
+
+impl Prober {
+    fn method(&self) -> PingMethod {
+        match self {
+            Prober::Icmp(_) => PingMethod::Icmp,
+            Prober::Tcp(_) => PingMethod::Tcp,
+        }
+    }
+
+    async fn probe(&self, seq: u16, wait: Duration) -> Option<f64> {
+        match self {
+            Prober::Icmp(socket) => socket.probe(seq, wait).await,
+            Prober::Tcp(address) => tcp_probe(*address, wait).await,
+        }
+    }
+}
+
+async fn run_probes<F>(prober: &Prober, options: &PingOptions, cancel: &CancelToken, on_reply: &mut F) -> Result<Vec<Option<f64>>, BoxError>
+where
+    F: FnMut(&PingReply),
+{
+    let count = options.count.max(1);
+    let mut rtts = Vec::with_capacity(count);
+    for seq in 0..count {
+        cancel.check()?;
+        let start = Instant::now();
+        let rtt_ms = prober.probe(seq as u16, options.timeout).await;
+        rtts.push(rtt_ms);
+        on_reply(&PingReply { method: prober.method(), seq, rtt_ms });
+        if seq + 1 < count {
+            tokio::time::sleep(options.interval.saturating_sub(start.elapsed())).await;
+        }
+    }
+    Ok(rtts)
+}
+
+// 只有完成三次握手才算收到回应；连接被拒绝也可能来自本机或中间防火墙的 REJECT，不能说明目标可达
+async fn tcp_probe(address: SocketAddr, wait: Duration) -> Option<f64> {
+    let start = Instant::now();
+    match timeout(wait, TcpStream::connect(address)).await {
+        Ok(Ok(_stream)) => Some(elapsed_ms(start)),
+        _ => None,
+    }
+}
+
+fn elapsed_ms(start: Instant) -> f64 {
+    start.elapsed().as_secs_f64() * 1000.0
+}
+
+/// 无需 root 的 ICMP 数据报套接字，内核负责填写标识符和校验和，并只把本套接字的应答交给它
+struct IcmpSocket {
+    socket: UdpSocket,
+    target: SocketAddr,
+    request_type: u8,
+    reply_type: u8,
+}
+
+impl IcmpSocket {
+    fn open(address: IpAddr) -> io::Result<Self> {
+        let (request_type, reply_type) = echo_types(address);
+        Ok(Self {
+            socket: UdpSocket::from_std(icmp_socket(address)?)?,
+            target: SocketAddr::new(address, 0),
+            request_type,
+            reply_type,
+        })
+    }
+
+    // 发送一个回显请求并等待序号相同的应答，迟到的旧应答直接丢弃
+    async fn probe(&self, seq: u16, wait: Duration) -> Option<f64> {
+        let start = Instant::now();
+        self.socket.send_to(&echo_request(self.request_type, seq), self.target).await.ok()?;
+        let mut buf = [0u8; 1500];
+        loop {
+            let remaining = wait.checked_sub(start.elapsed())?;
+            let (len, _) = timeout(remaining, self.socket.recv_from(&mut buf)).await.ok()?.ok()?;
+            if parse_echo_reply(&buf[..len], self.reply_type) == Some(seq) {
+                return Some(elapsed_ms(start));
+            }
+        }
+    }
+}
+
+/// 打开非阻塞的 ICMP 数据报套接字，当前用户组不在 net.ipv4.ping_group_range 中时返回 PermissionDenied
+pub fn icmp_socket(address: IpAddr) -> io::Result<std::net::UdpSocket> {
+    let (domain, protocol) = match address {
+        IpAddr::V4(_) => (libc::AF_INET, libc::IPPROTO_ICMP),
+        IpAddr::V6(_) => (libc::AF_INET6, libc::IPPROTO_ICMPV6),
+    };
+    // SAFETY: socket() 成功时返回新的文件描述符，所有权立即交给 UdpSocket
+    let fd = unsafe { libc::socket(domain, libc::SOCK_DGRAM | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC, protocol) };
+    if fd < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(unsafe { std::net::UdpSocket::from_raw_fd(fd) })
+}
+
+/// 目标地址对应的回显请求和回显应答类型
+pub fn echo_types(address: IpAddr) -> (u8, u8) {
+    match address {
+        IpAddr::V4(_) => (ICMP_ECHO_REQUEST, ICMP_ECHO_REPLY),
+        IpAddr::V6(_) => (ICMPV6_ECHO_REQUEST, ICMPV6_ECHO_REPLY),
+    }
+}
+
+/// 回显请求：类型、代码、校验和、标识符、序号，后接负载；校验和与标识符由内核填写
+pub fn echo_request(request_type: u8, seq: u16) -> Vec<u8> {
+    let mut packet = vec![request_type, 0, 0, 0, 0, 0];
+    packet.extend_from_slice(&seq.to_be_bytes());
+    packet.extend((0..PAYLOAD_SIZE).map(|i| i as u8));
+    packet
+}
+
+/// 数据报套接字收到的是不含 IP 头的 ICMP 报文，返回回显应答的序号
+pub fn parse_echo_reply(packet: &[u8], reply_type: u8) -> Option<u16> {
+    if packet.len() < 8 || packet[0] != reply_type || packet[1] != 0 {
+        return None;
+    }
+    Some(u16::from_be_bytes([packet[6], packet[7]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_echo_packets_and_stats() {
+        let request = echo_request(ICMP_ECHO_REQUEST, 0x1234);
+        assert_eq!(request.len(), 8 + PAYLOAD_SIZE);
+        assert_eq!(&request[..8], &[8, 0, 0, 0, 0, 0, 0x12, 0x34]);
+
+        let mut reply = request.clone();
+        reply[0] = ICMP_ECHO_REPLY;
+        assert_eq!(parse_echo_reply(&reply, ICMP_ECHO_REPLY), Some(0x1234));
+        assert_eq!(parse_echo_reply(&request, ICMP_ECHO_REPLY), None);
+        assert_eq!(parse_echo_reply(&reply[..6], ICMP_ECHO_REPLY), None);
+
+        let address = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let stats = PingStats::from_rtts(PingMethod::Icmp, address, vec![Some(10.0), None, Some(14.0), Some(12.0)]);
+        assert_eq!((stats.sent, stats.received), (4, 3));
+        assert_eq!(stats.loss_pct, 25.0);
+        assert_eq!((stats.min_ms, stats.avg_ms, stats.max_ms), (10.0, 12.0, 14.0));
+        assert_eq!(stats.jitter_ms, 3.0);
+
+        let lost = PingStats::from_rtts(PingMethod::Tcp, address, vec![None, None]);
+        assert_eq!((lost.loss_pct, lost.avg_ms), (100.0, 0.0));
+        assert!(!lost.summary().contains("延迟"));
+    }
+
+    #[tokio::test]
+    async fn test_ping_localhost() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let options = PingOptions {
+            count: 3,
+            interval: Duration::from_millis(10),
+            timeout: Duration::from_millis(500),
+            port: listener.local_addr().unwrap().port(),
+            protocol: IpProtocol::Ipv4,
+        };
+
+        // 是否允许 ICMP 套接字取决于系统设置，两种方式都应该收到全部回应
+        let mut replies = Vec::new();
+        let stats = ping("127.0.0.1", &options, &CancelToken::new(), |reply| replies.push(*reply)).await.unwrap();
+        assert_eq!((stats.sent, stats.received), (3, 3));
+        assert_eq!(stats.loss_pct, 0.0);
+        assert_eq!(stats.fallback.is_some(), stats.method == PingMethod::Tcp);
+        assert_eq!(replies.last().map(|r| (r.method, r.seq)), Some((stats.method, 2)));
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        assert!(ping("127.0.0.1", &options, &cancel, |_| {}).await.is_err());
+    }
+}
@@ -1,3 +1,5 @@
+use super::command::CommandRunner;
+
 pub fn get_open_port_info() -> String {
     let mut content = String::from("=== 开放端口 ===\n\n");
     
@@ -122,4 +124,214 @@ pub fn get_close_port_info() -> String {
     content.push_str("提示: 关闭端口前请确认不会影响正常服务。\n");
     
     content
-}
\ No newline at end of file
+}
+
+/// 端口协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortProtocol {
+    Tcp,
+    Udp,
+    Both,
+}
+
+impl PortProtocol {
+    fn names(&self) -> &'static [&'static str] {
+        match self {
+            PortProtocol::Tcp => &["tcp"],
+            PortProtocol::Udp => &["udp"],
+            PortProtocol::Both => &["tcp", "udp"],
+        }
+    }
+}
+
+/// 端口规格，如 8080、8080/tcp、8000-9000/udp
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PortSpec {
+    pub start: u16,
+    pub end: u16,
+    pub protocol: PortProtocol,
+}
+
+impl PortSpec {
+    /// 解析端口规格，未指定协议时同时作用于 tcp 和 udp
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (range, protocol) = match value.split_once('/') {
+            Some((range, "tcp")) => (range, PortProtocol::Tcp),
+            Some((range, "udp")) => (range, PortProtocol::Udp),
+            Some((_, other)) => return Err(format!("不支持的协议: {}", other)),
+            None => (value, PortProtocol::Both),
+        };
+
+        let parse_port = |s: &str| match s.trim().parse::<u16>() {
+            Ok(0) | Err(_) => Err(format!("无效的端口号: {}", s)),
+            Ok(port) => Ok(port),
+        };
+
+        let (start, end) = match range.split_once(['-', ':']) {
+            Some((start, end)) => (parse_port(start)?, parse_port(end)?),
+            None => {
+                let port = parse_port(range)?;
+                (port, port)
+            }
+        };
+
+        if start > end {
+            return Err(format!("端口范围起始值大于结束值: {}", range));
+        }
+
+        Ok(Self { start, end, protocol })
+    }
+
+    /// 以指定分隔符格式化端口范围
+    fn range(&self, separator: char) -> String {
+        if self.start == self.end {
+            self.start.to_string()
+        } else {
+            format!("{}{}{}", self.start, separator, self.end)
+        }
+    }
+}
+
+/// 系统防火墙后端
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FirewallBackend {
+    Ufw,
+    Firewalld,
+    Iptables,
+}
+
+impl FirewallBackend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FirewallBackend::Ufw => "ufw",
+            FirewallBackend::Firewalld => "firewalld",
+            FirewallBackend::Iptables => "iptables",
+        }
+    }
+}
+
+/// 检测当前生效的防火墙：优先使用已启用的 ufw / firewalld，否则退回 iptables
+pub fn detect_firewall() -> Option<FirewallBackend> {
+    if CommandRunner::command_exists("ufw") {
+        if let Ok(status) = CommandRunner::run("ufw", &["status"]) {
+            if status.contains("Status: active") {
+                return Some(FirewallBackend::Ufw);
+            }
+        }
+    }
+
+    if CommandRunner::command_exists("firewall-cmd")
+        && CommandRunner::run_status("firewall-cmd", &["--state"]).unwrap_or(false)
+    {
+        return Some(FirewallBackend::Firewalld);
+    }
+
+    if CommandRunner::command_exists("iptables") {
+        return Some(FirewallBackend::Iptables);
+    }
+
+    None
+}
+
+/// 生成开放/关闭端口所需执行的命令
+pub fn firewall_commands(backend: FirewallBackend, spec: &PortSpec, open: bool) -> Vec<Vec<String>> {
+    let mut commands = Vec::new();
+
+    for protocol in spec.protocol.names() {
+        let command: Vec<String> = match backend {
+            FirewallBackend::Ufw => {
+                let rule = format!("{}/{}", spec.range(':'), protocol);
+                if open {
+                    vec!["ufw".into(), "allow".into(), rule]
+                } else {
+                    vec!["ufw".into(), "delete".into(), "allow".into(), rule]
+                }
+            }
+            FirewallBackend::Firewalld => {
+                let action = if open { "--add-port" } else { "--remove-port" };
+                vec![
+                    "firewall-cmd".into(),
+                    "--permanent".into(),
+                    format!("{}={}/{}", action, spec.range('-'), protocol),
+                ]
+            }
+            FirewallBackend::Iptables => {
+                let action = if open { "-I" } else { "-D" };
+                vec![
+                    "iptables".into(),
+                    action.into(),
+                    "INPUT".into(),
+                    "-p".into(),
+                    protocol.to_string(),
+                    "--dport".into(),
+                    spec.range(':'),
+                    "-j".into(),
+                    "ACCEPT".into(),
+                ]
+            }
+        };
+        commands.push(command);
+    }
+
+    // firewalld 的永久规则需要重载后才生效
+    if backend == FirewallBackend::Firewalld {
+        commands.push(vec!["firewall-cmd".into(), "--reload".into()]);
+    }
+
+    commands
+}
+
+/// 开放或关闭端口，返回执行过的命令记录
+pub fn apply_port_rule(spec: &PortSpec, open: bool) -> Result<String, String> {
+    let backend = detect_firewall().ok_or("未检测到 ufw、firewalld 或 iptables")?;
+    let mut log = format!("防火墙: {}\n", backend.name());
+
+    for command in firewall_commands(backend, spec, open) {
+        let args: Vec<&str> = command[1..].iter().map(|s| s.as_str()).collect();
+        log.push_str(&format!("$ {}\n", command.join(" ")));
+        CommandRunner::run(&command[0], &args)
+            .map_err(|e| format!("{}执行失败: {}", log, e.to_string().trim()))?;
+    }
+
+    Ok(log)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_port_spec() {
+        assert_eq!(
+            PortSpec::parse("8080/tcp"),
+            Ok(PortSpec { start: 8080, end: 8080, protocol: PortProtocol::Tcp })
+        );
+        assert_eq!(
+            PortSpec::parse("8000-9000/udp"),
+            Ok(PortSpec { start: 8000, end: 9000, protocol: PortProtocol::Udp })
+        );
+        assert_eq!(PortSpec::parse("443").unwrap().protocol, PortProtocol::Both);
+
+        assert!(PortSpec::parse("0/tcp").is_err());
+        assert!(PortSpec::parse("70000").is_err());
+        assert!(PortSpec::parse("9000-8000").is_err());
+        assert!(PortSpec::parse("8080/sctp").is_err());
+    }
+
+    #[test]
+    fn test_firewall_commands() {
+        let spec = PortSpec::parse("8000-9000").unwrap();
+
+        let ufw = firewall_commands(FirewallBackend::Ufw, &spec, true);
+        assert_eq!(ufw[0].join(" "), "ufw allow 8000:9000/tcp");
+        assert_eq!(ufw[1].join(" "), "ufw allow 8000:9000/udp");
+
+        let firewalld = firewall_commands(FirewallBackend::Firewalld, &spec, false);
+        assert_eq!(firewalld[0].join(" "), "firewall-cmd --permanent --remove-port=8000-9000/tcp");
+        assert_eq!(firewalld.last().unwrap().join(" "), "firewall-cmd --reload");
+
+        let spec = PortSpec::parse("8080/tcp").unwrap();
+        let iptables = firewall_commands(FirewallBackend::Iptables, &spec, false);
+        assert_eq!(iptables, vec![vec!["iptables", "-D", "INPUT", "-p", "tcp", "--dport", "8080", "-j", "ACCEPT"]]);
+    }
+}
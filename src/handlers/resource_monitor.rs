@@ -0,0 +1,385 @@
+// src/handlers/resource_monitor.rs
+// 实时资源监控：后台线程每秒读取 /proc 下的计数器，由相邻两次采样算出每核 CPU 占用、
+// 内存/Swap 占用、每块磁盘的读写速度和每个网卡的收发速度，保留最近 HISTORY_LEN 个点用于绘制曲线
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::system_info::NetworkStats;
+use super::system_monitor::CpuTimes;
+
+/// 采样间隔
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 每条曲线保留的采样点数
+pub const HISTORY_LEN: usize = 120;
+
+// /proc/diskstats 中的扇区固定为 512 字节
+const SECTOR_SIZE: u64 = 512;
+
+/// 固定长度的滚动序列，超出后丢弃最旧的点
+#[derive(Debug, Clone, Default)]
+pub struct Rolling {
+    values: VecDeque<f64>,
+}
+
+impl Rolling {
+    pub fn push(&mut self, value: f64) {
+        if self.values.len() == HISTORY_LEN {
+            self.values.pop_front();
+        }
+        self.values.push_back(value);
+    }
+
+    /// 从旧到新的全部点
+    pub fn values(&self) -> impl Iterator<Item = f64> + '_ {
+        self.values.iter().copied()
+    }
+
+    pub fn last(&self) -> Option<f64> {
+        self.values.back().copied()
+    }
+}
+
+/// 内存和 Swap 用量（字节）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MemoryUsage {
+    pub total: u64,
+    pub used: u64,
+    pub swap_total: u64,
+    pub swap_used: u64,
+}
+
+impl MemoryUsage {
+    /// 由 /proc/meminfo 计算，已用内存 = MemTotal - MemAvailable
+    pub fn parse(meminfo: &str) -> Option<Self> {
+        let field = |name: &str| -> Option<u64> {
+            let line = meminfo.lines().find(|line| line.split(':').next() == Some(name))?;
+            let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+            Some(kib * 1024)
+        };
+        let total = field("MemTotal")?;
+        let swap_total = field("SwapTotal").unwrap_or(0);
+        Some(Self {
+            total,
+            used: total.saturating_sub(field("MemAvailable")?),
+            swap_total,
+            swap_used: swap_total.saturating_sub(field("SwapFree").unwrap_or(swap_total)),
+        })
+    }
+
+    pub fn memory_pct(&self) -> f64 {
+        percent(self.used, self.total)
+    }
+
+    pub fn swap_pct(&self) -> f64 {
+        percent(self.swap_used, self.swap_total)
+    }
+}
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total > 0 {
+        used as f64 / total as f64 * 100.0
+    } else {
+        0.0
+    }
+}
+
+/// /proc/diskstats 中一个块设备的累计读写字节数
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiskCounters {
+    pub name: String,
+    pub read_bytes: u64,
+    pub write_bytes: u64,
+}
+
+/// 解析 /proc/diskstats：第 3 列为设备名，第 6、10 列为累计读、写扇区数
+pub fn parse_diskstats(content: &str) -> Vec<DiskCounters> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 10 {
+                return None;
+            }
+            Some(DiskCounters {
+                name: parts[2].to_string(),
+                read_bytes: parts[5].parse::<u64>().ok()? * SECTOR_SIZE,
+                write_bytes: parts[9].parse::<u64>().ok()? * SECTOR_SIZE,
+            })
+        })
+        .collect()
+}
+
+// 只监控整块磁盘（/sys/block 下的设备），分区、loop 和 ram 设备不单独显示
+fn is_monitored_disk(name: &str) -> bool {
+    !name.starts_with("loop") && !name.starts_with("ram") && Path::new("/sys/block").join(name).exists()
+}
+
+/// 某一时刻的全部累计计数
+#[derive(Debug, Clone, Default)]
+pub struct Snapshot {
+    pub taken_at: Option<Instant>,
+    pub cpu: Option<CpuTimes>,
+    pub cores: Vec<(usize, CpuTimes)>,
+    pub memory: Option<MemoryUsage>,
+    pub disks: Vec<DiskCounters>,
+    pub interfaces: Vec<NetworkStats>,
+}
+
+impl Snapshot {
+    pub fn read() -> Self {
+        let stat = fs::read_to_string("/proc/stat").unwrap_or_default();
+        Self {
+            taken_at: Some(Instant::now()),
+            cpu: CpuTimes::parse(&stat),
+            cores: CpuTimes::parse_per_cpu(&stat),
+            memory: fs::read_to_string("/proc/meminfo").ok().and_then(|meminfo| MemoryUsage::parse(&meminfo)),
+            disks: fs::read_to_string("/proc/diskstats")
+                .map(|content| parse_diskstats(&content))
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|disk| is_monitored_disk(&disk.name))
+                .collect(),
+            interfaces: NetworkStats::read_all()
+                .into_iter()
+                .filter(|stats| stats.interface_name != "lo")
+                .collect(),
+        }
+    }
+}
+
+/// 相邻两次快照之间的变化，速度单位为字节/秒
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ResourceSample {
+    pub cpu_pct: f64,
+    pub cores_pct: Vec<(usize, f64)>,
+    pub memory: MemoryUsage,
+    pub disks: Vec<(String, f64, f64)>,       // (设备, 读, 写)
+    pub interfaces: Vec<(String, f64, f64)>,  // (网卡, 接收, 发送)
+}
+
+impl ResourceSample {
+    /// 由前后两次快照计算，seconds 为两次快照的间隔；只在前一次快照中出现过的设备才计算速度
+    pub fn between(previous: &Snapshot, current: &Snapshot, seconds: f64) -> Self {
+        let rate = |now: u64, before: u64| {
+            if seconds > 0.0 {
+                now.saturating_sub(before) as f64 / seconds
+            } else {
+                0.0
+            }
+        };
+        Self {
+            cpu_pct: match (current.cpu, previous.cpu) {
+                (Some(now), Some(before)) => now.busy_pct_since(&before),
+                _ => 0.0,
+            },
+            cores_pct: current
+                .cores
+                .iter()
+                .filter_map(|(cpu, now)| {
+                    let (_, before) = previous.cores.iter().find(|(c, _)| c == cpu)?;
+                    Some((*cpu, now.busy_pct_since(before)))
+                })
+                .collect(),
+            memory: current.memory.unwrap_or_default(),
+            disks: current
+                .disks
+                .iter()
+                .filter_map(|now| {
+                    let before = previous.disks.iter().find(|d| d.name == now.name)?;
+                    Some((now.name.clone(), rate(now.read_bytes, before.read_bytes), rate(now.write_bytes, before.write_bytes)))
+                })
+                .collect(),
+            interfaces: current
+                .interfaces
+                .iter()
+                .filter_map(|now| {
+                    let before = previous.interfaces.iter().find(|i| i.interface_name == now.interface_name)?;
+                    Some((now.interface_name.clone(), rate(now.rx_bytes, before.rx_bytes), rate(now.tx_bytes, before.tx_bytes)))
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 一个设备的两条速度曲线：磁盘为读/写，网卡为接收/发送
+#[derive(Debug, Clone, Default)]
+pub struct DeviceSeries {
+    pub name: String,
+    pub first: Rolling,
+    pub second: Rolling,
+    active: bool,  // 出现过非零速度，一直空闲的设备不显示
+}
+
+impl DeviceSeries {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+/// 各项资源的滚动历史
+#[derive(Debug, Clone, Default)]
+pub struct ResourceHistory {
+    pub cpu: Rolling,
+    pub cores: Vec<(usize, Rolling)>,  // 按 CPU 编号排列
+    pub memory_pct: Rolling,
+    pub swap_pct: Rolling,
+    pub memory: MemoryUsage,  // 最近一次的用量
+    pub disks: Vec<DeviceSeries>,
+    pub interfaces: Vec<DeviceSeries>,
+    pub samples: usize,
+}
+
+impl ResourceHistory {
+    pub fn record(&mut self, sample: &ResourceSample) {
+        self.samples += 1;
+        self.cpu.push(sample.cpu_pct);
+        for &(cpu, pct) in &sample.cores_pct {
+            match self.cores.iter_mut().find(|(c, _)| *c == cpu) {
+                Some((_, series)) => series.push(pct),
+                None => {
+                    let mut series = Rolling::default();
+                    series.push(pct);
+                    self.cores.push((cpu, series));
+                    self.cores.sort_by_key(|(c, _)| *c);
+                }
+            }
+        }
+        self.memory = sample.memory;
+        self.memory_pct.push(sample.memory.memory_pct());
+        self.swap_pct.push(sample.memory.swap_pct());
+        record_devices(&mut self.disks, &sample.disks);
+        record_devices(&mut self.interfaces, &sample.interfaces);
+    }
+}
+
+// 按名称追加速度，本次采样中消失的设备（如拔出的网卡）一并移除
+fn record_devices(series: &mut Vec<DeviceSeries>, rates: &[(String, f64, f64)]) {
+    series.retain(|s| rates.iter().any(|(name, _, _)| *name == s.name));
+    for (name, first, second) in rates {
+        let index = match series.iter().position(|s| s.name == *name) {
+            Some(index) => index,
+            None => {
+                series.push(DeviceSeries { name: name.clone(), ..Default::default() });
+                series.len() - 1
+            }
+        };
+        let entry = &mut series[index];
+        entry.first.push(*first);
+        entry.second.push(*second);
+        entry.active |= *first > 0.0 || *second > 0.0;
+    }
+}
+
+/// 按 B/s、KB/s、MB/s、GB/s 格式化速度
+pub fn format_rate(bytes_per_sec: f64) -> String {
+    const UNITS: [&str; 4] = ["B/s", "KB/s", "MB/s", "GB/s"];
+    let mut value = bytes_per_sec;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+// 全局状态管理
+static HISTORY: Mutex<Option<ResourceHistory>> = Mutex::new(None);
+static NEEDS_UI_REFRESH: AtomicBool = AtomicBool::new(false);
+static SAMPLER_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// 获取资源历史，首次调用时启动后台采样线程
+pub fn get_history() -> ResourceHistory {
+    start_sampler();
+    HISTORY.lock().ok().and_then(|history| history.clone()).unwrap_or_default()
+}
+
+/// 检查是否需要刷新UI
+pub fn check_needs_refresh() -> bool {
+    NEEDS_UI_REFRESH.swap(false, Ordering::Relaxed)
+}
+
+// 采样线程在程序退出前一直运行，每次只读取几个 /proc 文件，开销可以忽略
+fn start_sampler() {
+    if SAMPLER_STARTED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+    thread::spawn(|| {
+        let mut previous = Snapshot::read();
+        loop {
+            thread::sleep(SAMPLE_INTERVAL);
+            let current = Snapshot::read();
+            let seconds = match (current.taken_at, previous.taken_at) {
+                (Some(now), Some(before)) => now.duration_since(before).as_secs_f64(),
+                _ => SAMPLE_INTERVAL.as_secs_f64(),
+            };
+            let sample = ResourceSample::between(&previous, &current, seconds);
+            if let Ok(mut history) = HISTORY.lock() {
+                history.get_or_insert_with(ResourceHistory::default).record(&sample);
+                NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+            }
+            previous = current;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_samples_from_consecutive_snapshots() {
+        let meminfo = "MemTotal:       4000 kB\nMemFree:         500 kB\nMemAvailable:   1000 kB\nSwapTotal:      2000 kB\nSwapFree:       1500 kB\n";
+        let memory = MemoryUsage::parse(meminfo).unwrap();
+        assert_eq!(memory, MemoryUsage { total: 4000 * 1024, used: 3000 * 1024, swap_total: 2000 * 1024, swap_used: 500 * 1024 });
+        assert_eq!(memory.memory_pct(), 75.0);
+        assert_eq!(memory.swap_pct(), 25.0);
+
+        let disks = parse_diskstats("   8       0 sda 100 0 2048 10 50 0 4096 20 0 30 30\n");
+        assert_eq!(disks, vec![DiskCounters { name: "sda".to_string(), read_bytes: 2048 * 512, write_bytes: 4096 * 512 }]);
+
+        let net = |rx: u64, tx: u64| {
+            let content = format!(
+                "Inter-|   Receive\n face |bytes\n  eth0:{} 10 0 0 0 0 0 0 {} 20 0 0 0 0 0 0\n",
+                rx, tx
+            );
+            NetworkStats::parse_all(&content)
+        };
+        let stat = |busy: u64, idle: u64| format!("cpu  {b} 0 0 {i} 0\ncpu0 {b} 0 0 {i} 0\n", b = busy, i = idle);
+        let snapshot = |busy: u64, rx: u64, read_sectors: u64| Snapshot {
+            taken_at: None,
+            cpu: CpuTimes::parse(&stat(busy, busy)),
+            cores: CpuTimes::parse_per_cpu(&stat(busy, busy)),
+            memory: Some(memory),
+            disks: parse_diskstats(&format!("8 0 sda 0 0 {} 0 0 0 0 0 0 0 0\n", read_sectors)),
+            interfaces: net(rx, 0),
+        };
+
+        let before = snapshot(100, 1000, 0);
+        let after = snapshot(200, 3000, 8);
+        let sample = ResourceSample::between(&before, &after, 2.0);
+        assert!((sample.cpu_pct - 50.0).abs() < 1e-9);
+        assert_eq!(sample.cores_pct.len(), 1);
+        assert_eq!(sample.interfaces, vec![("eth0".to_string(), 1000.0, 0.0)]);
+        assert_eq!(sample.disks, vec![("sda".to_string(), 8.0 * 512.0 / 2.0, 0.0)]);
+
+        let mut history = ResourceHistory::default();
+        for _ in 0..HISTORY_LEN + 5 {
+            history.record(&sample);
+        }
+        assert_eq!(history.cpu.values().count(), HISTORY_LEN);
+        assert!(history.interfaces[0].is_active());
+        history.record(&ResourceSample { interfaces: Vec::new(), ..sample });
+        assert!(history.interfaces.is_empty());
+        assert_eq!(format_rate(1536.0), "1.5 KB/s");
+    }
+}
@@ -0,0 +1,209 @@
+// src/handlers/route_prefix.rs
+// 路由跳点的 ASN/运营商标注：内置国内三大运营商骨干网、精品网和国际出口的地址段，
+// 配置目录中的 route_prefixes.toml 可以补充或覆盖，按最长前缀匹配
+use std::net::IpAddr;
+use serde::Deserialize;
+
+use crate::config;
+
+/// 地址段，如 59.43.0.0/16
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Prefix {
+    network: IpAddr,
+    len: u8,
+}
+
+impl Prefix {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let (addr, len) = value.trim().split_once('/').ok_or_else(|| format!("地址段缺少前缀长度: {}", value))?;
+        let network: IpAddr = addr.parse().map_err(|_| format!("地址无效: {}", value))?;
+        let max_len = if network.is_ipv4() { 32 } else { 128 };
+        let len: u8 = len.parse().ok().filter(|&l| l <= max_len).ok_or_else(|| format!("前缀长度无效: {}", value))?;
+        Ok(Self { network, len })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.len as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.len as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// 跳点所属的网络
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Carrier {
+    pub asn: Option<u32>,  // 内网等没有 ASN
+    pub label: String,
+}
+
+impl Carrier {
+    /// 例如 "AS4809 电信 CN2"
+    pub fn tag(&self) -> String {
+        match self.asn {
+            Some(asn) => format!("AS{} {}", asn, self.label),
+            None => self.label.clone(),
+        }
+    }
+}
+
+// 内置地址段，只收录判断线路类型最常用的骨干网段
+const BUILTIN_PREFIXES: &[(&str, Option<u32>, &str)] = &[
+    ("202.97.0.0/16", Some(4134), "电信 163 骨干"),
+    ("59.43.0.0/16", Some(4809), "电信 CN2"),
+    ("219.158.0.0/16", Some(4837), "联通 169 骨干"),
+    ("218.105.0.0/16", Some(9929), "联通 9929"),
+    ("210.51.0.0/16", Some(9929), "联通 9929"),
+    ("221.183.0.0/16", Some(9808), "移动 CMNET 骨干"),
+    ("223.118.0.0/15", Some(58453), "移动 CMI"),
+    ("223.120.0.0/16", Some(58453), "移动 CMI"),
+    ("101.4.0.0/14", Some(4538), "教育网 CERNET"),
+    ("240e::/20", Some(4134), "中国电信"),
+    ("2408:8000::/20", Some(4837), "中国联通"),
+    ("2409:8000::/20", Some(9808), "中国移动"),
+    ("10.0.0.0/8", None, "内网"),
+    ("172.16.0.0/12", None, "内网"),
+    ("192.168.0.0/16", None, "内网"),
+    ("100.64.0.0/10", None, "运营商级 NAT"),
+    ("fc00::/7", None, "内网"),
+];
+
+/// 地址段表，前缀长度相同时靠前的条目优先
+#[derive(Debug, Clone, Default)]
+pub struct PrefixTable {
+    entries: Vec<(Prefix, Carrier)>,
+}
+
+impl PrefixTable {
+    pub fn builtin() -> Self {
+        let entries = BUILTIN_PREFIXES
+            .iter()
+            .map(|&(prefix, asn, label)| {
+                let prefix = Prefix::parse(prefix).expect("内置地址段格式正确");
+                (prefix, Carrier { asn, label: label.to_string() })
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// 最长前缀匹配
+    pub fn lookup(&self, ip: IpAddr) -> Option<&Carrier> {
+        self.entries
+            .iter()
+            .filter(|(prefix, _)| prefix.contains(ip))
+            .fold(None, |best: Option<&(Prefix, Carrier)>, entry| match best {
+                Some(best) if best.0.len >= entry.0.len => Some(best),
+                _ => Some(entry),
+            })
+            .map(|(_, carrier)| carrier)
+    }
+}
+
+// 配置文件格式，所有字段可选以便逐项给出校验错误
+#[derive(Debug, Deserialize)]
+struct PrefixFile {
+    #[serde(default)]
+    prefixes: Vec<PrefixEntry>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PrefixEntry {
+    prefix: Option<String>,
+    asn: Option<u32>,
+    label: Option<String>,
+}
+
+/// 读取配置目录中的 route_prefixes.toml / route_prefixes.json，排在内置地址段之前；
+/// 返回地址段表和逐项的配置错误
+pub fn load_prefix_table() -> (PrefixTable, Vec<String>) {
+    let builtin = PrefixTable::builtin();
+    let Some(path) = config::find_config_file(&["route_prefixes.toml", "route_prefixes.json"]) else {
+        return (builtin, Vec::new());
+    };
+    let file: PrefixFile = match config::load_config_file(&path) {
+        Ok(file) => file,
+        Err(e) => return (builtin, vec![e]),
+    };
+    let (mut table, errors) = table_from_entries(file.prefixes);
+    table.entries.extend(builtin.entries);
+    (table, errors)
+}
+
+fn table_from_entries(entries: Vec<PrefixEntry>) -> (PrefixTable, Vec<String>) {
+    let mut table = PrefixTable::default();
+    let mut errors = Vec::new();
+    for (index, entry) in entries.into_iter().enumerate() {
+        let parsed = entry
+            .prefix
+            .as_deref()
+            .ok_or_else(|| "缺少 prefix".to_string())
+            .and_then(Prefix::parse)
+            .and_then(|prefix| match entry.label.filter(|l| !l.trim().is_empty()) {
+                Some(label) => Ok((prefix, Carrier { asn: entry.asn, label })),
+                None => Err("缺少 label".to_string()),
+            });
+        match parsed {
+            Ok(item) => table.entries.push(item),
+            Err(e) => errors.push(format!("route_prefixes 第 {} 项: {}", index + 1, e)),
+        }
+    }
+    (table, errors)
+}
+
+/// 按经过的骨干网推断线路类型，例如只经过 CN2 不经过 163 的为 CN2 GIA
+pub fn classify_route<'a>(carriers: impl IntoIterator<Item = &'a Carrier>) -> Option<&'static str> {
+    let asns: Vec<u32> = carriers.into_iter().filter_map(|carrier| carrier.asn).collect();
+    let has = |asn: u32| asns.contains(&asn);
+    if has(4809) {
+        Some(if has(4134) { "CN2 GT" } else { "CN2 GIA" })
+    } else if has(9929) {
+        Some("联通 9929")
+    } else if has(4837) {
+        Some("联通 4837")
+    } else if has(58453) {
+        Some("移动 CMI")
+    } else if has(9808) {
+        Some("移动 CMNET")
+    } else if has(4134) {
+        Some("电信 163")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_and_route_classification() {
+        let (mut table, errors) = table_from_entries(vec![
+            PrefixEntry { prefix: Some("59.43.18.0/24".to_string()), asn: Some(64500), label: Some("自定义".to_string()) },
+            PrefixEntry { prefix: Some("59.43.0.0/33".to_string()), asn: None, label: Some("x".to_string()) },
+            PrefixEntry { prefix: Some("203.0.113.0/24".to_string()), asn: None, label: None },
+        ]);
+        assert_eq!(errors.len(), 2);
+        assert!(errors[0].contains("第 2 项") && errors[1].contains("label"));
+        table.entries.extend(PrefixTable::builtin().entries);
+
+        let lookup = |ip: &str| table.lookup(ip.parse().unwrap()).map(|c| c.tag());
+        assert_eq!(lookup("59.43.18.1").as_deref(), Some("AS64500 自定义"));
+        assert_eq!(lookup("59.43.246.1").as_deref(), Some("AS4809 电信 CN2"));
+        assert_eq!(lookup("240e:1:2::1").as_deref(), Some("AS4134 中国电信"));
+        assert_eq!(lookup("192.168.1.1").as_deref(), Some("内网"));
+        assert_eq!(lookup("8.8.8.8"), None);
+
+        let carrier = |asn: u32| Carrier { asn: Some(asn), label: String::new() };
+        assert_eq!(classify_route(&[carrier(4809)]), Some("CN2 GIA"));
+        assert_eq!(classify_route(&[carrier(4134), carrier(4809)]), Some("CN2 GT"));
+        assert_eq!(classify_route(&[carrier(4837)]), Some("联通 4837"));
+        assert_eq!(classify_route(&[]), None);
+    }
+}
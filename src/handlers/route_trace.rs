@@ -0,0 +1,744 @@
+// src/handlers/route_trace.rs
+// 路由追踪：类似 mtr，每一轮同时发出 TTL 从 1 到最大跳数的探测，由途经路由器回应的 ICMP 超时报文得到每一跳的地址和延迟，
+// 多轮之后统计每一跳的丢包率和延迟分布。探测优先使用 ICMP 数据报套接字，不可用时改用 UDP（目标端口 33434 起），
+// 两者都不需要 root：打开 IP_RECVERR 后内核把 ICMP 差错报文放进套接字的错误队列，其中带有回应者的地址
+use std::io;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::os::fd::AsRawFd;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cancel::{CancelToken, Cancelled, TestControl};
+use super::network_test::{self, PingTarget};
+use super::ping;
+use super::route_prefix::{self, Carrier, PrefixTable};
+
+/// 最大跳数
+pub const MAX_HOPS: u8 = 30;
+
+/// 单次探测的等待时间，超时计为丢包
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 相邻两轮开始的最小间隔
+pub const ROUND_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 界面中可切换的轮数
+pub const ROUND_COUNTS: [usize; 4] = [5, 10, 20, 50];
+
+// 传统 traceroute 的 UDP 目标端口起点，通常没有服务监听，目标会回应端口不可达
+const UDP_BASE_PORT: u16 = 33434;
+const UDP_PAYLOAD_SIZE: usize = 32;
+
+/// 探测方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeMethod {
+    Icmp,
+    Udp,
+}
+
+impl ProbeMethod {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProbeMethod::Icmp => "ICMP",
+            ProbeMethod::Udp => "UDP",
+        }
+    }
+
+    /// 当前用户能打开 ICMP 数据报套接字时使用 ICMP
+    pub fn detect(address: IpAddr) -> Self {
+        match ping::icmp_socket(address) {
+            Ok(_) => ProbeMethod::Icmp,
+            Err(_) => ProbeMethod::Udp,
+        }
+    }
+}
+
+/// 回应的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyKind {
+    TimeExceeded,  // 途经路由器：TTL 耗尽
+    Destination,   // 到达目标：回显应答或端口不可达
+    Unreachable,   // 路由器报告目标不可达，更远的跳点不会再有回应
+}
+
+/// 由 ICMP 差错报文的类型和代码判断回应类型
+pub fn classify_icmp_error(ipv6: bool, icmp_type: u8, code: u8) -> Option<ReplyKind> {
+    match (ipv6, icmp_type, code) {
+        (false, 11, _) | (true, 3, _) => Some(ReplyKind::TimeExceeded),
+        (false, 3, 3) | (true, 1, 4) => Some(ReplyKind::Destination),
+        (false, 3, _) | (true, 1, _) => Some(ReplyKind::Unreachable),
+        _ => None,
+    }
+}
+
+/// 一轮探测中某一跳的回应
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HopReply {
+    pub ttl: u8,
+    pub responder: IpAddr,
+    pub rtt_ms: f64,
+    pub kind: ReplyKind,
+}
+
+struct Probe {
+    ttl: u8,
+    socket: UdpSocket,
+    sent_at: Instant,
+    done: bool,
+}
+
+/// 对 TTL 1..=max_ttl 各发出一个探测，等待最多 wait，返回收到的回应（按 TTL 排序）
+///
+/// 每个探测使用独立的套接字，内核按套接字分发差错报文，不需要自己匹配报文内容
+pub fn probe_round(dest: IpAddr, method: ProbeMethod, max_ttl: u8, wait: Duration) -> io::Result<Vec<HopReply>> {
+    let mut probes = Vec::with_capacity(max_ttl as usize);
+    for ttl in 1..=max_ttl {
+        let socket = open_probe_socket(dest, method, ttl)?;
+        let sent_at = Instant::now();
+        // 本机立即报错（如没有路由）时这一跳计为丢失
+        if send_probe(&socket, dest, method, ttl).is_ok() {
+            probes.push(Probe { ttl, socket, sent_at, done: false });
+        }
+    }
+
+    let deadline = Instant::now() + wait;
+    let mut replies = Vec::new();
+    loop {
+        let pending: Vec<usize> = (0..probes.len()).filter(|&i| !probes[i].done).collect();
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if pending.is_empty() || remaining.is_zero() {
+            break;
+        }
+        let mut fds: Vec<libc::pollfd> = pending
+            .iter()
+            .map(|&i| libc::pollfd { fd: probes[i].socket.as_raw_fd(), events: libc::POLLIN, revents: 0 })
+            .collect();
+        // SAFETY: fds 在调用期间有效，长度与传入的数量一致
+        let ready = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, remaining.as_millis().max(1) as libc::c_int) };
+        if ready < 0 {
+            let error = io::Error::last_os_error();
+            if error.kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return Err(error);
+        }
+        let now = Instant::now();
+        for (fd, &index) in fds.iter().zip(&pending) {
+            if fd.revents == 0 {
+                continue;
+            }
+            let probe = &mut probes[index];
+            if let Some((responder, kind)) = read_reply(&probe.socket, dest, method, probe.ttl) {
+                replies.push(HopReply {
+                    ttl: probe.ttl,
+                    responder,
+                    rtt_ms: now.duration_since(probe.sent_at).as_secs_f64() * 1000.0,
+                    kind,
+                });
+                probe.done = true;
+            }
+        }
+    }
+
+    replies.sort_by_key(|reply| reply.ttl);
+    Ok(replies)
+}
+
+fn open_probe_socket(dest: IpAddr, method: ProbeMethod, ttl: u8) -> io::Result<UdpSocket> {
+    let socket = match method {
+        ProbeMethod::Icmp => ping::icmp_socket(dest)?,
+        ProbeMethod::Udp => {
+            let local: IpAddr = if dest.is_ipv4() { Ipv4Addr::UNSPECIFIED.into() } else { Ipv6Addr::UNSPECIFIED.into() };
+            let socket = UdpSocket::bind(SocketAddr::new(local, 0))?;
+            socket.set_nonblocking(true)?;
+            socket
+        }
+    };
+    let (level, ttl_option, recverr_option) = if dest.is_ipv4() {
+        (libc::SOL_IP, libc::IP_TTL, libc::IP_RECVERR)
+    } else {
+        (libc::SOL_IPV6, libc::IPV6_UNICAST_HOPS, libc::IPV6_RECVERR)
+    };
+    set_int_option(&socket, level, ttl_option, ttl as libc::c_int)?;
+    set_int_option(&socket, level, recverr_option, 1)?;
+    Ok(socket)
+}
+
+fn set_int_option(socket: &UdpSocket, level: libc::c_int, name: libc::c_int, value: libc::c_int) -> io::Result<()> {
+    // SAFETY: value 在调用期间有效，长度与类型一致
+    let result = unsafe {
+        libc::setsockopt(
+            socket.as_raw_fd(),
+            level,
+            name,
+            (&value as *const libc::c_int).cast(),
+            mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+    if result < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+fn send_probe(socket: &UdpSocket, dest: IpAddr, method: ProbeMethod, ttl: u8) -> io::Result<usize> {
+    match method {
+        ProbeMethod::Icmp => {
+            let (request_type, _) = ping::echo_types(dest);
+            socket.send_to(&ping::echo_request(request_type, ttl as u16), SocketAddr::new(dest, 0))
+        }
+        ProbeMethod::Udp => socket.send_to(&[0u8; UDP_PAYLOAD_SIZE], SocketAddr::new(dest, UDP_BASE_PORT + ttl as u16)),
+    }
+}
+
+// 先读错误队列中的 ICMP 差错报文，再读普通数据（ICMP 回显应答，或目标上恰好有服务回应了 UDP）
+fn read_reply(socket: &UdpSocket, dest: IpAddr, method: ProbeMethod, ttl: u8) -> Option<(IpAddr, ReplyKind)> {
+    if let Some((responder, icmp_type, code)) = read_icmp_error(socket) {
+        return classify_icmp_error(dest.is_ipv6(), icmp_type, code).map(|kind| (responder, kind));
+    }
+    let mut buf = [0u8; 1500];
+    let len = socket.recv(&mut buf).ok()?;
+    match method {
+        ProbeMethod::Icmp => {
+            let (_, reply_type) = ping::echo_types(dest);
+            (ping::parse_echo_reply(&buf[..len], reply_type) == Some(ttl as u16)).then_some((dest, ReplyKind::Destination))
+        }
+        ProbeMethod::Udp => Some((dest, ReplyKind::Destination)),
+    }
+}
+
+// 从错误队列取出一条 ICMP 差错，返回回应者地址、ICMP 类型和代码
+fn read_icmp_error(socket: &UdpSocket) -> Option<(IpAddr, u8, u8)> {
+    let mut data = [0u8; 512];
+    let mut control = [0u8; 512];
+    let mut iov = libc::iovec { iov_base: data.as_mut_ptr().cast(), iov_len: data.len() };
+    // SAFETY: 全零的 msghdr 是合法的初始值，iov 和 control 在 recvmsg 调用期间有效
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr().cast();
+    msg.msg_controllen = control.len() as _;
+
+    // SAFETY: 同上；控制消息按 CMSG_* 宏遍历，不会越过 msg_controllen
+    unsafe {
+        if libc::recvmsg(socket.as_raw_fd(), &mut msg, libc::MSG_ERRQUEUE | libc::MSG_DONTWAIT) < 0 {
+            return None;
+        }
+        let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+        while !cmsg.is_null() {
+            let header = std::ptr::read_unaligned(cmsg);
+            let is_error = (header.cmsg_level == libc::SOL_IP && header.cmsg_type == libc::IP_RECVERR)
+                || (header.cmsg_level == libc::SOL_IPV6 && header.cmsg_type == libc::IPV6_RECVERR);
+            if is_error {
+                let error_ptr = libc::CMSG_DATA(cmsg) as *const libc::sock_extended_err;
+                let error = std::ptr::read_unaligned(error_ptr);
+                if error.ee_origin == libc::SO_EE_ORIGIN_ICMP || error.ee_origin == libc::SO_EE_ORIGIN_ICMP6 {
+                    let responder = sockaddr_ip(libc::SO_EE_OFFENDER(error_ptr))?;
+                    return Some((responder, error.ee_type, error.ee_code));
+                }
+            }
+            cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+        }
+    }
+    None
+}
+
+// SAFETY: addr 指向错误队列控制消息中紧跟 sock_extended_err 的 sockaddr
+unsafe fn sockaddr_ip(addr: *const libc::sockaddr) -> Option<IpAddr> {
+    match std::ptr::read_unaligned(addr).sa_family as libc::c_int {
+        libc::AF_INET => {
+            let addr = std::ptr::read_unaligned(addr as *const libc::sockaddr_in);
+            Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr))))
+        }
+        libc::AF_INET6 => {
+            let addr = std::ptr::read_unaligned(addr as *const libc::sockaddr_in6);
+            Some(IpAddr::V6(Ipv6Addr::from(addr.sin6_addr.s6_addr)))
+        }
+        _ => None,
+    }
+}
+
+/// 一跳的多轮统计
+#[derive(Debug, Clone, Default)]
+pub struct HopStats {
+    pub ttl: u8,
+    pub addresses: Vec<(IpAddr, Option<Carrier>)>,  // 按首次出现的顺序，负载均衡时同一跳会有多个地址
+    pub sent: usize,
+    pub rtts: Vec<f64>,  // ms
+    pub last_ms: Option<f64>,
+    pub unreachable: bool,  // 该跳报告过目标不可达
+}
+
+impl HopStats {
+    pub fn received(&self) -> usize {
+        self.rtts.len()
+    }
+
+    pub fn loss_pct(&self) -> f64 {
+        if self.sent == 0 {
+            return 0.0;
+        }
+        (self.sent - self.received().min(self.sent)) as f64 / self.sent as f64 * 100.0
+    }
+
+    pub fn avg_ms(&self) -> Option<f64> {
+        (!self.rtts.is_empty()).then(|| self.rtts.iter().sum::<f64>() / self.rtts.len() as f64)
+    }
+
+    pub fn best_ms(&self) -> Option<f64> {
+        self.rtts.iter().copied().reduce(f64::min)
+    }
+
+    pub fn worst_ms(&self) -> Option<f64> {
+        self.rtts.iter().copied().reduce(f64::max)
+    }
+
+    /// 标准差，反映该跳延迟的波动
+    pub fn stdev_ms(&self) -> Option<f64> {
+        let avg = self.avg_ms()?;
+        let variance = self.rtts.iter().map(|rtt| (rtt - avg).powi(2)).sum::<f64>() / self.rtts.len() as f64;
+        Some(variance.sqrt())
+    }
+
+    /// 第一个有标注的地址所属网络
+    pub fn carrier(&self) -> Option<&Carrier> {
+        self.addresses.iter().find_map(|(_, carrier)| carrier.as_ref())
+    }
+}
+
+/// 到一个目标的路由追踪结果
+#[derive(Debug, Clone)]
+pub struct RouteTrace {
+    pub name: String,  // 服务器名称
+    pub target: PingTarget,
+    pub address: Option<IpAddr>,
+    pub method: Option<ProbeMethod>,
+    pub hops: Vec<HopStats>,
+    pub rounds: usize,
+    pub dest_ttl: Option<u8>,  // 到达目标（或被报告不可达）的跳数
+    pub error: Option<String>,
+}
+
+impl RouteTrace {
+    pub fn new(name: String, target: PingTarget) -> Self {
+        Self {
+            name,
+            target,
+            address: None,
+            method: None,
+            hops: Vec::new(),
+            rounds: 0,
+            dest_ttl: None,
+            error: None,
+        }
+    }
+
+    /// 解析目标地址并选择探测方式，失败时记入 error
+    pub fn prepare(&mut self) {
+        match resolve(&self.target) {
+            Ok(address) => {
+                self.address = Some(address);
+                self.method = Some(ProbeMethod::detect(address));
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    /// 可以继续探测
+    pub fn is_ready(&self) -> bool {
+        self.address.is_some() && self.method.is_some() && self.error.is_none()
+    }
+
+    /// 本轮探测的最大 TTL：到达目标后只探测到目标为止
+    pub fn probe_ttl(&self) -> u8 {
+        self.dest_ttl.unwrap_or(MAX_HOPS)
+    }
+
+    /// 已到达目标
+    pub fn reached(&self) -> bool {
+        self.dest_ttl.is_some() && self.hops.last().is_some_and(|hop| !hop.unreachable)
+    }
+
+    /// 记入一轮探测，probed 为本轮探测的最大 TTL
+    pub fn record_round(&mut self, probed: u8, replies: &[HopReply], table: &PrefixTable) {
+        self.rounds += 1;
+
+        // TTL 超过目标跳数的探测同样由目标回应，只保留最近的一跳
+        if let Some(terminal) = replies.iter().filter(|r| r.kind != ReplyKind::TimeExceeded).map(|r| r.ttl).min() {
+            self.dest_ttl = Some(self.dest_ttl.map_or(terminal, |ttl| ttl.min(terminal)));
+        }
+        let last = self.dest_ttl.unwrap_or(probed).min(probed);
+        self.hops.truncate(last as usize);
+        while self.hops.len() < last as usize {
+            self.hops.push(HopStats { ttl: self.hops.len() as u8 + 1, ..Default::default() });
+        }
+
+        for hop in &mut self.hops {
+            hop.sent += 1;
+            match replies.iter().find(|reply| reply.ttl == hop.ttl) {
+                Some(reply) => {
+                    hop.rtts.push(reply.rtt_ms);
+                    hop.last_ms = Some(reply.rtt_ms);
+                    hop.unreachable |= reply.kind == ReplyKind::Unreachable;
+                    if !hop.addresses.iter().any(|(address, _)| *address == reply.responder) {
+                        hop.addresses.push((reply.responder, table.lookup(reply.responder).cloned()));
+                    }
+                }
+                None => hop.last_ms = None,
+            }
+        }
+    }
+
+    /// 要显示的跳点：未到达目标时末尾连续无回应的跳点只保留一行
+    pub fn visible_hops(&self) -> &[HopStats] {
+        if self.dest_ttl.is_some() {
+            return &self.hops;
+        }
+        let responding = self.hops.iter().rposition(|hop| hop.received() > 0).map_or(0, |index| index + 1);
+        &self.hops[..(responding + 1).min(self.hops.len())]
+    }
+
+    /// 按经过的骨干网推断的线路类型
+    pub fn route_label(&self) -> Option<&'static str> {
+        route_prefix::classify_route(
+            self.hops.iter().flat_map(|hop| hop.addresses.iter().filter_map(|(_, carrier)| carrier.as_ref())),
+        )
+    }
+}
+
+fn resolve(target: &PingTarget) -> Result<IpAddr, String> {
+    (target.host.as_str(), 0)
+        .to_socket_addrs()
+        .map_err(|e| format!("无法解析 {}: {}", target.host, e))?
+        .find(|addr| target.protocol.accepts(addr))
+        .map(|addr| addr.ip())
+        .ok_or_else(|| format!("{} 没有符合协议要求的地址", target.host))
+}
+
+/// 对所有可以探测的目标并行执行一轮探测并记入统计，探测出错的目标记入 error 后不再探测
+pub fn trace_round(traces: &mut [RouteTrace], table: &PrefixTable) {
+    let results: Vec<Option<io::Result<Vec<HopReply>>>> = thread::scope(|scope| {
+        let handles: Vec<_> = traces
+            .iter()
+            .map(|trace| {
+                let (Some(address), Some(method)) = (trace.address, trace.method) else {
+                    return None;
+                };
+                let max_ttl = trace.probe_ttl();
+                trace.is_ready().then(|| scope.spawn(move || probe_round(address, method, max_ttl, PROBE_TIMEOUT)))
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.map(|h| h.join().unwrap_or_else(|_| Err(io::Error::other("探测线程异常退出")))))
+            .collect()
+    });
+
+    for (trace, result) in traces.iter_mut().zip(results) {
+        match result {
+            Some(Ok(replies)) => {
+                let probed = trace.probe_ttl();
+                trace.record_round(probed, &replies, table);
+            }
+            Some(Err(e)) => trace.error = Some(format!("探测失败: {}", e)),
+            None => {}
+        }
+    }
+}
+
+/// 路由追踪状态
+#[derive(Debug, Clone, Default)]
+pub struct RouteTraceInfo {
+    pub is_testing: bool,
+    pub current_stage: String,
+    pub round: usize,   // 已完成的轮数
+    pub rounds: usize,  // 总轮数
+    pub traces: Vec<RouteTrace>,
+    pub error_message: Option<String>,  // 服务器列表或地址段配置的错误
+    pub start_time: Option<Instant>,
+}
+
+// 全局状态管理
+static ROUTE_TRACE_INFO: Mutex<Option<RouteTraceInfo>> = Mutex::new(None);
+static NEEDS_UI_REFRESH: AtomicBool = AtomicBool::new(false);
+static CONTROL: TestControl = TestControl::new();
+static ROUNDS: AtomicUsize = AtomicUsize::new(10);
+
+/// 获取路由追踪信息
+pub fn get_info() -> String {
+    format_route_trace_info(&get_current_test_info())
+}
+
+/// 获取当前追踪状态
+pub fn get_current_test_info() -> RouteTraceInfo {
+    ROUTE_TRACE_INFO.lock().ok().and_then(|info| info.clone()).unwrap_or_default()
+}
+
+/// 每个目标的探测轮数
+pub fn rounds() -> usize {
+    ROUNDS.load(Ordering::Relaxed)
+}
+
+/// 切换探测轮数，追踪进行中不能修改
+pub fn cycle_rounds() -> Result<usize, String> {
+    if CONTROL.is_running() {
+        return Err("路由追踪进行中，完成或按 X 取消后才能修改轮数".to_string());
+    }
+    let current = rounds();
+    let next = ROUND_COUNTS.iter().copied().find(|&count| count > current).unwrap_or(ROUND_COUNTS[0]);
+    ROUNDS.store(next, Ordering::Relaxed);
+    Ok(next)
+}
+
+/// 对服务器列表中每台服务器的 ping_host 开始路由追踪，上一次的结果会被清除
+pub fn start_route_trace() -> Result<(), String> {
+    if CONTROL.is_running() {
+        return Err("路由追踪进行中，完成或按 X 取消后才能重新开始".to_string());
+    }
+
+    let provider_list = network_test::reload_network_providers();
+    let (table, prefix_errors) = route_prefix::load_prefix_table();
+    let mut errors: Vec<String> = provider_list.errors.into_iter().chain(prefix_errors).collect();
+    let traces: Vec<RouteTrace> = provider_list
+        .providers
+        .iter()
+        .filter_map(|provider| match provider.ping_target() {
+            Ok(target) => Some(RouteTrace::new(provider.name.clone(), target)),
+            Err(e) => {
+                errors.push(format!("{}: {}", provider.name, e));
+                None
+            }
+        })
+        .collect();
+    if traces.is_empty() {
+        return Err("服务器列表中没有可以追踪的目标".to_string());
+    }
+
+    let rounds = rounds();
+    if let Ok(mut info) = ROUTE_TRACE_INFO.lock() {
+        *info = Some(RouteTraceInfo {
+            is_testing: true,
+            current_stage: "解析目标地址...".to_string(),
+            rounds,
+            traces: traces.clone(),
+            error_message: (!errors.is_empty()).then(|| errors.join("; ")),
+            start_time: Some(Instant::now()),
+            ..Default::default()
+        });
+    }
+
+    let run = CONTROL.begin();
+    thread::spawn(move || {
+        let result = run_traces(traces, rounds, &table, &run);
+        finish(result);
+    });
+    Ok(())
+}
+
+/// 取消/暂停正在运行的路由追踪
+pub fn test_control() -> &'static TestControl {
+    &CONTROL
+}
+
+/// 检查是否需要刷新UI
+pub fn check_needs_refresh() -> bool {
+    NEEDS_UI_REFRESH.swap(false, Ordering::Relaxed)
+}
+
+// 内部函数实现
+
+fn run_traces(mut traces: Vec<RouteTrace>, rounds: usize, table: &PrefixTable, cancel: &CancelToken) -> Result<(), String> {
+    for trace in &mut traces {
+        trace.prepare();
+    }
+    update_info(|info| info.traces = traces.clone());
+    if !traces.iter().any(RouteTrace::is_ready) {
+        return Err("所有目标都无法追踪".to_string());
+    }
+
+    for round in 0..rounds {
+        if cancel.is_paused() {
+            update_info(|info| info.current_stage = "已暂停，按空格继续".to_string());
+        }
+        cancel.checkpoint().map_err(|e| e.to_string())?;
+        update_info(|info| info.current_stage = format!("第 {}/{} 轮探测", round + 1, rounds));
+
+        let started = Instant::now();
+        trace_round(&mut traces, table);
+        update_info(|info| {
+            info.traces = traces.clone();
+            info.round = round + 1;
+        });
+
+        if round + 1 < rounds {
+            thread::sleep(ROUND_INTERVAL.saturating_sub(started.elapsed()));
+        }
+    }
+    Ok(())
+}
+
+fn update_info(update: impl FnOnce(&mut RouteTraceInfo)) {
+    if let Ok(mut info) = ROUTE_TRACE_INFO.lock() {
+        if let Some(ref mut info) = info.as_mut() {
+            update(info);
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+// 追踪结束：取消或失败时保留已完成轮次的统计
+fn finish(result: Result<(), String>) {
+    update_info(|info| {
+        info.is_testing = false;
+        match result {
+            Ok(()) => info.current_stage = "追踪完成".to_string(),
+            Err(ref e) if *e == Cancelled.to_string() => info.current_stage = e.clone(),
+            Err(ref e) => {
+                info.current_stage = e.clone();
+                info.error_message = Some(e.clone());
+            }
+        }
+    });
+}
+
+/// 延迟单元格，没有数据时为 "-"
+pub fn format_ms(value: Option<f64>) -> String {
+    value.map(|ms| format!("{:.1}", ms)).unwrap_or_else(|| "-".to_string())
+}
+
+/// 格式化一个目标的追踪结果，每跳一行
+pub fn format_route_trace(trace: &RouteTrace) -> String {
+    let mut output = format!("{} ({}", trace.name, trace.target.host);
+    if let Some(address) = trace.address {
+        output.push_str(&format!(" → {}", address));
+    }
+    output.push(')');
+    if let Some(method) = trace.method {
+        output.push_str(&format!("  {}  {} 轮", method.name(), trace.rounds));
+    }
+    if let Some(label) = trace.route_label() {
+        output.push_str(&format!("  线路: {}", label));
+    }
+    output.push('\n');
+    if let Some(ref error) = trace.error {
+        output.push_str(&format!("  错误: {}\n", error));
+    }
+    if trace.hops.is_empty() {
+        return output;
+    }
+
+    // 中文字符占两列，表头直接按列宽写出
+    output.push_str(" 跳  地址                     网络                     丢包  发送   最近   平均   最好   最差  标准差\n");
+    for hop in trace.visible_hops() {
+        let address = match hop.addresses.first() {
+            Some((address, _)) if hop.addresses.len() > 1 => format!("{} (+{})", address, hop.addresses.len() - 1),
+            Some((address, _)) => address.to_string(),
+            None => "???".to_string(),
+        };
+        let carrier = hop.carrier().map(|carrier| carrier.tag()).unwrap_or_default();
+        output.push_str(&format!(
+            "{:>3}  {:<24} {:<width$} {:>5.1}% {:>4} {:>6} {:>6} {:>6} {:>6} {:>6}\n",
+            hop.ttl,
+            address,
+            carrier,
+            hop.loss_pct(),
+            hop.sent,
+            format_ms(hop.last_ms),
+            format_ms(hop.avg_ms()),
+            format_ms(hop.best_ms()),
+            format_ms(hop.worst_ms()),
+            format_ms(hop.stdev_ms()),
+            // 标注中的中文字符按两列计算
+            width = 24usize.saturating_sub(carrier.chars().filter(|c| !c.is_ascii()).count()),
+        ));
+    }
+    if trace.dest_ttl.is_none() && trace.rounds > 0 {
+        output.push_str(&format!("  未在 {} 跳内到达目标\n", MAX_HOPS));
+    }
+    output
+}
+
+fn format_route_trace_info(info: &RouteTraceInfo) -> String {
+    let mut output = String::from("=== 路由追踪 ===\n\n");
+    if let Some(ref error) = info.error_message {
+        output.push_str(&format!("错误: {}\n\n", error));
+    }
+    if info.traces.is_empty() {
+        output.push_str("尚未开始，在界面中按 Enter 追踪到每台测速服务器的路由\n");
+        return output;
+    }
+    for trace in &info.traces {
+        output.push_str(&format_route_trace(trace));
+        output.push('\n');
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::network_test::IpProtocol;
+
+    fn reply(ttl: u8, responder: &str, rtt_ms: f64, kind: ReplyKind) -> HopReply {
+        HopReply { ttl, responder: responder.parse().unwrap(), rtt_ms, kind }
+    }
+
+    #[test]
+    fn test_rounds_build_hop_statistics() {
+        assert_eq!(classify_icmp_error(false, 11, 0), Some(ReplyKind::TimeExceeded));
+        assert_eq!(classify_icmp_error(false, 3, 3), Some(ReplyKind::Destination));
+        assert_eq!(classify_icmp_error(true, 1, 0), Some(ReplyKind::Unreachable));
+        assert_eq!(classify_icmp_error(false, 0, 0), None);
+
+        let table = PrefixTable::builtin();
+        let target = PingTarget { host: "203.0.113.9".to_string(), port: 443, protocol: IpProtocol::Auto };
+        let mut trace = RouteTrace::new("测试".to_string(), target);
+
+        // 第一轮探测全部 30 跳，第 3 跳及更远的 TTL 都由目标回应
+        let mut first = vec![
+            reply(1, "192.168.1.1", 1.0, ReplyKind::TimeExceeded),
+            reply(2, "59.43.1.1", 10.0, ReplyKind::TimeExceeded),
+        ];
+        first.extend((3..=MAX_HOPS).map(|ttl| reply(ttl, "203.0.113.9", 20.0, ReplyKind::Destination)));
+        trace.record_round(MAX_HOPS, &first, &table);
+        assert_eq!(trace.dest_ttl, Some(3));
+        assert_eq!(trace.hops.len(), 3);
+        assert!(trace.reached());
+
+        // 第二轮第 2 跳丢失，换了一个负载均衡地址的第 1 跳
+        let second = [
+            reply(1, "192.168.1.2", 3.0, ReplyKind::TimeExceeded),
+            reply(3, "203.0.113.9", 24.0, ReplyKind::Destination),
+        ];
+        trace.record_round(trace.probe_ttl(), &second, &table);
+        let hops = trace.visible_hops();
+        assert_eq!(hops[0].addresses.len(), 2);
+        assert_eq!((hops[0].best_ms(), hops[0].worst_ms(), hops[0].avg_ms()), (Some(1.0), Some(3.0), Some(2.0)));
+        assert_eq!(hops[0].stdev_ms(), Some(1.0));
+        assert_eq!((hops[1].sent, hops[1].loss_pct(), hops[1].last_ms), (2, 50.0, None));
+        assert_eq!(hops[1].carrier().map(|c| c.asn), Some(Some(4809)));
+        assert_eq!(trace.route_label(), Some("CN2 GIA"));
+
+        // 未到达目标时末尾无回应的跳点只显示一行
+        let mut lost = RouteTrace::new("丢失".to_string(), trace.target.clone());
+        lost.record_round(MAX_HOPS, &[reply(1, "192.168.1.1", 1.0, ReplyKind::TimeExceeded)], &table);
+        assert_eq!(lost.hops.len(), MAX_HOPS as usize);
+        assert_eq!(lost.visible_hops().len(), 2);
+        assert!(!lost.reached());
+        assert!(format_route_trace(&lost).contains("???"));
+    }
+
+    #[test]
+    fn test_probe_round_reaches_localhost() {
+        // 本机回应 ICMP 回显或 UDP 端口不可达，第 1 跳即到达目标
+        let dest: IpAddr = Ipv4Addr::LOCALHOST.into();
+        let method = ProbeMethod::detect(dest);
+        let replies = probe_round(dest, method, 2, Duration::from_secs(1)).unwrap();
+        assert!(!replies.is_empty());
+        assert!(replies.iter().all(|r| r.kind == ReplyKind::Destination && r.responder == dest));
+    }
+}
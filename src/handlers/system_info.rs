@@ -13,13 +13,16 @@ static NEEDS_UI_REFRESH: AtomicBool = AtomicBool::new(false);
 #[derive(Debug, Clone)]
 pub struct BasicSystemInfo {
     pub uptime: String,
+    pub uptime_seconds: u64,
     pub hostname: String,
     pub cpu_model: String,
     pub cpu_arch: String,
     pub cpu_cores: usize,
     pub cpu_frequency: String,
+    pub cpu_frequency_mhz: u64,
     pub cpu_usage: f32,
     pub load_avg: String,
+    pub load_average: [f64; 3],
     pub memory_total: u64,
     pub memory_used: u64,
     pub swap_total: u64,
@@ -56,6 +59,37 @@ impl Default for NetworkStats {
     }
 }
 
+impl NetworkStats {
+    /// 读取 /proc/net/dev 中所有接口的累计流量
+    pub fn read_all() -> Vec<NetworkStats> {
+        std::fs::read_to_string("/proc/net/dev")
+            .map(|content| Self::parse_all(&content))
+            .unwrap_or_default()
+    }
+
+    pub fn parse_all(content: &str) -> Vec<NetworkStats> {
+        content
+            .lines()
+            .skip(2)
+            .filter_map(|line| {
+                // 计数较大时接口名和第一个字段之间可能没有空格，如 "eth0:123"
+                let (iface, counters) = line.split_once(':')?;
+                let parts: Vec<&str> = counters.split_whitespace().collect();
+                if parts.len() < 16 {
+                    return None;
+                }
+                Some(NetworkStats {
+                    interface_name: iface.trim().to_string(),
+                    rx_bytes: parts[0].parse().unwrap_or(0),
+                    rx_packets: parts[1].parse().unwrap_or(0),
+                    tx_bytes: parts[8].parse().unwrap_or(0),
+                    tx_packets: parts[9].parse().unwrap_or(0),
+                })
+            })
+            .collect()
+    }
+}
+
 // 磁盘信息
 #[derive(Debug, Clone)]
 pub struct DiskInfo {
@@ -148,9 +182,12 @@ impl SystemInfo {
         let cpu_usage = sys.global_cpu_usage();
         let cpu_arch = Self::get_cpu_arch();
         let cpu_frequency = Self::get_cpu_frequency();
+        let cpu_frequency_mhz = sys.cpus().first().map(|cpu| cpu.frequency()).unwrap_or(0);
 
         // 负载信息
         let load_avg = Self::get_load_average();
+        let load = System::load_average();
+        let load_average = [load.one, load.five, load.fifteen];
 
         // 内存信息
         let memory_total = sys.total_memory();
@@ -175,17 +212,21 @@ impl SystemInfo {
         let kernel = System::kernel_version().unwrap_or_else(|| "Unknown".to_string());
         let distro = Self::get_distro();
         let vm_type = Self::get_vm_type();
-        let uptime = Self::format_uptime(System::uptime());
+        let uptime_seconds = System::uptime();
+        let uptime = Self::format_uptime(uptime_seconds);
 
         BasicSystemInfo {
             uptime,
+            uptime_seconds,
             hostname,
             cpu_model,
             cpu_arch,
             cpu_cores,
             cpu_frequency,
+            cpu_frequency_mhz,
             cpu_usage,
             load_avg,
+            load_average,
             memory_total,
             memory_used,
             swap_total,
@@ -271,7 +312,7 @@ impl SystemInfo {
     }
 
     // 格式化运行时间
-    fn format_uptime(uptime_seconds: u64) -> String {
+    pub fn format_uptime(uptime_seconds: u64) -> String {
         let days = uptime_seconds / 86400;
         let hours = (uptime_seconds % 86400) / 3600;
         let minutes = (uptime_seconds % 3600) / 60;
@@ -521,25 +562,13 @@ impl SystemInfo {
         let interface_name = Self::get_default_interface();
         
         // 读取 /proc/net/dev 获取网络统计
-        if let Ok(content) = std::fs::read_to_string("/proc/net/dev") {
-            for line in content.lines().skip(2) {
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 17 {
-                    let iface = parts[0].trim_end_matches(':');
-                    if iface == interface_name || (interface_name == "Unknown" && !iface.starts_with("lo")) {
-                        return NetworkStats {
-                            interface_name: iface.to_string(),
-                            rx_bytes: parts[1].parse().unwrap_or(0),
-                            rx_packets: parts[2].parse().unwrap_or(0),
-                            tx_bytes: parts[9].parse().unwrap_or(0),
-                            tx_packets: parts[10].parse().unwrap_or(0),
-                        };
-                    }
-                }
-            }
-        }
-
-        NetworkStats::default()
+        NetworkStats::read_all()
+            .into_iter()
+            .find(|stats| {
+                stats.interface_name == interface_name
+                    || (interface_name == "Unknown" && !stats.interface_name.starts_with("lo"))
+            })
+            .unwrap_or_default()
     }
 
     // 获取默认网络接口
@@ -572,18 +601,8 @@ impl SystemInfo {
 
     // 获取 DNS 服务器
     fn get_dns_servers() -> Vec<String> {
-        let mut dns_servers = Vec::new();
-
         // 读取 /etc/resolv.conf
-        if let Ok(content) = std::fs::read_to_string("/etc/resolv.conf") {
-            for line in content.lines() {
-                if line.starts_with("nameserver") {
-                    if let Some(ip) = line.split_whitespace().nth(1) {
-                        dns_servers.push(ip.to_string());
-                    }
-                }
-            }
-        }
+        let mut dns_servers = super::dns_test::system_nameservers();
 
         if dns_servers.is_empty() {
             dns_servers.push("Unknown".to_string());
@@ -609,7 +628,7 @@ impl SystemInfo {
 }
 
 // 格式化字节大小为 GiB
-fn format_bytes_gib(bytes: u64) -> String {
+pub fn format_bytes_gib(bytes: u64) -> String {
     const GIB: u64 = 1024 * 1024 * 1024;
     format!("{:.1} GiB", bytes as f64 / GIB as f64)
 }
@@ -697,7 +716,14 @@ pub fn format_system_info(info: &SystemInfo) -> String {
 // 主要接口：获取系统信息字符串
 pub fn get_info() -> String {
     let system_info = SystemInfo::get_current();
-    format_system_info(&system_info)
+    let mut output = format_system_info(&system_info);
+    // 在系统信息页测试过 DNS 时一并导出
+    let dns = super::dns_test::get_current_test_info();
+    if !dns.probes.is_empty() && !dns.is_testing {
+        output.push_str("\n--- DNS 解析 ---\n");
+        output.push_str(&super::dns_test::format_dns_results(&dns.probes));
+    }
+    output
 }
 
 // 检查是否需要刷新UI
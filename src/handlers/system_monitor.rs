@@ -0,0 +1,278 @@
+// src/handlers/system_monitor.rs
+// 测试期间的系统监控：后台线程每秒采样 /proc/stat 中的 steal、iowait 和 CPU 频率，
+// 共享 VPS 上结果波动的主要原因是 steal 时间和降频，和测试结果放在一起才能判断结果是否可信
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// 采样间隔
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+// 等待下一次采样时检查停止标志的间隔
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// /proc/stat 中 CPU 汇总行的累计时间 (jiffies)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CpuTimes {
+    pub total: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub steal: u64,
+}
+
+impl CpuTimes {
+    pub fn read() -> Option<Self> {
+        Self::parse(&fs::read_to_string("/proc/stat").ok()?)
+    }
+
+    /// 解析 "cpu  user nice system idle iowait irq softirq steal guest guest_nice" 行
+    pub fn parse(stat: &str) -> Option<Self> {
+        Self::parse_line(stat.lines().find(|line| line.starts_with("cpu "))?)
+    }
+
+    /// 解析每个逻辑 CPU 的 "cpuN ..." 行，返回 (CPU 编号, 累计时间)
+    pub fn parse_per_cpu(stat: &str) -> Vec<(usize, Self)> {
+        stat.lines()
+            .filter_map(|line| {
+                let cpu = line.split_whitespace().next()?.strip_prefix("cpu")?.parse().ok()?;
+                Some((cpu, Self::parse_line(line)?))
+            })
+            .collect()
+    }
+
+    fn parse_line(line: &str) -> Option<Self> {
+        let fields: Vec<u64> = line.split_whitespace().skip(1).map_while(|v| v.parse().ok()).collect();
+        if fields.len() < 4 {
+            return None;
+        }
+        let field = |i: usize| fields.get(i).copied().unwrap_or(0);
+        // guest 时间已经计入 user，不重复累加
+        Some(Self {
+            total: fields.iter().take(8).sum(),
+            idle: field(3),
+            iowait: field(4),
+            steal: field(7),
+        })
+    }
+
+    /// earlier 之后 steal 时间占全部 CPU 时间的百分比
+    pub fn steal_pct_since(&self, earlier: &Self) -> f64 {
+        self.pct_since(earlier, |times| times.steal)
+    }
+
+    /// earlier 之后 iowait 时间占全部 CPU 时间的百分比
+    pub fn iowait_pct_since(&self, earlier: &Self) -> f64 {
+        self.pct_since(earlier, |times| times.iowait)
+    }
+
+    /// earlier 之后非空闲时间（idle 和 iowait 之外）占全部 CPU 时间的百分比
+    pub fn busy_pct_since(&self, earlier: &Self) -> f64 {
+        let total = self.total.saturating_sub(earlier.total);
+        if total == 0 {
+            return 0.0;
+        }
+        100.0 - self.pct_since(earlier, |times| times.idle + times.iowait)
+    }
+
+    fn pct_since(&self, earlier: &Self, pick: fn(&Self) -> u64) -> f64 {
+        let total = self.total.saturating_sub(earlier.total);
+        if total == 0 {
+            return 0.0;
+        }
+        pick(self).saturating_sub(pick(earlier)) as f64 / total as f64 * 100.0
+    }
+}
+
+/// 所有逻辑 CPU 当前频率的平均值 (MHz)
+///
+/// 优先读取 cpufreq，虚拟机中通常没有 cpufreq，改读 /proc/cpuinfo
+pub fn read_frequency_mhz() -> Option<f64> {
+    cpufreq_mhz().or_else(|| parse_cpuinfo_mhz(&fs::read_to_string("/proc/cpuinfo").ok()?))
+}
+
+fn cpufreq_mhz() -> Option<f64> {
+    let khz: Vec<f64> = fs::read_dir("/sys/devices/system/cpu")
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.strip_prefix("cpu").is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+        })
+        .filter_map(|entry| fs::read_to_string(entry.path().join("cpufreq/scaling_cur_freq")).ok())
+        .filter_map(|value| value.trim().parse::<f64>().ok())
+        .collect();
+    average(&khz).map(|khz| khz / 1000.0)
+}
+
+fn parse_cpuinfo_mhz(cpuinfo: &str) -> Option<f64> {
+    let mhz: Vec<f64> = cpuinfo
+        .lines()
+        .filter(|line| line.starts_with("cpu MHz"))
+        .filter_map(|line| line.split(':').nth(1)?.trim().parse().ok())
+        .collect();
+    average(&mhz)
+}
+
+fn average(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+/// 一次采样，百分比为与上一次采样之间的占比
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SystemSample {
+    pub elapsed_secs: f64,
+    pub steal_pct: f64,
+    pub iowait_pct: f64,
+    pub freq_mhz: Option<f64>,
+}
+
+/// 最小值、平均值、最大值
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MinAvgMax {
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+}
+
+impl MinAvgMax {
+    pub fn of(values: &[f64]) -> Option<Self> {
+        Some(Self {
+            min: values.iter().copied().reduce(f64::min)?,
+            avg: average(values)?,
+            max: values.iter().copied().reduce(f64::max)?,
+        })
+    }
+}
+
+/// 整个测试期间的监控汇总
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SystemSummary {
+    pub samples: usize,
+    pub steal_pct: MinAvgMax,
+    pub iowait_pct: MinAvgMax,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub freq_mhz: Option<MinAvgMax>,
+}
+
+impl SystemSummary {
+    /// 没有采样时返回 None
+    pub fn from_samples(samples: &[SystemSample]) -> Option<Self> {
+        let pick = |f: fn(&SystemSample) -> f64| samples.iter().map(f).collect::<Vec<_>>();
+        let freq: Vec<f64> = samples.iter().filter_map(|sample| sample.freq_mhz).collect();
+        Some(Self {
+            samples: samples.len(),
+            steal_pct: MinAvgMax::of(&pick(|s| s.steal_pct))?,
+            iowait_pct: MinAvgMax::of(&pick(|s| s.iowait_pct))?,
+            freq_mhz: MinAvgMax::of(&freq),
+        })
+    }
+
+    /// 例如 "steal 0.0/1.2/4.5%  iowait 0.0/0.3/2.0%  频率 2100/2100/2100 MHz (最低/平均/最高)"
+    pub fn label(&self) -> String {
+        let mut text = format!(
+            "steal {:.1}/{:.1}/{:.1}%  iowait {:.1}/{:.1}/{:.1}%",
+            self.steal_pct.min, self.steal_pct.avg, self.steal_pct.max, self.iowait_pct.min, self.iowait_pct.avg, self.iowait_pct.max
+        );
+        if let Some(freq) = self.freq_mhz {
+            text.push_str(&format!("  频率 {:.0}/{:.0}/{:.0} MHz", freq.min, freq.avg, freq.max));
+        }
+        text.push_str(" (最低/平均/最高)");
+        text
+    }
+}
+
+/// 后台采样线程，离开作用域时停止
+pub struct SystemMonitor {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl SystemMonitor {
+    /// 开始采样，每 SAMPLE_INTERVAL 调用一次 on_sample；读不到 /proc/stat 时不采样
+    pub fn start(mut on_sample: impl FnMut(SystemSample) + Send + 'static) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_flag = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            let start = Instant::now();
+            let Some(mut previous) = CpuTimes::read() else {
+                return;
+            };
+            let mut next = start + SAMPLE_INTERVAL;
+            loop {
+                while Instant::now() < next {
+                    if stop_flag.load(Ordering::Relaxed) {
+                        return;
+                    }
+                    thread::sleep(STOP_POLL_INTERVAL.min(next.saturating_duration_since(Instant::now())));
+                }
+                next += SAMPLE_INTERVAL;
+
+                let Some(current) = CpuTimes::read() else {
+                    return;
+                };
+                on_sample(SystemSample {
+                    elapsed_secs: start.elapsed().as_secs_f64(),
+                    steal_pct: current.steal_pct_since(&previous),
+                    iowait_pct: current.iowait_pct_since(&previous),
+                    freq_mhz: read_frequency_mhz(),
+                });
+                previous = current;
+            }
+        });
+        Self { stop, handle: Some(handle) }
+    }
+}
+
+impl Drop for SystemMonitor {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cpu_times_percentages() {
+        let before = CpuTimes::parse("cpu  100 0 50 800 10 0 0 40 0 0\ncpu0 100 0 50 800 10 0 0 40 0 0\n").unwrap();
+        let after = CpuTimes::parse("cpu  150 0 60 900 30 0 0 80 5 0\n").unwrap();
+        assert_eq!(before.total, 1000);
+        assert_eq!(after.total - before.total, 220);
+        assert!((after.steal_pct_since(&before) - 40.0 / 220.0 * 100.0).abs() < 1e-9);
+        assert!((after.iowait_pct_since(&before) - 20.0 / 220.0 * 100.0).abs() < 1e-9);
+        assert_eq!(after.steal_pct_since(&after), 0.0);
+        assert!((after.busy_pct_since(&before) - 100.0 * (1.0 - 120.0 / 220.0)).abs() < 1e-9);
+        assert_eq!(after.busy_pct_since(&after), 0.0);
+        assert!(CpuTimes::parse("intr 1 2 3").is_none());
+
+        let per_cpu = CpuTimes::parse_per_cpu("cpu  2 0 0 2 0\ncpu0 1 0 0 1 0\ncpu1 1 0 0 1 0\nintr 5\n");
+        assert_eq!(per_cpu.iter().map(|(cpu, _)| *cpu).collect::<Vec<_>>(), vec![0, 1]);
+        assert_eq!(per_cpu[1].1.total, 2);
+    }
+
+    #[test]
+    fn test_cpuinfo_frequency_and_summary() {
+        let cpuinfo = "processor\t: 0\ncpu MHz\t\t: 2000.000\n\nprocessor\t: 1\ncpu MHz\t\t: 3000.500\n";
+        assert_eq!(parse_cpuinfo_mhz(cpuinfo), Some(2500.25));
+        assert_eq!(parse_cpuinfo_mhz("processor\t: 0\n"), None);
+
+        let sample = |steal: f64, freq: Option<f64>| SystemSample { elapsed_secs: 0.0, steal_pct: steal, iowait_pct: 0.0, freq_mhz: freq };
+        assert!(SystemSummary::from_samples(&[]).is_none());
+        let summary = SystemSummary::from_samples(&[sample(1.0, Some(2000.0)), sample(5.0, None), sample(0.0, Some(1000.0))]).unwrap();
+        assert_eq!(summary.samples, 3);
+        assert_eq!(summary.steal_pct, MinAvgMax { min: 0.0, avg: 2.0, max: 5.0 });
+        assert_eq!(summary.freq_mhz, Some(MinAvgMax { min: 1000.0, avg: 1500.0, max: 2000.0 }));
+    }
+}
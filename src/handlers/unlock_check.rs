@@ -0,0 +1,545 @@
+// src/handlers/unlock_check.rs
+// 流媒体和 AI 服务解锁检测：按规则文件请求各服务的页面，根据状态码、最终地址和页面内容
+// 判断解锁、仅部分内容或不可用，并识别服务判定的地区
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::Deserialize;
+
+use crate::cancel::{CancelToken, Cancelled, TestControl};
+use crate::config;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// 单个请求的超时，包括重定向
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+// 部分服务对非浏览器的请求直接拒绝
+const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
+
+// 内置检测规则，格式说明见文件开头
+const BUILTIN_RULES: &str = include_str!("unlock_rules.toml");
+
+/// 检测结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnlockStatus {
+    Unlocked,
+    RegionOnly,
+    Blocked,
+    Unknown,  // 没有规则匹配
+    Failed,   // 请求失败
+}
+
+impl UnlockStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            UnlockStatus::Unlocked => "解锁",
+            UnlockStatus::RegionOnly => "仅部分内容",
+            UnlockStatus::Blocked => "不可用",
+            UnlockStatus::Unknown => "未知",
+            UnlockStatus::Failed => "检测失败",
+        }
+    }
+}
+
+/// 从哪里识别地区
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RegionSource {
+    Url,
+    Body,
+}
+
+/// 地区识别规则
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RegionRule {
+    pub url: Option<String>,  // 另外请求该地址识别地区
+    pub source: RegionSource,
+    pub after: String,
+    #[serde(default)]
+    pub until: String,
+    #[serde(default)]
+    pub part: usize,
+    pub default: Option<String>,
+}
+
+impl RegionRule {
+    /// 取 after 与 until 之间的文本，按 - 或 _ 分段取第 part 段，两个字母时返回大写的地区代码
+    pub fn extract(&self, response: &HttpSnapshot) -> Option<String> {
+        let text = match self.source {
+            RegionSource::Url => &response.url,
+            RegionSource::Body => &response.body,
+        };
+        let start = text.find(&self.after)? + self.after.len();
+        let rest = &text[start..];
+        let end = if self.until.is_empty() { rest.len() } else { rest.find(&self.until).unwrap_or(rest.len()) };
+        let code = rest[..end].trim().split(['-', '_']).nth(self.part)?;
+        (code.len() == 2 && code.chars().all(|c| c.is_ascii_alphabetic())).then(|| code.to_ascii_uppercase())
+    }
+}
+
+/// 匹配规则，所有给出的条件都满足时命中
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MatchRule {
+    #[serde(default)]
+    pub status: Vec<u16>,
+    pub url_contains: Option<String>,
+    pub body_contains: Option<String>,
+    pub body_not_contains: Option<String>,
+    pub result: UnlockStatus,
+    pub note: Option<String>,
+}
+
+impl MatchRule {
+    pub fn matches(&self, response: &HttpSnapshot) -> bool {
+        (self.status.is_empty() || self.status.contains(&response.status))
+            && self.url_contains.as_ref().is_none_or(|text| response.url.contains(text.as_str()))
+            && self.body_contains.as_ref().is_none_or(|text| response.body.contains(text.as_str()))
+            && self.body_not_contains.as_ref().is_none_or(|text| !response.body.contains(text.as_str()))
+    }
+}
+
+/// 一个服务的检测方式
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UnlockService {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub region: Option<RegionRule>,
+    #[serde(default)]
+    pub rules: Vec<MatchRule>,
+}
+
+impl UnlockService {
+    fn validate(&self) -> Result<(), String> {
+        let check_url = |url: &str| match url::Url::parse(url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => Ok(()),
+            _ => Err(format!("地址无效: {}", url)),
+        };
+        check_url(&self.url)?;
+        if let Some(url) = self.region.as_ref().and_then(|region| region.url.as_deref()) {
+            check_url(url)?;
+        }
+        if self.region.as_ref().is_some_and(|region| region.after.is_empty()) {
+            return Err("region.after 不能为空".to_string());
+        }
+        if self.rules.is_empty() {
+            return Err("没有 rules".to_string());
+        }
+        if self.rules.iter().any(|rule| matches!(rule.result, UnlockStatus::Unknown | UnlockStatus::Failed)) {
+            return Err("result 只能是 unlocked、region_only 或 blocked".to_string());
+        }
+        Ok(())
+    }
+
+    /// 按规则判断结果；region_response 为单独请求的地区识别地址的响应
+    pub fn classify(&self, response: &HttpSnapshot, region_response: Option<&HttpSnapshot>) -> UnlockResult {
+        let (status, note) = match self.rules.iter().find(|rule| rule.matches(response)) {
+            Some(rule) => (rule.result, rule.note.clone()),
+            None => (UnlockStatus::Unknown, Some(format!("未匹配任何规则 (HTTP {})", response.status))),
+        };
+        let region = self.region.as_ref().and_then(|rule| {
+            let detected = region_response.or(Some(response)).and_then(|response| rule.extract(response));
+            // 默认地区只用于服务可用时，被拒绝的响应里通常没有地区代码
+            detected.or_else(|| rule.default.clone().filter(|_| status != UnlockStatus::Blocked))
+        });
+        UnlockResult { service: self.name.clone(), status, region, note }
+    }
+}
+
+// 规则文件格式
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    #[serde(default)]
+    services: Vec<UnlockService>,
+}
+
+/// 本次检测的服务，以及读取规则时遇到的错误
+#[derive(Debug, Clone, Default)]
+pub struct UnlockPlan {
+    pub services: Vec<UnlockService>,
+    pub source: Option<std::path::PathBuf>,
+    pub errors: Vec<String>,
+}
+
+/// 内置规则，配置目录中 unlock_rules.toml / unlock_rules.json 的同名服务替换内置规则，新服务追加在后面
+pub fn load_rules() -> UnlockPlan {
+    let mut plan = UnlockPlan { services: builtin_services(), ..Default::default() };
+    let Some(path) = config::find_config_file(&["unlock_rules.toml", "unlock_rules.json"]) else {
+        return plan;
+    };
+    match config::load_config_file::<RulesFile>(&path) {
+        Ok(file) => {
+            merge_services(&mut plan, file.services);
+            plan.source = Some(path);
+        }
+        Err(e) => plan.errors.push(e),
+    }
+    plan
+}
+
+pub fn builtin_services() -> Vec<UnlockService> {
+    let file: RulesFile = toml::from_str(BUILTIN_RULES).expect("内置解锁规则格式正确");
+    file.services
+}
+
+fn merge_services(plan: &mut UnlockPlan, services: Vec<UnlockService>) {
+    for service in services {
+        if let Err(e) = service.validate() {
+            plan.errors.push(format!("{}: {}", service.name, e));
+            continue;
+        }
+        match plan.services.iter_mut().find(|existing| existing.name == service.name) {
+            Some(existing) => *existing = service,
+            None => plan.services.push(service),
+        }
+    }
+}
+
+/// 检测用到的响应内容
+#[derive(Debug, Clone, Default)]
+pub struct HttpSnapshot {
+    pub status: u16,
+    pub url: String,  // 重定向后的最终地址
+    pub body: String,
+}
+
+/// 一个服务的检测结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnlockResult {
+    pub service: String,
+    pub status: UnlockStatus,
+    pub region: Option<String>,
+    pub note: Option<String>,
+}
+
+pub fn build_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .user_agent(USER_AGENT)
+        .build()
+        .map_err(|e| format!("无法创建 HTTP 客户端: {}", e))
+}
+
+async fn fetch(client: &reqwest::Client, url: &str, headers: &BTreeMap<String, String>) -> Result<HttpSnapshot, BoxError> {
+    let mut request = client.get(url);
+    for (name, value) in headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    let response = request.send().await?;
+    let status = response.status().as_u16();
+    let url = response.url().to_string();
+    let body = response.text().await?;
+    Ok(HttpSnapshot { status, url, body })
+}
+
+/// 检测一个服务；地区识别地址请求失败不影响结果
+pub async fn check_service(client: &reqwest::Client, service: &UnlockService) -> UnlockResult {
+    let response = match fetch(client, &service.url, &service.headers).await {
+        Ok(response) => response,
+        Err(e) => {
+            return UnlockResult {
+                service: service.name.clone(),
+                status: UnlockStatus::Failed,
+                region: None,
+                note: Some(root_cause(e.as_ref())),
+            }
+        }
+    };
+    let region_response = match service.region.as_ref().and_then(|region| region.url.as_deref()) {
+        Some(url) => fetch(client, url, &service.headers).await.ok(),
+        None => None,
+    };
+    service.classify(&response, region_response.as_ref())
+}
+
+// reqwest 的错误逐层包含内层信息，只取最内层的原因，如域名解析失败、证书错误
+fn root_cause(error: &(dyn std::error::Error + 'static)) -> String {
+    let mut cause = error;
+    while let Some(inner) = cause.source() {
+        cause = inner;
+    }
+    cause.to_string()
+}
+
+/// 并行检测所有服务，每个服务完成后回调
+pub async fn run_checks<F>(services: &[UnlockService], cancel: &CancelToken, on_result: F) -> Result<Vec<UnlockResult>, String>
+where
+    F: Fn(usize, &UnlockResult),
+{
+    let client = build_client()?;
+    let client = &client;
+    let on_result = &on_result;
+    let checks = services.iter().enumerate().map(|(index, service)| async move {
+        cancel.checkpoint_async().await?;
+        let result = check_service(client, service).await;
+        on_result(index, &result);
+        Ok::<_, Cancelled>(result)
+    });
+    futures_util::future::join_all(checks)
+        .await
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())
+}
+
+/// 解锁检测状态
+#[derive(Debug, Clone, Default)]
+pub struct UnlockCheckInfo {
+    pub is_testing: bool,
+    pub current_stage: String,
+    pub services: Vec<String>,
+    pub results: Vec<Option<UnlockResult>>,  // 与 services 一一对应，未完成时为 None
+    pub error_message: Option<String>,
+    pub start_time: Option<Instant>,
+}
+
+// 全局状态
+static UNLOCK_CHECK_INFO: Mutex<Option<UnlockCheckInfo>> = Mutex::new(None);
+static NEEDS_UI_REFRESH: AtomicBool = AtomicBool::new(false);
+static CONTROL: TestControl = TestControl::new();
+
+/// 获取测试信息
+pub fn get_info() -> String {
+    format_unlock_check_info(&get_current_test_info())
+}
+
+/// 获取当前测试状态
+pub fn get_current_test_info() -> UnlockCheckInfo {
+    UNLOCK_CHECK_INFO.lock().ok().and_then(|info| info.clone()).unwrap_or_default()
+}
+
+/// 开始解锁检测，每次重新读取规则文件
+pub fn start_unlock_check() -> Result<(), String> {
+    if CONTROL.is_running() {
+        return Err("解锁检测进行中，完成或按 X 取消后才能重新开始".to_string());
+    }
+
+    let plan = load_rules();
+    if let Ok(mut info) = UNLOCK_CHECK_INFO.lock() {
+        *info = Some(UnlockCheckInfo {
+            is_testing: true,
+            current_stage: "正在检测...".to_string(),
+            services: plan.services.iter().map(|service| service.name.clone()).collect(),
+            results: vec![None; plan.services.len()],
+            error_message: (!plan.errors.is_empty()).then(|| plan.errors.join("; ")),
+            start_time: Some(Instant::now()),
+        });
+    }
+
+    let run = CONTROL.begin();
+    tokio::spawn(async move {
+        let result = run_checks(&plan.services, &run, |index, result| {
+            update_info(|info| info.results[index] = Some(result.clone()));
+        })
+        .await;
+        update_info(|info| {
+            info.is_testing = false;
+            info.current_stage = match result {
+                Ok(_) => "检测完成".to_string(),
+                Err(e) => e,
+            };
+        });
+    });
+    Ok(())
+}
+
+/// 取消正在运行的解锁检测
+pub fn test_control() -> &'static TestControl {
+    &CONTROL
+}
+
+/// 检查是否需要刷新UI
+pub fn check_needs_refresh() -> bool {
+    NEEDS_UI_REFRESH.swap(false, Ordering::Relaxed)
+}
+
+fn update_info(update: impl FnOnce(&mut UnlockCheckInfo)) {
+    if let Ok(mut info) = UNLOCK_CHECK_INFO.lock() {
+        if let Some(ref mut info) = info.as_mut() {
+            update(info);
+            NEEDS_UI_REFRESH.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 格式化检测结果，用于命令行输出和导出报告
+pub fn format_unlock_results(results: &[UnlockResult]) -> String {
+    let mut output = String::new();
+    for result in results {
+        output.push_str(&format!("{:<18} {:<width$} {:<4}", result.service, result.status.name(),
+            result.region.as_deref().unwrap_or("-"),
+            // 中文字符按两列计算
+            width = 12usize.saturating_sub(result.status.name().chars().count())));
+        if let Some(ref note) = result.note {
+            output.push_str(&format!(" {}", note));
+        }
+        output.push('\n');
+    }
+    output
+}
+
+fn format_unlock_check_info(info: &UnlockCheckInfo) -> String {
+    if info.services.is_empty() {
+        return "解锁检测\n\n按 Enter 开始检测\n".to_string();
+    }
+    let mut output = format!("解锁检测 - {}\n", info.current_stage);
+    if let Some(ref error) = info.error_message {
+        output.push_str(&format!("规则错误: {}\n", error));
+    }
+    let results: Vec<UnlockResult> = info.results.iter().flatten().cloned().collect();
+    output.push_str(&format_unlock_results(&results));
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    fn snapshot(status: u16, url: &str, body: &str) -> HttpSnapshot {
+        HttpSnapshot { status, url: url.to_string(), body: body.to_string() }
+    }
+
+    #[test]
+    fn test_builtin_rules() {
+        let services = builtin_services();
+        assert!(services.len() >= 5);
+        for service in &services {
+            service.validate().unwrap_or_else(|e| panic!("{}: {}", service.name, e));
+        }
+
+        let netflix = services.iter().find(|service| service.name == "Netflix").unwrap();
+        let check = |status: u16, url: &str| {
+            let result = netflix.classify(&snapshot(status, url, ""), None);
+            (result.status, result.region)
+        };
+        assert_eq!(check(200, "https://www.netflix.com/gb-en/title/81280792"), (UnlockStatus::Unlocked, Some("GB".to_string())));
+        assert_eq!(check(200, "https://www.netflix.com/title/81280792"), (UnlockStatus::Unlocked, Some("US".to_string())));
+        assert_eq!(check(404, "https://www.netflix.com/jp/title/81280792"), (UnlockStatus::RegionOnly, Some("JP".to_string())));
+        assert_eq!(check(403, "https://www.netflix.com/title/81280792"), (UnlockStatus::Blocked, None));
+        assert_eq!(check(500, "https://www.netflix.com/title/81280792").0, UnlockStatus::Unknown);
+
+        let youtube = services.iter().find(|service| service.name == "YouTube Premium").unwrap();
+        let page = r#"<script>ytcfg.set({"INNERTUBE_CONTEXT_GL":"SG"})</script> Enjoy ad-free videos"#;
+        let result = youtube.classify(&snapshot(200, "https://www.youtube.com/premium", page), None);
+        assert_eq!((result.status, result.region.as_deref()), (UnlockStatus::Unlocked, Some("SG")));
+    }
+
+    #[test]
+    fn test_custom_rules_override_builtin() {
+        let file: RulesFile = toml::from_str(r#"
+            [[services]]
+            name = "Netflix"
+            url = "https://example.com/netflix"
+            [[services.rules]]
+            result = "unlocked"
+
+            [[services]]
+            name = "Broken"
+            url = "ftp://example.com/"
+            [[services.rules]]
+            result = "unlocked"
+
+            [[services]]
+            name = "Empty"
+            url = "https://example.com/"
+        "#).unwrap();
+        let mut plan = UnlockPlan { services: builtin_services(), ..Default::default() };
+        let builtin_count = plan.services.len();
+        merge_services(&mut plan, file.services);
+
+        assert_eq!(plan.services.len(), builtin_count);
+        assert_eq!(plan.services[0].url, "https://example.com/netflix");
+        assert_eq!(plan.errors.len(), 2);
+        assert!(plan.errors[0].contains("Broken") && plan.errors[1].contains("rules"));
+
+        // 拼错的条件名会让规则变成无条件匹配，直接拒绝
+        assert!(toml::from_str::<RulesFile>("[[services]]\nname = \"x\"\nurl = \"https://x\"\n[[services.rules]]\nbody_contain = \"x\"\nresult = \"blocked\"").is_err());
+    }
+
+    // 本地 HTTP 替身：/ 重定向到 /gb-en/home，/gb-en/home 返回 200，/trace 返回 loc=DE，其余 404
+    async fn spawn_mock_service() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let (reader, mut writer) = socket.into_split();
+                    let mut lines = BufReader::new(reader).lines();
+                    let request = lines.next_line().await.ok().flatten().unwrap_or_default();
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        if line.is_empty() {
+                            break;
+                        }
+                    }
+                    let path = request.split_whitespace().nth(1).unwrap_or("/");
+                    let (status, extra, body) = match path {
+                        "/" => ("302 Found", "Location: /gb-en/home\r\n", ""),
+                        "/gb-en/home" => ("200 OK", "", "welcome, streaming available"),
+                        "/trace" => ("200 OK", "", "ip=203.0.113.9\nloc=DE\ntls=TLSv1.3\n"),
+                        _ => ("404 Not Found", "", "not here"),
+                    };
+                    let response = format!(
+                        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        status, extra, body.len(), body
+                    );
+                    let _ = writer.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_check_against_mock_service() {
+        let base = spawn_mock_service().await;
+        let file: RulesFile = toml::from_str(&format!(r#"
+            [[services]]
+            name = "Redirecting"
+            url = "{base}/"
+            [services.region]
+            source = "url"
+            after = "{base}/"
+            until = "/"
+            [[services.rules]]
+            status = [200]
+            body_contains = "available"
+            result = "unlocked"
+
+            [[services]]
+            name = "Trace"
+            url = "{base}/missing"
+            [services.region]
+            url = "{base}/trace"
+            source = "body"
+            after = "loc="
+            until = "\n"
+            [[services.rules]]
+            status = [404]
+            result = "blocked"
+            note = "地区不支持"
+        "#)).unwrap();
+
+        let results = run_checks(&file.services, &CancelToken::new(), |_, _| {}).await.unwrap();
+        assert_eq!(results[0], UnlockResult {
+            service: "Redirecting".to_string(),
+            status: UnlockStatus::Unlocked,
+            region: Some("GB".to_string()),
+            note: None,
+        });
+        assert_eq!((results[1].status, results[1].region.as_deref()), (UnlockStatus::Blocked, Some("DE")));
+        assert_eq!(results[1].note.as_deref(), Some("地区不支持"));
+
+        let unreachable = UnlockService { url: "http://127.0.0.1:1/".to_string(), ..file.services[0].clone() };
+        let result = check_service(&build_client().unwrap(), &unreachable).await;
+        assert_eq!(result.status, UnlockStatus::Failed);
+    }
+}
@@ -0,0 +1,327 @@
+// src/history.rs
+// 测试历史记录：每次测试完成后把报告追加到数据目录下的 history.jsonl，每行一条
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::handlers::memory_test::{level_latencies, StreamKernel};
+use crate::report::Report;
+
+const HISTORY_FILE_NAME: &str = "history.jsonl";
+
+/// 变化幅度超过该百分比才视为明显变化
+pub const SIGNIFICANT_CHANGE_PCT: f64 = 5.0;
+
+/// 一条历史记录对应的测试类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunKind {
+    Disk,
+    Cpu,
+    Memory,
+    Network,
+}
+
+impl RunKind {
+    pub fn of(report: &Report) -> Option<Self> {
+        if report.disk.is_some() {
+            Some(RunKind::Disk)
+        } else if report.cpu.is_some() {
+            Some(RunKind::Cpu)
+        } else if report.memory.is_some() {
+            Some(RunKind::Memory)
+        } else if report.network.is_some() {
+            Some(RunKind::Network)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            RunKind::Disk => "硬盘",
+            RunKind::Cpu => "CPU",
+            RunKind::Memory => "内存",
+            RunKind::Network => "网速",
+        }
+    }
+}
+
+/// 可比较的单项指标
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metric {
+    pub label: String,
+    pub unit: &'static str,
+    pub value: f64,
+    pub higher_is_better: bool,
+}
+
+/// 单项指标相对基准的变化
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricDiff {
+    pub label: String,
+    pub unit: &'static str,
+    pub baseline: f64,
+    pub current: f64,
+    pub change_pct: Option<f64>, // 基准为 0 时无法计算
+    pub higher_is_better: bool,
+}
+
+impl MetricDiff {
+    /// 正数表示变好，负数表示变差
+    pub fn improvement_pct(&self) -> Option<f64> {
+        self.change_pct
+            .map(|pct| if self.higher_is_better { pct } else { -pct })
+    }
+
+    pub fn is_regression(&self) -> bool {
+        self.improvement_pct()
+            .map(|pct| pct <= -SIGNIFICANT_CHANGE_PCT)
+            .unwrap_or(false)
+    }
+}
+
+/// 历史文件路径
+pub fn history_path() -> Option<PathBuf> {
+    config::data_dir().map(|dir| dir.join(HISTORY_FILE_NAME))
+}
+
+/// 追加一条记录，目录不存在时自动创建
+pub fn append_to(path: &Path, report: &Report) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("无法创建 {}: {}", dir.display(), e))?;
+    }
+
+    let line = serde_json::to_string(report).map_err(|e| format!("序列化失败: {}", e))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("无法打开 {}: {}", path.display(), e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))
+}
+
+/// 读取全部记录（按写入顺序），无法解析的行单独返回错误而不影响其他记录
+pub fn load_from(path: &Path) -> Result<(Vec<Report>, Vec<String>), String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok((Vec::new(), Vec::new())),
+        Err(e) => return Err(format!("无法读取 {}: {}", path.display(), e)),
+    };
+
+    let mut runs = Vec::new();
+    let mut errors = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<Report>(line) {
+            Ok(report) if RunKind::of(&report).is_some() => runs.push(report),
+            Ok(_) => errors.push(format!("第 {} 行: 没有测试结果", index + 1)),
+            Err(e) => errors.push(format!("第 {} 行: {}", index + 1, e)),
+        }
+    }
+
+    Ok((runs, errors))
+}
+
+/// 提取报告中可比较的指标
+pub fn metrics(report: &Report) -> Vec<Metric> {
+    let mut metrics = Vec::new();
+    let mut push = |label: String, unit: &'static str, value: f64, higher_is_better: bool| {
+        metrics.push(Metric { label, unit, value, higher_is_better });
+    };
+
+    if let Some(ref disk) = report.disk {
+        for result in &disk.results {
+            push(format!("{} 速度", result.name), "MB/s", result.read_mb_s + result.write_mb_s, true);
+            push(format!("{} IOPS", result.name), "", result.read_iops + result.write_iops, true);
+            if let Some(ref latency) = result.latency {
+                push(format!("{} p99 延迟", result.name), "µs", latency.p99_us, false);
+            }
+        }
+    }
+
+    if let Some(ref cpu) = report.cpu {
+        push("综合 单核".to_string(), "分", cpu.single_core_score as f64, true);
+        push("综合 多核".to_string(), "分", cpu.multi_core_score as f64, true);
+        for test in &cpu.tests {
+            push(format!("{} 单核", test.name), "分", test.single_core_score as f64, true);
+            push(format!("{} 多核", test.name), "分", test.multi_core_score as f64, true);
+        }
+    }
+
+    if let Some(ref memory) = report.memory {
+        for result in &memory.bandwidth {
+            push(format!("{} 单线程", result.kernel), "MB/s", result.single_mb_s, true);
+            push(format!("{} 多线程", result.kernel), "MB/s", result.multi_mb_s, true);
+        }
+        for (level, latency_ns) in level_latencies(&memory.latency) {
+            push(format!("{} 延迟", level.name()), "ns", latency_ns, false);
+        }
+    }
+
+    if let Some(ref network) = report.network {
+        for server in network.servers.iter().filter(|s| s.status == "completed") {
+            push(format!("{} 下载", server.name), "Mbps", server.download_mbps, true);
+            push(format!("{} 上传", server.name), "Mbps", server.upload_mbps, true);
+            push(format!("{} 延迟", server.name), "ms", server.ping_ms, false);
+            if server.ping_method.is_some() {
+                push(format!("{} 丢包", server.name), "%", server.packet_loss_pct, false);
+            }
+            if let Some(udp) = server.iperf.as_ref().and_then(|iperf| iperf.udp) {
+                push(format!("{} UDP 丢包", server.name), "%", udp.lost_pct.unwrap_or(0.0), false);
+            }
+        }
+    }
+
+    metrics
+}
+
+/// 一行摘要，用于历史列表
+pub fn summary(report: &Report) -> String {
+    if let Some(ref disk) = report.disk {
+        let best = disk
+            .results
+            .iter()
+            .map(|r| r.read_mb_s + r.write_mb_s)
+            .fold(0.0, f64::max);
+        return format!("{} 项, 最高 {:.1} MB/s", disk.results.len(), best);
+    }
+    if let Some(ref cpu) = report.cpu {
+        return format!("单核 {} / 多核 {}", cpu.single_core_score, cpu.multi_core_score);
+    }
+    if let Some(ref memory) = report.memory {
+        let triad = memory.bandwidth.iter().find(|r| r.kernel == StreamKernel::Triad.name());
+        let mut text = triad
+            .map(|r| format!("Triad {:.0} / {:.0} MB/s", r.single_mb_s, r.multi_mb_s))
+            .unwrap_or_default();
+        if let Some((level, latency_ns)) = level_latencies(&memory.latency).last() {
+            text.push_str(&format!(", {}延迟 {:.1} ns", level.name(), latency_ns));
+        }
+        return text;
+    }
+    if let Some(ref network) = report.network {
+        let completed: Vec<_> = network.servers.iter().filter(|s| s.status == "completed").collect();
+        let best = completed.iter().map(|s| s.download_mbps).fold(0.0, f64::max);
+        return format!("{}/{} 成功, 最高下载 {:.1} Mbps", completed.len(), network.servers.len(), best);
+    }
+    String::new()
+}
+
+/// 按指标名对比两次测试，只保留两边都有的指标
+pub fn compare(current: &Report, baseline: &Report) -> Vec<MetricDiff> {
+    let baseline_metrics = metrics(baseline);
+
+    metrics(current)
+        .into_iter()
+        .filter_map(|metric| {
+            let base = baseline_metrics.iter().find(|b| b.label == metric.label)?;
+            let change_pct = if base.value != 0.0 {
+                Some((metric.value - base.value) / base.value * 100.0)
+            } else {
+                None
+            };
+            Some(MetricDiff {
+                label: metric.label,
+                unit: metric.unit,
+                baseline: base.value,
+                current: metric.value,
+                change_pct,
+                higher_is_better: metric.higher_is_better,
+            })
+        })
+        .collect()
+}
+
+/// 两条记录能否对比：同类测试，CPU 测试还要求评分版本相同
+pub fn comparable(a: &Report, b: &Report) -> bool {
+    let score_version = |report: &Report| report.cpu.as_ref().map(|cpu| cpu.score_version);
+    RunKind::of(a).is_some() && RunKind::of(a) == RunKind::of(b) && score_version(a) == score_version(b)
+}
+
+/// 同一主机上位于 index 之前、可以对比的最近一次记录
+pub fn previous_run(runs: &[Report], index: usize) -> Option<usize> {
+    let current = runs.get(index)?;
+    runs[..index]
+        .iter()
+        .rposition(|run| run.host.hostname == current.host.hostname && comparable(run, current))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{CpuReport, HostReport};
+
+    fn cpu_run(hostname: &str, single: u32, multi: u32) -> Report {
+        let host: HostReport = serde_json::from_value(serde_json::json!({
+            "hostname": hostname, "distro": "", "kernel": "", "arch": "", "virtualization": "",
+            "cpu_model": "", "cpu_cores": 1, "cpu_frequency_mhz": 0, "memory_total_bytes": 0,
+            "memory_used_bytes": 0, "swap_total_bytes": 0, "swap_used_bytes": 0, "uptime_seconds": 0,
+            "load_average": [0.0, 0.0, 0.0], "tcp_congestion_control": "", "dns_servers": [], "disks": []
+        }))
+        .unwrap();
+        let mut report = Report::new(host);
+        report.cpu = Some(CpuReport {
+            error: None,
+            threads: None,
+            single_core_score: single,
+            multi_core_score: multi,
+            tests: Vec::new(),
+            crypto: None,
+            compression: Vec::new(),
+            score_version: Some(1),
+            topology: None,
+            system: None,
+        });
+        report
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join(HISTORY_FILE_NAME);
+
+        append_to(&path, &cpu_run("a", 1000, 4000)).unwrap();
+        append_to(&path, &cpu_run("b", 900, 900)).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        writeln!(file, "not json").unwrap();
+        append_to(&path, &cpu_run("a", 900, 4400)).unwrap();
+
+        let (runs, errors) = load_from(&path).unwrap();
+        assert_eq!(runs.len(), 3);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("第 3 行"));
+
+        // 对比同一主机的上一次记录，跳过其他主机
+        assert_eq!(previous_run(&runs, 2), Some(0));
+        assert_eq!(previous_run(&runs, 1), None);
+
+        let diffs = compare(&runs[2], &runs[0]);
+        let single = diffs.iter().find(|d| d.label == "综合 单核").unwrap();
+        assert_eq!(single.change_pct, Some(-10.0));
+        assert!(single.is_regression());
+        let multi = diffs.iter().find(|d| d.label == "综合 多核").unwrap();
+        assert_eq!(multi.change_pct, Some(10.0));
+        assert!(!multi.is_regression());
+    }
+
+    #[test]
+    fn test_cpu_runs_compare_only_within_score_version() {
+        let mut old = cpu_run("a", 300, 1200);
+        old.cpu.as_mut().unwrap().score_version = None;
+        let runs = vec![cpu_run("a", 1000, 4000), old, cpu_run("a", 1100, 4100)];
+
+        // 跳过没有评分版本的旧记录
+        assert!(!comparable(&runs[2], &runs[1]));
+        assert_eq!(previous_run(&runs, 2), Some(0));
+        assert_eq!(previous_run(&runs, 1), None);
+    }
+
+    #[test]
+    fn test_missing_history_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let (runs, errors) = load_from(&dir.path().join(HISTORY_FILE_NAME)).unwrap();
+        assert!(runs.is_empty() && errors.is_empty());
+    }
+}
@@ -0,0 +1,246 @@
+// src/latency.rs
+// HDR 风格的延迟直方图：按 2 的幂分段，每段再线性细分，记录开销固定且相对误差小于 1%
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// 每个 2 的幂区间细分的桶数 (2^7 = 128)，决定精度
+const SUB_BUCKET_BITS: u32 = 7;
+const SUB_BUCKET_COUNT: u64 = 1 << SUB_BUCKET_BITS;
+const BUCKET_COUNT: usize = (SUB_BUCKET_COUNT as usize) * (64 - SUB_BUCKET_BITS as usize + 1);
+
+/// 延迟直方图，单位纳秒
+#[derive(Debug, Clone)]
+pub struct LatencyHistogram {
+    counts: Vec<u64>,
+    total: u64,
+    sum: u128,
+    min: u64,
+    max: u64,
+}
+
+/// 延迟统计结果，单位微秒
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub samples: u64,
+    pub mean_us: f64,
+    pub p50_us: f64,
+    pub p95_us: f64,
+    pub p99_us: f64,
+    pub p999_us: f64,
+    pub max_us: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub distribution: Vec<LatencyBucket>,
+}
+
+/// 延迟分布中的一段：上一段上限 < 延迟 <= upper_us
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LatencyBucket {
+    pub upper_us: f64,
+    pub count: u64,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            counts: vec![0; BUCKET_COUNT],
+            total: 0,
+            sum: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+
+    pub fn record(&mut self, latency: Duration) {
+        self.record_nanos(latency.as_nanos().min(u64::MAX as u128) as u64);
+    }
+
+    pub fn record_nanos(&mut self, nanos: u64) {
+        self.counts[bucket_index(nanos)] += 1;
+        self.total += 1;
+        self.sum += nanos as u128;
+        self.min = self.min.min(nanos);
+        self.max = self.max.max(nanos);
+    }
+
+    /// 合并另一个直方图（如多个工作线程各自记录的结果）
+    pub fn merge(&mut self, other: &LatencyHistogram) {
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.total += other.total;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    /// 百分位延迟（纳秒），percentile 取 0-100；返回所在桶的上限，不超过实际最大值
+    pub fn percentile_nanos(&self, percentile: f64) -> u64 {
+        if self.total == 0 {
+            return 0;
+        }
+        let rank = ((percentile / 100.0) * self.total as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (index, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return bucket_upper(index).min(self.max);
+            }
+        }
+        self.max
+    }
+
+    /// 汇总为常用百分位和按 2 的幂划分的分布
+    pub fn stats(&self) -> LatencyStats {
+        if self.total == 0 {
+            return LatencyStats::default();
+        }
+        let us = |nanos: u64| nanos as f64 / 1000.0;
+        LatencyStats {
+            samples: self.total,
+            mean_us: self.sum as f64 / self.total as f64 / 1000.0,
+            p50_us: us(self.percentile_nanos(50.0)),
+            p95_us: us(self.percentile_nanos(95.0)),
+            p99_us: us(self.percentile_nanos(99.0)),
+            p999_us: us(self.percentile_nanos(99.9)),
+            max_us: us(self.max),
+            distribution: self.distribution(),
+        }
+    }
+
+    // 以 1µs、2µs、4µs... 为上限划分，只保留最小值到最大值之间的段
+    fn distribution(&self) -> Vec<LatencyBucket> {
+        let mut buckets: Vec<LatencyBucket> = Vec::new();
+        let mut upper_nanos = 1000u64;
+        while upper_nanos < self.min {
+            upper_nanos *= 2;
+        }
+
+        for (index, &count) in self.counts.iter().enumerate() {
+            if count == 0 {
+                continue;
+            }
+            let value = bucket_lower(index).max(self.min);
+            while value > upper_nanos {
+                upper_nanos *= 2;
+            }
+            let upper_us = upper_nanos as f64 / 1000.0;
+            match buckets.last_mut() {
+                Some(last) if last.upper_us == upper_us => last.count += count,
+                _ => {
+                    // 中间没有样本的段也保留，图表横轴才连续
+                    let mut next = buckets.last().map(|b| b.upper_us * 2.0);
+                    while let Some(upper) = next.filter(|&u| u < upper_us) {
+                        buckets.push(LatencyBucket { upper_us: upper, count: 0 });
+                        next = Some(upper * 2.0);
+                    }
+                    buckets.push(LatencyBucket { upper_us, count });
+                }
+            }
+        }
+
+        buckets
+    }
+}
+
+fn bucket_index(value: u64) -> usize {
+    if value < SUB_BUCKET_COUNT {
+        return value as usize;
+    }
+    let magnitude = 63 - value.leading_zeros(); // >= SUB_BUCKET_BITS
+    let shift = magnitude - SUB_BUCKET_BITS;
+    let sub = (value >> shift) - SUB_BUCKET_COUNT;
+    (SUB_BUCKET_COUNT + shift as u64 * SUB_BUCKET_COUNT + sub) as usize
+}
+
+// 桶内可能出现的最小值
+fn bucket_lower(index: usize) -> u64 {
+    let index = index as u64;
+    if index < SUB_BUCKET_COUNT {
+        return index;
+    }
+    let shift = (index - SUB_BUCKET_COUNT) / SUB_BUCKET_COUNT;
+    let sub = (index - SUB_BUCKET_COUNT) % SUB_BUCKET_COUNT;
+    (SUB_BUCKET_COUNT + sub) << shift
+}
+
+// 桶内可能出现的最大值
+fn bucket_upper(index: usize) -> u64 {
+    let index = index as u64;
+    if index < SUB_BUCKET_COUNT {
+        return index;
+    }
+    let shift = (index - SUB_BUCKET_COUNT) / SUB_BUCKET_COUNT;
+    let sub = (index - SUB_BUCKET_COUNT) % SUB_BUCKET_COUNT;
+    let upper = ((SUB_BUCKET_COUNT + sub + 1) as u128) << shift;
+    (upper - 1).min(u64::MAX as u128) as u64
+}
+
+/// 格式化微秒延迟，自动选择 µs / ms / s
+pub fn format_latency_us(us: f64) -> String {
+    if us >= 1_000_000.0 {
+        format!("{:.2}s", us / 1_000_000.0)
+    } else if us >= 1000.0 {
+        format!("{:.2}ms", us / 1000.0)
+    } else {
+        format!("{:.0}µs", us)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles() {
+        let mut histogram = LatencyHistogram::new();
+        // 1..=1000 µs 各一次
+        for us in 1..=1000u64 {
+            histogram.record(Duration::from_micros(us));
+        }
+
+        let stats = histogram.stats();
+        assert_eq!(stats.samples, 1000);
+        assert!((stats.mean_us - 500.5).abs() < 0.01);
+        // 相对误差小于 1%
+        for (actual, expected) in [(stats.p50_us, 500.0), (stats.p99_us, 990.0), (stats.p999_us, 999.0)] {
+            assert!((actual - expected).abs() / expected < 0.01, "{} vs {}", actual, expected);
+        }
+        assert_eq!(stats.max_us, 1000.0);
+
+        // 分布覆盖全部样本，上限依次翻倍
+        assert_eq!(stats.distribution.iter().map(|b| b.count).sum::<u64>(), 1000);
+        assert_eq!(stats.distribution.first().unwrap().upper_us, 1.0);
+        assert_eq!(stats.distribution.last().unwrap().upper_us, 1024.0);
+        assert!(stats.distribution.windows(2).all(|w| w[1].upper_us == w[0].upper_us * 2.0));
+    }
+
+    #[test]
+    fn test_merge() {
+        let mut a = LatencyHistogram::new();
+        let mut b = LatencyHistogram::new();
+        a.record(Duration::from_micros(10));
+        b.record(Duration::from_micros(30));
+        a.merge(&b);
+
+        let stats = a.stats();
+        assert_eq!(stats.samples, 2);
+        assert_eq!(stats.mean_us, 20.0);
+        assert_eq!(stats.max_us, 30.0);
+    }
+
+    #[test]
+    fn test_bucket_bounds() {
+        for value in [0, 1, 127, 128, 129, 1000, 123_456, 9_876_543_210, u64::MAX] {
+            let index = bucket_index(value);
+            assert!(index < BUCKET_COUNT);
+            assert!(bucket_lower(index) <= value && bucket_upper(index) >= value);
+            assert!(bucket_upper(index) - value <= value / SUB_BUCKET_COUNT);
+        }
+    }
+}
@@ -6,6 +6,13 @@ mod ui;
 mod handlers;
 mod utils;
 mod theme;
+mod config;
+mod cli;
+mod report;
+mod history;
+mod export;
+mod latency;
+mod cancel;
 
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
@@ -19,6 +26,14 @@ use crate::{app::App, events::handle_events, types::Result};
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // 带参数时以无界面的命令行模式运行
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::parse_args(&args) {
+        Ok(Some(command)) => std::process::exit(cli::run(command).await),
+        Ok(None) => {}
+        Err(e) => std::process::exit(cli::usage_error(&e)),
+    }
+
     // 初始化终端
     let mut terminal = init_terminal()?;
     
@@ -28,6 +43,9 @@ async fn main() -> Result<()> {
     // 运行应用
     let res = run_app(&mut terminal, &mut app).await;
     
+    // 界面中启动的 iperf3 服务端随程序退出
+    handlers::iperf::stop_server();
+    
     // 恢复终端
     restore_terminal(&mut terminal)?;
     
@@ -94,6 +112,41 @@ async fn run_app(
                 app.needs_refresh = true; // 标记需要UI刷新
             }
             
+            // 检查内存测试是否需要刷新
+            if handlers::memory_test::check_needs_refresh() {
+                app.clear_cache();
+                app.needs_refresh = true;
+            }
+            
+            // 检查路由追踪是否需要刷新
+            if handlers::route_trace::check_needs_refresh() {
+                app.clear_cache();
+                app.needs_refresh = true;
+            }
+            
+            // 检查解锁检测是否需要刷新
+            if handlers::unlock_check::check_needs_refresh() {
+                app.clear_cache();
+                app.needs_refresh = true;
+            }
+            
+            // 检查 DNS 测试是否需要刷新
+            if handlers::dns_test::check_needs_refresh() {
+                app.clear_cache();
+                app.needs_refresh = true;
+            }
+            
+            // 检查实时监控是否有新采样
+            if handlers::resource_monitor::check_needs_refresh() {
+                app.needs_refresh = true;
+            }
+            
+            // 检查历史记录是否有新记录
+            if handlers::history::check_needs_refresh() {
+                app.clear_cache();
+                app.needs_refresh = true;
+            }
+            
             // 如果是磁盘测试界面且在测试中，更新动画帧
             if let crate::menu::MenuItem::DiskTest = app.menu.selected_item() {
                 handlers::disk_test::update_animation_frame();
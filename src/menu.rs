@@ -12,6 +12,10 @@ pub enum MenuItem {
     K3s,
     K8s,
     TcpOptimization,
+    MemoryTest,
+    RouteTrace,
+    UnlockCheck,
+    History,
 }
 
 impl MenuItem {
@@ -27,6 +31,10 @@ impl MenuItem {
             MenuItem::K3s,
             MenuItem::K8s,
             MenuItem::TcpOptimization,
+            MenuItem::MemoryTest,
+            MenuItem::RouteTrace,
+            MenuItem::UnlockCheck,
+            MenuItem::History,
         ]
     }
     
@@ -42,6 +50,10 @@ impl MenuItem {
             MenuItem::K3s => "8. k3s",
             MenuItem::K8s => "9. k8s",
             MenuItem::TcpOptimization => "0. tcp调优",
+            MenuItem::MemoryTest => "R. 内存测试",
+            MenuItem::RouteTrace => "T. 路由追踪",
+            MenuItem::UnlockCheck => "U. 解锁检测",
+            MenuItem::History => "H. 历史记录",
         }
     }
     
@@ -57,6 +69,10 @@ impl MenuItem {
             MenuItem::K3s => "部署轻量级Kubernetes",
             MenuItem::K8s => "部署完整版Kubernetes",
             MenuItem::TcpOptimization => "优化TCP网络参数",
+            MenuItem::MemoryTest => "测试内存带宽和各级缓存延迟",
+            MenuItem::RouteTrace => "追踪到测速服务器的路由和线路类型",
+            MenuItem::UnlockCheck => "检测流媒体和 AI 服务的解锁情况和地区",
+            MenuItem::History => "查看和对比历史测试结果",
         }
     }
 }
@@ -117,6 +133,10 @@ impl Menu {
             '8' => 7, // K3s
             '9' => 8, // K8s
             '0' => 9, // TcpOptimization
+            'r' | 'R' => 10, // MemoryTest
+            't' | 'T' => 11, // RouteTrace
+            'u' | 'U' => 12, // UnlockCheck
+            'h' | 'H' => 13, // History
             _ => return false,
         };
         
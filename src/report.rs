@@ -0,0 +1,464 @@
+// src/report.rs
+// 统一的测试报告格式，所有数值都以数字输出，单位体现在字段名中：
+// *_bytes 字节, *_mb_s MB/s, *_mbps Mbit/s, *_ms 毫秒, *_seconds 秒, *_mhz MHz
+use serde::{Deserialize, Serialize};
+
+use crate::handlers::cpu_test::CpuTestInfo;
+use crate::handlers::cpu_score::{self, KernelResult};
+use crate::handlers::compression_bench::CompressionRatio;
+use crate::handlers::cpu_topology::TopologyResult;
+use crate::handlers::system_monitor::SystemSummary;
+use crate::handlers::crypto_bench::CryptoBenchResult;
+use crate::handlers::disk_test::{DiskIoMode, DiskTestInfo};
+use crate::handlers::memory_test::{BandwidthResult, CacheSizes, LatencyPoint, MemoryTestInfo};
+use crate::handlers::network_test::{NetworkProvider, NetworkTestInfo, TestStatus};
+use crate::handlers::iperf::IperfStats;
+use crate::handlers::ping::PingMethod;
+use crate::handlers::system_info::{NetworkInfo, SystemInfo};
+use crate::latency::LatencyStats;
+
+/// 报告格式版本，字段含义变化或删除字段时递增；新增字段不需要递增
+pub const REPORT_SCHEMA_VERSION: u32 = 2;
+
+/// CPU 测试中综合评分结果的名称
+const CPU_OVERALL_TEST_NAME: &str = cpu_score::OVERALL;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub schema_version: u32,
+    pub tool_version: String,
+    pub generated_at: String, // RFC 3339
+    pub host: HostReport,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub disk: Option<DiskReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<CpuReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory: Option<MemoryReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<NetworkReport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostReport {
+    pub hostname: String,
+    pub distro: String,
+    pub kernel: String,
+    pub arch: String,
+    pub virtualization: String,
+    pub cpu_model: String,
+    pub cpu_cores: usize,
+    pub cpu_frequency_mhz: u64,
+    pub memory_total_bytes: u64,
+    pub memory_used_bytes: u64,
+    pub swap_total_bytes: u64,
+    pub swap_used_bytes: u64,
+    pub uptime_seconds: u64,
+    pub load_average: [f64; 3],
+    pub tcp_congestion_control: String,
+    pub dns_servers: Vec<String>,
+    pub disks: Vec<HostDiskReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_network: Option<NetworkInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostDiskReport {
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<DiskTargetReport>,
+    // fio 测试配置名称，内置引擎为 None
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    pub results: Vec<DiskResultReport>,
+    // 测试过程中的实时速度采样（最近 100 个点），用于绘制读写曲线
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub read_samples: Vec<SpeedSample>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub write_samples: Vec<SpeedSample>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskTargetReport {
+    pub dir: String,
+    pub device: String,
+    pub mount_point: String,
+    pub file_system: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SpeedSample {
+    pub seconds: f64, // 相对测试开始的时间
+    pub mb_s: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiskResultReport {
+    pub name: String,
+    pub read_mb_s: f64,
+    pub write_mb_s: f64,
+    pub read_iops: f64,
+    pub write_iops: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub io_mode: Option<DiskIoMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency: Option<LatencyStats>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpuReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<usize>,
+    pub single_core_score: u32,
+    pub multi_core_score: u32,
+    pub tests: Vec<CpuTestReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crypto: Option<CryptoBenchResult>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compression: Vec<CompressionRatio>,
+    /// 评分模型版本，只有版本相同的分数可以比较；旧记录没有该字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score_version: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topology: Option<TopologyResult>,
+    /// 测试期间 steal、iowait 和 CPU 频率的最低/平均/最高值
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<SystemSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpuTestReport {
+    pub name: String,
+    pub single_core_score: u32,
+    pub multi_core_score: u32,
+    pub duration_ms: u64,
+    /// 各评分任务的耗时统计，界面中的测试详情由这些数值生成
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kernels: Vec<CpuKernelReport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpuKernelReport {
+    pub name: String,
+    pub threads: usize,
+    pub runs: usize,
+    pub single_median_ms: f64,
+    pub single_stddev_ms: f64,
+    pub multi_median_ms: f64,
+    pub multi_stddev_ms: f64,
+    pub single_score: f64,
+    pub multi_score: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub single_mb_s: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multi_mb_s: Option<f64>,
+}
+
+impl CpuKernelReport {
+    pub fn from_result(result: &KernelResult) -> Self {
+        Self {
+            name: result.name.clone(),
+            threads: result.threads,
+            runs: result.single.runs,
+            single_median_ms: result.single.median_ms,
+            single_stddev_ms: result.single.stddev_ms,
+            multi_median_ms: result.multi.median_ms,
+            multi_stddev_ms: result.multi.stddev_ms,
+            single_score: result.single_score,
+            multi_score: result.multi_score,
+            single_mb_s: result.single_mb_s(),
+            multi_mb_s: result.multi_mb_s(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoryReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub threads: usize,
+    pub array_bytes: usize, // STREAM 每个数组的大小
+    pub cache: CacheSizes,
+    pub bandwidth: Vec<BandwidthResult>,
+    /// 按工作集从小到大排列的延迟曲线
+    pub latency: Vec<LatencyPoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub servers: Vec<NetworkServerReport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkServerReport {
+    pub name: String,
+    pub status: String, // completed / failed / testing / not_started
+    pub download_mbps: f64,
+    pub upload_mbps: f64,
+    pub ping_ms: f64,
+    pub http_latency_ms: f64,
+    pub jitter_ms: f64,
+    #[serde(default)]
+    pub packet_loss_pct: f64,
+    /// 延迟探测方式，None 表示探测全部失败，延迟取自 HTTP 请求
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ping_method: Option<PingMethod>,
+    /// iperf3 节点的 TCP 重传次数和 UDP 下载结果
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iperf: Option<IperfStats>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Report {
+    /// 创建只包含主机信息的报告，测试结果按需填充
+    pub fn new(host: HostReport) -> Self {
+        Self {
+            schema_version: REPORT_SCHEMA_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: chrono::Local::now().to_rfc3339(),
+            host,
+            disk: None,
+            cpu: None,
+            memory: None,
+            network: None,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e))
+    }
+}
+
+impl HostReport {
+    pub fn from_system_info(info: &SystemInfo) -> Self {
+        let basic = &info.basic;
+        Self {
+            hostname: basic.hostname.clone(),
+            distro: basic.distro.clone(),
+            kernel: basic.kernel.clone(),
+            arch: basic.cpu_arch.clone(),
+            virtualization: basic.vm_type.clone(),
+            cpu_model: basic.cpu_model.clone(),
+            cpu_cores: basic.cpu_cores,
+            cpu_frequency_mhz: basic.cpu_frequency_mhz,
+            memory_total_bytes: basic.memory_total,
+            memory_used_bytes: basic.memory_used,
+            swap_total_bytes: basic.swap_total,
+            swap_used_bytes: basic.swap_used,
+            uptime_seconds: basic.uptime_seconds,
+            load_average: basic.load_average,
+            tcp_congestion_control: basic.network_algorithm.clone(),
+            dns_servers: basic.dns_servers.clone(),
+            disks: basic
+                .disk_info
+                .iter()
+                .map(|disk| HostDiskReport {
+                    name: disk.name.clone(),
+                    mount_point: disk.mount_point.clone(),
+                    file_system: disk.file_system.clone(),
+                    total_bytes: disk.total_space,
+                    available_bytes: disk.available_space,
+                })
+                .collect(),
+            // 公网信息仍在获取中时不输出，避免把空值当作结果
+            public_network: if info.network_loading { None } else { Some(info.network.clone()) },
+        }
+    }
+}
+
+impl DiskReport {
+    pub fn from_test_info(info: &DiskTestInfo) -> Self {
+        Self {
+            error: info.error_message.clone(),
+            target: info.target.as_ref().map(|target| DiskTargetReport {
+                dir: target.dir.display().to_string(),
+                device: target.device.clone(),
+                mount_point: target.mount_point.clone(),
+                file_system: target.file_system.clone(),
+                warning: target.warning().map(str::to_string),
+            }),
+            profile: info.profile.uses_fio().then(|| info.profile.name()),
+            results: info
+                .results
+                .iter()
+                .map(|result| DiskResultReport {
+                    name: result.test_name.clone(),
+                    read_mb_s: result.metrics.read_mb_s,
+                    write_mb_s: result.metrics.write_mb_s,
+                    read_iops: result.metrics.read_iops,
+                    write_iops: result.metrics.write_iops,
+                    io_mode: result.io_mode,
+                    latency: result.latency.clone(),
+                })
+                .collect(),
+            read_samples: speed_samples(&info.read_chart_data),
+            write_samples: speed_samples(&info.write_chart_data),
+        }
+    }
+}
+
+fn speed_samples(points: &[(f64, f64)]) -> Vec<SpeedSample> {
+    points.iter().map(|&(seconds, mb_s)| SpeedSample { seconds, mb_s }).collect()
+}
+
+impl CpuReport {
+    pub fn from_test_info(info: &CpuTestInfo) -> Self {
+        let overall = info.results.iter().find(|r| r.test_name == CPU_OVERALL_TEST_NAME);
+        Self {
+            error: info.error_message.clone(),
+            threads: Some(info.threads),
+            single_core_score: overall.map(|r| r.single_core_score).unwrap_or(0),
+            multi_core_score: overall.map(|r| r.multi_core_score).unwrap_or(0),
+            tests: info
+                .results
+                .iter()
+                .filter(|r| r.test_name != CPU_OVERALL_TEST_NAME)
+                .map(|result| CpuTestReport {
+                    name: result.test_name.clone(),
+                    single_core_score: result.single_core_score,
+                    multi_core_score: result.multi_core_score,
+                    duration_ms: result.duration_ms,
+                    kernels: result.kernels.iter().map(CpuKernelReport::from_result).collect(),
+                })
+                .collect(),
+            crypto: info.crypto.clone(),
+            compression: info.compression.clone(),
+            score_version: Some(cpu_score::CPU_SCORE_VERSION),
+            topology: info.topology.clone(),
+            system: info.system_summary.clone(),
+        }
+    }
+}
+
+impl MemoryReport {
+    pub fn from_test_info(info: &MemoryTestInfo) -> Self {
+        Self {
+            error: info.error_message.clone(),
+            threads: info.threads,
+            array_bytes: info.array_bytes,
+            cache: info.cache,
+            bandwidth: info.bandwidth.clone(),
+            latency: info.latency.clone(),
+        }
+    }
+}
+
+impl NetworkReport {
+    /// 按服务器列表顺序输出，未测试的服务器标记为 not_started
+    pub fn from_test_info(info: &NetworkTestInfo, providers: &[NetworkProvider]) -> Self {
+        Self {
+            error: info.error_message.clone(),
+            servers: providers
+                .iter()
+                .map(|provider| {
+                    let result = info.results.get(&provider.name).cloned().unwrap_or_default();
+                    NetworkServerReport {
+                        name: provider.name.clone(),
+                        status: match result.status {
+                            TestStatus::NotStarted => "not_started",
+                            TestStatus::Testing => "testing",
+                            TestStatus::Completed => "completed",
+                            TestStatus::Failed => "failed",
+                        }
+                        .to_string(),
+                        download_mbps: result.download_speed,
+                        upload_mbps: result.upload_speed,
+                        ping_ms: result.ping,
+                        http_latency_ms: result.http_latency,
+                        jitter_ms: result.jitter,
+                        packet_loss_pct: result.packet_loss,
+                        ping_method: result.ping_method,
+                        iperf: result.iperf,
+                        error: result.error_message,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::cpu_test::CpuTestResult;
+    use crate::handlers::disk_test::{DiskMetrics, DiskTestResult};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_disk_report_uses_numeric_metrics() {
+        let mut info = DiskTestInfo::default();
+        info.results.push(DiskTestResult {
+            test_name: "4K 读取".to_string(),
+            read_speed: "1.37 GB/s".to_string(),
+            write_speed: "N/A".to_string(),
+            read_iops: "359.0k".to_string(),
+            write_iops: "N/A".to_string(),
+            total_speed: "1.37 GB/s".to_string(),
+            total_iops: "359.0k".to_string(),
+            io_mode: None,
+            latency: None,
+            metrics: DiskMetrics {
+                read_mb_s: 1402.88,
+                read_iops: 359_137.0,
+                ..Default::default()
+            },
+        });
+
+        let value = serde_json::to_value(DiskReport::from_test_info(&info)).unwrap();
+        let result = &value["results"][0];
+        assert_eq!(result["name"], "4K 读取");
+        assert_eq!(result["read_mb_s"].as_f64(), Some(1402.88));
+        assert_eq!(result["read_iops"].as_f64(), Some(359_137.0));
+        assert_eq!(result["write_mb_s"].as_f64(), Some(0.0));
+        assert!(value.get("error").is_none());
+    }
+
+    #[test]
+    fn test_cpu_report_uses_numeric_kernels() {
+        let measurement = |median_ms| cpu_score::Measurement { median_ms, stddev_ms: 0.5, runs: 5 };
+        let mut info = CpuTestInfo::default();
+        info.results.push(CpuTestResult {
+            test_name: cpu_score::CRYPTO.to_string(),
+            single_core_score: 1000,
+            multi_core_score: 4000,
+            duration_ms: 900,
+            details: HashMap::from([("硬件加速".to_string(), "AES-NI".to_string())]),
+            kernels: vec![KernelResult {
+                name: "SHA-256".to_string(),
+                bytes: Some(64 * 1024 * 1024),
+                threads: 4,
+                single: measurement(64.0),
+                multi: measurement(128.0),
+                single_score: 1000.0,
+                multi_score: 2000.0,
+            }],
+        });
+
+        let value = serde_json::to_value(CpuReport::from_test_info(&info)).unwrap();
+        let test = &value["tests"][0];
+        assert!(test.get("details").is_none());
+        let kernel = &test["kernels"][0];
+        assert_eq!(kernel["single_median_ms"].as_f64(), Some(64.0));
+        assert_eq!(kernel["multi_stddev_ms"].as_f64(), Some(0.5));
+        assert_eq!(kernel["single_mb_s"].as_f64(), Some(1000.0));
+        assert_eq!(kernel["multi_mb_s"].as_f64(), Some(2000.0));
+        assert_eq!(kernel["runs"].as_u64(), Some(5));
+    }
+}
@@ -1,10 +1,11 @@
 use ratatui::{
-    layout::{Alignment, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Scrollbar, ScrollbarOrientation},
+    widgets::{Block, Borders, List, ListItem, Paragraph, Scrollbar, ScrollbarOrientation, Sparkline},
     Frame,
 };
 
+use crate::handlers::system_monitor::SystemSample;
 use crate::{app::App, theme::Theme};
 
 /// 绘制左侧菜单
@@ -80,3 +81,57 @@ pub fn draw_scrollbar(f: &mut Frame, app: &mut App, area: Rect, is_focused: bool
     
     f.render_stateful_widget(scrollbar, scrollbar_area, &mut app.scrollbar_state);
 }
+
+/// 系统监控面板所需的高度（边框 + steal、iowait、频率三行）
+pub const SYSTEM_MONITOR_HEIGHT: u16 = 5;
+
+/// 绘制测试期间的 steal、iowait 和 CPU 频率曲线，显示最近的采样
+pub fn draw_system_monitor(f: &mut Frame, area: Rect, samples: &[SystemSample], is_focused: bool) {
+    let (border_style, title_style) = if is_focused {
+        (Theme::border_focused(), Theme::title_focused())
+    } else {
+        (Theme::border_unfocused(), Theme::title_unfocused())
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" 系统监控 ")
+        .title_style(title_style)
+        .border_style(border_style);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Length(1), Constraint::Length(1)].as_ref())
+        .split(inner);
+
+    // 百分比按 0.1% 取整；纵轴至少 10%，steal 偶尔的小波动不会占满整行
+    let percent = |pick: fn(&SystemSample) -> f64| -> Vec<u64> { samples.iter().map(|s| (pick(s) * 10.0).round() as u64).collect() };
+    let freq: Vec<u64> = samples.iter().map(|s| s.freq_mhz.unwrap_or(0.0).round() as u64).collect();
+    let last = samples.last();
+
+    let series = [
+        ("steal", percent(|s| s.steal_pct), 100, last.map(|s| format!("{:.1}%", s.steal_pct)), Theme::error()),
+        ("iowait", percent(|s| s.iowait_pct), 100, last.map(|s| format!("{:.1}%", s.iowait_pct)), Theme::warning()),
+        ("频率", freq, 1, last.and_then(|s| s.freq_mhz).map(|mhz| format!("{:.0} MHz", mhz)), Theme::accent()),
+    ];
+
+    for ((label, data, min_max, value, style), row) in series.into_iter().zip(rows.iter()) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(8), Constraint::Min(10), Constraint::Length(10)].as_ref())
+            .split(*row);
+        f.render_widget(Paragraph::new(label).style(Theme::muted()), columns[0]);
+
+        let visible = &data[data.len().saturating_sub(columns[1].width as usize)..];
+        let max = visible.iter().copied().max().unwrap_or(0).max(min_max);
+        f.render_widget(Sparkline::default().data(visible).max(max).style(style), columns[1]);
+
+        f.render_widget(
+            Paragraph::new(value.unwrap_or_else(|| "--".to_string()))
+                .style(Theme::secondary())
+                .alignment(Alignment::Right),
+            columns[2],
+        );
+    }
+}
@@ -7,7 +7,8 @@ use ratatui::{
 };
 
 use crate::{app::App, theme::Theme};
-use super::components::draw_scrollbar;
+use crate::handlers::cpu_topology::TopologyResult;
+use super::components::{draw_scrollbar, draw_system_monitor, SYSTEM_MONITOR_HEIGHT};
 
 /// 绘制CPU测试内容
 pub fn draw_cpu_test_content(f: &mut Frame, app: &mut App, area: Rect, is_focused: bool) {
@@ -26,23 +27,29 @@ pub fn draw_cpu_test_content(f: &mut Frame, app: &mut App, area: Rect, is_focuse
 
 // 绘制合并的测试和结果界面
 fn draw_combined_test_results_ui(f: &mut Frame, app: &mut App, area: Rect, test_info: &crate::handlers::cpu_test::CpuTestInfo, is_focused: bool) {
-    // 创建上下布局
+    // 创建上下布局，有采样数据后在实时状态下方显示系统监控
+    let monitor_height = if test_info.system_samples.is_empty() { 0 } else { SYSTEM_MONITOR_HEIGHT };
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(8),   // 上部：实时测试状态
-            Constraint::Min(6),      // 下部：测试结果
+            Constraint::Length(8),              // 上部：实时测试状态
+            Constraint::Length(monitor_height), // 中部：steal、iowait 和 CPU 频率
+            Constraint::Min(6),                 // 下部：测试结果
         ].as_ref())
         .split(area);
 
     // 上部分：实时测试状态
     draw_realtime_test_status(f, main_chunks[0], test_info, is_focused);
     
+    if monitor_height > 0 {
+        draw_system_monitor(f, main_chunks[1], &test_info.system_samples, is_focused);
+    }
+    
     // 下部分：测试结果
     if !test_info.results.is_empty() {
-        draw_compact_test_results(f, app, main_chunks[1], &test_info.results, is_focused);
+        draw_compact_test_results(f, app, main_chunks[2], &test_info.results, test_info.topology.as_ref(), is_focused);
     } else if test_info.is_testing {
-        draw_testing_progress_info(f, main_chunks[1], test_info, is_focused);
+        draw_testing_progress_info(f, main_chunks[2], test_info, is_focused);
     }
 }
 
@@ -213,7 +220,7 @@ fn draw_realtime_test_status(f: &mut Frame, area: Rect, test_info: &crate::handl
 }
 
 // 绘制紧凑的测试结果（下半部分）
-fn draw_compact_test_results(f: &mut Frame, app: &mut App, area: Rect, results: &[crate::handlers::cpu_test::CpuTestResult], is_focused: bool) {
+fn draw_compact_test_results(f: &mut Frame, app: &mut App, area: Rect, results: &[crate::handlers::cpu_test::CpuTestResult], topology: Option<&TopologyResult>, is_focused: bool) {
     let mut items = Vec::new();
     
     // 查找综合评分
@@ -242,6 +249,13 @@ fn draw_compact_test_results(f: &mut Frame, app: &mut App, area: Rect, results:
             Span::styled("└─────────────────────────────────────────────────┘", Theme::accent())
         ])));
         
+        // 评分版本和参考机器
+        for (key, value) in crate::handlers::cpu_test::sorted_details(final_score) {
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled(format!("  {}: {}", key, value), Theme::muted()),
+            ])));
+        }
+        
         items.push(ListItem::new(Line::from(vec![Span::raw("")])));
     }
     
@@ -271,10 +285,22 @@ fn draw_compact_test_results(f: &mut Frame, app: &mut App, area: Rect, results:
                 Span::styled(format!("{}ms", result.duration_ms), Theme::secondary()),
             ])));
             
+            // 吞吐量等详细数据
+            for (key, value) in crate::handlers::cpu_test::sorted_details(result) {
+                items.push(ListItem::new(Line::from(vec![
+                    Span::styled(format!("   {}: ", key), Theme::muted()),
+                    Span::styled(value.clone(), Theme::secondary()),
+                ])));
+            }
+            
             items.push(ListItem::new(Line::from(vec![Span::raw("")])));
         }
     }
     
+    if let Some(topology) = topology {
+        items.extend(topology_items(topology));
+    }
+    
     // 性能对比参考
     if let Some(final_score) = final_result {
         items.push(ListItem::new(Line::from(vec![
@@ -356,6 +382,59 @@ fn draw_compact_test_results(f: &mut Frame, app: &mut App, area: Rect, results:
     }
 }
 
+// 拓扑、逐核分数和扩展曲线
+fn topology_items(topology: &TopologyResult) -> Vec<ListItem<'static>> {
+    let mut items = vec![
+        ListItem::new(Line::from(vec![
+            Span::styled("🧭 核心拓扑与扩展性", Theme::primary().add_modifier(Modifier::BOLD))
+        ])),
+        ListItem::new(Line::from(vec![
+            Span::styled("─".repeat(50), Theme::muted())
+        ])),
+        ListItem::new(Line::from(vec![
+            Span::styled("   拓扑: ", Theme::muted()),
+            Span::styled(topology.topology.label(), Theme::secondary()),
+        ])),
+    ];
+    
+    if let Some(steal) = topology.steal_pct {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("   steal 时间: ", Theme::muted()),
+            Span::styled(format!("{:.1}%", steal), Theme::secondary()),
+        ])));
+    }
+    
+    // 逐核分数，慢核心标红
+    let slow_cores = topology.slow_cores();
+    let mut core_spans = vec![Span::styled(format!("   逐核 ({}): ", topology.kernel), Theme::muted())];
+    for core in &topology.cores {
+        let style = if slow_cores.contains(&core.cpu) { Theme::error() } else { Theme::success() };
+        core_spans.push(Span::styled(format!("{}:", core.cpu), Theme::muted()));
+        core_spans.push(Span::styled(format!("{} ", core.score), style));
+    }
+    items.push(ListItem::new(Line::from(core_spans)));
+    
+    // 扩展曲线，效率用条形表示
+    for point in &topology.scaling {
+        let filled = ((point.efficiency.clamp(0.0, 1.0) * 20.0).round()) as usize;
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(format!("   {:>3} 线程 ", point.threads), Theme::muted()),
+            Span::styled("█".repeat(filled), Theme::accent()),
+            Span::styled("░".repeat(20 - filled), Theme::muted()),
+            Span::styled(format!(" {:.2}x  效率 {:.0}%  {} 分", point.speedup, point.efficiency * 100.0, point.score), Theme::secondary()),
+        ])));
+    }
+    
+    for warning in &topology.warnings {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(format!("   ⚠ {}", warning), Theme::warning()),
+        ])));
+    }
+    
+    items.push(ListItem::new(Line::from(vec![Span::raw("")])));
+    items
+}
+
 // 绘制测试进行中的信息
 fn draw_testing_progress_info(f: &mut Frame, area: Rect, test_info: &crate::handlers::cpu_test::CpuTestInfo, is_focused: bool) {
     let elapsed_time = if let Some(start_time) = test_info.test_start_time {
@@ -423,13 +502,26 @@ fn draw_cpu_test_static_content(f: &mut Frame, app: &mut App, area: Rect, test_i
         ])));
         items.push(ListItem::new(Line::from(vec![Span::raw("")])));
     } else {
-        // 准备测试状态
+        // 准备状态，等待用户确认参数后开始
         items.push(ListItem::new(Line::from(vec![
-            Span::styled("🚀 准备执行CPU性能测试...", Theme::accent().add_modifier(Modifier::ITALIC))
+            Span::styled("▶ 按 Enter 开始测试", Theme::accent().add_modifier(Modifier::BOLD))
         ])));
         items.push(ListItem::new(Line::from(vec![Span::raw("")])));
     }
     
+    // 多核测试线程数
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("🧵 多核测试线程数: ", Theme::accent()),
+        Span::styled(test_info.threads.to_string(), Theme::secondary()),
+        Span::styled(format!(" / {} 个逻辑核心", num_cpus::get()), Theme::muted()),
+        Span::styled("  (C 切换)", Theme::muted()),
+    ])));
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("🔐 加密指令: ", Theme::accent()),
+        Span::styled(crate::handlers::crypto_bench::HardwareFeatures::detect().label(), Theme::secondary()),
+    ])));
+    items.push(ListItem::new(Line::from(vec![Span::raw("")])));
+    
     // CPU信息显示
     items.push(ListItem::new(Line::from(vec![
         Span::styled("💻 系统CPU信息", Theme::primary().add_modifier(Modifier::BOLD))
@@ -462,7 +554,7 @@ fn draw_cpu_test_static_content(f: &mut Frame, app: &mut App, area: Rect, test_i
         ("🔢", "整数运算", "斐波那契数列、质数计算"),
         ("🧮", "浮点运算", "三角函数、开方运算"),
         ("⚡", "矢量运算", "SIMD操作模拟"),
-        ("🔐", "加密算法", "哈希计算性能"),
+        ("🔐", "加密算法", "SHA-256 / AES-128-GCM / ChaCha20-Poly1305 吞吐量"),
         ("📦", "压缩算法", "数据压缩性能"),
         ("💾", "内存带宽", "内存访问速度"),
         ("🎯", "综合测试", "混合负载性能"),
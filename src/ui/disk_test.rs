@@ -2,12 +2,13 @@ use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::Modifier,
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Gauge, Padding},
+    widgets::{BarChart, Block, Borders, List, ListItem, Gauge, Padding, Paragraph, Row, Table, TableState},
     Frame,
 };
 
-use crate::{app::App, theme::Theme};
-use super::components::draw_scrollbar;
+use crate::{app::App, latency::{format_latency_us, LatencyStats}, theme::Theme};
+use super::helpers::create_block;
+use super::components::{draw_scrollbar, draw_system_monitor, SYSTEM_MONITOR_HEIGHT};
 
 /// 绘制磁盘测试内容
 pub fn draw_disk_test_content(f: &mut Frame, app: &mut App, area: Rect, is_focused: bool) {
@@ -26,24 +27,114 @@ pub fn draw_disk_test_content(f: &mut Frame, app: &mut App, area: Rect, is_focus
 
 // 绘制合并的测试和结果界面
 fn draw_combined_test_results_ui(f: &mut Frame, app: &mut App, area: Rect, test_info: &crate::handlers::disk_test::DiskTestInfo, is_focused: bool) {
-    // 创建上下布局
+    // 创建上下布局，有采样数据后在实时状态下方显示系统监控
+    let monitor_height = if test_info.system_samples.is_empty() { 0 } else { SYSTEM_MONITOR_HEIGHT };
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(8),   // 上部：实时测试状态
-            Constraint::Min(6),      // 下部：测试结果
+            Constraint::Length(4),              // 顶部：测试目标、I/O 模式、测试配置和测试参数
+            Constraint::Length(8),              // 上部：实时测试状态
+            Constraint::Length(monitor_height), // 中部：steal、iowait 和 CPU 频率
+            Constraint::Min(6),                 // 下部：测试结果
         ].as_ref())
         .split(area);
 
+    f.render_widget(
+        Paragraph::new(vec![target_line(test_info), io_mode_line(test_info), profile_line(test_info), params_line(test_info)]),
+        main_chunks[0],
+    );
+
     // 上部分：实时测试状态
-    draw_realtime_test_status(f, main_chunks[0], test_info, is_focused);
+    draw_realtime_test_status(f, main_chunks[1], test_info, is_focused);
+    
+    if monitor_height > 0 {
+        draw_system_monitor(f, main_chunks[2], &test_info.system_samples, is_focused);
+    }
     
     // 下部分：测试结果
-    if !test_info.results.is_empty() {
-        draw_compact_test_results(f, app, main_chunks[1], &test_info.results, is_focused);
+    if test_info.results.iter().any(|r| r.latency.is_some()) {
+        // 有延迟数据时右侧显示延迟面板
+        let result_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(55), // 测试结果
+                Constraint::Percentage(45), // 延迟百分位和分布
+            ].as_ref())
+            .split(main_chunks[3]);
+        draw_compact_test_results(f, app, result_chunks[0], &test_info.results, is_focused);
+        draw_latency_panel(f, app, result_chunks[1], &test_info.results, is_focused);
+    } else if !test_info.results.is_empty() {
+        draw_compact_test_results(f, app, main_chunks[3], &test_info.results, is_focused);
     } else if test_info.is_testing {
-        draw_testing_progress_info(f, main_chunks[1], test_info, is_focused);
+        draw_testing_progress_info(f, main_chunks[3], test_info, is_focused);
+    }
+}
+
+// 测试目标及文件系统警告
+fn target_line(test_info: &crate::handlers::disk_test::DiskTestInfo) -> Line<'static> {
+    let Some(ref target) = test_info.target else {
+        return Line::from(Span::styled("🎯 测试目标: 未知", Theme::muted()));
+    };
+
+    let mut spans = vec![
+        Span::styled("🎯 测试目标: ", Theme::accent()),
+        Span::styled(crate::handlers::disk_test::describe_target(target), Theme::secondary()),
+    ];
+    if let Some(warning) = target.warning() {
+        spans.push(Span::styled(format!("  ⚠ {}", warning), Theme::warning()));
+    }
+    if !test_info.is_testing {
+        spans.push(Span::styled("  (T 切换)", Theme::muted()));
+    }
+    Line::from(spans)
+}
+
+// I/O 模式，回退时说明原因
+fn io_mode_line(test_info: &crate::handlers::disk_test::DiskTestInfo) -> Line<'static> {
+    let mut spans = vec![
+        Span::styled("⚙️  I/O 模式: ", Theme::accent()),
+        Span::styled(test_info.io_mode.label(), Theme::secondary()),
+    ];
+    if let Some(ref note) = test_info.io_mode_note {
+        spans.push(Span::styled(format!("  ⚠ {}", note), Theme::warning()));
+    }
+    if !test_info.is_testing {
+        spans.push(Span::styled("  (I 直接 I/O 开关, S 同步方式, D 队列深度, J 任务数)", Theme::muted()));
+    }
+    Line::from(spans)
+}
+
+// 测试配置，fio 不可用时说明回退
+fn profile_line(test_info: &crate::handlers::disk_test::DiskTestInfo) -> Line<'static> {
+    let mut spans = vec![
+        Span::styled("🧪 测试配置: ", Theme::accent()),
+        Span::styled(test_info.profile.name(), Theme::secondary()),
+    ];
+    if let Some(ref note) = test_info.profile_note {
+        spans.push(Span::styled(format!("  ⚠ {}", note), Theme::warning()));
+    }
+    if !test_info.is_testing {
+        spans.push(Span::styled("  (P 切换)", Theme::muted()));
     }
+    Line::from(spans)
+}
+
+// 测试时长和读取文件大小，fio 任务使用任务文件中的参数
+fn params_line(test_info: &crate::handlers::disk_test::DiskTestInfo) -> Line<'static> {
+    if test_info.profile.uses_fio() {
+        return Line::from(vec![
+            Span::styled("⏱️  测试参数: ", Theme::accent()),
+            Span::styled("由 fio 任务决定", Theme::secondary()),
+        ]);
+    }
+    let mut spans = vec![
+        Span::styled("⏱️  测试参数: ", Theme::accent()),
+        Span::styled(test_info.params.label(), Theme::secondary()),
+    ];
+    if !test_info.is_testing {
+        spans.push(Span::styled("  (U 时长, F 文件大小)", Theme::muted()));
+    }
+    Line::from(spans)
 }
 
 // 绘制实时测试状态（上半部分）
@@ -241,9 +332,9 @@ fn draw_compact_test_results(f: &mut Frame, app: &mut App, area: Rect, results:
     // 创建清晰易读的卡片式表格显示
     let block_sizes = ["4K", "64K", "512K", "1M"];
     
-    // 检查是否有多块大小测试结果
+    // 检查是否有多块大小测试结果（fio 任务名可能也包含块大小，按完整名称判断）
     let has_multi_block_results = block_sizes.iter().any(|&block_size| {
-        results.iter().any(|r| r.test_name.contains(block_size))
+        results.iter().any(|r| r.test_name == format!("{} 读取", block_size) || r.test_name == format!("{} 写入", block_size))
     });
     
     if has_multi_block_results {
@@ -295,6 +386,83 @@ fn draw_compact_test_results(f: &mut Frame, app: &mut App, area: Rect, results:
     }
 }
 
+// 绘制延迟面板：上方为各项测试的百分位，下方为选中测试的延迟分布
+fn draw_latency_panel(f: &mut Frame, app: &mut App, area: Rect, results: &[crate::handlers::disk_test::DiskTestResult], is_focused: bool) {
+    let entries: Vec<(&str, &LatencyStats)> = results
+        .iter()
+        .filter_map(|r| r.latency.as_ref().map(|latency| (r.test_name.as_str(), latency)))
+        .collect();
+    if entries.is_empty() {
+        return;
+    }
+    let selected = app.disk_latency_selected % entries.len();
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(entries.len() as u16 + 3), // 百分位表格
+            Constraint::Min(6),                            // 分布图
+        ].as_ref())
+        .split(area);
+
+    let header = Row::new(vec!["测试", "p50", "p95", "p99", "p99.9", "最大"])
+        .style(Theme::primary().add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = entries
+        .iter()
+        .map(|(name, latency)| {
+            let mut cells = vec![name.to_string()];
+            cells.extend(
+                [latency.p50_us, latency.p95_us, latency.p99_us, latency.p999_us, latency.max_us]
+                    .iter()
+                    .map(|&us| format_latency_us(us)),
+            );
+            Row::new(cells).style(Theme::secondary())
+        })
+        .collect();
+    let table = Table::new(rows, [
+        Constraint::Min(9),     // 测试
+        Constraint::Length(7),  // p50
+        Constraint::Length(7),  // p95
+        Constraint::Length(7),  // p99
+        Constraint::Length(7),  // p99.9
+        Constraint::Length(7),  // 最大
+    ])
+    .header(header)
+    .block(create_block("⏱️ 延迟百分位", is_focused))
+    .highlight_style(Theme::list_selected())
+    .column_spacing(1);
+    let mut state = TableState::default().with_selected(Some(selected));
+    f.render_stateful_widget(table, chunks[0], &mut state);
+
+    // 选中测试的分布，横轴为延迟上限（按 2 的幂划分）
+    let (name, latency) = entries[selected];
+    let labels: Vec<String> = latency
+        .distribution
+        .iter()
+        .map(|bucket| format!("≤{}", format_latency_us(bucket.upper_us)))
+        .collect();
+    let data: Vec<(&str, u64)> = labels
+        .iter()
+        .zip(&latency.distribution)
+        .map(|(label, bucket)| (label.as_str(), bucket.count))
+        .collect();
+
+    let inner_width = chunks[1].width.saturating_sub(2);
+    let bar_count = data.len().max(1) as u16;
+    let bar_width = (inner_width.saturating_sub(bar_count - 1) / bar_count).clamp(1, 8);
+
+    let title = format!("📈 延迟分布: {} (L 切换)", name);
+    let chart = BarChart::default()
+        .block(create_block(&title, is_focused))
+        .data(&data)
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .bar_style(Theme::accent())
+        .value_style(Theme::primary().add_modifier(Modifier::REVERSED))
+        .label_style(Theme::muted());
+    f.render_widget(chart, chunks[1]);
+}
+
 // 绘制清晰易读的卡片式表格
 fn draw_fio_style_table(items: &mut Vec<ListItem>, results: &[crate::handlers::disk_test::DiskTestResult], block_sizes: &[&str]) {
     // 创建卡片式的结果展示，每个块大小一个卡片
@@ -532,13 +700,20 @@ fn draw_disk_test_static_content(f: &mut Frame, app: &mut App, area: Rect, test_
         ])));
         items.push(ListItem::new(Line::from(vec![Span::raw("")])));
     } else {
-        // 准备测试状态
+        // 准备状态，等待用户确认参数后开始
         items.push(ListItem::new(Line::from(vec![
-            Span::styled("🔄 准备执行磁盘测试...", Theme::accent().add_modifier(Modifier::ITALIC))
+            Span::styled("▶ 按 Enter 开始测试", Theme::accent().add_modifier(Modifier::BOLD))
         ])));
         items.push(ListItem::new(Line::from(vec![Span::raw("")])));
     }
     
+    // 测试目标
+    items.push(ListItem::new(target_line(test_info)));
+    items.push(ListItem::new(io_mode_line(test_info)));
+    items.push(ListItem::new(profile_line(test_info)));
+    items.push(ListItem::new(params_line(test_info)));
+    items.push(ListItem::new(Line::from(vec![Span::raw("")])));
+    
     // 工具检查状态 - 使用卡片式布局
     items.push(ListItem::new(Line::from(vec![
         Span::styled("🔧 测试工具检查", Theme::primary().add_modifier(Modifier::BOLD))
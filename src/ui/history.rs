@@ -0,0 +1,173 @@
+use ratatui::{
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use crate::{
+    app::App,
+    handlers::history::{comparison_target, format_time, get_history, HistoryData},
+    history::{compare, summary, MetricDiff, RunKind, SIGNIFICANT_CHANGE_PCT},
+    theme::Theme,
+    ui::helpers::create_block,
+};
+
+/// 绘制历史记录界面：上方为记录列表，下方为与基准的对比
+pub fn draw_history_content(f: &mut Frame, app: &mut App, area: Rect, is_focused: bool) {
+    let data = get_history();
+    let order = data.display_order();
+
+    // 记录被删除或重新加载后修正选中行
+    if app.history_selected >= order.len() {
+        app.history_selected = order.len().saturating_sub(1);
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(45), // 历史列表
+            Constraint::Min(6),         // 对比结果
+            Constraint::Length(1),      // 提示/错误
+        ].as_ref())
+        .split(area);
+
+    draw_run_list(f, app, &data, &order, chunks[0], is_focused);
+
+    let selected = order.get(app.history_selected).copied();
+    draw_comparison(f, app, &data, selected, chunks[1], is_focused);
+
+    let footer = match data.errors.first() {
+        Some(error) => Line::from(Span::styled(format!("⚠ {}", error), Theme::error())),
+        None => Line::from(Span::styled("↑↓ 选择记录 │ B 设为/取消对比基准 │ R 重新读取", Theme::muted())),
+    };
+    f.render_widget(Paragraph::new(footer).alignment(Alignment::Center), chunks[2]);
+}
+
+fn draw_run_list(f: &mut Frame, app: &App, data: &HistoryData, order: &[usize], area: Rect, is_focused: bool) {
+    let title = match data.path {
+        Some(ref path) => format!("历史记录 ({})", path.display()),
+        None => "历史记录".to_string(),
+    };
+    let block = create_block(&title, is_focused);
+
+    if order.is_empty() {
+        let text = vec![
+            Line::from(""),
+            Line::from(Span::styled("暂无记录", Theme::muted())),
+            Line::from(""),
+            Line::from("完成一次硬盘/CPU/网速测试后会自动保存结果"),
+        ];
+        f.render_widget(Paragraph::new(text).block(block).alignment(Alignment::Center), area);
+        return;
+    }
+
+    let header = Row::new(vec!["", "时间", "主机", "类型", "摘要"])
+        .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD));
+
+    let rows: Vec<Row> = order
+        .iter()
+        .map(|&index| {
+            let run = &data.runs[index];
+            let is_baseline = app.history_baseline.as_deref() == Some(run.generated_at.as_str());
+            Row::new(vec![
+                if is_baseline { "★".to_string() } else { String::new() },
+                format_time(&run.generated_at),
+                run.host.hostname.clone(),
+                RunKind::of(run).map(|k| k.name()).unwrap_or("-").to_string(),
+                summary(run),
+            ])
+            .style(if is_baseline { Theme::accent() } else { Theme::secondary() })
+        })
+        .collect();
+
+    let table = Table::new(rows, [
+        Constraint::Length(2),   // 基准标记
+        Constraint::Length(17),  // 时间
+        Constraint::Length(16),  // 主机
+        Constraint::Length(5),   // 类型
+        Constraint::Min(20),     // 摘要
+    ])
+    .header(header)
+    .block(block)
+    .highlight_style(Theme::list_selected())
+    .column_spacing(1);
+
+    let mut state = TableState::default().with_selected(Some(app.history_selected));
+    f.render_stateful_widget(table, area, &mut state);
+}
+
+fn draw_comparison(f: &mut Frame, app: &App, data: &HistoryData, selected: Option<usize>, area: Rect, is_focused: bool) {
+    let target = selected.and_then(|index| comparison_target(data, index, app.history_baseline.as_deref()));
+
+    let (Some(current), Some(base)) = (selected, target) else {
+        let block = create_block("对比", is_focused);
+        let message = if selected.is_some() {
+            "没有可对比的记录（同一主机上的同类测试至少需要两次）"
+        } else {
+            ""
+        };
+        f.render_widget(
+            Paragraph::new(Span::styled(message, Theme::muted())).block(block).alignment(Alignment::Center),
+            area,
+        );
+        return;
+    };
+
+    let current_run = &data.runs[current];
+    let base_run = &data.runs[base];
+    let is_baseline = app.history_baseline.as_deref() == Some(base_run.generated_at.as_str());
+    let title = format!(
+        "对比 {} ({}) → {}",
+        if is_baseline { "基准" } else { "上一次" },
+        format_time(&base_run.generated_at),
+        format_time(&current_run.generated_at),
+    );
+    let block = create_block(&title, is_focused);
+
+    let header = Row::new(vec!["指标", "基准", "当前", "变化"])
+        .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD));
+
+    let rows: Vec<Row> = compare(current_run, base_run)
+        .iter()
+        .map(|diff| {
+            Row::new(vec![
+                diff.label.clone(),
+                format_value(diff.baseline, diff.unit),
+                format_value(diff.current, diff.unit),
+                diff.change_pct.map(|pct| format!("{:+.1}%", pct)).unwrap_or_else(|| "-".to_string()),
+            ])
+            .style(diff_style(diff))
+        })
+        .collect();
+
+    let table = Table::new(rows, [
+        Constraint::Min(20),     // 指标
+        Constraint::Length(14),  // 基准
+        Constraint::Length(14),  // 当前
+        Constraint::Length(9),   // 变化
+    ])
+    .header(header)
+    .block(block)
+    .column_spacing(1);
+
+    f.render_widget(table, area);
+}
+
+fn format_value(value: f64, unit: &str) -> String {
+    match unit {
+        "" => format!("{:.0}", value),
+        "分" => format!("{:.0} {}", value, unit),
+        _ => format!("{:.1} {}", value, unit),
+    }
+}
+
+// 明显变差标红，明显变好标绿
+fn diff_style(diff: &MetricDiff) -> Style {
+    match diff.improvement_pct() {
+        Some(_) if diff.is_regression() => Theme::error(),
+        Some(pct) if pct >= SIGNIFICANT_CHANGE_PCT => Theme::success(),
+        _ => Theme::secondary(),
+    }
+}
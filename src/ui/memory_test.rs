@@ -0,0 +1,284 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::Modifier,
+    symbols::Marker,
+    text::{Line, Span},
+    widgets::{Axis, Block, Borders, Chart, Dataset, Gauge, GraphType, List, ListItem, Padding, Row, Table},
+    Frame,
+};
+
+use crate::{app::App, theme::Theme};
+use crate::handlers::memory_test::{self, format_size, level_latencies, MemoryTestInfo, StreamKernel};
+use super::components::draw_scrollbar;
+use super::helpers::create_block;
+
+/// 绘制内存测试内容
+pub fn draw_memory_test_content(f: &mut Frame, app: &mut App, area: Rect, is_focused: bool) {
+    let test_info = memory_test::get_current_test_info();
+
+    if test_info.is_testing || !test_info.bandwidth.is_empty() {
+        draw_test_results_ui(f, area, &test_info, is_focused);
+        return;
+    }
+
+    // 显示准备状态或错误信息的静态界面
+    draw_memory_test_static_content(f, app, area, &test_info, is_focused);
+}
+
+// 进度、带宽表格和延迟曲线
+fn draw_test_results_ui(f: &mut Frame, area: Rect, test_info: &MemoryTestInfo, is_focused: bool) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),                                // 上部：进度
+            Constraint::Length(StreamKernel::ALL.len() as u16 + 3), // 中部：带宽
+            Constraint::Min(8),                                   // 下部：延迟曲线
+        ].as_ref())
+        .split(area);
+
+    draw_progress(f, chunks[0], test_info, is_focused);
+    draw_bandwidth_table(f, chunks[1], test_info, is_focused);
+
+    let latency_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(30), Constraint::Length(28)].as_ref())
+        .split(chunks[2]);
+    draw_latency_chart(f, latency_chunks[0], test_info, is_focused);
+    draw_level_summary(f, latency_chunks[1], test_info, is_focused);
+}
+
+fn draw_progress(f: &mut Frame, area: Rect, test_info: &MemoryTestInfo, is_focused: bool) {
+    let (border_style, title_style) = if is_focused {
+        (Theme::border_focused(), Theme::title_focused())
+    } else {
+        (Theme::border_unfocused(), Theme::title_unfocused())
+    };
+    let elapsed = test_info.test_start_time.map(|start| start.elapsed().as_secs()).unwrap_or(0);
+    let progress = test_info.progress.min(100);
+
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", test_info.current_test))
+                .title_style(title_style)
+                .border_style(border_style),
+        )
+        .gauge_style(Theme::disk_test_progress_style(progress as f64))
+        .ratio(progress as f64 / 100.0)
+        .label(format!("{}%  {}s", progress, elapsed));
+    f.render_widget(gauge, area);
+}
+
+fn draw_bandwidth_table(f: &mut Frame, area: Rect, test_info: &MemoryTestInfo, is_focused: bool) {
+    let multi_header = format!("{} 线程", test_info.threads);
+    let title = format!("📶 STREAM 带宽 (3 × {})", format_size(test_info.array_bytes));
+    let header = Row::new(vec!["内核", "公式", "单线程", multi_header.as_str()])
+        .style(Theme::primary().add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = StreamKernel::ALL
+        .iter()
+        .map(|kernel| {
+            let result = test_info.bandwidth.iter().find(|r| r.kernel == kernel.name());
+            let mb_s = |value: Option<f64>| value.map(|v| format!("{:.0} MB/s", v)).unwrap_or_else(|| "--".to_string());
+            Row::new(vec![
+                kernel.name().to_string(),
+                kernel.formula().to_string(),
+                mb_s(result.map(|r| r.single_mb_s)),
+                mb_s(result.map(|r| r.multi_mb_s)),
+            ])
+            .style(Theme::secondary())
+        })
+        .collect();
+    let table = Table::new(rows, [
+        Constraint::Length(7),  // 内核
+        Constraint::Length(12), // 公式
+        Constraint::Length(12), // 单线程
+        Constraint::Length(12), // 多线程
+    ])
+    .header(header)
+    .block(create_block(&title, is_focused))
+    .column_spacing(2);
+    f.render_widget(table, area);
+}
+
+// 横轴为工作集大小的 log2，纵轴为每次访问的延迟，竖线标出各级缓存容量
+fn draw_latency_chart(f: &mut Frame, area: Rect, test_info: &MemoryTestInfo, is_focused: bool) {
+    let log2 = |bytes: usize| (bytes as f64).log2();
+    let (Some(first), Some(last)) = (test_info.working_sets.first(), test_info.working_sets.last()) else {
+        return;
+    };
+    let x_bounds = [log2(*first), log2(*last).max(log2(*first) + 1.0)];
+
+    let points: Vec<(f64, f64)> = test_info.latency.iter().map(|p| (log2(p.size_bytes), p.latency_ns)).collect();
+    let y_max = points.iter().map(|&(_, ns)| ns).fold(1.0, f64::max) * 1.1;
+
+    let cache = &test_info.cache;
+    let boundaries: Vec<(String, Vec<(f64, f64)>)> = [("L1d", cache.l1d_bytes), ("L2", cache.l2_bytes), ("L3", cache.l3_bytes)]
+        .iter()
+        .filter_map(|&(name, bytes)| {
+            let bytes = bytes?;
+            let x = log2(bytes);
+            (x >= x_bounds[0] && x <= x_bounds[1])
+                .then(|| (format!("{} {}", name, format_size(bytes)), vec![(x, 0.0), (x, y_max)]))
+        })
+        .collect();
+
+    let mut datasets: Vec<Dataset> = boundaries
+        .iter()
+        .map(|(name, line)| {
+            Dataset::default()
+                .name(name.clone())
+                .marker(Marker::Dot)
+                .graph_type(GraphType::Line)
+                .style(Theme::muted())
+                .data(line)
+        })
+        .collect();
+    datasets.push(
+        Dataset::default()
+            .name("延迟")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(ratatui::style::Style::default().fg(Theme::chart_read_color()))
+            .data(&points),
+    );
+
+    let x_labels: Vec<Span> = [x_bounds[0], (x_bounds[0] + x_bounds[1]) / 2.0, x_bounds[1]]
+        .iter()
+        .map(|x| Span::styled(format_size(x.round().exp2() as usize), Theme::muted()))
+        .collect();
+    let y_labels: Vec<Span> = [0.0, y_max / 2.0, y_max]
+        .iter()
+        .map(|ns| Span::styled(format!("{:.0}", ns), Theme::muted()))
+        .collect();
+
+    let chart = Chart::new(datasets)
+        .block(create_block("⏱️ 访问延迟 - 工作集大小", is_focused))
+        .x_axis(Axis::default().title("工作集").bounds(x_bounds).labels(x_labels))
+        .y_axis(Axis::default().title("ns").bounds([0.0, y_max]).labels(y_labels));
+    f.render_widget(chart, area);
+}
+
+fn draw_level_summary(f: &mut Frame, area: Rect, test_info: &MemoryTestInfo, is_focused: bool) {
+    let mut items = vec![ListItem::new(Line::from(vec![
+        Span::styled("各级延迟 (中位数)", Theme::primary().add_modifier(Modifier::BOLD)),
+    ]))];
+    let levels = level_latencies(&test_info.latency);
+    if levels.is_empty() {
+        items.push(ListItem::new(Line::from(vec![Span::styled("--", Theme::muted())])));
+    }
+    for (level, latency_ns) in levels {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(format!("{:<5}", level.name()), Theme::accent()),
+            Span::styled(format!("{:>8.1} ns", latency_ns), Theme::success()),
+        ])));
+    }
+    items.push(ListItem::new(Line::from(vec![Span::raw("")])));
+
+    // 最近测得的几个点，测试进行中可以看到曲线的推进
+    for point in test_info.latency.iter().rev().take(area.height.saturating_sub(6) as usize) {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(format!("{:>6}", format_size(point.size_bytes)), Theme::muted()),
+            Span::styled(format!("{:>8.1} ns", point.latency_ns), Theme::secondary()),
+            Span::styled(format!(" {}", point.level.map(|l| l.name()).unwrap_or("")), Theme::muted()),
+        ])));
+    }
+
+    f.render_widget(List::new(items).block(create_block("📋 延迟", is_focused)), area);
+}
+
+// 准备状态或错误信息
+fn draw_memory_test_static_content(f: &mut Frame, app: &mut App, area: Rect, test_info: &MemoryTestInfo, is_focused: bool) {
+    let mut items = vec![
+        ListItem::new(Line::from(vec![
+            Span::styled("🧠 内存带宽与延迟测试", Theme::primary().add_modifier(Modifier::BOLD))
+        ])),
+        ListItem::new(Line::from(vec![Span::raw("")])),
+    ];
+
+    if let Some(ref error) = test_info.error_message {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("❌ 错误: ", Theme::error()),
+            Span::styled(error.clone(), Theme::error().add_modifier(Modifier::BOLD))
+        ])));
+    } else {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("▶ 按 Enter 开始测试", Theme::accent().add_modifier(Modifier::BOLD))
+        ])));
+    }
+    items.push(ListItem::new(Line::from(vec![Span::raw("")])));
+
+    let settings = [
+        ("🗄️ 缓存: ", test_info.cache.label()),
+        ("📶 STREAM 数组: ", format!("3 × {}", format_size(test_info.array_bytes))),
+        ("🧵 多线程带宽线程数: ", test_info.threads.to_string()),
+        (
+            "📏 延迟工作集: ",
+            format!(
+                "{} - {} ({} 档)",
+                test_info.working_sets.first().map(|&s| format_size(s)).unwrap_or_default(),
+                test_info.working_sets.last().map(|&s| format_size(s)).unwrap_or_default(),
+                test_info.working_sets.len()
+            ),
+        ),
+    ];
+    for (label, value) in settings {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(label, Theme::accent()),
+            Span::styled(value, Theme::secondary()),
+        ])));
+    }
+    items.push(ListItem::new(Line::from(vec![Span::raw("")])));
+
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("💡 测试说明", Theme::primary().add_modifier(Modifier::BOLD))
+    ])));
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("─".repeat(40), Theme::muted())
+    ])));
+    for note in [
+        "• 带宽: STREAM 的 Copy / Scale / Add / Triad，数组远大于末级缓存",
+        "• 延迟: 在随机单环链表上做指针追逐，每次访问依赖上一次的结果",
+        "• 工作集逐级翻倍，延迟曲线的台阶对应 L1、L2、L3 和内存",
+        "• 大工作集的延迟包含 TLB 未命中的开销",
+    ] {
+        items.push(ListItem::new(Line::from(vec![Span::styled(note, Theme::muted())])));
+    }
+
+    // 更新滚动状态
+    let content_height = items.len() as u16;
+    let viewport_height = area.height.saturating_sub(2);
+    app.update_content_height(content_height, viewport_height);
+
+    let visible_items = if content_height > viewport_height {
+        items
+            .iter()
+            .skip(app.scroll_position.current as usize)
+            .take(viewport_height as usize)
+            .cloned()
+            .collect::<Vec<_>>()
+    } else {
+        items
+    };
+
+    let (border_style, title_style) = if is_focused {
+        (Theme::border_focused(), Theme::title_focused())
+    } else {
+        (Theme::border_unfocused(), Theme::title_unfocused())
+    };
+
+    let list = List::new(visible_items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" 🧠 内存测试 ")
+                .title_style(title_style)
+                .border_style(border_style)
+                .padding(Padding::uniform(1)),
+        );
+    f.render_widget(list, area);
+
+    if content_height > viewport_height {
+        draw_scrollbar(f, app, area, is_focused);
+    }
+}
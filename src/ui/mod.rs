@@ -3,6 +3,10 @@ pub mod system_info;
 pub mod disk_test;
 pub mod cpu_test;
 pub mod network_test;
+pub mod memory_test;
+pub mod route_trace;
+pub mod unlock_check;
+pub mod history;
 pub mod helpers;
 
 use ratatui::{
@@ -18,6 +22,10 @@ use system_info::draw_system_info_content;
 use disk_test::draw_disk_test_content;
 use cpu_test::draw_cpu_test_content;
 use network_test::draw_network_test_content;
+use memory_test::draw_memory_test_content;
+use route_trace::draw_route_trace_content;
+use unlock_check::draw_unlock_check_content;
+use history::draw_history_content;
 
 pub fn draw(f: &mut Frame, app: &mut App) {
     let size = f.size();
@@ -64,23 +72,23 @@ fn draw_content(f: &mut Frame, app: &mut App, area: Rect, is_focused: bool) {
             draw_disk_test_content(f, app, content_area, is_focused);
         },
         crate::menu::MenuItem::CpuTest => {
-            // 检查是否需要自动启动CPU测试
-            let test_info = crate::handlers::cpu_test::get_current_test_info();
-            if !test_info.is_testing && test_info.results.is_empty() && test_info.error_message.is_none() {
-                // 自动启动CPU测试
-                crate::handlers::cpu_test::start_cpu_test();
-            }
             draw_cpu_test_content(f, app, content_area, is_focused);
         },
         crate::menu::MenuItem::NetworkSpeedTest => {
-            // 检查是否需要自动启动网速测试
-            let test_info = crate::handlers::network_test::get_current_test_info();
-            if !test_info.is_testing && test_info.results.is_empty() && test_info.error_message.is_none() {
-                // 自动启动网速测试
-                crate::handlers::network_test::start_network_test();
-            }
             draw_network_test_content(f, app, content_area, is_focused);
         },
+        crate::menu::MenuItem::MemoryTest => {
+            draw_memory_test_content(f, app, content_area, is_focused);
+        },
+        crate::menu::MenuItem::RouteTrace => {
+            draw_route_trace_content(f, app, content_area, is_focused);
+        },
+        crate::menu::MenuItem::UnlockCheck => {
+            draw_unlock_check_content(f, app, content_area, is_focused);
+        },
+        crate::menu::MenuItem::History => {
+            draw_history_content(f, app, content_area, is_focused);
+        },
         _ => {
             draw_regular_content(f, app, content_area, is_focused);
         }
@@ -88,14 +96,28 @@ fn draw_content(f: &mut Frame, app: &mut App, area: Rect, is_focused: bool) {
 }
 
 fn draw_help_bar(f: &mut Frame, app: &App, area: Rect) {
-    let help_text = if app.show_menu {
+    let control = crate::handlers::test_control(app.menu.selected_item());
+    // 系统信息页只有 DNS 测试，未运行时仍显示滚动提示
+    let is_test_page = control.is_some() && app.menu.selected_item() != crate::menu::MenuItem::SystemInfo;
+    let is_running = control.is_some_and(|control| control.is_running());
+    let help_text = if control.is_some_and(|control| control.is_paused()) {
+        " 测试已暂停 │ 空格 继续 │ X 取消测试 │ Q 退出 "
+    } else if app.show_menu {
         match app.focus_area {
             FocusArea::Menu => " Ctrl+D/Q 退出 │ ↑↓ 选择菜单 │ →/Tab 切换到内容 │ M 隐藏菜单 │ Enter 选择 ",
-            FocusArea::Content => " Q 退出 │ ↑↓/PgUp/PgDn 滚动 │ ←/Tab 切换到菜单 │ M 隐藏菜单 ",
+            FocusArea::Content if is_running => " Q 退出 │ ←/Tab 切换到菜单 │ X 取消测试 │ 空格 暂停/继续 │ E 导出报告 ",
+            FocusArea::Content if is_test_page => " Q 退出 │ ←/Tab 切换到菜单 │ Enter 开始测试 │ E 导出报告 ",
+            FocusArea::Content => " Q 退出 │ ↑↓/PgUp/PgDn 滚动 │ ←/Tab 切换到菜单 │ M 隐藏菜单 │ E 导出报告 ",
         }
+    } else if is_running {
+        " Q 退出 │ M 显示菜单 │ X 取消测试 │ 空格 暂停/继续 │ E 导出报告 "
+    } else if is_test_page {
+        " Q 退出 │ M 显示菜单 │ Enter 开始测试 │ E 导出报告 "
     } else {
-        " Q 退出 │ ↑↓/PgUp/PgDn 滚动内容 │ M 显示菜单 "
+        " Q 退出 │ ↑↓/PgUp/PgDn 滚动内容 │ M 显示菜单 │ E 导出报告 "
     };
+    // 有临时提示时优先显示
+    let help_text = app.current_status().unwrap_or(help_text);
     
     let help = Paragraph::new(help_text)
         .style(Theme::help_bar())
@@ -8,7 +8,11 @@ use ratatui::{
 
 use crate::{
     app::App,
-    handlers::network_test::{get_current_test_info, NetworkTestInfo, TestStatus},
+    handlers::iperf,
+    handlers::network_test::{
+        get_current_test_info, get_network_providers, parse_hex_color, selected_server, speed_test_config,
+        NetworkTestInfo, TestStatus,
+    },
     ui::helpers::{create_block, get_progress_color},
 };
 
@@ -25,34 +29,75 @@ pub fn draw_network_test_content(f: &mut Frame, _app: &mut App, area: Rect, is_f
 fn draw_test_welcome(f: &mut Frame, area: Rect, is_focused: bool) {
     let block = create_block("网速测试", is_focused);
     
-    let welcome_text = vec![
+    let provider_list = get_network_providers();
+    let source = match provider_list.source {
+        Some(ref path) => format!("测速节点（{}）：", path.display()),
+        None => "测速节点（内置）：".to_string(),
+    };
+    let selected = selected_server();
+    
+    let mut welcome_text = vec![
         Line::from(""),
         Line::from(vec![
             Span::styled("🌐 ", Style::default().fg(Color::Cyan)),
-            Span::styled("三网测速工具", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+            Span::styled("网络测速工具", Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
         ]),
         Line::from(""),
-        Line::from("支持运营商："),
+        Line::from(source),
+    ];
+    
+    for provider in &provider_list.providers {
+        let color = provider_color(&provider.color);
+        // 只测一台服务器时标出选中的那台，其余置灰
+        let (marker, style) = match selected {
+            Some(ref name) if *name == provider.name => ("  ▶ ", Style::default().fg(color).add_modifier(Modifier::BOLD)),
+            Some(_) => ("  • ", Style::default().fg(Color::DarkGray)),
+            None => ("  • ", Style::default().fg(color)),
+        };
+        let mut line = vec![
+            Span::styled(marker, style),
+            Span::styled(provider.name.clone(), style),
+        ];
+        if let Some(ref target) = provider.iperf {
+            line.push(Span::styled(format!("  iperf3 {}", target.label()), Style::default().fg(Color::DarkGray)));
+        }
+        welcome_text.push(Line::from(line));
+    }
+    
+    for error in &provider_list.errors {
+        welcome_text.push(Line::from(vec![
+            Span::styled("  ⚠ ", Style::default().fg(Color::Red)),
+            Span::styled(error.clone(), Style::default().fg(Color::Red)),
+        ]));
+    }
+    
+    welcome_text.extend([
+        Line::from(""),
         Line::from(vec![
-            Span::styled("  • ", Style::default().fg(Color::Green)),
-            Span::styled("中国移动", Style::default().fg(Color::Green)),
+            Span::styled("测速服务器：", Style::default().fg(Color::White)),
+            Span::styled(selected.unwrap_or_else(|| "全部".to_string()), Style::default().fg(Color::Cyan)),
+            Span::styled("  (T 切换)", Style::default().fg(Color::DarkGray)),
         ]),
         Line::from(vec![
-            Span::styled("  • ", Style::default().fg(Color::Red)),
-            Span::styled("中国联通", Style::default().fg(Color::Red)),
+            Span::styled("测试参数：", Style::default().fg(Color::White)),
+            Span::styled(speed_test_config().label(), Style::default().fg(Color::Cyan)),
+            Span::styled("  (U 时长, C 连接数)", Style::default().fg(Color::DarkGray)),
         ]),
+        Line::from("测试项目：延迟、下载速度、上传速度（iperf3 节点另测 TCP 重传和 UDP 丢包）"),
         Line::from(vec![
-            Span::styled("  • ", Style::default().fg(Color::Blue)),
-            Span::styled("中国电信", Style::default().fg(Color::Blue)),
+            Span::styled("iperf3 服务端：", Style::default().fg(Color::White)),
+            match iperf::server_port() {
+                Some(port) => Span::styled(format!("运行中，端口 {}", port), Style::default().fg(Color::Green)),
+                None => Span::styled("未运行", Style::default().fg(Color::Gray)),
+            },
+            Span::styled("  (S 启动/停止，供另一台机器测试本机)", Style::default().fg(Color::DarkGray)),
         ]),
         Line::from(""),
-        Line::from("测试项目：延迟、下载速度、上传速度"),
-        Line::from(""),
         Line::from(vec![
             Span::styled("💡 ", Style::default().fg(Color::Yellow)),
-            Span::styled("进入此页面将自动开始测试", Style::default().fg(Color::White)),
+            Span::styled("按 Enter 开始测试", Style::default().fg(Color::White)),
         ]),
-    ];
+    ]);
     
     let paragraph = Paragraph::new(welcome_text)
         .block(block)
@@ -130,7 +175,18 @@ fn draw_progress_section(f: &mut Frame, test_info: &NetworkTestInfo, area: Rect,
                 let mut info_parts = Vec::new();
                 
                 if result.ping > 0.0 {
-                    info_parts.push(format!("延迟: {:.1}ms", result.ping));
+                    match result.ping_method {
+                        Some(method) => info_parts.push(format!(
+                            "延迟: {:.1}ms ({}, 丢包 {:.0}%)",
+                            result.ping,
+                            method.name(),
+                            result.packet_loss
+                        )),
+                        None => info_parts.push(format!("延迟: {:.1}ms", result.ping)),
+                    }
+                }
+                if result.http_latency > 0.0 {
+                    info_parts.push(format!("HTTP: {:.1}ms", result.http_latency));
                 }
                 if result.download_speed > 0.0 {
                     info_parts.push(format!("下载: {:.1} Mbps", result.download_speed));
@@ -165,7 +221,25 @@ fn draw_results_section(f: &mut Frame, test_info: &NetworkTestInfo, area: Rect,
     let inner = block.inner(area);
     f.render_widget(block, area);
     
+    let providers = get_network_providers().providers;
+    
+    // 配置文件校验错误占用结果区域底部一行
+    let inner = if let Some(ref error) = test_info.error_message {
+        let error_area = Rect { y: inner.bottom().saturating_sub(1), height: 1.min(inner.height), ..inner };
+        f.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled("⚠ ", Style::default().fg(Color::Red)),
+                Span::styled(error.as_str(), Style::default().fg(Color::Red)),
+            ])),
+            error_area,
+        );
+        Rect { height: inner.height.saturating_sub(1), ..inner }
+    } else {
+        inner
+    };
+    
     if test_info.results.is_empty() {
+        let names: Vec<&str> = providers.iter().map(|p| p.name.as_str()).collect();
         let waiting_text = vec![
             Line::from(""),
             Line::from(vec![
@@ -173,7 +247,7 @@ fn draw_results_section(f: &mut Frame, test_info: &NetworkTestInfo, area: Rect,
                 Span::styled("等待测试结果...", Style::default().fg(Color::Gray)),
             ]),
             Line::from(""),
-            Line::from("将依次测试：中国移动 → 中国联通 → 中国电信"),
+            Line::from(format!("将依次测试：{}", names.join(" → "))),
         ];
         
         let paragraph = Paragraph::new(waiting_text)
@@ -184,12 +258,12 @@ fn draw_results_section(f: &mut Frame, test_info: &NetworkTestInfo, area: Rect,
     
     // 创建表格显示结果
     let header = Row::new(vec![
-        "运营商", "状态", "延迟(ms)", "抖动(ms)", "下载(Mbps)", "上传(Mbps)", "评级"
+        "运营商", "状态", "延迟(ms)", "抖动(ms)", "丢包", "下载(Mbps)", "上传(Mbps)", "评级"
     ]).style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD));
     
-    let rows: Vec<Row> = vec!["中国移动", "中国联通", "中国电信"]
-        .iter()
-        .map(|&provider_name| {
+    let mut rows: Vec<Row> = Vec::new();
+    for provider_name in providers.iter().map(|provider| provider.name.as_str()) {
+        let row = {
             if let Some(result) = test_info.results.get(provider_name) {
                 let status_style = match result.status {
                     TestStatus::NotStarted => Style::default().fg(Color::Gray),
@@ -217,6 +291,13 @@ fn draw_results_section(f: &mut Frame, test_info: &NetworkTestInfo, area: Rect,
                     "-".to_string()
                 };
                 
+                // 探测全部失败、延迟取自 HTTP 请求时没有丢包数据
+                let loss_cell = if result.ping_method.is_some() {
+                    format!("{:.0}%", result.packet_loss)
+                } else {
+                    "-".to_string()
+                };
+                
                 let download_cell = if result.download_speed > 0.0 {
                     format!("{:.1}", result.download_speed)
                 } else {
@@ -236,6 +317,7 @@ fn draw_results_section(f: &mut Frame, test_info: &NetworkTestInfo, area: Rect,
                     status_text.to_string(),
                     ping_cell,
                     jitter_cell,
+                    loss_cell,
                     download_cell,
                     upload_cell,
                     rating.0,
@@ -249,15 +331,39 @@ fn draw_results_section(f: &mut Frame, test_info: &NetworkTestInfo, area: Rect,
                     "-".to_string(),
                     "-".to_string(),
                     "-".to_string(),
+                    "-".to_string(),
                 ]).style(Style::default().fg(Color::Gray))
             }
-        }).collect();
+        };
+        rows.push(row);
+    }
+    
+    // iperf3 节点的重传和 UDP 结果显示在表格下方，每个节点一行
+    let iperf_lines: Vec<Line> = providers
+        .iter()
+        .filter_map(|provider| {
+            let stats = test_info.results.get(&provider.name)?.iperf.as_ref()?;
+            Some(Line::from(vec![
+                Span::styled(format!("{} iperf3: ", provider.name), Style::default().fg(Color::Cyan)),
+                Span::styled(stats.summary(), Style::default().fg(Color::Gray)),
+            ]))
+        })
+        .collect();
+    let inner = if iperf_lines.is_empty() {
+        inner
+    } else {
+        let height = (iperf_lines.len() as u16).min(inner.height / 2);
+        let detail_area = Rect { y: inner.bottom().saturating_sub(height), height, ..inner };
+        f.render_widget(Paragraph::new(iperf_lines), detail_area);
+        Rect { height: inner.height.saturating_sub(height), ..inner }
+    };
     
     let table = Table::new(rows, [
-        Constraint::Length(8),   // 运营商
+        Constraint::Length(14),  // 运营商
         Constraint::Length(8),   // 状态
         Constraint::Length(10),  // 延迟
         Constraint::Length(10),  // 抖动
+        Constraint::Length(6),   // 丢包
         Constraint::Length(12),  // 下载
         Constraint::Length(12),  // 上传
         Constraint::Length(8),   // 评级
@@ -268,6 +374,12 @@ fn draw_results_section(f: &mut Frame, test_info: &NetworkTestInfo, area: Rect,
     f.render_widget(table, inner);
 }
 
+fn provider_color(hex: &str) -> Color {
+    parse_hex_color(hex)
+        .map(|(r, g, b)| Color::Rgb(r, g, b))
+        .unwrap_or(Color::White)
+}
+
 fn get_speed_rating(speed_mbps: f64) -> (String, Color) {
     if speed_mbps >= 100.0 {
         ("优秀".to_string(), Color::Green)
@@ -0,0 +1,254 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Gauge, List, ListItem, Padding, Paragraph, Row, Table},
+    Frame,
+};
+
+use crate::{app::App, theme::Theme};
+use crate::handlers::network_test;
+use crate::handlers::route_trace::{self, format_ms, HopStats, RouteTrace, RouteTraceInfo, MAX_HOPS};
+use super::components::draw_scrollbar;
+use super::helpers::create_block;
+
+/// 绘制路由追踪内容
+pub fn draw_route_trace_content(f: &mut Frame, app: &mut App, area: Rect, is_focused: bool) {
+    let info = route_trace::get_current_test_info();
+
+    if !info.traces.is_empty() {
+        draw_trace_results_ui(f, app, area, &info, is_focused);
+        return;
+    }
+
+    // 显示准备状态或错误信息的静态界面
+    draw_route_trace_static_content(f, app, area, &info, is_focused);
+}
+
+// 进度、目标概况和逐跳统计
+fn draw_trace_results_ui(f: &mut Frame, app: &App, area: Rect, info: &RouteTraceInfo, is_focused: bool) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // 上部：进度
+            Constraint::Length(if info.error_message.is_some() { 5 } else { 4 }), // 中部：目标
+            Constraint::Min(6),    // 下部：逐跳统计
+        ].as_ref())
+        .split(area);
+
+    let trace = &info.traces[app.route_trace_selected % info.traces.len()];
+    draw_progress(f, chunks[0], info, is_focused);
+    draw_target_summary(f, chunks[1], info, trace, is_focused);
+    draw_hop_table(f, chunks[2], trace, is_focused);
+}
+
+fn draw_progress(f: &mut Frame, area: Rect, info: &RouteTraceInfo, is_focused: bool) {
+    let (border_style, title_style) = if is_focused {
+        (Theme::border_focused(), Theme::title_focused())
+    } else {
+        (Theme::border_unfocused(), Theme::title_unfocused())
+    };
+    let elapsed = info.start_time.map(|start| start.elapsed().as_secs()).unwrap_or(0);
+    let ratio = if info.rounds == 0 { 0.0 } else { (info.round as f64 / info.rounds as f64).min(1.0) };
+
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", info.current_stage))
+                .title_style(title_style)
+                .border_style(border_style),
+        )
+        .gauge_style(Theme::disk_test_progress_style(ratio * 100.0))
+        .ratio(ratio)
+        .label(format!("{}/{} 轮  {}s", info.round, info.rounds, elapsed));
+    f.render_widget(gauge, area);
+}
+
+// 第一行列出所有目标并高亮当前目标，第二行是当前目标的地址、探测方式和线路类型
+fn draw_target_summary(f: &mut Frame, area: Rect, info: &RouteTraceInfo, selected: &RouteTrace, is_focused: bool) {
+    let mut names = Vec::new();
+    for trace in &info.traces {
+        let style = if std::ptr::eq(trace, selected) {
+            Theme::accent().add_modifier(Modifier::BOLD | Modifier::REVERSED)
+        } else {
+            Theme::secondary()
+        };
+        names.push(Span::styled(format!(" {} ", trace.name), style));
+        names.push(Span::raw(" "));
+    }
+
+    let mut detail = vec![Span::styled(selected.target.host.clone(), Theme::secondary())];
+    if let Some(address) = selected.address {
+        detail.push(Span::styled(format!(" → {}", address), Theme::muted()));
+    }
+    if let Some(method) = selected.method {
+        detail.push(Span::styled(format!("  {}", method.name()), Theme::muted()));
+    }
+    match (&selected.error, selected.route_label()) {
+        (Some(error), _) => detail.push(Span::styled(format!("  ❌ {}", error), Theme::error())),
+        (None, Some(label)) => {
+            detail.push(Span::styled("  线路: ", Theme::accent()));
+            detail.push(Span::styled(label, Theme::success().add_modifier(Modifier::BOLD)));
+        }
+        (None, None) if selected.rounds > 0 && !selected.reached() => {
+            detail.push(Span::styled(format!("  未在 {} 跳内到达目标", MAX_HOPS), Theme::warning()));
+        }
+        (None, None) => {}
+    }
+
+    let mut lines = vec![Line::from(names), Line::from(detail)];
+    // 服务器列表或地址段配置的错误不影响其余目标的追踪
+    if let Some(ref error) = info.error_message {
+        lines.push(Line::from(vec![Span::styled(format!("⚠️ {}", error), Theme::warning())]));
+    }
+
+    let title = if info.traces.len() > 1 { "🎯 目标 (T 切换)" } else { "🎯 目标" };
+    let paragraph = Paragraph::new(lines).block(create_block(title, is_focused));
+    f.render_widget(paragraph, area);
+}
+
+fn draw_hop_table(f: &mut Frame, area: Rect, trace: &RouteTrace, is_focused: bool) {
+    let header = Row::new(vec!["跳", "地址", "ASN / 运营商", "丢包", "发送", "最近", "平均", "最好", "最差", "标准差"])
+        .style(Theme::primary().add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = trace.visible_hops().iter().map(hop_row).collect();
+    let title = format!("🛰️ 逐跳统计 ({} 轮)", trace.rounds);
+    let table = Table::new(rows, [
+        Constraint::Length(3),  // 跳
+        Constraint::Min(16),    // 地址
+        Constraint::Length(22), // ASN / 运营商
+        Constraint::Length(6),  // 丢包
+        Constraint::Length(4),  // 发送
+        Constraint::Length(7),  // 最近
+        Constraint::Length(7),  // 平均
+        Constraint::Length(7),  // 最好
+        Constraint::Length(7),  // 最差
+        Constraint::Length(7),  // 标准差
+    ])
+    .header(header)
+    .block(create_block(&title, is_focused))
+    .column_spacing(1);
+    f.render_widget(table, area);
+}
+
+fn hop_row(hop: &HopStats) -> Row<'static> {
+    let address = match hop.addresses.first() {
+        Some((address, _)) if hop.addresses.len() > 1 => format!("{} (+{})", address, hop.addresses.len() - 1),
+        Some((address, _)) => address.to_string(),
+        None => "???".to_string(),
+    };
+    let carrier = hop.carrier().map(|carrier| carrier.tag()).unwrap_or_default();
+    let loss = hop.loss_pct();
+    let loss_style = if loss == 0.0 {
+        Theme::success()
+    } else if loss < 20.0 {
+        Theme::warning()
+    } else {
+        Theme::error()
+    };
+    let address_style = if hop.addresses.is_empty() { Theme::muted() } else { Theme::secondary() };
+
+    let cell = |text: String, style: Style| Cell::from(text).style(style);
+    Row::new(vec![
+        cell(hop.ttl.to_string(), Theme::muted()),
+        cell(address, address_style),
+        cell(carrier, Theme::accent()),
+        cell(format!("{:.0}%", loss), loss_style),
+        cell(hop.sent.to_string(), Theme::muted()),
+        cell(format_ms(hop.last_ms), Theme::secondary()),
+        cell(format_ms(hop.avg_ms()), Theme::secondary()),
+        cell(format_ms(hop.best_ms()), Theme::secondary()),
+        cell(format_ms(hop.worst_ms()), Theme::secondary()),
+        cell(format_ms(hop.stdev_ms()), Theme::secondary()),
+    ])
+}
+
+// 准备状态或错误信息
+fn draw_route_trace_static_content(f: &mut Frame, app: &mut App, area: Rect, info: &RouteTraceInfo, is_focused: bool) {
+    let mut items = vec![
+        ListItem::new(Line::from(vec![
+            Span::styled("🛰️ 路由追踪", Theme::primary().add_modifier(Modifier::BOLD))
+        ])),
+        ListItem::new(Line::from(vec![Span::raw("")])),
+    ];
+
+    if let Some(ref error) = info.error_message {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("❌ 错误: ", Theme::error()),
+            Span::styled(error.clone(), Theme::error().add_modifier(Modifier::BOLD))
+        ])));
+    } else {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("▶ 按 Enter 开始追踪", Theme::accent().add_modifier(Modifier::BOLD))
+        ])));
+    }
+    items.push(ListItem::new(Line::from(vec![Span::raw("")])));
+
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("🔁 探测轮数: ", Theme::accent()),
+        Span::styled(format!("{} 轮 (U 切换)", route_trace::rounds()), Theme::secondary()),
+    ])));
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("🎯 目标:", Theme::accent()),
+    ])));
+    for provider in network_test::get_network_providers().providers {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(format!("  • {:<16}", provider.name), Theme::secondary()),
+            Span::styled(provider.ping_host, Theme::muted()),
+        ])));
+    }
+    items.push(ListItem::new(Line::from(vec![Span::raw("")])));
+
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("💡 追踪说明", Theme::primary().add_modifier(Modifier::BOLD))
+    ])));
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("─".repeat(40), Theme::muted())
+    ])));
+    for note in [
+        "• 每轮同时发出 TTL 1 - 30 的探测，由途经路由器的 ICMP 超时报文得到每一跳",
+        "• 优先使用 ICMP，当前用户不能发送 ICMP 时改用 UDP，都不需要 root",
+        "• 跳点按内置地址段标注 ASN 和运营商，可在 route_prefixes.toml 中补充",
+        "• 经过 AS4809 且不经过 AS4134 为 CN2 GIA，两者都经过为 CN2 GT",
+        "• 中间跳点丢包而后续跳点正常，通常是路由器限制了 ICMP 回应速率",
+    ] {
+        items.push(ListItem::new(Line::from(vec![Span::styled(note, Theme::muted())])));
+    }
+
+    // 更新滚动状态
+    let content_height = items.len() as u16;
+    let viewport_height = area.height.saturating_sub(2);
+    app.update_content_height(content_height, viewport_height);
+
+    let visible_items = if content_height > viewport_height {
+        items
+            .iter()
+            .skip(app.scroll_position.current as usize)
+            .take(viewport_height as usize)
+            .cloned()
+            .collect::<Vec<_>>()
+    } else {
+        items
+    };
+
+    let (border_style, title_style) = if is_focused {
+        (Theme::border_focused(), Theme::title_focused())
+    } else {
+        (Theme::border_unfocused(), Theme::title_unfocused())
+    };
+
+    let list = List::new(visible_items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" 🛰️ 路由追踪 ")
+                .title_style(title_style)
+                .border_style(border_style)
+                .padding(Padding::uniform(1)),
+        );
+    f.render_widget(list, area);
+
+    if content_height > viewport_height {
+        draw_scrollbar(f, app, area, is_focused);
+    }
+}
@@ -1,12 +1,14 @@
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::Modifier,
+    style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, List, ListItem, Gauge, Paragraph},
+    widgets::{Block, Borders, Cell, List, ListItem, Gauge, Paragraph, Row, Sparkline, Table},
     Frame,
 };
 
 use crate::{app::App, theme::Theme};
+use crate::handlers::dns_test::{self, format_ms, DnsProbe, DnsTestInfo};
+use crate::handlers::resource_monitor::{self, format_rate, ResourceHistory, Rolling};
 use super::components::draw_scrollbar;
 
 /// 绘制系统信息内容
@@ -285,105 +287,243 @@ fn draw_basic_system_info(f: &mut Frame, app: &mut App, area: Rect, system_info:
     }
 }/// 绘制系统状态监控面板（右侧面板）
 fn draw_system_status_panel(f: &mut Frame, area: Rect, system_info: &crate::handlers::system_info::SystemInfo, is_focused: bool) {
-    // 创建上下布局：性能监控 + 存储信息
+    // 创建上下布局：实时监控 + 存储信息 + DNS 测试，监控区域按行数伸缩，至少给存储信息留 6 行；
+    // DNS 测试与左侧的网络信息相邻，最多占一半高度
+    let history = resource_monitor::get_history();
+    let dns_info = dns_test::get_current_test_info();
+    let dns_height = dns_panel_height(&dns_info).min(area.height / 2);
+    let monitor_height = (monitor_rows(&history).len().max(1) as u16 + 2).min(area.height.saturating_sub(6 + dns_height).max(5));
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(10), // 性能监控区域
-            Constraint::Min(6),     // 存储信息区域
+            Constraint::Length(monitor_height), // 实时监控区域
+            Constraint::Min(6),                 // 存储信息区域
+            Constraint::Length(dns_height),     // DNS 测试区域
         ])
         .split(area);
     
-    // 绘制性能监控区域
-    draw_performance_gauges(f, chunks[0], system_info, is_focused);
+    // 绘制实时监控区域
+    draw_resource_monitor(f, chunks[0], &history, is_focused);
     
     // 绘制存储信息区域
     draw_storage_info(f, chunks[1], system_info, is_focused);
+    
+    // 绘制 DNS 测试区域
+    draw_dns_panel(f, chunks[2], &dns_info, is_focused);
 }
 
-/// 绘制性能监控区域
-fn draw_performance_gauges(f: &mut Frame, area: Rect, system_info: &crate::handlers::system_info::SystemInfo, is_focused: bool) {
-    // 创建约束：CPU占用 + 内存占用 + 虚拟内存（如果有）
-    let mut constraints = vec![
-        Constraint::Length(3), // CPU占用
-        Constraint::Length(3), // 内存占用
-    ];
-    
-    if system_info.basic.swap_total > 0 {
-        constraints.push(Constraint::Length(3)); // 虚拟内存
+// 未测试时两行说明，测试后为表头、每项一行和可疑/错误说明
+fn dns_panel_height(info: &DnsTestInfo) -> u16 {
+    if info.probes.is_empty() {
+        return 4;
     }
-    
+    (info.probes.len() + dns_notes(info).len() + 3) as u16
+}
+
+// 表格下方的说明：可疑应答、全部失败的查询方式和配置错误
+fn dns_notes(info: &DnsTestInfo) -> Vec<Line<'static>> {
+    let mut notes = Vec::new();
+    for probe in info.probes.iter().filter(|probe| !probe.suspicious.is_empty()) {
+        notes.push(Line::from(vec![
+            Span::styled(format!("⚠ {} {}: ", probe.resolver, probe.transport.name()), Theme::warning()),
+            Span::styled(probe.suspicious.join(", "), Theme::secondary()),
+        ]));
+    }
+    if !info.is_testing {
+        for probe in info.probes.iter().filter(|probe| probe.is_unreachable()) {
+            notes.push(Line::from(vec![
+                Span::styled(format!("✗ {} {}: ", probe.resolver, probe.transport.name()), Theme::error()),
+                Span::styled(probe.last_error.clone().unwrap_or_default(), Theme::muted()),
+            ]));
+        }
+    }
+    if let Some(ref error) = info.error_message {
+        notes.push(Line::from(vec![Span::styled(format!("⚠ {}", error), Theme::warning())]));
+    }
+    notes
+}
+
+/// 绘制 DNS 解析测试：每个解析器每种查询方式一行
+fn draw_dns_panel(f: &mut Frame, area: Rect, info: &DnsTestInfo, is_focused: bool) {
+    let (border_style, title_style) = if is_focused {
+        (Theme::border_focused(), Theme::title_focused())
+    } else {
+        (Theme::border_unfocused(), Theme::title_unfocused())
+    };
+    let title = if info.is_testing {
+        let (done, total) = info.progress();
+        let elapsed = info.start_time.map(|start| start.elapsed().as_secs()).unwrap_or(0);
+        format!(" DNS 解析 ({}/{}  {}s) ", done, total, elapsed)
+    } else if info.probes.is_empty() {
+        " DNS 解析 ".to_string()
+    } else {
+        format!(" DNS 解析 · {} (D 重新测试) ", info.current_stage)
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .title_style(title_style)
+        .border_style(border_style);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if info.probes.is_empty() {
+        let lines = vec![
+            Line::from(Span::styled("按 D 测试系统和公共解析器", Theme::accent())),
+            Line::from(Span::styled("UDP/TCP/DoH 延迟、失败率和污染检测", Theme::muted())),
+        ];
+        f.render_widget(Paragraph::new(lines), inner);
+        return;
+    }
+
+    let notes = dns_notes(info);
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints(constraints)
-        .split(area);
-    
+        .constraints([Constraint::Min(2), Constraint::Length(notes.len() as u16)].as_ref())
+        .split(inner);
+
+    let header = Row::new(vec!["解析器", "协议", "中位", "平均", "失败", "可疑"])
+        .style(Theme::primary().add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = info.probes.iter().map(dns_row).collect();
+    let table = Table::new(rows, [
+        Constraint::Min(10),   // 解析器
+        Constraint::Length(4), // 协议
+        Constraint::Length(6), // 中位
+        Constraint::Length(6), // 平均
+        Constraint::Length(4), // 失败
+        Constraint::Length(4), // 可疑
+    ])
+    .header(header)
+    .column_spacing(1);
+    f.render_widget(table, chunks[0]);
+    f.render_widget(Paragraph::new(notes), chunks[1]);
+}
+
+fn dns_row(probe: &DnsProbe) -> Row<'static> {
+    let failure = probe.failure_pct();
+    let failure_style = if probe.queries == 0 {
+        Theme::muted()
+    } else if failure == 0.0 {
+        Theme::success()
+    } else if failure < 20.0 {
+        Theme::warning()
+    } else {
+        Theme::error()
+    };
+    let suspicious_style = if probe.suspicious.is_empty() { Theme::muted() } else { Theme::warning() };
+    let cell = |text: String, style: Style| Cell::from(text).style(style);
+    Row::new(vec![
+        cell(probe.resolver.clone(), Theme::secondary()),
+        cell(probe.transport.name().to_string(), Theme::muted()),
+        cell(format_ms(probe.median_ms()), Theme::secondary()),
+        cell(format_ms(probe.avg_ms()), Theme::secondary()),
+        cell(format!("{:.0}%", failure), failure_style),
+        cell(probe.suspicious.len().to_string(), suspicious_style),
+    ])
+}
+
+/// 绘制实时资源监控：每行为 名称 | 最近采样的曲线 | 当前值
+fn draw_resource_monitor(f: &mut Frame, area: Rect, history: &ResourceHistory, is_focused: bool) {
     let (border_style, title_style) = if is_focused {
         (Theme::border_focused(), Theme::title_focused())
     } else {
         (Theme::border_unfocused(), Theme::title_unfocused())
     };
-    
-    let mut chunk_index = 0;
-    
-    // CPU使用率Gauge
-    let cpu_usage_percent = system_info.basic.cpu_usage as f64;
-    let cpu_ratio = (cpu_usage_percent / 100.0).max(0.0).min(1.0);
-    let cpu_gauge = Gauge::default()
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" CPU占用 ")
-                .title_style(title_style)
-                .border_style(border_style)
-        )
-        .gauge_style(Theme::gauge_bar_style(cpu_usage_percent))
-        .ratio(cpu_ratio)
-        .label(format!("{:.1}%", cpu_usage_percent));
-    
-    f.render_widget(cpu_gauge, chunks[chunk_index]);
-    chunk_index += 1;
-    
-    // 内存使用率Gauge
-    let memory_percent = (system_info.basic.memory_used as f64 / system_info.basic.memory_total as f64) * 100.0;
-    let memory_used_gb = system_info.basic.memory_used as f64 / (1024.0 * 1024.0 * 1024.0);
-    let memory_total_gb = system_info.basic.memory_total as f64 / (1024.0 * 1024.0 * 1024.0);
-    let memory_ratio = (memory_percent / 100.0).max(0.0).min(1.0);
-    let memory_gauge = Gauge::default()
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title(" 物理内存 ")
-                .title_style(title_style)
-                .border_style(border_style)
-        )
-        .gauge_style(Theme::gauge_bar_style(memory_percent))
-        .ratio(memory_ratio)
-        .label(format!("{:.1}/{:.1}G ({:.1}%)", memory_used_gb, memory_total_gb, memory_percent));
-    
-    f.render_widget(memory_gauge, chunks[chunk_index]);
-    chunk_index += 1;
-    
-    // 虚拟内存Gauge（如果有）
-    if system_info.basic.swap_total > 0 && chunk_index < chunks.len() {
-        let swap_percent = (system_info.basic.swap_used as f64 / system_info.basic.swap_total as f64) * 100.0;
-        let swap_used_gb = system_info.basic.swap_used as f64 / (1024.0 * 1024.0 * 1024.0);
-        let swap_total_gb = system_info.basic.swap_total as f64 / (1024.0 * 1024.0 * 1024.0);
-        let swap_ratio = (swap_percent / 100.0).max(0.0).min(1.0);
-        let swap_gauge = Gauge::default()
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title(" 虚拟内存 ")
-                    .title_style(title_style)
-                    .border_style(border_style)
-            )
-            .gauge_style(Theme::gauge_bar_style(swap_percent))
-            .ratio(swap_ratio)
-            .label(format!("{:.1}/{:.1}G ({:.1}%)", swap_used_gb, swap_total_gb, swap_percent));
-        
-        f.render_widget(swap_gauge, chunks[chunk_index]);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" 实时监控 ")
+        .title_style(title_style)
+        .border_style(border_style);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let rows = monitor_rows(history);
+    if rows.is_empty() {
+        f.render_widget(Paragraph::new("采样中...").style(Theme::muted()), inner);
+        return;
+    }
+
+    let row_areas = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(1); rows.len()])
+        .split(inner);
+
+    for (row, row_area) in rows.into_iter().zip(row_areas.iter()) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(10), Constraint::Min(10), Constraint::Length(11)].as_ref())
+            .split(*row_area);
+        f.render_widget(Paragraph::new(row.label).style(Theme::muted()), columns[0]);
+
+        // 百分比按 0.1% 取整，速度按字节/秒取整；速度曲线的纵轴随最近的峰值缩放
+        let visible: Vec<u64> = row.values[row.values.len().saturating_sub(columns[1].width as usize)..].to_vec();
+        let max = visible.iter().copied().max().unwrap_or(0).max(row.min_max);
+        f.render_widget(Sparkline::default().data(&visible).max(max).style(row.style), columns[1]);
+
+        f.render_widget(
+            Paragraph::new(row.value).style(Theme::secondary()).alignment(Alignment::Right),
+            columns[2],
+        );
+    }
+}
+
+// 监控面板中的一行
+struct MonitorRow {
+    label: String,
+    values: Vec<u64>,
+    min_max: u64,
+    value: String,
+    style: Style,
+}
+
+fn monitor_rows(history: &ResourceHistory) -> Vec<MonitorRow> {
+    let percent_row = |label: String, series: &Rolling, style: Style| MonitorRow {
+        label,
+        values: series.values().map(|pct| (pct * 10.0).round() as u64).collect(),
+        min_max: 1000,
+        value: series.last().map(|pct| format!("{:.1}%", pct)).unwrap_or_else(|| "--".to_string()),
+        style,
+    };
+    let rate_row = |label: String, series: &Rolling, style: Style| MonitorRow {
+        label,
+        values: series.values().map(|rate| rate.round() as u64).collect(),
+        min_max: 1024,
+        value: series.last().map(format_rate).unwrap_or_else(|| "--".to_string()),
+        style,
+    };
+
+    let mut rows = Vec::new();
+    if history.samples == 0 {
+        return rows;
+    }
+    rows.push(percent_row("CPU".to_string(), &history.cpu, Theme::accent()));
+    // 单核时与总占用相同，不重复显示
+    if history.cores.len() > 1 {
+        for (cpu, series) in &history.cores {
+            rows.push(percent_row(format!("  cpu{}", cpu), series, Theme::muted()));
+        }
+    }
+    let gib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+    let memory = history.memory;
+    rows.push(MonitorRow {
+        value: format!("{:.1}/{:.1}G", gib(memory.used), gib(memory.total)),
+        ..percent_row("内存".to_string(), &history.memory_pct, Theme::success())
+    });
+    if memory.swap_total > 0 {
+        rows.push(MonitorRow {
+            value: format!("{:.1}/{:.1}G", gib(memory.swap_used), gib(memory.swap_total)),
+            ..percent_row("Swap".to_string(), &history.swap_pct, Theme::warning())
+        });
+    }
+    for disk in history.disks.iter().filter(|disk| disk.is_active()) {
+        rows.push(rate_row(format!("{} 读", disk.name), &disk.first, Style::default().fg(Theme::chart_read_color())));
+        rows.push(rate_row(format!("{} 写", disk.name), &disk.second, Style::default().fg(Theme::chart_write_color())));
+    }
+    for interface in history.interfaces.iter().filter(|interface| interface.is_active()) {
+        rows.push(rate_row(format!("{} ↓", interface.name), &interface.first, Theme::success()));
+        rows.push(rate_row(format!("{} ↑", interface.name), &interface.second, Theme::primary()));
     }
+    rows
 }
 
 /// 绘制存储信息区域
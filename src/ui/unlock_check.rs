@@ -0,0 +1,205 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Gauge, List, ListItem, Padding, Paragraph, Row, Table},
+    Frame,
+};
+
+use crate::{app::App, theme::Theme};
+use crate::handlers::unlock_check::{self, UnlockCheckInfo, UnlockResult, UnlockStatus};
+use super::components::draw_scrollbar;
+use super::helpers::create_block;
+
+/// 绘制解锁检测内容
+pub fn draw_unlock_check_content(f: &mut Frame, app: &mut App, area: Rect, is_focused: bool) {
+    let info = unlock_check::get_current_test_info();
+
+    if !info.services.is_empty() {
+        draw_unlock_results_ui(f, area, &info, is_focused);
+        return;
+    }
+
+    // 显示准备状态的静态界面
+    draw_unlock_check_static_content(f, app, area, is_focused);
+}
+
+// 进度和逐项结果
+fn draw_unlock_results_ui(f: &mut Frame, area: Rect, info: &UnlockCheckInfo, is_focused: bool) {
+    let notes_height = if info.error_message.is_some() { 3 } else { 0 };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),            // 上部：进度
+            Constraint::Min(6),               // 中部：结果表格
+            Constraint::Length(notes_height), // 下部：规则错误
+        ].as_ref())
+        .split(area);
+
+    draw_progress(f, chunks[0], info, is_focused);
+    draw_result_table(f, chunks[1], info, is_focused);
+    if let Some(ref error) = info.error_message {
+        let paragraph = Paragraph::new(Line::from(Span::styled(format!("⚠️ {}", error), Theme::warning())))
+            .block(create_block("规则错误", is_focused));
+        f.render_widget(paragraph, chunks[2]);
+    }
+}
+
+fn draw_progress(f: &mut Frame, area: Rect, info: &UnlockCheckInfo, is_focused: bool) {
+    let (border_style, title_style) = if is_focused {
+        (Theme::border_focused(), Theme::title_focused())
+    } else {
+        (Theme::border_unfocused(), Theme::title_unfocused())
+    };
+    let done = info.results.iter().flatten().count();
+    let total = info.services.len();
+    let ratio = if total == 0 { 0.0 } else { done as f64 / total as f64 };
+    let elapsed = info.start_time.map(|start| start.elapsed().as_secs()).unwrap_or(0);
+
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", info.current_stage))
+                .title_style(title_style)
+                .border_style(border_style),
+        )
+        .gauge_style(Theme::disk_test_progress_style(ratio * 100.0))
+        .ratio(ratio)
+        .label(format!("{}/{} 项  {}s", done, total, elapsed));
+    f.render_widget(gauge, area);
+}
+
+fn draw_result_table(f: &mut Frame, area: Rect, info: &UnlockCheckInfo, is_focused: bool) {
+    let header = Row::new(vec!["服务", "结果", "地区", "说明"])
+        .style(Theme::primary().add_modifier(Modifier::BOLD));
+    let rows: Vec<Row> = info
+        .services
+        .iter()
+        .zip(&info.results)
+        .map(|(service, result)| match result {
+            Some(result) => result_row(result),
+            None => Row::new(vec![
+                Cell::from(service.clone()).style(Theme::secondary()),
+                Cell::from("检测中...").style(Theme::muted()),
+            ]),
+        })
+        .collect();
+    let table = Table::new(rows, [
+        Constraint::Length(18), // 服务
+        Constraint::Length(12), // 结果
+        Constraint::Length(6),  // 地区
+        Constraint::Min(10),    // 说明
+    ])
+    .header(header)
+    .block(create_block("📺 解锁检测", is_focused))
+    .column_spacing(1);
+    f.render_widget(table, area);
+}
+
+fn result_row(result: &UnlockResult) -> Row<'static> {
+    let status_style = match result.status {
+        UnlockStatus::Unlocked => Theme::success().add_modifier(Modifier::BOLD),
+        UnlockStatus::RegionOnly => Theme::warning(),
+        UnlockStatus::Blocked | UnlockStatus::Failed => Theme::error(),
+        UnlockStatus::Unknown => Theme::muted(),
+    };
+    let cell = |text: String, style: Style| Cell::from(text).style(style);
+    Row::new(vec![
+        cell(result.service.clone(), Theme::secondary()),
+        cell(result.status.name().to_string(), status_style),
+        cell(result.region.clone().unwrap_or_else(|| "-".to_string()), Theme::accent()),
+        cell(result.note.clone().unwrap_or_default(), Theme::muted()),
+    ])
+}
+
+// 准备状态：列出要检测的服务和规则来源
+fn draw_unlock_check_static_content(f: &mut Frame, app: &mut App, area: Rect, is_focused: bool) {
+    let plan = unlock_check::load_rules();
+    let mut items = vec![
+        ListItem::new(Line::from(vec![
+            Span::styled("📺 解锁检测", Theme::primary().add_modifier(Modifier::BOLD))
+        ])),
+        ListItem::new(Line::from(vec![Span::raw("")])),
+        ListItem::new(Line::from(vec![
+            Span::styled("▶ 按 Enter 开始检测", Theme::accent().add_modifier(Modifier::BOLD))
+        ])),
+        ListItem::new(Line::from(vec![Span::raw("")])),
+    ];
+
+    let source = match plan.source {
+        Some(ref path) => format!("内置规则 + {}", path.display()),
+        None => "内置规则".to_string(),
+    };
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("📄 检测规则: ", Theme::accent()),
+        Span::styled(source, Theme::secondary()),
+    ])));
+    for error in &plan.errors {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(format!("  ⚠️ {}", error), Theme::warning()),
+        ])));
+    }
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("🎯 服务:", Theme::accent()),
+    ])));
+    for service in &plan.services {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(format!("  • {:<18}", service.name), Theme::secondary()),
+            Span::styled(service.url.clone(), Theme::muted()),
+        ])));
+    }
+    items.push(ListItem::new(Line::from(vec![Span::raw("")])));
+
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("💡 检测说明", Theme::primary().add_modifier(Modifier::BOLD))
+    ])));
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("─".repeat(40), Theme::muted())
+    ])));
+    for note in [
+        "• 各服务并行请求，根据状态码、重定向后的地址和页面内容判断结果",
+        "• 地区为服务根据本机出口 IP 判定的地区，可能与 IP 库中的位置不同",
+        "• 服务调整检测方式后，在配置目录的 unlock_rules.toml 中更新同名服务的规则即可",
+        "• 请求经过 HTTP(S)_PROXY 代理时，检测的是代理出口的解锁情况",
+    ] {
+        items.push(ListItem::new(Line::from(vec![Span::styled(note, Theme::muted())])));
+    }
+
+    // 更新滚动状态
+    let content_height = items.len() as u16;
+    let viewport_height = area.height.saturating_sub(2);
+    app.update_content_height(content_height, viewport_height);
+
+    let visible_items = if content_height > viewport_height {
+        items
+            .iter()
+            .skip(app.scroll_position.current as usize)
+            .take(viewport_height as usize)
+            .cloned()
+            .collect::<Vec<_>>()
+    } else {
+        items
+    };
+
+    let (border_style, title_style) = if is_focused {
+        (Theme::border_focused(), Theme::title_focused())
+    } else {
+        (Theme::border_unfocused(), Theme::title_unfocused())
+    };
+
+    let list = List::new(visible_items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" 📺 解锁检测 ")
+                .title_style(title_style)
+                .border_style(border_style)
+                .padding(Padding::uniform(1)),
+        );
+    f.render_widget(list, area);
+
+    if content_height > viewport_height {
+        draw_scrollbar(f, app, area, is_focused);
+    }
+}